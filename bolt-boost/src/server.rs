@@ -57,6 +57,10 @@ const GET_HEADER_WITH_PROOFS_PATH: &str =
 
 const TIMEOUT_ERROR_CODE: u16 = 555;
 
+/// The W3C trace context headers, propagated from the sidecar to the relays.
+const TRACEPARENT_HEADER: &str = "traceparent";
+const TRACESTATE_HEADER: &str = "tracestate";
+
 // Extra state available at runtime
 #[derive(Clone)]
 pub struct BuilderState {
@@ -112,9 +116,10 @@ impl BuilderApi<BuilderState> for ConstraintsApi {
 
 /// Submit signed constraints to the builder.
 /// Spec: <https://docs.boltprotocol.xyz/technical-docs/api/builder#constraints>
-#[tracing::instrument(skip_all)]
+#[tracing::instrument(skip_all, fields(traceparent = ?req_headers.get(TRACEPARENT_HEADER)))]
 async fn submit_constraints(
     State(state): State<PbsState<BuilderState>>,
+    req_headers: HeaderMap,
     Json(constraints): Json<Vec<SignedConstraints>>,
 ) -> Result<impl IntoResponse, PbsClientError> {
     info!("Submitting {} constraints to relays", constraints.len());
@@ -136,31 +141,33 @@ async fn submit_constraints(
         }
     }
 
-    post_request(state, SUBMIT_CONSTRAINTS_PATH, &constraints).await?;
+    post_request(state, SUBMIT_CONSTRAINTS_PATH, &constraints, &req_headers).await?;
     Ok(StatusCode::OK)
 }
 
 /// Delegate constraint submission rights to another BLS key.
 /// Spec: <https://docs.boltprotocol.xyz/technical-docs/api/builder#delegate>
-#[tracing::instrument(skip_all)]
+#[tracing::instrument(skip_all, fields(traceparent = ?req_headers.get(TRACEPARENT_HEADER)))]
 async fn delegate(
     State(state): State<PbsState<BuilderState>>,
+    req_headers: HeaderMap,
     Json(delegations): Json<Vec<SignedDelegation>>,
 ) -> Result<impl IntoResponse, PbsClientError> {
     info!(count = %delegations.len(), "Delegating signing rights");
-    post_request(state, DELEGATE_PATH, &delegations).await?;
+    post_request(state, DELEGATE_PATH, &delegations, &req_headers).await?;
     Ok(StatusCode::OK)
 }
 
 /// Revoke constraint submission rights from a BLS key.
 /// Spec: <https://docs.boltprotocol.xyz/technical-docs/api/builder#revoke>
-#[tracing::instrument(skip_all)]
+#[tracing::instrument(skip_all, fields(traceparent = ?req_headers.get(TRACEPARENT_HEADER)))]
 async fn revoke(
     State(state): State<PbsState<BuilderState>>,
+    req_headers: HeaderMap,
     Json(revocations): Json<Vec<SignedRevocation>>,
 ) -> Result<impl IntoResponse, PbsClientError> {
     info!(count = %revocations.len(), "Revoking signing rights");
-    post_request(state, REVOKE_PATH, &revocations).await?;
+    post_request(state, REVOKE_PATH, &revocations, &req_headers).await?;
     Ok(StatusCode::OK)
 }
 
@@ -527,6 +534,7 @@ async fn post_request<T>(
     state: PbsState<BuilderState>,
     path: &str,
     body: &T,
+    req_headers: &HeaderMap,
 ) -> Result<(), PbsClientError>
 where
     T: Serialize,
@@ -535,9 +543,17 @@ where
     // Forward constraints to all relays.
     let mut responses = FuturesUnordered::new();
 
+    // Propagate the trace context of the caller, so that relays can join the same trace.
+    let mut headers = HeaderMap::new();
+    for name in [TRACEPARENT_HEADER, TRACESTATE_HEADER] {
+        if let Some(value) = req_headers.get(name) {
+            headers.insert(name, value.clone());
+        }
+    }
+
     for relay in state.relays() {
         let url = relay.get_url(path).map_err(|_| PbsClientError::BadRequest)?;
-        responses.push(relay.client.post(url).json(&body).send());
+        responses.push(relay.client.post(url).headers(headers.clone()).json(&body).send());
     }
 
     let mut success = false;
@@ -42,6 +42,9 @@ pub enum Cmd {
     /// Handle operators in the bolt network.
     Operators(OperatorsCommand),
 
+    /// Open and inspect challenges against commitments in the bolt network.
+    Challenges(ChallengesCommand),
+
     /// Useful data generation commands.
     Generate(GenerateCommand),
 }
@@ -55,6 +58,7 @@ impl Cmd {
             Self::Send(cmd) => cmd.run().await,
             Self::Validators(cmd) => cmd.run().await,
             Self::Operators(cmd) => cmd.run().await,
+            Self::Challenges(cmd) => cmd.run().await,
             Self::Generate(cmd) => cmd.run(),
         }
     }
@@ -134,6 +138,16 @@ pub struct SendCommand {
     #[clap(long, env = "PRIORITY_FEE", default_value = "2")]
     pub priority_fee: u128,
 
+    /// If set, the requests will be signed over their EIP-712 typed data instead of their
+    /// raw digest, binding the signatures to the chain and to the operator of the sidecar.
+    #[clap(long, env = "EIP712", default_value = "false")]
+    pub eip712: bool,
+
+    /// The operator address of the target sidecar, used in the EIP-712 domain of the requests.
+    /// If not provided, the EIP-712 domain is fetched from the target sidecar.
+    #[clap(long, env = "OPERATOR_ADDRESS", requires = "eip712")]
+    pub operator: Option<Address>,
+
     /// If set, the transaction will target the devnet environment.
     /// This is only used in Kurtosis for internal testing purposes
     #[clap(long, hide = true, env = "DEVNET", default_value = "false")]
@@ -314,6 +328,82 @@ pub enum SymbioticSubcommand {
     },
 }
 
+#[derive(Debug, Clone, Parser)]
+pub struct ChallengesCommand {
+    #[clap(subcommand)]
+    pub subcommand: ChallengesSubcommand,
+}
+
+#[derive(Debug, Clone, Parser)]
+pub enum ChallengesSubcommand {
+    /// List the challenges in the BoltChallenger contract.
+    List {
+        /// The URL of the RPC to query.
+        #[clap(long, env = "RPC_URL")]
+        rpc_url: Url,
+        /// The address of the BoltChallenger contract.
+        #[clap(long, env = "BOLT_CHALLENGER_ADDRESS")]
+        challenger_address: Address,
+        /// Only list the challenges against the commitments signed by this operator.
+        #[clap(long, env = "OPERATOR_ADDRESS")]
+        operator: Option<Address>,
+        /// If set, resolved challenges are listed as well.
+        #[clap(long, default_value_t = false)]
+        all: bool,
+    },
+
+    /// Show a challenge by its ID.
+    Show {
+        /// The URL of the RPC to query.
+        #[clap(long, env = "RPC_URL")]
+        rpc_url: Url,
+        /// The address of the BoltChallenger contract.
+        #[clap(long, env = "BOLT_CHALLENGER_ADDRESS")]
+        challenger_address: Address,
+        /// The ID of the challenge.
+        #[clap(long, env = "CHALLENGE_ID")]
+        id: B256,
+    },
+
+    /// Open a challenge against a bundle of broken inclusion commitments.
+    Open {
+        /// The URL of the RPC to broadcast the transaction.
+        #[clap(long, env = "RPC_URL")]
+        rpc_url: Url,
+        /// The address of the BoltChallenger contract.
+        #[clap(long, env = "BOLT_CHALLENGER_ADDRESS")]
+        challenger_address: Address,
+        /// The private key of the challenger, paying for the challenge bond.
+        #[clap(long, env = "CHALLENGER_PRIVATE_KEY", hide_env_values = true)]
+        private_key: B256,
+        /// The paths to the JSON files of the inclusion commitments, as returned by the sidecar.
+        /// Each commitment must contain a single transaction, and all of them must be for the
+        /// same slot and sender, with consecutive nonces.
+        #[clap(long, env = "COMMITMENTS_PATHS", value_delimiter = ',', required = true)]
+        commitments_paths: Vec<PathBuf>,
+        /// The expected signer of the commitments. If set, the commitments signed by another
+        /// address are refused.
+        #[clap(long, env = "OPERATOR_ADDRESS")]
+        operator: Option<Address>,
+    },
+
+    /// Resolve an expired challenge as breached, slashing the operator.
+    ResolveExpired {
+        /// The URL of the RPC to broadcast the transaction.
+        #[clap(long, env = "RPC_URL")]
+        rpc_url: Url,
+        /// The address of the BoltChallenger contract.
+        #[clap(long, env = "BOLT_CHALLENGER_ADDRESS")]
+        challenger_address: Address,
+        /// The private key to sign the transaction with.
+        #[clap(long, env = "CHALLENGER_PRIVATE_KEY", hide_env_values = true)]
+        private_key: B256,
+        /// The ID of the challenge.
+        #[clap(long, env = "CHALLENGE_ID")]
+        id: B256,
+    },
+}
+
 #[derive(Debug, Clone, Parser)]
 pub struct GenerateCommand {
     #[clap(subcommand)]
@@ -0,0 +1,302 @@
+use std::path::Path;
+
+use alloy::{
+    consensus::{transaction::PooledTransaction, TxEnvelope},
+    eips::eip2718::{Decodable2718, Encodable2718},
+    network::EthereumWallet,
+    primitives::{keccak256, Address, Bytes, PrimitiveSignature},
+    providers::ProviderBuilder,
+    signers::local::PrivateKeySigner,
+    sol_types::SolInterface,
+};
+use eyre::{bail, Context};
+use serde::Deserialize;
+use tracing::info;
+
+use crate::{
+    cli::{ChallengesCommand, ChallengesSubcommand},
+    common::{request_confirmation, try_parse_contract_error},
+    contracts::bolt::{
+        BoltChallenger::{self, BoltChallengerErrors, SignedCommitment},
+        BoltParameters,
+    },
+};
+
+impl ChallengesCommand {
+    pub async fn run(self) -> eyre::Result<()> {
+        match self.subcommand {
+            ChallengesSubcommand::List { rpc_url, challenger_address, operator, all } => {
+                let provider = ProviderBuilder::new().on_http(rpc_url);
+                let bolt_challenger = BoltChallenger::new(challenger_address, provider);
+
+                let challenges = if all {
+                    bolt_challenger.getAllChallenges().call().await?._0
+                } else {
+                    bolt_challenger.getOpenChallenges().call().await?._0
+                };
+
+                let challenges = challenges
+                    .into_iter()
+                    .filter(|c| operator.map_or(true, |op| c.commitmentSigner == op))
+                    .collect::<Vec<_>>();
+
+                info!(challenges = challenges.len(), %challenger_address, "Fetched challenges");
+                println!("{}", serde_json::to_string_pretty(&challenges)?);
+
+                Ok(())
+            }
+
+            ChallengesSubcommand::Show { rpc_url, challenger_address, id } => {
+                let provider = ProviderBuilder::new().on_http(rpc_url);
+                let bolt_challenger = BoltChallenger::new(challenger_address, provider);
+
+                match bolt_challenger.getChallengeByID(id).call().await {
+                    Ok(challenge) => {
+                        println!("{}", serde_json::to_string_pretty(&challenge._0)?);
+                        Ok(())
+                    }
+                    Err(e) => match try_parse_contract_error::<BoltChallengerErrors>(e)? {
+                        BoltChallengerErrors::ChallengeDoesNotExist(_) => {
+                            bail!("Challenge {id} does not exist")
+                        }
+                        other => bail!("Unexpected error with selector {:?}", other.selector()),
+                    },
+                }
+            }
+
+            ChallengesSubcommand::Open {
+                rpc_url,
+                challenger_address,
+                private_key,
+                commitments_paths,
+                operator,
+            } => {
+                let signer =
+                    PrivateKeySigner::from_bytes(&private_key).wrap_err("valid private key")?;
+
+                let mut commitments = Vec::with_capacity(commitments_paths.len());
+                let mut commitment_signer = None;
+                for path in &commitments_paths {
+                    let commitment = InclusionCommitment::from_file(path)?;
+                    let (signer, signed) = commitment.into_signed_commitment()?;
+
+                    if operator.is_some_and(|op| op != signer) {
+                        bail!(
+                            "Commitment {} is not signed by the operator, but by {signer}",
+                            path.display()
+                        );
+                    }
+                    if commitment_signer.is_some_and(|s| s != signer) {
+                        bail!("Commitments must be signed by the same operator");
+                    }
+
+                    commitment_signer = Some(signer);
+                    commitments.push(signed);
+                }
+
+                let provider = ProviderBuilder::new()
+                    .with_recommended_fillers()
+                    .wallet(EthereumWallet::from(signer))
+                    .on_http(rpc_url);
+
+                let bolt_challenger = BoltChallenger::new(challenger_address, provider.clone());
+                let parameters = bolt_challenger.parameters().call().await?._0;
+                let bond =
+                    BoltParameters::new(parameters, provider).CHALLENGE_BOND().call().await?._0;
+
+                info!(
+                    commitments = commitments.len(),
+                    slot = commitments[0].slot,
+                    operator = ?commitment_signer,
+                    %bond,
+                    "Opening challenge against commitments",
+                );
+
+                request_confirmation();
+
+                match bolt_challenger.openChallenge(commitments).value(bond).send().await {
+                    Ok(pending) => {
+                        info!(
+                            hash = ?pending.tx_hash(),
+                            "openChallenge transaction sent, awaiting receipt..."
+                        );
+                        let receipt = pending.get_receipt().await?;
+                        if !receipt.status() {
+                            eyre::bail!("Transaction failed: {:?}", receipt)
+                        }
+
+                        info!("Successfully opened challenge");
+                    }
+                    Err(e) => match try_parse_contract_error::<BoltChallengerErrors>(e)? {
+                        BoltChallengerErrors::ChallengeAlreadyExists(_) => {
+                            bail!("A challenge for these commitments already exists")
+                        }
+                        BoltChallengerErrors::BlockIsNotFinalized(_) => {
+                            bail!("The target slot is not finalized yet, try again later")
+                        }
+                        BoltChallengerErrors::IncorrectChallengeBond(_) => {
+                            bail!("Incorrect challenge bond")
+                        }
+                        BoltChallengerErrors::UnexpectedMixedSenders(_) => {
+                            bail!("Commitments must be for transactions from the same sender")
+                        }
+                        BoltChallengerErrors::UnexpectedMixedSlots(_) => {
+                            bail!("Commitments must be for the same slot")
+                        }
+                        BoltChallengerErrors::UnexpectedNonceOrder(_) => {
+                            bail!("Committed transactions must have consecutive nonces")
+                        }
+                        other => bail!("Unexpected error with selector {:?}", other.selector()),
+                    },
+                }
+
+                Ok(())
+            }
+
+            ChallengesSubcommand::ResolveExpired {
+                rpc_url,
+                challenger_address,
+                private_key,
+                id,
+            } => {
+                let signer =
+                    PrivateKeySigner::from_bytes(&private_key).wrap_err("valid private key")?;
+
+                let provider = ProviderBuilder::new()
+                    .with_recommended_fillers()
+                    .wallet(EthereumWallet::from(signer))
+                    .on_http(rpc_url);
+
+                let bolt_challenger = BoltChallenger::new(challenger_address, provider);
+
+                info!(%id, "Resolving expired challenge");
+
+                match bolt_challenger.resolveExpiredChallenge(id).send().await {
+                    Ok(pending) => {
+                        info!(
+                            hash = ?pending.tx_hash(),
+                            "resolveExpiredChallenge transaction sent, awaiting receipt..."
+                        );
+                        let receipt = pending.get_receipt().await?;
+                        if !receipt.status() {
+                            eyre::bail!("Transaction failed: {:?}", receipt)
+                        }
+
+                        info!("Successfully resolved expired challenge");
+                    }
+                    Err(e) => match try_parse_contract_error::<BoltChallengerErrors>(e)? {
+                        BoltChallengerErrors::ChallengeDoesNotExist(_) => {
+                            bail!("Challenge {id} does not exist")
+                        }
+                        BoltChallengerErrors::ChallengeAlreadyResolved(_) => {
+                            bail!("Challenge {id} is already resolved")
+                        }
+                        BoltChallengerErrors::ChallengeNotExpired(_) => {
+                            bail!("Challenge {id} has not expired yet")
+                        }
+                        other => bail!("Unexpected error with selector {:?}", other.selector()),
+                    },
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+/// An inclusion commitment signed by a bolt sidecar, as returned by `bolt_requestInclusion`.
+#[derive(Debug, Clone, Deserialize)]
+struct InclusionCommitment {
+    /// The slot at which the transactions were committed to be included.
+    slot: u64,
+    /// The EIP-2718 encoded committed transactions.
+    txs: Vec<Bytes>,
+    /// The signature of the commitment signer over the commitment digest.
+    signature: Bytes,
+}
+
+impl InclusionCommitment {
+    /// Reads a commitment from a JSON file.
+    fn from_file(path: &Path) -> eyre::Result<Self> {
+        let file = std::fs::File::open(path)
+            .wrap_err_with(|| format!("failed to open commitment file {}", path.display()))?;
+        serde_json::from_reader(file).wrap_err("invalid commitment JSON")
+    }
+
+    /// Verifies the commitment signature locally and converts the commitment to the format
+    /// expected by the BoltChallenger contract. Returns the recovered commitment signer.
+    ///
+    /// The digest of a commitment is `keccak256(tx_hash | le_bytes(slot))`, and the contract
+    /// verifies it for each transaction separately: only single-transaction commitments can be
+    /// challenged.
+    fn into_signed_commitment(self) -> eyre::Result<(Address, SignedCommitment)> {
+        let [raw_tx] = self.txs.as_slice() else {
+            bail!("Only commitments to a single transaction can be challenged");
+        };
+
+        // Blob transactions are returned with their sidecar, but the contract expects the
+        // canonical encoding from which the transaction hash is computed.
+        let tx = PooledTransaction::decode_2718(&mut raw_tx.as_ref())
+            .wrap_err("invalid committed transaction")?;
+        let signed_tx = Bytes::from(TxEnvelope::from(tx).encoded_2718());
+
+        let mut data = keccak256(&signed_tx).to_vec();
+        data.extend_from_slice(&self.slot.to_le_bytes());
+        let digest = keccak256(data);
+
+        let signature = PrimitiveSignature::try_from(self.signature.as_ref())
+            .wrap_err("invalid commitment signature")?;
+        let signer = signature
+            .recover_address_from_prehash(&digest)
+            .wrap_err("failed to recover commitment signer")?;
+
+        // The contract expects the recovery id to be encoded as 27/28.
+        let signature = Bytes::from(signature.as_bytes());
+
+        Ok((signer, SignedCommitment { slot: self.slot, signature, signedTx: signed_tx }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::{
+        consensus::{SignableTransaction, TxEip1559},
+        network::TxSignerSync,
+        primitives::{hex, U256},
+        signers::SignerSync,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_verify_commitment_signature() {
+        let operator = PrivateKeySigner::random();
+        let sender = PrivateKeySigner::random();
+        let slot: u64 = 123_456;
+
+        let mut tx = TxEip1559 { chain_id: 17000, value: U256::from(1), ..Default::default() };
+        let signature = sender.sign_transaction_sync(&mut tx).unwrap();
+        let raw_tx = TxEnvelope::from(tx.into_signed(signature)).encoded_2718();
+
+        let mut data = keccak256(&raw_tx).to_vec();
+        data.extend_from_slice(&slot.to_le_bytes());
+        let commitment_signature = operator.sign_hash_sync(&keccak256(data)).unwrap();
+
+        // The sidecar encodes the recovery id as 0/1
+        let mut signature = commitment_signature.as_bytes();
+        signature[64] -= 27;
+
+        let json = format!(
+            r#"{{"slot":{slot},"txs":["{}"],"signature":"{}"}}"#,
+            hex::encode_prefixed(&raw_tx),
+            hex::encode_prefixed(signature)
+        );
+        let commitment: InclusionCommitment = serde_json::from_str(&json).unwrap();
+
+        let (signer, signed) = commitment.into_signed_commitment().unwrap();
+        assert_eq!(signer, operator.address());
+        assert_eq!(signed.slot, slot);
+        assert_eq!(signed.signedTx.as_ref(), raw_tx.as_slice());
+        assert_eq!(signed.signature.as_ref(), commitment_signature.as_bytes().as_slice());
+    }
+}
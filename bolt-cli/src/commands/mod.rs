@@ -16,5 +16,8 @@ pub mod validators;
 /// Module for the operators-related commands to interact with the bolt network.
 pub mod operators;
 
+/// Module for the challenges-related commands to interact with the BoltChallenger contract.
+pub mod challenges;
+
 /// Module for generating various types of data like BLS keys.
 pub mod generate;
@@ -1,4 +1,4 @@
-use std::time::Duration;
+use std::{borrow::Cow, time::Duration};
 
 use alloy::{
     consensus::{
@@ -8,9 +8,11 @@ use alloy::{
     hex,
     network::{EthereumWallet, TransactionBuilder, TransactionBuilder4844},
     primitives::{keccak256, Address, B256, U256},
-    providers::{ProviderBuilder, SendableTx},
+    providers::{Provider, ProviderBuilder, SendableTx},
     rpc::types::TransactionRequest,
     signers::{local::PrivateKeySigner, Signer},
+    sol,
+    sol_types::{Eip712Domain, SolStruct},
 };
 use eyre::{bail, Context, ContextCompat, Result};
 use rand::Rng;
@@ -24,6 +26,23 @@ use crate::cli::SendCommand;
 /// Path to the lookahead endpoint on the Bolt RPC server.
 const BOLT_LOOKAHEAD_PATH: &str = "/api/v1/proposers/lookahead";
 
+/// The name of the EIP-712 domain of commitment requests.
+const REQUEST_DOMAIN_NAME: &str = "Bolt";
+
+/// The version of the EIP-712 domain of commitment requests.
+const REQUEST_DOMAIN_VERSION: &str = "1";
+
+/// The prefix of the signature header for signatures over the EIP-712 typed data of requests.
+const EIP712_V1_SCHEME: &str = "eip712-v1";
+
+sol! {
+    /// The EIP-712 typed data of an inclusion request.
+    struct InclusionRequest {
+        uint64 slot;
+        bytes32[] transactionHashes;
+    }
+}
+
 impl SendCommand {
     /// Run the `send` command.
     pub async fn run(self) -> Result<()> {
@@ -71,6 +90,14 @@ impl SendCommand {
         let target_slot = lookahead_res[0].slot;
         info!("Target slot: {}", target_slot);
 
+        // The requests are forwarded by the gateway to the sidecar of the target slot, so the
+        // domain must be the one of that sidecar rather than the one of the gateway.
+        let domain_url = match (&self.override_bolt_sidecar_url, &lookahead_res[0].sidecar_url) {
+            (None, Some(sidecar_url)) => sidecar_url.parse().wrap_err("invalid sidecar URL")?,
+            _ => target_url.clone(),
+        };
+        let domain = self.request_domain(&domain_url, &provider).await?;
+
         // Send the transactions to the Bolt sidecar
         let mut next_nonce = None;
         for _ in 0..self.count {
@@ -100,6 +127,7 @@ impl SendCommand {
                 target_slot,
                 target_url.clone(),
                 &wallet,
+                domain.as_ref(),
             )
             .await?;
 
@@ -122,6 +150,8 @@ impl SendCommand {
             .wallet(transaction_signer)
             .on_http(el_url);
 
+        let domain = self.request_domain(&sidecar_url, &provider).await?;
+
         // Fetch the current slot from the devnet beacon node
         let slot = request_current_slot_number(&cl_url).await?;
 
@@ -147,6 +177,7 @@ impl SendCommand {
                 slot + 2,
                 sidecar_url.clone(),
                 &wallet,
+                domain.as_ref(),
             )
             .await?;
 
@@ -156,6 +187,70 @@ impl SendCommand {
 
         Ok(())
     }
+
+    /// Returns the EIP-712 domain to sign the requests with, if enabled. If the operator of
+    /// the target sidecar isn't provided, the domain is fetched from the sidecar itself.
+    async fn request_domain<P: Provider>(
+        &self,
+        target_url: &Url,
+        provider: &P,
+    ) -> Result<Option<RequestDomain>> {
+        if !self.eip712 {
+            return Ok(None);
+        }
+
+        let domain = if let Some(operator) = self.operator {
+            RequestDomain { chain_id: provider.get_chain_id().await?, operator }
+        } else {
+            fetch_request_domain(target_url).await?
+        };
+
+        info!(chain_id = domain.chain_id, operator = %domain.operator, "Using EIP-712 domain");
+        Ok(Some(domain))
+    }
+}
+
+/// The parameters of the EIP-712 domain of the requests sent to a sidecar, as exposed in its
+/// `bolt_metadata` endpoint.
+#[derive(Debug, Clone, Copy, Deserialize)]
+struct RequestDomain {
+    chain_id: u64,
+    operator: Address,
+}
+
+impl RequestDomain {
+    fn eip712_domain(&self) -> Eip712Domain {
+        Eip712Domain::new(
+            Some(Cow::Borrowed(REQUEST_DOMAIN_NAME)),
+            Some(Cow::Borrowed(REQUEST_DOMAIN_VERSION)),
+            Some(U256::from(self.chain_id)),
+            Some(self.operator),
+            None,
+        )
+    }
+}
+
+async fn fetch_request_domain(target_url: &Url) -> Result<RequestDomain> {
+    let request = serde_json::json!({
+        "id": "1",
+        "jsonrpc": "2.0",
+        "method": "bolt_metadata",
+        "params": [],
+    });
+
+    let response = reqwest::Client::new()
+        .post(target_url.clone())
+        .json(&request)
+        .send()
+        .await
+        .wrap_err("failed to fetch sidecar metadata")?
+        .json::<Value>()
+        .await?;
+
+    let domain = response
+        .pointer("/result/request_domain")
+        .wrap_err("the target does not support EIP-712 requests, try setting --operator")?;
+    Ok(serde_json::from_value(domain.clone())?)
 }
 
 async fn request_current_slot_number(beacon_url: &Url) -> Result<u64> {
@@ -186,6 +281,7 @@ async fn send_rpc_request(
     target_slot: u64,
     target_sidecar_url: Url,
     wallet: &PrivateKeySigner,
+    domain: Option<&RequestDomain>,
 ) -> Result<()> {
     let request = prepare_rpc_request(
         "bolt_requestInclusion",
@@ -196,7 +292,7 @@ async fn send_rpc_request(
     );
 
     info!(?tx_hashes, target_slot, %target_sidecar_url);
-    let signature = sign_request(tx_hashes, target_slot, wallet).await?;
+    let signature = sign_request(tx_hashes, target_slot, wallet, domain).await?;
 
     let response = reqwest::Client::new()
         .post(target_sidecar_url)
@@ -219,7 +315,16 @@ async fn sign_request(
     tx_hashes: Vec<B256>,
     target_slot: u64,
     wallet: &PrivateKeySigner,
+    domain: Option<&RequestDomain>,
 ) -> eyre::Result<String> {
+    if let Some(domain) = domain {
+        let typed = InclusionRequest { slot: target_slot, transactionHashes: tx_hashes };
+        let digest = typed.eip712_signing_hash(&domain.eip712_domain());
+        let signature = hex::encode_prefixed(wallet.sign_hash(&digest).await?.as_bytes());
+
+        return Ok(format!("{EIP712_V1_SCHEME}:{}:{}", wallet.address(), signature));
+    }
+
     let digest = {
         let mut data = Vec::new();
         let hashes = tx_hashes.iter().map(|hash| hash.as_slice()).collect::<Vec<_>>().concat();
@@ -255,3 +360,28 @@ pub struct LookaheadSlot {
     /// Optional URL of the Bolt sidecar associated with the proposer
     pub sidecar_url: Option<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::b256;
+
+    use super::*;
+
+    #[test]
+    fn test_eip712_signing_hash() {
+        // Must match the signing hash computed by the sidecar for the same request, see
+        // `test_eip712_signing_hash` in `bolt-sidecar/src/primitives/commitment.rs`.
+        let typed = InclusionRequest {
+            slot: 633067,
+            transactionHashes: vec![b256!(
+                "ea49f475608be9311fd787f4b30425464a48508b0890b27802d5501f050c6c1b"
+            )],
+        };
+        let domain = RequestDomain { chain_id: 17000, operator: Address::repeat_byte(1) };
+
+        assert_eq!(
+            typed.eip712_signing_hash(&domain.eip712_domain()),
+            b256!("cbed59f0d3ff554624dcd53972c76b2e365b866d250c909599b17c5a5cc3ed62")
+        );
+    }
+}
@@ -91,3 +91,85 @@ sol! {
         error KeyNotFound();
     }
 }
+
+sol! {
+    #[allow(missing_docs)]
+    #[sol(rpc)]
+    interface BoltChallenger {
+        #[derive(Debug, Serialize)]
+        enum ChallengeStatus {
+            Open,
+            Defended,
+            Breached
+        }
+
+        #[derive(Debug, Serialize)]
+        struct Challenge {
+            bytes32 id;
+            uint48 openedAt;
+            ChallengeStatus status;
+            uint256 targetSlot;
+            address challenger;
+            address commitmentSigner;
+            address commitmentReceiver;
+            TransactionData[] committedTxs;
+        }
+
+        #[derive(Debug, Serialize)]
+        struct SignedCommitment {
+            uint64 slot;
+            bytes signature;
+            bytes signedTx;
+        }
+
+        #[derive(Debug, Serialize)]
+        struct TransactionData {
+            bytes32 txHash;
+            uint256 nonce;
+            uint256 gasLimit;
+        }
+
+        /// @notice The Bolt parameters contract.
+        function parameters() external view returns (address);
+
+        /// @notice Get all the challenges.
+        function getAllChallenges() external view returns (Challenge[] memory);
+
+        /// @notice Get the open challenges.
+        function getOpenChallenges() external view returns (Challenge[] memory);
+
+        /// @notice Get a challenge by its ID.
+        function getChallengeByID(bytes32 challengeID) external view returns (Challenge memory);
+
+        /// @notice Open a challenge against a bundle of committed transactions.
+        /// @dev The challenge bond must be sent along with this call.
+        function openChallenge(SignedCommitment[] calldata commitments) external payable;
+
+        /// @notice Resolve a challenge that has expired without being resolved.
+        function resolveExpiredChallenge(bytes32 challengeID) external;
+
+        error SlotInTheFuture();
+        error BlockIsNotFinalized();
+        error IncorrectChallengeBond();
+        error ChallengeAlreadyExists();
+        error ChallengeAlreadyResolved();
+        error ChallengeDoesNotExist();
+        error ChallengeNotExpired();
+        error EmptyCommitments();
+        error UnexpectedMixedSenders();
+        error UnexpectedMixedSlots();
+        error UnexpectedMixedSigners();
+        error UnexpectedNonceOrder();
+        error BondTransferFailed();
+    }
+
+    #[allow(missing_docs)]
+    #[sol(rpc)]
+    interface BoltParameters {
+        /// @notice The maximum duration of a challenge before it is considered breached.
+        function MAX_CHALLENGE_DURATION() external view returns (uint48);
+
+        /// @notice The bond required to open a challenge.
+        function CHALLENGE_BOND() external view returns (uint256);
+    }
+}
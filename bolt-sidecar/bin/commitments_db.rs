@@ -0,0 +1,10 @@
+use clap::Parser;
+
+use bolt_sidecar::config::CommitmentsDbCommand;
+
+/// Offline maintenance of the signed commitments database, without starting the sidecar.
+fn main() -> eyre::Result<()> {
+    let _ = dotenvy::dotenv();
+
+    CommitmentsDbCommand::parse().run()
+}
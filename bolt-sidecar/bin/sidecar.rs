@@ -1,8 +1,11 @@
 use eyre::bail;
-use tracing::info;
+use tracing::{error, info};
 
 use bolt_sidecar::{
-    common::BOLT_SIDECAR_VERSION, config::Opts, telemetry::init_telemetry_stack, SidecarDriver,
+    common::BOLT_SIDECAR_VERSION,
+    config::Opts,
+    telemetry::{init_telemetry_stack, shutdown_tracer_provider},
+    SidecarDriver,
 };
 
 const BOLT: &str = r#"
@@ -20,21 +23,37 @@ async fn main() -> eyre::Result<()> {
 
     let opts = Opts::try_parse()?;
 
-    init_telemetry_stack(opts.telemetry.metrics_port())?;
+    init_telemetry_stack(opts.telemetry.metrics_port(), opts.telemetry.otlp_endpoint())?;
+
+    // Install the default TLS provider used by the gRPC clients of remote signers
+    if let Err(err) = rustls::crypto::ring::default_provider().install_default() {
+        error!("Failed to install default TLS provider: {:?}", err);
+    }
 
     info!(chain = opts.chain.name(), "Starting Bolt sidecar");
 
     let use_local_signer = opts.constraint_signing.constraint_private_key.is_some();
     let use_commit_boost_signer = opts.constraint_signing.commit_boost_signer_url.is_some();
     let use_keystore_signer = opts.constraint_signing.keystore_path.is_some();
+    let use_web3signer = opts.constraint_signing.web3signer_url.is_some();
+    let use_dirk_signer = opts.constraint_signing.dirk_url.is_some();
 
     if use_local_signer {
-        SidecarDriver::with_local_signer(&opts).await?.run_forever().await
+        SidecarDriver::with_local_signer(&opts).await?.run().await
     } else if use_commit_boost_signer {
-        SidecarDriver::with_commit_boost_signer(&opts).await?.run_forever().await
+        SidecarDriver::with_commit_boost_signer(&opts).await?.run().await
     } else if use_keystore_signer {
-        SidecarDriver::with_keystore_signer(&opts).await?.run_forever().await
+        SidecarDriver::with_keystore_signer(&opts).await?.run().await
+    } else if use_web3signer {
+        SidecarDriver::with_web3signer(&opts).await?.run().await
+    } else if use_dirk_signer {
+        SidecarDriver::with_dirk_signer(&opts).await?.run().await
     } else {
         bail!("No signing method specified")
     }
+
+    // Export the spans that are still buffered before exiting
+    shutdown_tracer_provider();
+
+    Ok(())
 }
@@ -0,0 +1,747 @@
+use std::{collections::HashSet, fs, io, net::SocketAddr, path::PathBuf, sync::Arc};
+
+use alloy::primitives::Address;
+use axum::{
+    extract::{Path, Request, State},
+    http::{header::AUTHORIZATION, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use ethereum_consensus::crypto::PublicKey as BlsPublicKey;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use subtle::ConstantTimeEq;
+use tokio::{
+    net::TcpListener,
+    sync::{mpsc, oneshot},
+};
+use tracing::{error, info, warn};
+
+use crate::{
+    api::spec::{BuilderApiError, ConstraintsApi},
+    client::ConstraintsMultiplexer,
+    config::ChainConfig,
+    primitives::{
+        read_signed_revocations_from_file, write_signed_revocations_to_file,
+        BatchedSignedConstraints, DelegationsReport, SignedDelegation, SignedRevocation, Slot,
+    },
+    state::{
+        commitments_db::{CommitmentsDbError, CommitmentsInterchange},
+        consensus::ConsensusSnapshot,
+        fulfillment::SlotAudit,
+        AccountSnapshot, CommitmentsDb, ExecutionSnapshot, FulfillmentAudits,
+    },
+};
+
+/// The path to the admin API delegations endpoint.
+pub const DELEGATIONS_PATH: &str = "/admin/v1/delegations";
+/// The path to the admin API revocations endpoint.
+pub const REVOCATIONS_PATH: &str = "/admin/v1/revocations";
+/// The path to the admin API signed commitments export and import endpoint.
+pub const COMMITMENTS_PATH: &str = "/admin/v1/commitments";
+/// The path to the admin API commitments fulfillment audits endpoint.
+pub const FULFILLMENT_PATH: &str = "/admin/v1/fulfillment";
+
+/// The path to the admin API consensus state endpoint.
+pub const CONSENSUS_PATH: &str = "/admin/v1/consensus";
+/// The path to the admin API execution state endpoint.
+pub const EXECUTION_PATH: &str = "/admin/v1/execution";
+/// The path to the admin API account states cache endpoint.
+pub const ACCOUNTS_PATH: &str = "/admin/v1/accounts";
+/// The path to the admin API signers endpoint.
+pub const SIGNERS_PATH: &str = "/admin/v1/signers";
+/// The path to the admin API endpoint pausing the acceptance of commitments.
+pub const PAUSE_PATH: &str = "/admin/v1/pause";
+/// The path to the admin API endpoint resuming the acceptance of commitments.
+pub const RESUME_PATH: &str = "/admin/v1/resume";
+/// The path to the admin API endpoint resubmitting the constraints of a slot.
+pub const RESUBMIT_CONSTRAINTS_PATH: &str = "/admin/v1/constraints/:slot/resubmit";
+
+/// The maximum number of slot audits returned by the fulfillment endpoint.
+const MAX_FULFILLMENT_AUDITS: usize = 64;
+
+/// Errors that can occur when handling admin API requests.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum AdminApiError {
+    #[error("Missing or invalid admin token")]
+    Unauthorized,
+    #[error("Invalid delegations: {0}")]
+    InvalidDelegations(String),
+    #[error("Invalid signature for revocation {0:?} -> {1:?}")]
+    InvalidRevocation(BlsPublicKey, BlsPublicKey),
+    #[error("Failed to persist revocations: {0}")]
+    RevocationsPersistence(String),
+    #[error("Applied locally but failed to propagate to relays: {0}")]
+    Propagation(#[from] BuilderApiError),
+    #[error("Failed to import signed commitments: {0}")]
+    CommitmentsDb(#[from] CommitmentsDbError),
+    #[error("No constraints found for slot {0}")]
+    NoConstraints(Slot),
+    #[error("Failed to resubmit constraints: {0}")]
+    Resubmission(BuilderApiError),
+    #[error("Failed to persist the commitments status: {0}")]
+    StatusPersistence(String),
+    #[error("The sidecar driver is not responding")]
+    DriverUnavailable,
+}
+
+impl IntoResponse for AdminApiError {
+    fn into_response(self) -> Response {
+        let status = match self {
+            Self::Unauthorized => StatusCode::UNAUTHORIZED,
+            Self::InvalidDelegations(_) => StatusCode::BAD_REQUEST,
+            Self::InvalidRevocation(..) => StatusCode::BAD_REQUEST,
+            Self::RevocationsPersistence(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::Propagation(_) => StatusCode::BAD_GATEWAY,
+            Self::CommitmentsDb(_) => StatusCode::BAD_REQUEST,
+            Self::NoConstraints(_) => StatusCode::NOT_FOUND,
+            Self::Resubmission(_) => StatusCode::BAD_GATEWAY,
+            Self::StatusPersistence(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::DriverUnavailable => StatusCode::SERVICE_UNAVAILABLE,
+        };
+
+        (status, Json(ErrorBody { message: self.to_string() })).into_response()
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ErrorBody {
+    message: String,
+}
+
+/// A request from the admin API to the sidecar driver, which owns the consensus and execution
+/// states. Each request carries the channel on which the driver responds.
+#[derive(Debug)]
+pub enum AdminRequest {
+    /// Get a snapshot of the consensus state.
+    Consensus(oneshot::Sender<ConsensusSnapshot>),
+    /// Get a snapshot of the execution state.
+    Execution(oneshot::Sender<ExecutionSnapshot>),
+    /// Get the contents of the account states cache.
+    Accounts(oneshot::Sender<Vec<AccountSnapshot>>),
+    /// Get the public keys of the signers.
+    Signers(oneshot::Sender<SignersInfo>),
+    /// Pause or resume the acceptance of commitments, responding with the resulting state.
+    /// The state is applied even if it couldn't be persisted.
+    SetPaused(bool, oneshot::Sender<Result<CommitmentsStatus, AdminApiError>>),
+    /// Get the signed constraints of a pending slot.
+    Constraints(Slot, oneshot::Sender<Option<BatchedSignedConstraints>>),
+}
+
+/// The signers of the sidecar.
+#[derive(Debug, Clone, Serialize)]
+pub struct SignersInfo {
+    /// The kind of constraints signer, e.g. `local` or `web3signer`.
+    pub constraint_signer: &'static str,
+    /// The public keys available to sign constraints.
+    pub constraint_pubkeys: Vec<BlsPublicKey>,
+    /// The address of the commitment signer.
+    pub commitment_signer: Address,
+}
+
+/// Whether the sidecar accepts new commitments, persisted so that a pause survives restarts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommitmentsStatus {
+    /// Whether the acceptance of commitments is paused by the operator.
+    pub paused: bool,
+}
+
+impl CommitmentsStatus {
+    /// Reads the status persisted in the given file. A missing file means that commitments
+    /// were never paused.
+    pub fn read_from_file(path: impl AsRef<std::path::Path>) -> io::Result<Self> {
+        match fs::read(path) {
+            Ok(contents) => Ok(serde_json::from_slice(&contents)?),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Atomically persists the status in the given file.
+    pub fn write_to_file(&self, path: impl AsRef<std::path::Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, serde_json::to_vec(self)?)?;
+        fs::rename(tmp_path, path)
+    }
+}
+
+/// The result of a manual resubmission of constraints.
+#[derive(Debug, Clone, Serialize)]
+pub struct ResubmissionResult {
+    /// The slot of the resubmitted constraints.
+    pub slot: Slot,
+    /// The number of resubmitted constraints.
+    pub constraints: usize,
+    /// The outcome of the resubmission to each relay.
+    pub relays: Vec<RelayResubmission>,
+}
+
+/// The outcome of a manual resubmission of constraints to a relay.
+#[derive(Debug, Clone, Serialize)]
+pub struct RelayResubmission {
+    /// The URL of the relay.
+    pub relay: String,
+    /// The error returned by the relay, if the resubmission failed.
+    pub error: Option<String>,
+}
+
+/// Configuration for the admin API server.
+#[derive(Debug, Clone)]
+pub struct AdminApiConfig {
+    /// The port on which the admin API should listen on localhost.
+    pub port: u16,
+    /// The bearer token required to authenticate requests.
+    pub token: String,
+    /// The constraints client, holding the delegations of the sidecar.
+    pub constraints_client: ConstraintsMultiplexer,
+    /// The chain to verify the signatures of delegations and revocations against.
+    pub chain: ChainConfig,
+    /// The public keys available in the constraint signer, which every delegatee must be one of.
+    pub available_pubkeys: HashSet<BlsPublicKey>,
+    /// The file in which revocations are persisted, so that they survive restarts.
+    pub revocations_path: Option<PathBuf>,
+    /// The database of signed commitments, exported and imported through the API.
+    pub commitments_db: Arc<Mutex<CommitmentsDb>>,
+    /// The audits of the commitments made for past slots.
+    pub fulfillment: FulfillmentAudits,
+    /// The channel to send requests to the sidecar driver.
+    pub driver: mpsc::Sender<AdminRequest>,
+}
+
+/// The shared state of the admin API handlers.
+#[derive(Debug)]
+struct AdminApiState {
+    token: String,
+    constraints_client: ConstraintsMultiplexer,
+    chain: ChainConfig,
+    available_pubkeys: HashSet<BlsPublicKey>,
+    revocations_path: Option<PathBuf>,
+    commitments_db: Arc<Mutex<CommitmentsDb>>,
+    fulfillment: FulfillmentAudits,
+    driver: mpsc::Sender<AdminRequest>,
+}
+
+impl AdminApiState {
+    /// Applies the given update to the revocations persisted in the revocations file, if any.
+    /// Without a revocations file, revocations applied at runtime are lost on restart.
+    fn update_persisted_revocations(
+        &self,
+        update: impl FnOnce(&mut Vec<SignedRevocation>),
+    ) -> Result<(), AdminApiError> {
+        let Some(path) = &self.revocations_path else {
+            warn!("No revocations path configured, revocations will not persist across restarts");
+            return Ok(());
+        };
+
+        let mut revocations = if path.exists() {
+            read_signed_revocations_from_file(path)
+                .map_err(|err| AdminApiError::RevocationsPersistence(err.to_string()))?
+        } else {
+            Vec::new()
+        };
+
+        update(&mut revocations);
+
+        write_signed_revocations_to_file(path, &revocations).map_err(|err| {
+            error!(?err, ?path, "Failed to persist revocations");
+            AdminApiError::RevocationsPersistence(err.to_string())
+        })
+    }
+
+    /// Sends a request to the driver and waits for its response.
+    async fn request<T>(
+        &self,
+        request: impl FnOnce(oneshot::Sender<T>) -> AdminRequest,
+    ) -> Result<T, AdminApiError> {
+        let (tx, rx) = oneshot::channel();
+        self.driver.send(request(tx)).await.map_err(|_| AdminApiError::DriverUnavailable)?;
+        rx.await.map_err(|_| AdminApiError::DriverUnavailable)
+    }
+}
+
+/// Start the admin API server with the given configuration.
+///
+/// The server only listens on localhost and requires every request to carry the configured
+/// token as `Authorization: Bearer <token>`.
+pub async fn start_admin_server(config: AdminApiConfig) -> eyre::Result<()> {
+    let addr = SocketAddr::from(([127, 0, 0, 1], config.port));
+    let listener = TcpListener::bind(addr).await?;
+    info!(%addr, "Admin API server bound");
+
+    let state = Arc::new(AdminApiState {
+        token: config.token,
+        constraints_client: config.constraints_client,
+        chain: config.chain,
+        available_pubkeys: config.available_pubkeys,
+        revocations_path: config.revocations_path,
+        commitments_db: config.commitments_db,
+        fulfillment: config.fulfillment,
+        driver: config.driver,
+    });
+    axum::serve(listener, make_router(state)).await?;
+
+    Ok(())
+}
+
+/// Creates the admin API [Router] with the authentication middleware.
+fn make_router(state: Arc<AdminApiState>) -> Router {
+    Router::new()
+        .route(DELEGATIONS_PATH, get(get_delegations).post(post_delegations))
+        .route(REVOCATIONS_PATH, post(post_revocations))
+        .route(COMMITMENTS_PATH, get(get_commitments).post(post_commitments))
+        .route(FULFILLMENT_PATH, get(get_fulfillment))
+        .route(CONSENSUS_PATH, get(get_consensus))
+        .route(EXECUTION_PATH, get(get_execution))
+        .route(ACCOUNTS_PATH, get(get_accounts))
+        .route(SIGNERS_PATH, get(get_signers))
+        .route(PAUSE_PATH, post(pause))
+        .route(RESUME_PATH, post(resume))
+        .route(RESUBMIT_CONSTRAINTS_PATH, post(resubmit_constraints))
+        .route_layer(middleware::from_fn_with_state(state.clone(), authenticate))
+        .with_state(state)
+}
+
+/// Middleware rejecting requests that don't carry the admin token.
+async fn authenticate(
+    State(state): State<Arc<AdminApiState>>,
+    req: Request,
+    next: Next,
+) -> Result<Response, AdminApiError> {
+    let token = req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    match token {
+        Some(token) if bool::from(token.as_bytes().ct_eq(state.token.as_bytes())) => {
+            Ok(next.run(req).await)
+        }
+        _ => {
+            warn!(path = %req.uri().path(), "Rejected unauthenticated admin API request");
+            Err(AdminApiError::Unauthorized)
+        }
+    }
+}
+
+/// Returns the active delegations of the sidecar.
+async fn get_delegations(State(state): State<Arc<AdminApiState>>) -> Json<Vec<SignedDelegation>> {
+    Json(state.constraints_client.delegations().all())
+}
+
+/// Adds new delegations and propagates them to the relays. Delegations submitted through the
+/// admin API re-enable previously revoked delegatees, and their persisted revocations are
+/// removed.
+///
+/// Like the delegations loaded from disk, the delegations are rejected as a whole if any of them
+/// has an invalid signature or a delegatee that isn't available in the constraint signer.
+async fn post_delegations(
+    State(state): State<Arc<AdminApiState>>,
+    Json(delegations): Json<Vec<SignedDelegation>>,
+) -> Result<Json<Vec<SignedDelegation>>, AdminApiError> {
+    let report = DelegationsReport::new(&state.chain, &delegations, &state.available_pubkeys);
+    report.log();
+    report.into_result().map_err(|err| AdminApiError::InvalidDelegations(err.to_string()))?;
+
+    let pairs = delegations
+        .iter()
+        .map(|d| (d.validator_pubkey.clone(), d.delegatee_pubkey.clone()))
+        .collect::<HashSet<_>>();
+    let result = state.constraints_client.update_delegations(delegations, true).await;
+
+    state.update_persisted_revocations(|revocations| {
+        revocations
+            .retain(|r| !pairs.contains(&(r.validator_pubkey.clone(), r.delegatee_pubkey.clone())));
+    })?;
+
+    Ok(Json(result?))
+}
+
+/// Revokes delegatees and propagates the revocations to the relays. The revocations are
+/// persisted, so that revoked delegatees stay revoked after a restart.
+///
+/// Like the revocations loaded from disk, the revocations are rejected as a whole if any of them
+/// has an invalid signature.
+async fn post_revocations(
+    State(state): State<Arc<AdminApiState>>,
+    Json(revocations): Json<Vec<SignedRevocation>>,
+) -> Result<Json<Vec<SignedRevocation>>, AdminApiError> {
+    if let Some(invalid) = revocations.iter().find(|r| !r.verify(&state.chain)) {
+        return Err(AdminApiError::InvalidRevocation(
+            invalid.validator_pubkey.clone(),
+            invalid.delegatee_pubkey.clone(),
+        ));
+    }
+
+    let result = state.constraints_client.revoke_delegations(revocations.clone()).await;
+
+    state.update_persisted_revocations(|persisted| {
+        for revocation in revocations {
+            let exists = persisted.iter().any(|r| {
+                r.validator_pubkey == revocation.validator_pubkey &&
+                    r.delegatee_pubkey == revocation.delegatee_pubkey
+            });
+            if !exists {
+                persisted.push(revocation);
+            }
+        }
+    })?;
+
+    Ok(Json(result?))
+}
+
+/// Exports the signed commitments of the sidecar in the interchange format.
+async fn get_commitments(State(state): State<Arc<AdminApiState>>) -> Json<CommitmentsInterchange> {
+    Json(state.commitments_db.lock().export())
+}
+
+/// Imports signed commitments in the interchange format, for instance exported from another
+/// sidecar instance. Returns the number of newly imported commitments.
+async fn post_commitments(
+    State(state): State<Arc<AdminApiState>>,
+    Json(interchange): Json<CommitmentsInterchange>,
+) -> Result<Json<usize>, AdminApiError> {
+    let imported = state.commitments_db.lock().import(interchange)?;
+    Ok(Json(imported))
+}
+
+/// Returns the fulfillment audits of the most recent slots with commitments, latest first.
+async fn get_fulfillment(State(state): State<Arc<AdminApiState>>) -> Json<Vec<SlotAudit>> {
+    Json(state.fulfillment.recent(MAX_FULFILLMENT_AUDITS))
+}
+
+/// Returns the current epoch and proposer duties.
+async fn get_consensus(
+    State(state): State<Arc<AdminApiState>>,
+) -> Result<Json<ConsensusSnapshot>, AdminApiError> {
+    state.request(AdminRequest::Consensus).await.map(Json)
+}
+
+/// Returns the execution head, basefees and pending block templates.
+async fn get_execution(
+    State(state): State<Arc<AdminApiState>>,
+) -> Result<Json<ExecutionSnapshot>, AdminApiError> {
+    state.request(AdminRequest::Execution).await.map(Json)
+}
+
+/// Returns the contents of the account states cache.
+async fn get_accounts(
+    State(state): State<Arc<AdminApiState>>,
+) -> Result<Json<Vec<AccountSnapshot>>, AdminApiError> {
+    state.request(AdminRequest::Accounts).await.map(Json)
+}
+
+/// Returns the public keys of the constraints and commitment signers.
+async fn get_signers(
+    State(state): State<Arc<AdminApiState>>,
+) -> Result<Json<SignersInfo>, AdminApiError> {
+    state.request(AdminRequest::Signers).await.map(Json)
+}
+
+/// Pauses the acceptance of new commitments. Commitments already signed are unaffected.
+async fn pause(
+    State(state): State<Arc<AdminApiState>>,
+) -> Result<Json<CommitmentsStatus>, AdminApiError> {
+    warn!("Pausing the acceptance of commitments");
+    state.request(|tx| AdminRequest::SetPaused(true, tx)).await?.map(Json)
+}
+
+/// Resumes the acceptance of new commitments.
+async fn resume(
+    State(state): State<Arc<AdminApiState>>,
+) -> Result<Json<CommitmentsStatus>, AdminApiError> {
+    info!("Resuming the acceptance of commitments");
+    state.request(|tx| AdminRequest::SetPaused(false, tx)).await?.map(Json)
+}
+
+/// Submits again all the signed constraints of a pending slot to every relay, including the ones
+/// that were already submitted successfully, and reports the outcome for each relay.
+///
+/// Fails only if the resubmission failed for all relays.
+async fn resubmit_constraints(
+    State(state): State<Arc<AdminApiState>>,
+    Path(slot): Path<Slot>,
+) -> Result<Json<ResubmissionResult>, AdminApiError> {
+    let constraints = state
+        .request(|tx| AdminRequest::Constraints(slot, tx))
+        .await?
+        .filter(|constraints| !constraints.is_empty())
+        .ok_or(AdminApiError::NoConstraints(slot))?;
+
+    info!(slot, count = constraints.len(), "Manually resubmitting constraints");
+    let client = &state.constraints_client;
+    let all_relays = (0..client.relays_len()).collect::<Vec<_>>();
+    let results = client.submit_constraints_to(&constraints, &all_relays).await;
+
+    let targets = client.targets();
+    let relays = results
+        .iter()
+        .map(|(index, result)| RelayResubmission {
+            relay: targets[*index].to_string(),
+            error: result.as_ref().err().map(ToString::to_string),
+        })
+        .collect::<Vec<_>>();
+
+    if relays.iter().all(|relay| relay.error.is_some()) {
+        let err = results.into_any_ok().expect_err("resubmission failed for all relays");
+        return Err(AdminApiError::Resubmission(err));
+    }
+
+    for relay in relays.iter().filter(|relay| relay.error.is_some()) {
+        warn!(slot, relay = relay.relay, error = ?relay.error, "Failed to resubmit constraints");
+    }
+
+    Ok(Json(ResubmissionResult { slot, constraints: constraints.len(), relays }))
+}
+
+#[cfg(test)]
+mod tests {
+    use ethereum_consensus::crypto::Signature as BlsSignature;
+
+    use super::*;
+    use crate::{
+        api::spec::SUBMIT_CONSTRAINTS_PATH,
+        client::ConstraintsClient,
+        crypto::SignableBLS,
+        primitives::{ConstraintsMessage, DelegationMessage, RevocationMessage, SignedConstraints},
+        signer::local::LocalSigner,
+        test_util::{random_constraints, temp_file_path},
+    };
+
+    fn sign(signer: &LocalSigner, digest: [u8; 32]) -> BlsSignature {
+        let signature = signer.sign_commit_boost_root(digest).unwrap();
+        BlsSignature::try_from(signature.as_ref()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_admin_delegations_lifecycle() -> eyre::Result<()> {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let validator_signer = LocalSigner::random();
+        let validator = validator_signer.pubkey();
+        let delegatee = LocalSigner::random().pubkey();
+        let revocations_path = std::env::temp_dir()
+            .join(format!("bolt_sidecar_revocations_{}.json", rand::random::<u64>()));
+
+        // No relays are reachable, so propagation is expected to fail.
+        let constraints_client = ConstraintsMultiplexer::new(vec![ConstraintsClient::new(
+            reqwest::Url::parse("http://127.0.0.1:1").unwrap(),
+        )]);
+        let state = Arc::new(AdminApiState {
+            token: "secret".to_string(),
+            constraints_client: constraints_client.clone(),
+            chain: ChainConfig::mainnet(),
+            available_pubkeys: HashSet::from([delegatee.clone()]),
+            revocations_path: Some(revocations_path.clone()),
+            commitments_db: Arc::new(Mutex::new(CommitmentsDb::in_memory(1))),
+            fulfillment: FulfillmentAudits::default(),
+            driver: mpsc::channel(1).0,
+        });
+
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        tokio::spawn(async move { axum::serve(listener, make_router(state)).await });
+
+        let client = reqwest::Client::new();
+        let url = format!("http://{addr}{DELEGATIONS_PATH}");
+
+        let res = client.get(&url).send().await?;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+        let res = client.get(&url).bearer_auth("wrong").send().await?;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+        // Delegations with invalid signatures are rejected
+        let forged = SignedDelegation {
+            message: DelegationMessage::new(validator.clone(), delegatee.clone()),
+            signature: BlsSignature::default(),
+        };
+        let res = client.post(&url).bearer_auth("secret").json(&[forged]).send().await?;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        assert!(constraints_client.delegations().delegatees(&validator).is_empty());
+
+        let message = DelegationMessage::new(validator.clone(), delegatee.clone());
+        let delegation =
+            SignedDelegation { signature: sign(&validator_signer, message.digest()), message };
+
+        let res = client.post(&url).bearer_auth("secret").json(&[delegation]).send().await?;
+        assert_eq!(res.status(), StatusCode::BAD_GATEWAY);
+        assert!(constraints_client.delegations().delegatees(&validator).contains(&delegatee));
+
+        let revocations_url = format!("http://{addr}{REVOCATIONS_PATH}");
+
+        // Revocations with invalid signatures are rejected
+        let forged = SignedRevocation {
+            message: RevocationMessage::new(validator.clone(), delegatee.clone()),
+            signature: BlsSignature::default(),
+        };
+        let res =
+            client.post(&revocations_url).bearer_auth("secret").json(&[forged]).send().await?;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        assert!(!constraints_client.delegations().is_revoked(&validator, &delegatee));
+
+        let message = RevocationMessage::new(validator.clone(), delegatee.clone());
+        let revocation =
+            SignedRevocation { signature: sign(&validator_signer, message.digest()), message };
+
+        let res = client
+            .post(&revocations_url)
+            .bearer_auth("secret")
+            .json(&[revocation.clone()])
+            .send()
+            .await?;
+        assert_eq!(res.status(), StatusCode::BAD_GATEWAY);
+        assert!(constraints_client.delegations().delegatees(&validator).is_empty());
+        assert!(constraints_client.delegations().is_revoked(&validator, &delegatee));
+
+        // The revocation is persisted, so that it survives a restart
+        assert_eq!(read_signed_revocations_from_file(&revocations_path)?, vec![revocation]);
+
+        let res = client.get(&url).bearer_auth("secret").send().await?;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert!(res.json::<Vec<SignedDelegation>>().await?.is_empty());
+
+        let _ = std::fs::remove_file(revocations_path);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_admin_driver_requests() -> eyre::Result<()> {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        // A relay accepting constraints, next to an unreachable one
+        let relay = Router::new().route(SUBMIT_CONSTRAINTS_PATH, post(|| async { StatusCode::OK }));
+        let relay_listener = TcpListener::bind("127.0.0.1:0").await?;
+        let relay_url = format!("http://{}", relay_listener.local_addr()?);
+        tokio::spawn(async move { axum::serve(relay_listener, relay).await });
+
+        // A stand-in for the driver, holding the paused flag and pending constraints for slot 1
+        let (driver_tx, mut driver_rx) = mpsc::channel(8);
+        tokio::spawn(async move {
+            let mut paused = false;
+            while let Some(request) = driver_rx.recv().await {
+                match request {
+                    AdminRequest::Consensus(tx) => {
+                        let _ = tx.send(ConsensusSnapshot {
+                            epoch: 0,
+                            epoch_start_slot: 0,
+                            latest_slot: 1,
+                            unsafe_lookahead_enabled: false,
+                            proposer_duties: Vec::new(),
+                        });
+                    }
+                    AdminRequest::Execution(tx) => {
+                        let _ = tx.send(ExecutionSnapshot {
+                            chain_id: 1,
+                            block_number: 1,
+                            slot: 1,
+                            basefee: 0,
+                            blob_basefee: 0,
+                            block_templates: Vec::new(),
+                        });
+                    }
+                    AdminRequest::Accounts(tx) => {
+                        let _ = tx.send(Vec::new());
+                    }
+                    AdminRequest::Signers(tx) => {
+                        let _ = tx.send(SignersInfo {
+                            constraint_signer: "local",
+                            constraint_pubkeys: Vec::new(),
+                            commitment_signer: Address::ZERO,
+                        });
+                    }
+                    AdminRequest::SetPaused(value, tx) => {
+                        paused = value;
+                        let _ = tx.send(Ok(CommitmentsStatus { paused }));
+                    }
+                    AdminRequest::Constraints(slot, tx) => {
+                        let transaction = random_constraints(1).remove(0);
+                        let message =
+                            ConstraintsMessage::from_tx(Default::default(), 1, transaction);
+                        let constraints =
+                            vec![SignedConstraints { message, signature: Default::default() }];
+                        let _ = tx.send((slot == 1).then_some(constraints));
+                    }
+                }
+            }
+        });
+
+        let constraints_client = ConstraintsMultiplexer::new(vec![
+            ConstraintsClient::new(reqwest::Url::parse(&relay_url).unwrap()),
+            ConstraintsClient::new(reqwest::Url::parse("http://127.0.0.1:1").unwrap()),
+        ]);
+        let state = Arc::new(AdminApiState {
+            token: "secret".to_string(),
+            constraints_client,
+            chain: ChainConfig::mainnet(),
+            available_pubkeys: HashSet::new(),
+            revocations_path: None,
+            commitments_db: Arc::new(Mutex::new(CommitmentsDb::in_memory(1))),
+            fulfillment: FulfillmentAudits::default(),
+            driver: driver_tx,
+        });
+
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        tokio::spawn(async move { axum::serve(listener, make_router(state)).await });
+
+        let client = reqwest::Client::new();
+
+        let res = client.post(format!("http://{addr}{PAUSE_PATH}")).send().await?;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+        let res =
+            client.post(format!("http://{addr}{PAUSE_PATH}")).bearer_auth("secret").send().await?;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert!(res.json::<serde_json::Value>().await?["paused"].as_bool().unwrap());
+
+        let res =
+            client.post(format!("http://{addr}{RESUME_PATH}")).bearer_auth("secret").send().await?;
+        assert!(!res.json::<serde_json::Value>().await?["paused"].as_bool().unwrap());
+
+        for path in [CONSENSUS_PATH, EXECUTION_PATH, ACCOUNTS_PATH, SIGNERS_PATH] {
+            let res =
+                client.get(format!("http://{addr}{path}")).bearer_auth("secret").send().await?;
+            assert_eq!(res.status(), StatusCode::OK);
+        }
+
+        let res = client
+            .post(format!("http://{addr}/admin/v1/constraints/42/resubmit"))
+            .bearer_auth("secret")
+            .send()
+            .await?;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+        // The resubmission succeeds if any relay accepted the constraints, reporting the others
+        let res = client
+            .post(format!("http://{addr}/admin/v1/constraints/1/resubmit"))
+            .bearer_auth("secret")
+            .send()
+            .await?;
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = res.json::<serde_json::Value>().await?;
+        assert_eq!(body["constraints"], 1);
+        assert!(body["relays"][0]["error"].is_null());
+        assert!(body["relays"][1]["error"].is_string());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_commitments_status_persistence() -> eyre::Result<()> {
+        let path = temp_file_path("bolt_admin_state");
+
+        // Commitments are not paused until persisted otherwise
+        assert_eq!(CommitmentsStatus::read_from_file(&path)?, CommitmentsStatus::default());
+
+        CommitmentsStatus { paused: true }.write_to_file(&path)?;
+        assert!(CommitmentsStatus::read_from_file(&path)?.paused);
+
+        std::fs::remove_file(path)?;
+        Ok(())
+    }
+}
@@ -3,32 +3,37 @@ use std::{sync::Arc, time::Duration};
 use axum::{
     body::{self, Body},
     extract::{Path, Request, State},
-    http::StatusCode,
-    response::Html,
+    http::{header::CONTENT_TYPE, HeaderMap, HeaderName, HeaderValue, StatusCode},
+    response::{Html, IntoResponse, Response},
     routing::{get, post},
     Json, Router,
 };
 use beacon_api_client::VersionedValue;
 use ethereum_consensus::{
     builder::SignedValidatorRegistration,
-    deneb::mainnet::SignedBlindedBeaconBlock,
+    deneb::mainnet::{BeaconBlock, BeaconBlockBody, SignedBeaconBlock, SignedBlindedBeaconBlock},
     primitives::{BlsPublicKey, Hash32},
+    ssz::prelude::{deserialize, serialize, SimpleSerialize},
+    types::mainnet::ExecutionPayload,
     Fork,
 };
+use futures::future::join_all;
 use parking_lot::Mutex;
+use reqwest::Url;
 use serde::Deserialize;
 use thiserror::Error;
 use tokio::net::TcpListener;
 use tracing::{debug, error, info, warn};
 
 use super::spec::{
-    BuilderApiError, ConstraintsApi, GET_HEADER_PATH, GET_PAYLOAD_PATH, REGISTER_VALIDATORS_PATH,
-    STATUS_PATH,
+    BuilderApiError, ConstraintsApi, Encoding, CONSENSUS_VERSION_HEADER, GET_HEADER_PATH,
+    GET_PAYLOAD_PATH, REGISTER_VALIDATORS_PATH, STATUS_PATH,
 };
 use crate::{
     builder::PayloadFetcher,
-    client::ConstraintsClient,
-    primitives::{GetPayloadResponse, SignedBuilderBid},
+    client::{BeaconClient, ConstraintsMultiplexer},
+    primitives::{GetPayloadResponse, SignedBlockContents, SignedBuilderBid},
+    state::signed_headers::{HeaderSource, SignedHeaderRecord, SignedHeadersDb},
     telemetry::ApiMetrics,
 };
 
@@ -37,6 +42,10 @@ const MAX_BLINDED_BLOCK_LENGTH: usize = 1024 * 1024;
 /// TODO: determine value
 const GET_HEADER_WITH_PROOFS_TIMEOUT: Duration = Duration::from_millis(500);
 
+/// The maximum time to wait for fallback relays to return a payload when the
+/// main relay fails to unblind a signed header.
+const PAYLOAD_RECOVERY_TIMEOUT: Duration = Duration::from_secs(2);
+
 /// A proxy server for the builder API.
 /// Forwards all requests to the target after interception.
 #[derive(Debug)]
@@ -46,10 +55,19 @@ pub struct BuilderProxyServer<T, P> {
     local_payload: Mutex<Option<GetPayloadResponse>>,
     /// The payload fetcher to get locally built payloads.
     payload_fetcher: P,
+    /// Persisted record of the headers signed by our proposers, used to refuse
+    /// serving conflicting headers or payloads for the same slot.
+    signed_headers: Arc<Mutex<SignedHeadersDb>>,
+    /// Other relays to query for the payload if the proxy target fails to unblind a
+    /// signed header.
+    fallback_relays: Option<T>,
+    /// Beacon nodes to publish the full block to, if its payload was recovered
+    /// from a fallback relay.
+    block_publishers: Vec<BeaconClient>,
 }
 
 /// Parameters for the get_header request.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 #[allow(missing_docs)]
 pub struct GetHeaderParams {
     pub slot: u64,
@@ -64,8 +82,31 @@ where
     P: PayloadFetcher + Send + Sync,
 {
     /// Create a new builder proxy server.
-    pub fn new(proxy_target: T, payload_fetcher: P) -> Self {
-        Self { proxy_target, local_payload: Mutex::new(None), payload_fetcher }
+    pub fn new(
+        proxy_target: T,
+        payload_fetcher: P,
+        signed_headers: Arc<Mutex<SignedHeadersDb>>,
+    ) -> Self {
+        Self {
+            proxy_target,
+            local_payload: Mutex::new(None),
+            payload_fetcher,
+            signed_headers,
+            fallback_relays: None,
+            block_publishers: Vec::new(),
+        }
+    }
+
+    /// Set the fallback relays and the beacon nodes used to recover and publish a block
+    /// whose payload the proxy target failed to return.
+    pub fn with_payload_recovery(
+        mut self,
+        fallback_relays: Option<T>,
+        block_publishers: Vec<BeaconClient>,
+    ) -> Self {
+        self.fallback_relays = fallback_relays;
+        self.block_publishers = block_publishers;
+        self
     }
 
     /// Gets the status. Just forwards the request to constraints client and returns the status.
@@ -103,11 +144,18 @@ where
     ///
     /// In case of a builder or relay failure, we return the locally built block header
     /// and store the actual payload so we can return it later.
+    ///
+    /// If a header has already been signed for this slot, any header with a different
+    /// block hash is refused to prevent equivocation.
+    ///
+    /// The bid is returned SSZ-encoded if the beacon client prefers `application/octet-stream`.
     pub async fn get_header(
         State(server): State<Arc<Self>>,
+        headers: HeaderMap,
         Path(params): Path<GetHeaderParams>,
-    ) -> Result<Json<VersionedValue<SignedBuilderBid>>, BuilderApiError> {
+    ) -> Result<Response, BuilderApiError> {
         let start = std::time::Instant::now();
+        let encoding = Encoding::from_accept(&headers);
 
         debug!("Received get_header request");
         let slot = params.slot;
@@ -121,6 +169,11 @@ where
             Ok(res) => match res {
                 Err(builder_err) => builder_err,
                 Ok(header) => {
+                    server
+                        .signed_headers
+                        .lock()
+                        .check(slot, &header.data.message.header.block_hash)?;
+
                     // Clear the local payload cache if we have a successful response
                     // By definition of `server.local_payload`, this will be `Some` IFF we have
                     // signed a local header
@@ -128,14 +181,16 @@ where
                     *local_payload = None;
 
                     debug!(elapsed = ?start.elapsed(), "Returning signed builder bid");
-                    return Ok(Json(header));
+                    return signed_bid_response(encoding, header);
                 }
             },
             Err(err) => BuilderApiError::Timeout(err),
         };
 
         // On ANY error, we fall back to locally built block
-        warn!(slot, elapsed = ?start.elapsed(), err = ?err, "Proxy error, fetching local payload instead");
+        let reason = err.fallback_reason();
+        warn!(slot, elapsed = ?start.elapsed(), err = ?err, reason, "Proxy error, fetching local payload instead");
+        ApiMetrics::increment_get_header_fallbacks(reason);
 
         let Some(payload_and_bid) = server.payload_fetcher.fetch_payload(slot).await else {
             // In this case, we don't have a fallback block which means we haven't made any
@@ -148,6 +203,8 @@ where
         let number = payload_and_bid.bid.message.header.block_number;
         info!(elapsed = ?start.elapsed(), %hash, "Fetched local payload for slot {slot}");
 
+        server.signed_headers.lock().check(slot, &hash)?;
+
         {
             // Since we've signed a local header, set the payload for
             // the following `get_payload` request.
@@ -162,18 +219,28 @@ where
         };
 
         info!(elapsed = ?start.elapsed(), %hash, number, ?versioned_bid, "Returning locally built header");
-        Ok(Json(versioned_bid))
+        signed_bid_response(encoding, versioned_bid)
     }
 
     /// Gets the payload. If we have a locally built payload, we return it.
     /// Otherwise, we forward the request to the constraints client.
+    ///
+    /// Both JSON and SSZ-encoded signed blinded blocks are accepted, and the payload
+    /// is returned in the encoding preferred by the beacon client.
+    ///
+    /// The signed header is recorded in the signed headers database before any payload
+    /// is served. Requests for a header that conflicts with an already signed one for the
+    /// same slot are refused.
     pub async fn get_payload(
         State(server): State<Arc<Self>>,
         req: Request<Body>,
-    ) -> Result<Json<GetPayloadResponse>, BuilderApiError> {
+    ) -> Result<Response, BuilderApiError> {
         let start = std::time::Instant::now();
         debug!("Received get_payload request");
 
+        let request_encoding = Encoding::from_content_type(req.headers());
+        let response_encoding = Encoding::from_accept(req.headers());
+
         let body_bytes =
             body::to_bytes(req.into_body(), MAX_BLINDED_BLOCK_LENGTH).await.map_err(|e| {
                 error!(error = %e, "Failed to read request body");
@@ -181,50 +248,264 @@ where
             })?;
 
         // Convert to signed blinded beacon block
-        let signed_blinded_block = serde_json::from_slice::<SignedBlindedBeaconBlock>(&body_bytes)
-            .map_err(|e| {
-                error!(error = %e, "Failed to parse signed blinded block");
-                e
-            })?;
+        let signed_blinded_block = match request_encoding {
+            Encoding::Json => serde_json::from_slice::<SignedBlindedBeaconBlock>(&body_bytes)
+                .map_err(|e| {
+                    error!(error = %e, "Failed to parse signed blinded block");
+                    BuilderApiError::from(e)
+                })?,
+            Encoding::Ssz => deserialize::<SignedBlindedBeaconBlock>(&body_bytes).map_err(|e| {
+                error!(error = ?e, "Failed to decode SSZ signed blinded block");
+                BuilderApiError::from(e)
+            })?,
+        };
+
+        let slot = signed_blinded_block.message.slot;
+        let signed_header = &signed_blinded_block.message.body.execution_payload_header;
 
         // If we have a locally built payload, it means we signed a local header.
         // Return it and clear the cache.
         if let Some(local_payload) = server.local_payload.lock().take() {
-            check_locally_built_payload_integrity(&signed_blinded_block, &local_payload)?;
+            check_payload_integrity(&signed_blinded_block, &local_payload)?;
+
+            server.signed_headers.lock().insert(
+                slot,
+                SignedHeaderRecord {
+                    block_hash: signed_header.block_hash.clone(),
+                    parent_hash: signed_header.parent_hash.clone(),
+                    source: HeaderSource::Local,
+                },
+            )?;
 
             info!("Valid local block found, returning: {:?}", local_payload.block_hash());
             ApiMetrics::increment_local_blocks_proposed();
 
-            return Ok(Json(local_payload));
+            return payload_response(response_encoding, local_payload);
         }
 
-        // TODO: how do we deal with failures here? What if we submit the signed blinded block but
-        // don't get a response? should we ignore the error or proceed with a local block
-        // (highly risky -> equivocation risk)
-        let payload = server
-            .proxy_target
-            .get_payload(signed_blinded_block)
-            .await
-            .map(Json)
-            .map_err(|e| {
-                error!(elapsed = ?start.elapsed(), error = %e, "Failed to get payload from constraints client");
-                e
-            })?;
+        // Record the signed header before revealing it to the relay. From this point on,
+        // we will never serve a different payload for this slot, even if the relay fails
+        // to return the payload and even across restarts.
+        server.signed_headers.lock().insert(
+            slot,
+            SignedHeaderRecord {
+                block_hash: signed_header.block_hash.clone(),
+                parent_hash: signed_header.parent_hash.clone(),
+                source: HeaderSource::Remote,
+            },
+        )?;
+
+        let payload = match server.proxy_target.get_payload(signed_blinded_block.clone()).await {
+            Ok(payload) => payload,
+            Err(err) => {
+                error!(elapsed = ?start.elapsed(), error = %err, "Failed to get payload from constraints client");
+
+                // The header has already been signed, so the only safe way forward is to
+                // recover the exact same payload from somewhere else.
+                let Some(payload) = server.recover_payload(&signed_blinded_block).await else {
+                    return Err(err);
+                };
+
+                payload
+            }
+        };
 
         info!(elapsed = ?start.elapsed(), "Returning payload from constraints client");
         ApiMetrics::increment_remote_blocks_proposed();
 
-        Ok(payload)
+        payload_response(response_encoding, payload)
+    }
+
+    /// Try to recover the payload for a signed blinded block from the fallback relays.
+    ///
+    /// If a payload matching the signed header is found, the full block is also published
+    /// directly to the configured beacon nodes in the background, in case the beacon client
+    /// has already given up on the `get_payload` request.
+    async fn recover_payload(
+        &self,
+        signed_blinded_block: &SignedBlindedBeaconBlock,
+    ) -> Option<GetPayloadResponse> {
+        let fallback_relays = self.fallback_relays.as_ref()?;
+
+        let slot = signed_blinded_block.message.slot;
+        warn!(slot, "Querying fallback relays for payload");
+
+        // Submitting the same signed blinded block to other relays is safe: it can only
+        // be unblinded into the payload committed to by the signed header.
+        let request = async {
+            let payload = fallback_relays.get_payload(signed_blinded_block.clone()).await?;
+            check_payload_integrity(signed_blinded_block, &payload)?;
+            Ok::<_, BuilderApiError>(payload)
+        };
+
+        let payload = match tokio::time::timeout(PAYLOAD_RECOVERY_TIMEOUT, request).await {
+            Ok(Ok(payload)) => payload,
+            Ok(Err(err)) => {
+                error!(slot, ?err, "Failed to recover payload from fallback relays");
+                return None;
+            }
+            Err(_) => {
+                error!(slot, "Timed out recovering payload from fallback relays");
+                return None;
+            }
+        };
+
+        info!(slot, block_hash = %payload.block_hash(), "Recovered payload from fallback relay");
+
+        // Guard: never publish a block whose payload doesn't match the header we signed.
+        if let Err(err) = self.signed_headers.lock().check(slot, payload.block_hash()) {
+            error!(slot, ?err, "Recovered payload conflicts with signed header, not publishing");
+            return None;
+        }
+
+        match to_signed_block_contents(signed_blinded_block, &payload) {
+            Ok(block_contents) => {
+                let publishers = self.block_publishers.clone();
+                tokio::spawn(publish_block_contents(publishers, block_contents, slot));
+            }
+            Err(err) => error!(slot, ?err, "Failed to build full block from recovered payload"),
+        }
+
+        Some(payload)
     }
 }
 
+/// Build a `getHeader` response in the given encoding.
+fn signed_bid_response(
+    encoding: Encoding,
+    bid: VersionedValue<SignedBuilderBid>,
+) -> Result<Response, BuilderApiError> {
+    match encoding {
+        Encoding::Json => Ok(Json(bid).into_response()),
+        Encoding::Ssz => ssz_response(&bid.data),
+    }
+}
+
+/// Build a `submitBlindedBlock` response in the given encoding.
+fn payload_response(
+    encoding: Encoding,
+    payload: GetPayloadResponse,
+) -> Result<Response, BuilderApiError> {
+    match encoding {
+        Encoding::Json => Ok(Json(payload).into_response()),
+        Encoding::Ssz => {
+            let Some(payload) = payload.to_ssz_deneb() else {
+                let fork = payload.execution_payload().version();
+                return Err(BuilderApiError::InvalidFork(fork.to_string()));
+            };
+            ssz_response(&payload)
+        }
+    }
+}
+
+/// Build an SSZ-encoded response for a Deneb Builder API object.
+fn ssz_response<T: SimpleSerialize>(value: &T) -> Result<Response, BuilderApiError> {
+    let bytes = serialize(value)?;
+    let headers = [
+        (CONTENT_TYPE, Encoding::Ssz.header_value()),
+        (HeaderName::from_static(CONSENSUS_VERSION_HEADER), HeaderValue::from_static("deneb")),
+    ];
+
+    Ok((headers, bytes).into_response())
+}
+
+/// Publish the full block to all the given beacon nodes concurrently.
+async fn publish_block_contents(
+    publishers: Vec<BeaconClient>,
+    block_contents: SignedBlockContents,
+    slot: u64,
+) {
+    let results = join_all(publishers.iter().map(|beacon| async {
+        (beacon.url(), beacon.publish_block_contents(&block_contents).await)
+    }))
+    .await;
+
+    for (url, result) in results {
+        match result {
+            Ok(()) => info!(slot, %url, "Published recovered block to beacon node"),
+            Err(err) => {
+                error!(slot, %url, ?err, "Failed to publish recovered block to beacon node")
+            }
+        }
+    }
+}
+
+/// Build a full Deneb [`SignedBlockContents`] from a signed blinded block and its
+/// matching payload.
+///
+/// The signature over the blinded block is also valid for the full block, since the
+/// hash tree root of the execution payload matches the one of its header.
+fn to_signed_block_contents(
+    signed_blinded_block: &SignedBlindedBeaconBlock,
+    payload: &GetPayloadResponse,
+) -> Result<SignedBlockContents, BuilderApiError> {
+    let fork = payload.execution_payload().version();
+    let GetPayloadResponse::Deneb(payload_and_blobs) = payload else {
+        return Err(BuilderApiError::InvalidFork(fork.to_string()));
+    };
+    let ExecutionPayload::Deneb(execution_payload) = &payload_and_blobs.execution_payload else {
+        return Err(BuilderApiError::InvalidFork(fork.to_string()));
+    };
+
+    // Guard: the blobs must match the commitments included in the signed block.
+    let blinded_block = &signed_blinded_block.message;
+    let blinded_body = &blinded_block.body;
+    if !blinded_body
+        .blob_kzg_commitments
+        .iter()
+        .eq(payload_and_blobs.blobs_bundle.commitments.iter())
+    {
+        return Err(BuilderApiError::Generic("blob commitments mismatch".to_string()));
+    }
+
+    let body = BeaconBlockBody {
+        randao_reveal: blinded_body.randao_reveal.clone(),
+        eth1_data: blinded_body.eth1_data.clone(),
+        graffiti: blinded_body.graffiti.clone(),
+        proposer_slashings: blinded_body.proposer_slashings.clone(),
+        attester_slashings: blinded_body.attester_slashings.clone(),
+        attestations: blinded_body.attestations.clone(),
+        deposits: blinded_body.deposits.clone(),
+        voluntary_exits: blinded_body.voluntary_exits.clone(),
+        sync_aggregate: blinded_body.sync_aggregate.clone(),
+        execution_payload: execution_payload.clone(),
+        bls_to_execution_changes: blinded_body.bls_to_execution_changes.clone(),
+        blob_kzg_commitments: blinded_body.blob_kzg_commitments.clone(),
+    };
+
+    let signed_block = SignedBeaconBlock {
+        message: BeaconBlock {
+            slot: blinded_block.slot,
+            proposer_index: blinded_block.proposer_index,
+            parent_root: blinded_block.parent_root,
+            state_root: blinded_block.state_root,
+            body,
+        },
+        signature: signed_blinded_block.signature.clone(),
+    };
+
+    Ok(SignedBlockContents {
+        signed_block,
+        kzg_proofs: payload_and_blobs.blobs_bundle.proofs.clone(),
+        blobs: payload_and_blobs.blobs_bundle.blobs.clone(),
+    })
+}
+
 /// Configuration for the builder proxy.
 #[derive(Debug, Clone)]
 pub struct BuilderProxyConfig {
-    /// The target constraints client server.
-    pub constraints_client: ConstraintsClient,
+    /// The target constraints client servers.
+    pub constraints_client: ConstraintsMultiplexer,
     /// The port on which the builder proxy should listen.
     pub server_port: u16,
+    /// The persisted record of signed headers, shared with the fulfillment auditor.
+    pub signed_headers: Arc<Mutex<SignedHeadersDb>>,
+    /// Other relays to query for the payload if the constraints client fails to unblind
+    /// a signed header.
+    pub fallback_relays: Option<ConstraintsMultiplexer>,
+    /// Beacon nodes to publish the full block to when its payload was recovered
+    /// from a fallback relay.
+    pub beacon_api_urls: Vec<Url>,
 }
 
 /// Start the builder proxy with the given payload fetcher and configuration.
@@ -237,11 +518,15 @@ where
 {
     info!(
         port = config.server_port,
-        target = config.constraints_client.target(),
+        targets = ?config.constraints_client.targets(),
         "Starting builder proxy..."
     );
 
-    let server = Arc::new(BuilderProxyServer::new(config.constraints_client, payload_fetcher));
+    let block_publishers = config.beacon_api_urls.into_iter().map(BeaconClient::new).collect();
+    let server = Arc::new(
+        BuilderProxyServer::new(config.constraints_client, payload_fetcher, config.signed_headers)
+            .with_payload_recovery(config.fallback_relays, block_publishers),
+    );
 
     let router = Router::new()
         .route("/", get(index))
@@ -262,12 +547,12 @@ async fn index() -> Html<&'static str> {
     Html("Hello")
 }
 
-/// Errors that can occur when checking the integrity of a locally built payload.
+/// Errors that can occur when checking the integrity of a payload against its signed header.
 #[derive(Error, Debug, Clone)]
 #[allow(missing_docs)]
-pub enum LocalPayloadIntegrityError {
+pub enum PayloadIntegrityError {
     #[error(
-        "Locally built payload does not match signed header. 
+        "Payload does not match signed header. 
         {field_name} mismatch: expected {expected}, have {have}"
     )]
     FieldMismatch { field_name: String, expected: String, have: String },
@@ -281,9 +566,9 @@ macro_rules! assert_payload_fields_eq {
                 field_name = stringify!($field_name),
                 expected = %$expected,
                 have = %$have,
-                "Local block does not match signed header"
+                "Payload does not match signed header"
             );
-            return Err(LocalPayloadIntegrityError::FieldMismatch {
+            return Err(PayloadIntegrityError::FieldMismatch {
                 field_name: stringify!($field_name).to_string(),
                 expected: $expected.to_string(),
                 have: $have.to_string(),
@@ -292,104 +577,105 @@ macro_rules! assert_payload_fields_eq {
     };
 }
 
-/// Perform some integrity checks on the locally built payload.
+/// Perform some integrity checks on a payload, either built locally or returned by a
+/// relay, against the header signed by the proposer.
 /// This is to ensure that the beacon node will accept the header that was signed
 /// when we submit the full payload.
 #[inline]
-fn check_locally_built_payload_integrity(
+fn check_payload_integrity(
     signed_blinded_block: &SignedBlindedBeaconBlock,
-    local_payload: &GetPayloadResponse,
-) -> Result<(), LocalPayloadIntegrityError> {
+    payload: &GetPayloadResponse,
+) -> Result<(), PayloadIntegrityError> {
     let header_signed_by_cl = &signed_blinded_block.message.body.execution_payload_header;
-    let local_execution_payload = local_payload.execution_payload();
+    let execution_payload = payload.execution_payload();
 
     assert_payload_fields_eq!(
         &header_signed_by_cl.block_hash,
-        local_execution_payload.block_hash(),
+        execution_payload.block_hash(),
         BlockHash
     );
 
     assert_payload_fields_eq!(
         header_signed_by_cl.block_number,
-        local_execution_payload.block_number(),
+        execution_payload.block_number(),
         BlockNumber
     );
 
     assert_payload_fields_eq!(
         &header_signed_by_cl.state_root,
-        local_execution_payload.state_root(),
+        execution_payload.state_root(),
         StateRoot
     );
 
     assert_payload_fields_eq!(
         &header_signed_by_cl.receipts_root,
-        local_execution_payload.receipts_root(),
+        execution_payload.receipts_root(),
         ReceiptsRoot
     );
 
     assert_payload_fields_eq!(
         &header_signed_by_cl.prev_randao,
-        local_execution_payload.prev_randao(),
+        execution_payload.prev_randao(),
         PrevRandao
     );
 
     assert_payload_fields_eq!(
         &header_signed_by_cl.gas_limit,
-        &local_execution_payload.gas_limit(),
+        &execution_payload.gas_limit(),
         GasLimit
     );
 
     assert_payload_fields_eq!(
         &header_signed_by_cl.gas_used,
-        &local_execution_payload.gas_used(),
+        &execution_payload.gas_used(),
         GasUsed
     );
 
     assert_payload_fields_eq!(
         &header_signed_by_cl.timestamp,
-        &local_execution_payload.timestamp(),
+        &execution_payload.timestamp(),
         Timestamp
     );
 
     assert_payload_fields_eq!(
         &header_signed_by_cl.extra_data,
-        local_execution_payload.extra_data(),
+        execution_payload.extra_data(),
         ExtraData
     );
 
     assert_payload_fields_eq!(
         &header_signed_by_cl.base_fee_per_gas,
-        local_execution_payload.base_fee_per_gas(),
+        execution_payload.base_fee_per_gas(),
         BaseFeePerGas
     );
 
     assert_payload_fields_eq!(
         &header_signed_by_cl.parent_hash,
-        local_execution_payload.parent_hash(),
+        execution_payload.parent_hash(),
         ParentHash
     );
 
     assert_payload_fields_eq!(
         &header_signed_by_cl.fee_recipient,
-        local_execution_payload.fee_recipient(),
+        execution_payload.fee_recipient(),
         FeeRecipient
     );
 
     assert_payload_fields_eq!(
         &header_signed_by_cl.logs_bloom,
-        local_execution_payload.logs_bloom(),
+        execution_payload.logs_bloom(),
         LogsBloom
     );
 
     assert_payload_fields_eq!(
         &header_signed_by_cl.blob_gas_used,
-        &local_execution_payload.blob_gas_used().unwrap_or_default(),
+        &execution_payload.blob_gas_used().unwrap_or_default(),
         BlobGasUsed
     );
 
     assert_payload_fields_eq!(
         &header_signed_by_cl.excess_blob_gas,
-        &local_execution_payload.excess_blob_gas().unwrap_or_default(),
+        &execution_payload.excess_blob_gas().unwrap_or_default(),
         ExcessBlobGas
     );
 
@@ -401,3 +687,103 @@ fn check_locally_built_payload_integrity(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use ethereum_consensus::{
+        bellatrix::mainnet::Transaction,
+        crypto::{KzgCommitment, KzgProof},
+        deneb::mainnet::{
+            Blob, BlobsBundle, ExecutionPayload as DenebExecutionPayload, ExecutionPayloadHeader,
+        },
+        ssz::prelude::{HashTreeRoot, List},
+    };
+
+    use super::*;
+    use crate::primitives::{ExecutionPayloadAndBlobsBundle, PayloadAndBlobs};
+
+    #[test]
+    fn test_to_signed_block_contents() {
+        let signed_blinded_block = SignedBlindedBeaconBlock::default();
+        let payload = GetPayloadResponse::Deneb(PayloadAndBlobs::default());
+
+        let contents = to_signed_block_contents(&signed_blinded_block, &payload).unwrap();
+        assert!(contents.kzg_proofs.is_empty());
+        assert!(contents.blobs.is_empty());
+
+        let json = serde_json::to_value(&contents).unwrap();
+        let body = json.pointer("/signed_block/message/body").unwrap();
+        assert!(body.get("execution_payload_header").is_none());
+        assert!(body.get("execution_payload").is_some());
+    }
+
+    #[test]
+    fn test_to_signed_block_contents_with_transactions_and_blobs() {
+        let mut execution_payload = DenebExecutionPayload::default();
+        for i in 0..3u8 {
+            let tx = Transaction::try_from([0x02, i, 0xaa, 0xbb].as_slice()).unwrap();
+            execution_payload.transactions.push(tx);
+        }
+        execution_payload.block_number = 42;
+
+        // The signed header commits to the payload through its SSZ roots
+        let header = ExecutionPayloadHeader {
+            block_number: execution_payload.block_number,
+            block_hash: execution_payload.block_hash.clone(),
+            transactions_root: execution_payload.transactions.hash_tree_root().unwrap(),
+            withdrawals_root: execution_payload.withdrawals.hash_tree_root().unwrap(),
+            ..Default::default()
+        };
+
+        let commitment = KzgCommitment::try_from([1u8; 48].as_slice()).unwrap();
+        let proof = KzgProof::try_from([2u8; 48].as_slice()).unwrap();
+        let blob = Blob::try_from(vec![3u8; 131_072].as_slice()).unwrap();
+
+        let mut signed_blinded_block = SignedBlindedBeaconBlock::default();
+        signed_blinded_block.message.slot = 100;
+        signed_blinded_block.message.body.execution_payload_header = header;
+        signed_blinded_block.message.body.blob_kzg_commitments =
+            List::try_from(vec![commitment.clone()]).unwrap();
+
+        let payload = GetPayloadResponse::Deneb(PayloadAndBlobs {
+            execution_payload: ExecutionPayload::Deneb(execution_payload),
+            blobs_bundle: BlobsBundle {
+                commitments: vec![commitment],
+                proofs: vec![proof.clone()],
+                blobs: vec![blob.clone()],
+            },
+        });
+
+        let contents = to_signed_block_contents(&signed_blinded_block, &payload).unwrap();
+
+        let block = &contents.signed_block.message;
+        assert_eq!(block.slot, 100);
+        assert_eq!(block.body.execution_payload.transactions.len(), 3);
+        assert_eq!(block.body.blob_kzg_commitments.len(), 1);
+        assert_eq!(contents.kzg_proofs, vec![proof]);
+        assert_eq!(contents.blobs, vec![blob]);
+
+        // The proposer signature over the blinded block must be valid for the full block
+        assert_eq!(
+            block.hash_tree_root().unwrap(),
+            signed_blinded_block.message.hash_tree_root().unwrap()
+        );
+
+        // Blobs that don't match the signed commitments are refused
+        let GetPayloadResponse::Deneb(mut payload_and_blobs) = payload else { unreachable!() };
+        payload_and_blobs.blobs_bundle.commitments.clear();
+        let payload = GetPayloadResponse::Deneb(payload_and_blobs);
+        assert!(to_signed_block_contents(&signed_blinded_block, &payload).is_err());
+    }
+
+    #[test]
+    fn test_ssz_payload_roundtrip() {
+        let payload = GetPayloadResponse::Deneb(PayloadAndBlobs::default());
+
+        let bytes = serialize(&payload.to_ssz_deneb().unwrap()).unwrap();
+        let decoded: GetPayloadResponse =
+            deserialize::<ExecutionPayloadAndBlobsBundle>(&bytes).unwrap().into();
+
+        assert_eq!(decoded.block_hash(), payload.block_hash());
+    }
+}
@@ -1,20 +1,25 @@
-use std::sync::Arc;
+use std::{net::SocketAddr, sync::Arc};
 
 use axum::{
     body::Body,
-    extract::State,
-    http::{HeaderMap, Request},
+    extract::{ConnectInfo, State},
+    http::{HeaderMap, Request, StatusCode},
     response::Html,
     Json,
 };
 use axum_extra::extract::WithRejection;
 use serde_json::Value;
-use tracing::{debug, error, info, instrument};
+use tracing::{debug, error, info, instrument, Span};
 
 use crate::{
     api::commitments::headers::auth_from_headers,
     common::BOLT_SIDECAR_VERSION,
-    primitives::{commitment::SignatureError, InclusionRequest},
+    primitives::{
+        commitment::{RequestSigningScheme, SignatureError},
+        InclusionRequest,
+    },
+    state::health::HealthReport,
+    telemetry,
 };
 
 use super::{
@@ -30,10 +35,14 @@ use super::{
 #[instrument(skip_all, name = "POST /rpc", fields(method = %payload.method))]
 pub async fn rpc_entrypoint(
     headers: HeaderMap,
+    ConnectInfo(client_addr): ConnectInfo<SocketAddr>,
     State(api): State<Arc<CommitmentsApiInner>>,
     WithRejection(Json(payload), _): WithRejection<Json<JsonPayload>, CommitmentError>,
 ) -> Result<Json<JsonResponse>, CommitmentError> {
-    debug!("Received new request");
+    // Continue the trace of the caller, if any. The span is carried to the driver with the
+    // commitment event, so that signing and constraint submission are part of the same trace.
+    telemetry::set_parent_from_headers(&Span::current(), &headers);
+    debug!(trace_id = ?telemetry::trace_id(&Span::current()), "Received new request");
 
     match payload.method.as_str() {
         GET_VERSION_METHOD => Ok(Json(JsonResponse {
@@ -45,15 +54,20 @@ pub async fn rpc_entrypoint(
         GET_METADATA_METHOD => {
             let response = JsonResponse {
                 id: payload.id,
-                result: serde_json::to_value(api.limits()).expect("infallible"),
+                result: serde_json::to_value(api.metadata()).expect("infallible"),
                 ..Default::default()
             };
             Ok(Json(response))
         }
 
         REQUEST_INCLUSION_METHOD => {
+            // Rate limit requests before doing any work on them
+            api.check_rate_limits(client_addr.ip()).inspect_err(|err| {
+                debug!(?err, "Rate limited inclusion request");
+            })?;
+
             // Validate the authentication header and extract the signer and signature
-            let (signer, signature) = auth_from_headers(&headers).inspect_err(|e| {
+            let (signer, signature, scheme) = auth_from_headers(&headers).inspect_err(|e| {
                 error!("Failed to extract signature from headers: {:?}", e);
             })?;
 
@@ -71,7 +85,16 @@ pub async fn rpc_entrypoint(
             // Set the signature here for later processing
             inclusion_request.set_signature(signature);
 
-            let digest = inclusion_request.digest();
+            let digest = match scheme {
+                RequestSigningScheme::Digest => inclusion_request.digest(),
+                RequestSigningScheme::Eip712V1 => {
+                    let Some(domain) = api.request_domain() else {
+                        error!("EIP-712 signed requests are not supported");
+                        return Err(CommitmentError::MalformedHeader);
+                    };
+                    inclusion_request.eip712_signing_hash(&domain)
+                }
+            };
             let recovered_signer = signature.recover_address_from_prehash(&digest)?;
 
             if recovered_signer != signer {
@@ -88,12 +111,12 @@ pub async fn rpc_entrypoint(
             inclusion_request.set_signer(recovered_signer);
 
             info!(signer = ?recovered_signer, %digest, "New valid inclusion request received");
-            let inclusion_commitment = api.request_inclusion(inclusion_request).await?;
+            let inclusion_response = api.request_inclusion(inclusion_request).await?;
 
             // Create the JSON-RPC response
             let response = JsonResponse {
                 id: payload.id,
-                result: serde_json::to_value(inclusion_commitment).expect("infallible"),
+                result: serde_json::to_value(inclusion_response).expect("infallible"),
                 ..Default::default()
             };
 
@@ -120,3 +143,23 @@ pub async fn not_found(req: Request<Body>) -> Html<&'static str> {
 pub async fn status() -> Html<&'static str> {
     Html("OK")
 }
+
+/// Health handler, reporting the health of the sidecar and its dependencies.
+///
+/// This is a liveness check: it always responds with 200 OK while the server is running.
+#[instrument(skip_all, name = "GET /health")]
+pub async fn health(State(api): State<Arc<CommitmentsApiInner>>) -> Json<HealthReport> {
+    Json(api.health())
+}
+
+/// Readiness handler, reporting the health of the sidecar and its dependencies.
+///
+/// Responds with 503 Service Unavailable if the sidecar isn't ready to accept commitments.
+#[instrument(skip_all, name = "GET /ready")]
+pub async fn ready(
+    State(api): State<Arc<CommitmentsApiInner>>,
+) -> (StatusCode, Json<HealthReport>) {
+    let report = api.health();
+    let status = if report.ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (status, Json(report))
+}
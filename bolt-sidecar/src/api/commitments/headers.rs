@@ -3,18 +3,31 @@ use std::str::FromStr;
 use alloy::primitives::{Address, PrimitiveSignature as Signature};
 use axum::http::HeaderMap;
 
-use crate::primitives::commitment::SignatureError;
+use crate::primitives::commitment::{RequestSigningScheme, SignatureError};
 
-use super::spec::{CommitmentError, SIGNATURE_HEADER};
+use super::spec::{CommitmentError, EIP712_V1_SCHEME, SIGNATURE_HEADER};
 
 /// Extracts the signature ([SIGNATURE_HEADER]) from the HTTP headers.
+///
+/// The header value is either `<address>:<signature>` for a signature over the raw request
+/// digest, or `eip712-v1:<address>:<signature>` for a signature over the EIP-712 typed data.
 #[inline]
-pub fn auth_from_headers(headers: &HeaderMap) -> Result<(Address, Signature), CommitmentError> {
+pub fn auth_from_headers(
+    headers: &HeaderMap,
+) -> Result<(Address, Signature, RequestSigningScheme), CommitmentError> {
     let auth = headers.get(SIGNATURE_HEADER).ok_or(CommitmentError::NoSignature)?;
 
     // Remove the "0x" prefix
     let auth = auth.to_str().map_err(|_| CommitmentError::MalformedHeader)?;
 
+    let (scheme, auth) = match auth.strip_prefix(EIP712_V1_SCHEME) {
+        Some(auth) => {
+            let auth = auth.strip_prefix(':').ok_or(CommitmentError::MalformedHeader)?;
+            (RequestSigningScheme::Eip712V1, auth)
+        }
+        None => (RequestSigningScheme::Digest, auth),
+    };
+
     let mut split = auth.split(':');
 
     let address = split.next().ok_or(CommitmentError::MalformedHeader)?;
@@ -24,7 +37,7 @@ pub fn auth_from_headers(headers: &HeaderMap) -> Result<(Address, Signature), Co
     let sig =
         Signature::from_str(sig).map_err(|_| CommitmentError::InvalidSignature(SignatureError))?;
 
-    Ok((address, sig))
+    Ok((address, sig, scheme))
 }
 
 #[cfg(test)]
@@ -50,8 +63,21 @@ mod test {
             format!("{addr}:{}", expected_sig.as_bytes().encode_hex()).parse().unwrap(),
         );
 
-        let (address, signature) = auth_from_headers(&headers).unwrap();
+        let (address, signature, scheme) = auth_from_headers(&headers).unwrap();
+        assert_eq!(signature, Signature::try_from(expected_sig.as_bytes().as_ref()).unwrap());
+        assert_eq!(address, addr);
+        assert_eq!(scheme, RequestSigningScheme::Digest);
+
+        headers.insert(
+            SIGNATURE_HEADER,
+            format!("{EIP712_V1_SCHEME}:{addr}:{}", expected_sig.as_bytes().encode_hex())
+                .parse()
+                .unwrap(),
+        );
+
+        let (address, signature, scheme) = auth_from_headers(&headers).unwrap();
         assert_eq!(signature, Signature::try_from(expected_sig.as_bytes().as_ref()).unwrap());
         assert_eq!(address, addr);
+        assert_eq!(scheme, RequestSigningScheme::Eip712V1);
     }
 }
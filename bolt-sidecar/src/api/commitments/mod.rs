@@ -6,6 +6,8 @@ mod headers;
 mod jsonrpc;
 /// The commitments-API middleware.
 mod middleware;
+/// Rate limits and quotas for the commitments-API.
+pub mod rate_limit;
 /// The commitments-API JSON-RPC server implementation.
 pub mod server;
 /// The commitments-API specification and errors.
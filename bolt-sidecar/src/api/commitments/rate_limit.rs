@@ -0,0 +1,243 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    net::IpAddr,
+    num::NonZero,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use alloy::primitives::Address;
+use parking_lot::{Mutex, MutexGuard};
+
+use crate::{config::rate_limits::RateLimitOpts, primitives::Slot};
+
+use super::spec::RateLimitError;
+
+/// The duration of the window over which requests are counted.
+const WINDOW: Duration = Duration::from_secs(1);
+
+/// The requests counted in the current window.
+#[derive(Debug)]
+struct Window {
+    started: Instant,
+    total: u32,
+    by_ip: HashMap<IpAddr, u32>,
+    by_signer: HashMap<Address, u32>,
+}
+
+impl Window {
+    fn new() -> Self {
+        Self { started: Instant::now(), total: 0, by_ip: HashMap::new(), by_signer: HashMap::new() }
+    }
+}
+
+/// Limits the rate of commitment requests overall, per IP address and per signer.
+///
+/// Requests are counted over fixed windows of one second. Every request counts towards the
+/// per-IP and per-signer limits, whether it is accepted or not, while only the requests
+/// admitted by the per-IP limit count towards the overall limit, so that a single client
+/// can't exhaust it.
+///
+/// NOTE: the IP address is the one of the TCP connection. Behind a reverse proxy, all the
+/// requests share the address of the proxy, so the per-IP limit should be enforced by the
+/// proxy instead.
+#[derive(Debug)]
+pub struct RateLimiter {
+    opts: RateLimitOpts,
+    window: Mutex<Window>,
+}
+
+impl RateLimiter {
+    /// Creates a new rate limiter with the given limits.
+    pub fn new(opts: RateLimitOpts) -> Self {
+        Self { opts, window: Mutex::new(Window::new()) }
+    }
+
+    /// Counts a new request from the given IP address, returning an error if it exceeds the
+    /// per-IP or overall rate limits.
+    pub fn check_ip(&self, ip: IpAddr) -> Result<(), RateLimitError> {
+        let mut window = self.current_window();
+
+        let count = window.by_ip.entry(ip).or_default();
+        *count += 1;
+        if *count > self.opts.max_requests_per_ip.get() {
+            return Err(RateLimitError::Ip(ip));
+        }
+
+        if window.total >= self.opts.max_requests_per_second.get() {
+            return Err(RateLimitError::Global);
+        }
+        window.total += 1;
+
+        Ok(())
+    }
+
+    /// Counts a new request from the given signer, returning an error if it exceeds the
+    /// per-signer rate limit.
+    pub fn check_signer(&self, signer: Address) -> Result<(), RateLimitError> {
+        let mut window = self.current_window();
+
+        let count = window.by_signer.entry(signer).or_default();
+        *count += 1;
+        if *count > self.opts.max_requests_per_signer.get() {
+            return Err(RateLimitError::Signer(signer));
+        }
+
+        Ok(())
+    }
+
+    /// Returns the current window, starting a new one if the previous one has expired.
+    fn current_window(&self) -> MutexGuard<'_, Window> {
+        let mut window = self.window.lock();
+        if window.started.elapsed() >= WINDOW {
+            *window = Window::new();
+        }
+        window
+    }
+}
+
+/// The number of pending commitments per signer for each upcoming slot, shared between clones.
+///
+/// Commitments are reserved by the driver once the target slot of a request has been validated
+/// against the consensus state, so that only upcoming slots are tracked, and pruned as the
+/// head slot advances.
+#[derive(Debug, Clone)]
+pub struct PendingCommitments {
+    max_per_signer: NonZero<usize>,
+    /// The number of commitments made or in progress per signer, indexed by target slot.
+    slots: Arc<Mutex<BTreeMap<Slot, HashMap<Address, usize>>>>,
+}
+
+impl PendingCommitments {
+    /// Creates a new tracker with the given max pending commitments per signer for a slot.
+    pub fn new(max_per_signer: NonZero<usize>) -> Self {
+        Self { max_per_signer, slots: Default::default() }
+    }
+
+    /// Reserves a pending commitment for the given signer and slot, returning an error if the
+    /// signer already reached the max pending commitments for the slot.
+    ///
+    /// The reservation is released when the returned guard is dropped, unless it is kept with
+    /// [`PendingReservation::keep`] once the commitment has been made.
+    pub fn reserve(
+        &self,
+        signer: Address,
+        slot: Slot,
+    ) -> Result<PendingReservation, RateLimitError> {
+        let mut slots = self.slots.lock();
+        let count = slots.entry(slot).or_default().entry(signer).or_default();
+        if *count >= self.max_per_signer.get() {
+            return Err(RateLimitError::Pending(signer, slot));
+        }
+        *count += 1;
+
+        Ok(PendingReservation { pending: self.clone(), signer, slot, kept: false })
+    }
+
+    /// Releases a pending commitment reserved for the given signer and slot, dropping the
+    /// entries left empty.
+    fn release(&self, signer: Address, slot: Slot) {
+        let mut slots = self.slots.lock();
+        let Some(signers) = slots.get_mut(&slot) else { return };
+
+        if let Some(count) = signers.get_mut(&signer) {
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                signers.remove(&signer);
+            }
+        }
+        if signers.is_empty() {
+            slots.remove(&slot);
+        }
+    }
+
+    /// Drops the pending commitments for the given head slot and the ones before it.
+    pub fn prune(&self, head_slot: Slot) {
+        let mut slots = self.slots.lock();
+        *slots = slots.split_off(&(head_slot + 1));
+    }
+}
+
+/// A pending commitment reserved for a signer and slot, released on drop unless kept.
+#[derive(Debug)]
+pub struct PendingReservation {
+    pending: PendingCommitments,
+    signer: Address,
+    slot: Slot,
+    kept: bool,
+}
+
+impl PendingReservation {
+    /// Keeps the reservation, once the commitment has been made.
+    pub fn keep(mut self) {
+        self.kept = true;
+    }
+}
+
+impl Drop for PendingReservation {
+    fn drop(&mut self) {
+        if !self.kept {
+            self.pending.release(self.signer, self.slot);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZero;
+
+    use super::*;
+
+    fn limiter() -> RateLimiter {
+        RateLimiter::new(RateLimitOpts {
+            max_requests_per_second: NonZero::new(3).unwrap(),
+            max_requests_per_ip: NonZero::new(2).unwrap(),
+            max_requests_per_signer: NonZero::new(2).unwrap(),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_rate_limits() {
+        let limiter = limiter();
+        let (ip_a, ip_b, ip_c) =
+            ([127, 0, 0, 1].into(), [127, 0, 0, 2].into(), [127, 0, 0, 3].into());
+
+        assert!(limiter.check_ip(ip_a).is_ok());
+        assert!(limiter.check_ip(ip_a).is_ok());
+        assert!(matches!(limiter.check_ip(ip_a), Err(RateLimitError::Ip(ip)) if ip == ip_a));
+
+        // Requests rejected by the per-IP limit don't count towards the overall limit
+        assert!(limiter.check_ip(ip_b).is_ok());
+        assert!(matches!(limiter.check_ip(ip_c), Err(RateLimitError::Global)));
+
+        let signer = Address::random();
+        assert!(limiter.check_signer(signer).is_ok());
+        assert!(limiter.check_signer(signer).is_ok());
+        assert!(matches!(limiter.check_signer(signer), Err(RateLimitError::Signer(_))));
+        assert!(limiter.check_signer(Address::random()).is_ok());
+    }
+
+    #[test]
+    fn test_pending_commitments_per_signer() {
+        let pending = PendingCommitments::new(NonZero::new(1).unwrap());
+        let signer = Address::random();
+
+        pending.reserve(signer, 10).unwrap().keep();
+        assert!(matches!(pending.reserve(signer, 10), Err(RateLimitError::Pending(_, 10))));
+
+        // Other signers and slots are not affected
+        pending.reserve(Address::random(), 10).unwrap().keep();
+        pending.reserve(signer, 11).unwrap().keep();
+
+        // Dropped reservations are released, and their entries removed
+        drop(pending.reserve(signer, 12).unwrap());
+        assert!(!pending.slots.lock().contains_key(&12));
+        assert!(pending.reserve(signer, 12).is_ok());
+
+        // Pruning drops the slots up to the head slot
+        pending.prune(10);
+        assert_eq!(pending.slots.lock().keys().copied().collect::<Vec<_>>(), vec![11]);
+        pending.reserve(signer, 10).unwrap().keep();
+    }
+}
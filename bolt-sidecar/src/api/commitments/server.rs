@@ -1,7 +1,7 @@
 use std::{
     fmt,
     future::Future,
-    net::{SocketAddr, ToSocketAddrs},
+    net::{IpAddr, SocketAddr, ToSocketAddrs},
     pin::Pin,
     sync::Arc,
 };
@@ -11,26 +11,36 @@ use axum::{
     routing::{get, post},
     Router,
 };
+use serde::Serialize;
 use tokio::{
     net::TcpListener,
-    sync::{mpsc, oneshot},
+    sync::{
+        mpsc::{self, error::TrySendError},
+        oneshot, watch,
+    },
 };
 use tower_http::timeout::TimeoutLayer;
-use tracing::{error, info};
+use tracing::{error, info, Span};
 
 use crate::{
     api::commitments::handlers,
-    config::limits::LimitsOpts,
+    config::{limits::LimitsOpts, rate_limits::RateLimitOpts, ReloadableOpts},
     primitives::{
-        commitment::{InclusionCommitment, SignedCommitment},
+        commitment::{CommitmentResponse, RequestDomain},
         CommitmentRequest, InclusionRequest,
     },
+    state::{
+        health::HealthReport, validator_status::ValidatorStatusReport, SidecarHealth,
+        ValidatorStatuses,
+    },
+    telemetry::ApiMetrics,
 };
 
 use super::{
     middleware::track_server_metrics,
+    rate_limit::RateLimiter,
     spec,
-    spec::{CommitmentError, CommitmentsApi},
+    spec::{CommitmentError, CommitmentsApi, RateLimitError},
 };
 
 /// Event type emitted by the commitments API.
@@ -39,7 +49,22 @@ pub struct CommitmentEvent {
     /// The request to process.
     pub request: CommitmentRequest,
     /// The response channel.
-    pub response: oneshot::Sender<Result<SignedCommitment, CommitmentError>>,
+    pub response: oneshot::Sender<Result<CommitmentResponse, CommitmentError>>,
+    /// The span of the API request, used as the parent of the spans processing the event.
+    pub span: Span,
+}
+
+/// The sidecar metadata exposed in the `bolt_metadata` endpoint.
+#[derive(Debug, Serialize)]
+pub struct SidecarMetadata {
+    /// The sidecar's operating limits.
+    #[serde(flatten)]
+    pub limits: LimitsOpts,
+    /// The last known on-chain statuses of the validators.
+    pub validator_statuses: ValidatorStatusReport,
+    /// The EIP-712 domain of the typed-data signatures of requests, if supported.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_domain: Option<RequestDomain>,
 }
 
 /// The inner commitments-API handler that implements the [CommitmentsApi] spec.
@@ -50,17 +75,85 @@ pub struct CommitmentsApiInner {
     events: mpsc::Sender<CommitmentEvent>,
     /// The sidecar's operating limits that should be exposed in a metadata endpoint
     limits: LimitsOpts,
+    /// The options reloaded at runtime, overriding the limits above if set
+    reloads: Option<watch::Receiver<ReloadableOpts>>,
+    /// The on-chain statuses of the validators that should be exposed in a metadata endpoint
+    validator_statuses: ValidatorStatuses,
+    /// The health of the sidecar that should be exposed in the health endpoints
+    health: SidecarHealth,
+    /// The rate limits and quotas applied to commitment requests
+    rate_limiter: RateLimiter,
+    /// The EIP-712 domain of the typed-data signatures of requests, if supported
+    request_domain: Option<RequestDomain>,
 }
 
 impl CommitmentsApiInner {
     /// Creates a new instance of the commitments API handler.
-    pub fn new(events: mpsc::Sender<CommitmentEvent>, limits: LimitsOpts) -> Self {
-        Self { events, limits }
+    pub fn new(
+        events: mpsc::Sender<CommitmentEvent>,
+        limits: LimitsOpts,
+        validator_statuses: ValidatorStatuses,
+        health: SidecarHealth,
+    ) -> Self {
+        Self {
+            events,
+            limits,
+            reloads: None,
+            validator_statuses,
+            health,
+            rate_limiter: RateLimiter::new(RateLimitOpts::default()),
+            request_domain: None,
+        }
+    }
+
+    /// Follows the limits reloaded at runtime from the given channel.
+    pub fn with_reloads(mut self, reloads: watch::Receiver<ReloadableOpts>) -> Self {
+        self.reloads = Some(reloads);
+        self
+    }
+
+    /// Applies the given rate limits and quotas to commitment requests.
+    pub fn with_rate_limits(mut self, rate_limits: RateLimitOpts) -> Self {
+        self.rate_limiter = RateLimiter::new(rate_limits);
+        self
+    }
+
+    /// Accepts requests signed over their EIP-712 typed data in the given domain.
+    pub fn with_request_domain(mut self, request_domain: RequestDomain) -> Self {
+        self.request_domain = Some(request_domain);
+        self
+    }
+
+    /// Returns the EIP-712 domain of the typed-data signatures of requests, if supported.
+    pub fn request_domain(&self) -> Option<RequestDomain> {
+        self.request_domain
+    }
+
+    /// Counts a new commitment request from the given IP address, returning an error if it
+    /// exceeds the overall or per-IP rate limits.
+    pub fn check_rate_limits(&self, ip: IpAddr) -> Result<(), RateLimitError> {
+        self.rate_limiter.check_ip(ip).inspect_err(|err| {
+            ApiMetrics::increment_rate_limited_requests(err.reason());
+        })
     }
 
     /// Returns the operating limits for the sidecar.
     pub fn limits(&self) -> LimitsOpts {
-        self.limits
+        self.reloads.as_ref().map_or(self.limits, |reloads| reloads.borrow().limits)
+    }
+
+    /// Returns the metadata of the sidecar.
+    pub fn metadata(&self) -> SidecarMetadata {
+        SidecarMetadata {
+            limits: self.limits(),
+            validator_statuses: self.validator_statuses.report(),
+            request_domain: self.request_domain,
+        }
+    }
+
+    /// Returns the last health report of the sidecar.
+    pub fn health(&self) -> HealthReport {
+        self.health.report()
     }
 }
 
@@ -69,17 +162,32 @@ impl CommitmentsApi for CommitmentsApiInner {
     async fn request_inclusion(
         &self,
         inclusion_request: InclusionRequest,
-    ) -> Result<InclusionCommitment, CommitmentError> {
+    ) -> Result<CommitmentResponse, CommitmentError> {
+        // The signer is always recovered before the request reaches this point.
+        let signer = inclusion_request.signer.unwrap_or_default();
+
+        if let Err(err) = self.rate_limiter.check_signer(signer) {
+            ApiMetrics::increment_rate_limited_requests(err.reason());
+            return Err(err.into());
+        }
+
         let (response_tx, response_rx) = oneshot::channel();
 
         let event = CommitmentEvent {
             request: CommitmentRequest::Inclusion(inclusion_request),
             response: response_tx,
+            span: Span::current(),
         };
 
-        self.events.send(event).await.unwrap();
-
-        response_rx.await.map_err(|_| CommitmentError::Internal)?.map(|c| c.into())
+        // Reject the request right away instead of waiting for room in the queue.
+        match self.events.try_send(event) {
+            Ok(()) => response_rx.await.unwrap_or(Err(CommitmentError::Internal)),
+            Err(TrySendError::Full(_)) => {
+                ApiMetrics::increment_rate_limited_requests(RateLimitError::QueueFull.reason());
+                Err(RateLimitError::QueueFull.into())
+            }
+            Err(TrySendError::Closed(_)) => Err(CommitmentError::Internal),
+        }
     }
 }
 
@@ -90,6 +198,16 @@ pub struct CommitmentsApiServer {
     addr: SocketAddr,
     /// The shutdown signal.
     signal: Option<Pin<Box<dyn Future<Output = ()> + Send>>>,
+    /// The on-chain statuses of the validators, exposed in the metadata endpoint.
+    validator_statuses: ValidatorStatuses,
+    /// The health of the sidecar, exposed in the health endpoints.
+    health: SidecarHealth,
+    /// The options reloaded at runtime, exposing up-to-date limits in the metadata endpoint.
+    reloads: Option<watch::Receiver<ReloadableOpts>>,
+    /// The rate limits and quotas applied to commitment requests.
+    rate_limits: RateLimitOpts,
+    /// The EIP-712 domain of the typed-data signatures of requests, if supported.
+    request_domain: Option<RequestDomain>,
 }
 
 impl fmt::Debug for CommitmentsApiServer {
@@ -106,6 +224,11 @@ impl CommitmentsApiServer {
             signal: Some(Box::pin(async {
                 let _ = tokio::signal::ctrl_c().await;
             })),
+            validator_statuses: ValidatorStatuses::default(),
+            health: SidecarHealth::default(),
+            reloads: None,
+            rate_limits: RateLimitOpts::default(),
+            request_domain: None,
         }
     }
 
@@ -118,12 +241,61 @@ impl CommitmentsApiServer {
         Self {
             addr: addr.to_socket_addrs().unwrap().next().unwrap(),
             signal: Some(Box::pin(signal)),
+            validator_statuses: self.validator_statuses,
+            health: self.health,
+            reloads: self.reloads,
+            rate_limits: self.rate_limits,
+            request_domain: self.request_domain,
         }
     }
 
+    /// Exposes the given on-chain validator statuses in the metadata endpoint.
+    pub fn with_validator_statuses(mut self, validator_statuses: ValidatorStatuses) -> Self {
+        self.validator_statuses = validator_statuses;
+        self
+    }
+
+    /// Exposes the given sidecar health in the `/health` and `/ready` endpoints.
+    pub fn with_health(mut self, health: SidecarHealth) -> Self {
+        self.health = health;
+        self
+    }
+
+    /// Exposes the limits reloaded at runtime from the given channel in the metadata endpoint.
+    pub fn with_reloads(mut self, reloads: watch::Receiver<ReloadableOpts>) -> Self {
+        self.reloads = Some(reloads);
+        self
+    }
+
+    /// Applies the given rate limits and quotas to commitment requests.
+    pub fn with_rate_limits(mut self, rate_limits: RateLimitOpts) -> Self {
+        self.rate_limits = rate_limits;
+        self
+    }
+
+    /// Accepts requests signed over their EIP-712 typed data in the given domain, and exposes
+    /// it in the metadata endpoint.
+    pub fn with_request_domain(mut self, request_domain: RequestDomain) -> Self {
+        self.request_domain = Some(request_domain);
+        self
+    }
+
     /// Runs the JSON-RPC server, sending events to the provided channel.
     pub async fn run(&mut self, events_tx: mpsc::Sender<CommitmentEvent>, limits: LimitsOpts) {
-        let api = Arc::new(CommitmentsApiInner::new(events_tx, limits));
+        let mut api = CommitmentsApiInner::new(
+            events_tx,
+            limits,
+            self.validator_statuses.clone(),
+            self.health.clone(),
+        )
+        .with_rate_limits(self.rate_limits);
+        if let Some(reloads) = self.reloads.clone() {
+            api = api.with_reloads(reloads);
+        }
+        if let Some(request_domain) = self.request_domain {
+            api = api.with_request_domain(request_domain);
+        }
+        let api = Arc::new(api);
 
         let router = make_router(api);
 
@@ -143,7 +315,9 @@ impl CommitmentsApiServer {
         let signal = self.signal.take().expect("Signal not set");
 
         tokio::spawn(async move {
-            if let Err(err) = axum::serve(listener, router).with_graceful_shutdown(signal).await {
+            // The address of the client is needed to rate limit requests per IP.
+            let service = router.into_make_service_with_connect_info::<SocketAddr>();
+            if let Err(err) = axum::serve(listener, service).with_graceful_shutdown(signal).await {
                 error!(?err, "Commitments API Server error");
             }
         });
@@ -164,6 +338,8 @@ fn make_router(state: Arc<CommitmentsApiInner>) -> Router {
     Router::new()
         .route("/", post(handlers::rpc_entrypoint))
         .route("/status", get(handlers::status))
+        .route("/health", get(handlers::health))
+        .route("/ready", get(handlers::ready))
         .fallback(handlers::not_found)
         .layer(TimeoutLayer::new(spec::MAX_REQUEST_TIMEOUT))
         .route_layer(middleware::from_fn(track_server_metrics))
@@ -172,8 +348,13 @@ fn make_router(state: Arc<CommitmentsApiInner>) -> Router {
 
 #[cfg(test)]
 mod test {
+    use std::num::NonZero;
+
     use crate::api::commitments::{jsonrpc::JsonResponse, spec::SIGNATURE_HEADER};
-    use alloy::signers::{k256::SecretKey, local::PrivateKeySigner};
+    use alloy::{
+        primitives::{Address, PrimitiveSignature as Signature},
+        signers::{k256::SecretKey, local::PrivateKeySigner, SignerSync},
+    };
     use serde_json::json;
 
     use crate::{
@@ -274,17 +455,107 @@ mod test {
             let _ = tx.send(());
         });
 
-        let CommitmentEvent { request, response } = events.recv().await.unwrap();
+        let CommitmentEvent { request, response, .. } = events.recv().await.unwrap();
 
         let commitment_signer = PrivateKeySigner::random();
 
         let commitment = request.commit_and_sign(&commitment_signer).await.unwrap();
 
-        response.send(Ok(commitment)).unwrap();
+        response.send(Ok(commitment.into())).unwrap();
+
+        rx.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_request_eip712_success() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let domain = RequestDomain { chain_id: 17000, operator: Address::random() };
+        let mut server = CommitmentsApiServer::new("0.0.0.0:0").with_request_domain(domain);
+
+        let (events_tx, mut events) = mpsc::channel(1);
+
+        server.run(events_tx, LimitsOpts::default()).await;
+        let addr = server.local_addr();
+
+        let sk = SecretKey::random(&mut rand::thread_rng());
+        let signer = PrivateKeySigner::from(sk.clone());
+        let tx = default_test_transaction(signer.address(), None);
+        let req = create_signed_inclusion_request(&[tx], &sk, 12).await.unwrap();
+
+        // Sign the typed data of the request instead of its raw digest
+        let sig = signer.sign_hash_sync(&req.eip712_signing_hash(&domain)).unwrap();
+        let sig = Signature::try_from(sig.as_bytes().as_ref()).unwrap().to_hex();
+
+        let payload = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "bolt_requestInclusion",
+            "params": [req]
+        });
+
+        let url = format!("http://{addr}");
+        let client = reqwest::Client::new();
+
+        let (tx, rx) = oneshot::channel();
+
+        tokio::spawn(async move {
+            let response = client
+                .post(url)
+                .header(SIGNATURE_HEADER, format!("eip712-v1:{}:{}", signer.address(), sig))
+                .json(&payload)
+                .send()
+                .await
+                .unwrap();
+
+            let json = response.json::<JsonResponse>().await.unwrap();
+            assert!(json.error.is_none());
+
+            let _ = tx.send(());
+        });
+
+        let CommitmentEvent { request, response, .. } = events.recv().await.unwrap();
+
+        let commitment = request.commit_and_sign(&PrivateKeySigner::random()).await.unwrap();
+        response.send(Ok(commitment.into())).unwrap();
 
         rx.await.unwrap();
     }
 
+    #[tokio::test]
+    async fn test_request_rate_limited() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let rate_limits =
+            RateLimitOpts { max_requests_per_ip: NonZero::new(1).unwrap(), ..Default::default() };
+        let mut server = CommitmentsApiServer::new("0.0.0.0:0").with_rate_limits(rate_limits);
+
+        let (events_tx, _) = mpsc::channel(1);
+
+        server.run(events_tx, LimitsOpts::default()).await;
+        let addr = server.local_addr();
+
+        let payload = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "bolt_requestInclusion",
+            "params": []
+        });
+
+        let url = format!("http://{addr}");
+        let client = reqwest::Client::new();
+
+        // The first request goes through and fails because of the missing signature
+        let response = client.post(&url).json(&payload).send().await.unwrap();
+        let json = response.json::<JsonResponse>().await.unwrap();
+        assert_eq!(json.error.unwrap().code, -32003);
+
+        let response = client.post(&url).json(&payload).send().await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::TOO_MANY_REQUESTS);
+        let json = response.json::<JsonResponse>().await.unwrap();
+        assert_eq!(json.error.unwrap().code, -32010);
+    }
+
     #[tokio::test]
     async fn test_request_metadata() {
         let _ = tracing_subscriber::fmt::try_init();
@@ -317,8 +588,56 @@ mod test {
             .await
             .unwrap();
 
+        assert!(response.result["validator_statuses"]["validators"].is_array());
+
         let limits: LimitsOpts = serde_json::from_value(response.result).unwrap();
 
         assert_eq!(limits, LimitsOpts::default());
     }
+
+    #[tokio::test]
+    async fn test_health_endpoints() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let health = SidecarHealth::default();
+        let mut server = CommitmentsApiServer::new("0.0.0.0:0").with_health(health.clone());
+
+        let (events_tx, _) = mpsc::channel(1);
+
+        server.run(events_tx, LimitsOpts::default()).await;
+        let addr = server.local_addr();
+
+        let client = reqwest::Client::new();
+
+        // The sidecar is not ready before the first health check
+        let response = client.get(format!("http://{addr}/ready")).send().await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::SERVICE_UNAVAILABLE);
+
+        let response = client.get(format!("http://{addr}/health")).send().await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        let report = response.json::<serde_json::Value>().await.unwrap();
+        assert_eq!(report["ready"], false);
+        assert!(report["execution"].is_object());
+
+        health.update(HealthReport { ready: true, ..Default::default() });
+        let response = client.get(format!("http://{addr}/ready")).send().await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+    }
+
+    #[test]
+    fn test_metadata_follows_reloaded_limits() {
+        let (events_tx, _) = mpsc::channel(1);
+        let (reloads_tx, reloads_rx) = watch::channel(ReloadableOpts::default());
+        let api = CommitmentsApiInner::new(
+            events_tx,
+            LimitsOpts::default(),
+            ValidatorStatuses::default(),
+            SidecarHealth::default(),
+        )
+        .with_reloads(reloads_rx);
+
+        let limits = LimitsOpts { min_priority_fee: 42, ..Default::default() };
+        reloads_tx.send_modify(|opts| opts.limits = limits);
+        assert_eq!(api.metadata().limits, limits);
+    }
 }
@@ -1,4 +1,6 @@
-use alloy::primitives::SignatureError;
+use std::net::IpAddr;
+
+use alloy::primitives::{Address, SignatureError};
 use axum::{
     body::Body,
     extract::rejection::JsonRejection,
@@ -9,14 +11,18 @@ use axum::{
 use thiserror::Error;
 
 use crate::{
-    primitives::{commitment::InclusionCommitment, InclusionRequest},
-    state::{consensus::ConsensusError, ValidationError},
+    primitives::{commitment::CommitmentResponse, InclusionRequest, Slot},
+    state::{commitments_db::CommitmentsDbError, consensus::ConsensusError, ValidationError},
 };
 
 use super::jsonrpc::JsonResponse;
 
 pub(super) const SIGNATURE_HEADER: &str = "x-bolt-signature";
 
+/// The prefix of the [SIGNATURE_HEADER] value for signatures over the version 1 of the EIP-712
+/// typed data of requests.
+pub(super) const EIP712_V1_SCHEME: &str = "eip712-v1";
+
 pub(super) const GET_VERSION_METHOD: &str = "bolt_getVersion";
 
 pub(super) const REQUEST_INCLUSION_METHOD: &str = "bolt_requestInclusion";
@@ -37,9 +43,18 @@ pub enum CommitmentError {
     /// Request validation failed.
     #[error("Validation failed: {0}")]
     Validation(#[from] ValidationError),
+    /// Request conflicts with a previously signed commitment.
+    #[error("Conflicting commitment: {0}")]
+    Conflict(#[from] CommitmentsDbError),
     /// Duplicate request.
     #[error("Duplicate request")]
     Duplicate,
+    /// Commitment acceptance has been paused by the operator.
+    #[error("Commitment acceptance is paused")]
+    Paused,
+    /// Rate limit exceeded.
+    #[error("Rate limit exceeded: {0}")]
+    RateLimited(#[from] RateLimitError),
     /// Internal server error.
     #[error("Internal server error")]
     Internal,
@@ -103,6 +118,20 @@ impl IntoResponse for CommitmentError {
                 (StatusCode::BAD_REQUEST, Json(JsonResponse::from_error(-32007, self.to_string())))
                     .into_response()
             }
+            Self::Conflict(err) => {
+                (StatusCode::BAD_REQUEST, Json(JsonResponse::from_error(-32008, err.to_string())))
+                    .into_response()
+            }
+            Self::Paused => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(JsonResponse::from_error(-32009, self.to_string())),
+            )
+                .into_response(),
+            Self::RateLimited(err) => (
+                StatusCode::TOO_MANY_REQUESTS,
+                Json(JsonResponse::from_error(-32010, err.to_string())),
+            )
+                .into_response(),
             Self::UnknownMethod => {
                 (StatusCode::BAD_REQUEST, Json(JsonResponse::from_error(-32601, self.to_string())))
                     .into_response()
@@ -128,12 +157,49 @@ pub enum RejectionError {
     Json(#[from] serde_json::Error),
 }
 
+/// Error indicating that a commitment request exceeded the rate limits or quotas of the
+/// commitments API. This should be returned to the user.
+#[derive(Debug, Error)]
+pub enum RateLimitError {
+    /// Too many requests overall.
+    #[error("too many requests")]
+    Global,
+    /// Too many requests from the same IP address.
+    #[error("too many requests from {0}")]
+    Ip(IpAddr),
+    /// Too many requests from the same signer.
+    #[error("too many requests from signer {0}")]
+    Signer(Address),
+    /// Too many pending commitments from the same signer for the target slot.
+    #[error("too many pending commitments from signer {0} for slot {1}")]
+    Pending(Address, Slot),
+    /// The queue of requests waiting to be processed is full.
+    #[error("request queue is full")]
+    QueueFull,
+}
+
+impl RateLimitError {
+    /// Returns the reason of the error, used as a metrics label.
+    pub fn reason(&self) -> &'static str {
+        match self {
+            Self::Global => "global",
+            Self::Ip(_) => "ip",
+            Self::Signer(_) => "signer",
+            Self::Pending(..) => "pending",
+            Self::QueueFull => "queue_full",
+        }
+    }
+}
+
 /// Implements the commitments-API: <https://chainbound.github.io/bolt-docs/api/rpc>
 #[async_trait::async_trait]
 pub trait CommitmentsApi {
     /// Implements: <https://chainbound.github.io/bolt-docs/api/rpc#bolt_requestinclusion>
+    ///
+    /// In shadow mode, the response is an unsigned "would-commit" response instead of a signed
+    /// commitment.
     async fn request_inclusion(
         &self,
         inclusion_request: InclusionRequest,
-    ) -> Result<InclusionCommitment, CommitmentError>;
+    ) -> Result<CommitmentResponse, CommitmentError>;
 }
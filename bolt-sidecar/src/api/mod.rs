@@ -7,3 +7,6 @@ pub mod spec;
 
 /// Commitments-API spec and errors.
 pub mod commitments;
+
+/// Authenticated admin API to manage the sidecar at runtime.
+pub mod admin;
@@ -1,5 +1,8 @@
 use axum::{
-    http::StatusCode,
+    http::{
+        header::{ACCEPT, CONTENT_TYPE},
+        HeaderMap, HeaderValue, StatusCode,
+    },
     response::{IntoResponse, Response},
     Json,
 };
@@ -31,6 +34,78 @@ pub const DELEGATE_PATH: &str = "/constraints/v1/builder/delegate";
 /// The path to the constraints API revoke endpoint.
 pub const REVOKE_PATH: &str = "/constraints/v1/builder/revoke";
 
+/// The header carrying the fork version of SSZ-encoded Builder API payloads.
+pub const CONSENSUS_VERSION_HEADER: &str = "eth-consensus-version";
+/// The `Accept` header value used by the sidecar, preferring SSZ over JSON responses.
+pub const ACCEPT_SSZ_OR_JSON: &str = "application/octet-stream;q=1.0,application/json;q=0.9";
+
+/// The encoding of a Builder API request or response body.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Encoding {
+    /// `application/json`
+    #[default]
+    Json,
+    /// `application/octet-stream`, i.e. SSZ
+    Ssz,
+}
+
+impl Encoding {
+    /// Returns the media type associated with the encoding.
+    pub const fn content_type(&self) -> &'static str {
+        match self {
+            Self::Json => "application/json",
+            Self::Ssz => "application/octet-stream",
+        }
+    }
+
+    /// Returns the encoding of a body from its `Content-Type` header.
+    /// Defaults to JSON if the header is missing or unknown.
+    pub fn from_content_type(headers: &HeaderMap) -> Self {
+        let content_type = headers.get(CONTENT_TYPE).and_then(|v| v.to_str().ok());
+        match content_type.map(|v| v.split(';').next().unwrap_or_default().trim()) {
+            Some("application/octet-stream") => Self::Ssz,
+            _ => Self::Json,
+        }
+    }
+
+    /// Returns the preferred encoding for a response from the `Accept` header, according
+    /// to the quality values of the listed media types. Defaults to JSON if the header is
+    /// missing or no supported media type is listed.
+    pub fn from_accept(headers: &HeaderMap) -> Self {
+        let Some(accept) = headers.get(ACCEPT).and_then(|v| v.to_str().ok()) else {
+            return Self::Json;
+        };
+
+        let mut preferred = (Self::Json, 0.0);
+        for media_range in accept.split(',') {
+            let mut parts = media_range.split(';').map(str::trim);
+
+            let encoding = match parts.next() {
+                Some("application/octet-stream") => Self::Ssz,
+                Some("application/json" | "*/*" | "application/*") => Self::Json,
+                _ => continue,
+            };
+
+            let quality = parts
+                .find_map(|p| p.strip_prefix("q="))
+                .and_then(|q| q.parse::<f32>().ok())
+                .unwrap_or(1.0);
+
+            // On ties, JSON is preferred as it is the default encoding
+            if quality > preferred.1 || (quality == preferred.1 && encoding == Self::Json) {
+                preferred = (encoding, quality);
+            }
+        }
+
+        preferred.0
+    }
+
+    /// Returns the `Content-Type` header value for the encoding.
+    pub fn header_value(&self) -> HeaderValue {
+        HeaderValue::from_static(self.content_type())
+    }
+}
+
 /// A response object for errors.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ErrorResponse {
@@ -65,24 +140,47 @@ pub enum BuilderApiError {
     FailedRevoking(ErrorResponse),
     #[error("No bids found for slot {0}")]
     NoBids(u64),
+    #[error("All bids for slot {0} have invalid inclusion proofs")]
+    InvalidProofs(u64),
     #[error("Failed to fetch local payload for slot {0}")]
     FailedToFetchLocalPayload(u64),
     #[error("Axum error: {0:?}")]
     AxumError(#[from] axum::Error),
     #[error("Json error: {0:?}")]
     JsonError(#[from] serde_json::Error),
+    #[error("SSZ serialization error: {0:?}")]
+    SszSerialize(#[from] ethereum_consensus::ssz::prelude::SerializeError),
+    #[error("SSZ deserialization error: {0:?}")]
+    SszDeserialize(#[from] ethereum_consensus::ssz::prelude::DeserializeError),
     #[error("Reqwest error: {0:?}")]
     ReqwestError(#[from] reqwest::Error),
     #[error("API request timed out : {0:?}")]
     Timeout(#[from] tokio::time::error::Elapsed),
     #[error("Invalid fork: {0}")]
     InvalidFork(String),
-    #[error("Locally-built payload does not match expected signed header")]
-    LocalPayloadIntegrity(#[from] super::builder::LocalPayloadIntegrityError),
+    #[error("Payload does not match expected signed header")]
+    PayloadIntegrity(#[from] super::builder::PayloadIntegrityError),
+    #[error("Refusing to serve header or payload: {0}")]
+    Equivocation(#[from] crate::state::signed_headers::EquivocationError),
     #[error("Generic error: {0}")]
     Generic(String),
 }
 
+impl BuilderApiError {
+    /// Returns the reason for falling back to a locally built block after this error on
+    /// `get_header`, used as a metrics label.
+    pub const fn fallback_reason(&self) -> &'static str {
+        match self {
+            Self::Timeout(_) => "timeout",
+            Self::NoBids(_) => "no_bids",
+            Self::InvalidProofs(_) => "invalid_proofs",
+            Self::ReqwestError(_) => "relay_unreachable",
+            Self::InvalidFork(_) => "invalid_fork",
+            _ => "relay_error",
+        }
+    }
+}
+
 impl IntoResponse for BuilderApiError {
     fn into_response(self) -> Response {
         match self {
@@ -94,9 +192,17 @@ impl IntoResponse for BuilderApiError {
             | Self::FailedRevoking(error) => {
                 (StatusCode::from_u16(error.code).unwrap(), Json(error)).into_response()
             }
-            Self::NoBids(_) => (StatusCode::NO_CONTENT, self.to_string()).into_response(),
+            Self::NoBids(_) | Self::InvalidProofs(_) => {
+                (StatusCode::NO_CONTENT, self.to_string()).into_response()
+            }
             Self::AxumError(err) => (StatusCode::BAD_REQUEST, err.to_string()).into_response(),
             Self::JsonError(err) => (StatusCode::BAD_REQUEST, err.to_string()).into_response(),
+            Self::SszDeserialize(err) => {
+                (StatusCode::BAD_REQUEST, format!("{err:?}")).into_response()
+            }
+            Self::SszSerialize(err) => {
+                (StatusCode::INTERNAL_SERVER_ERROR, format!("{err:?}")).into_response()
+            }
             Self::FailedToFetchLocalPayload(_) => {
                 (StatusCode::NO_CONTENT, self.to_string()).into_response()
             }
@@ -111,14 +217,85 @@ impl IntoResponse for BuilderApiError {
             )
                 .into_response(),
             Self::InvalidFork(err) => (StatusCode::BAD_REQUEST, Json(err)).into_response(),
-            Self::LocalPayloadIntegrity(err) => {
+            Self::PayloadIntegrity(err) => {
                 (StatusCode::BAD_REQUEST, err.to_string()).into_response()
             }
+            Self::Equivocation(err) => (StatusCode::CONFLICT, err.to_string()).into_response(),
             Self::Generic(err) => (StatusCode::INTERNAL_SERVER_ERROR, Json(err)).into_response(),
         }
     }
 }
 
+/// The index of a relay behind a [`ConstraintsApi`] client, in the order the relays were
+/// configured. Clients backed by a single relay only have the relay at index 0.
+pub type RelayIndex = usize;
+
+/// The results of a request sent to multiple relays, by relay index.
+#[derive(Debug)]
+pub struct RelayResults<T> {
+    results: Vec<(RelayIndex, Result<T, BuilderApiError>)>,
+}
+
+impl<T> Default for RelayResults<T> {
+    fn default() -> Self {
+        Self { results: Vec::new() }
+    }
+}
+
+impl<T> FromIterator<(RelayIndex, Result<T, BuilderApiError>)> for RelayResults<T> {
+    fn from_iter<I: IntoIterator<Item = (RelayIndex, Result<T, BuilderApiError>)>>(
+        iter: I,
+    ) -> Self {
+        Self { results: iter.into_iter().collect() }
+    }
+}
+
+impl<T> IntoIterator for RelayResults<T> {
+    type Item = (RelayIndex, Result<T, BuilderApiError>);
+    type IntoIter = std::vec::IntoIter<Self::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.results.into_iter()
+    }
+}
+
+impl<T> RelayResults<T> {
+    /// Records the result of the request to the relay at the given index.
+    pub fn push(&mut self, relay: RelayIndex, result: Result<T, BuilderApiError>) {
+        self.results.push((relay, result));
+    }
+
+    /// Returns the results of the request by relay index.
+    pub fn iter(&self) -> impl Iterator<Item = &(RelayIndex, Result<T, BuilderApiError>)> {
+        self.results.iter()
+    }
+
+    /// Returns the indexes of the relays for which the request succeeded.
+    pub fn succeeded(&self) -> Vec<RelayIndex> {
+        self.results.iter().filter(|(_, res)| res.is_ok()).map(|(relay, _)| *relay).collect()
+    }
+
+    /// Returns the indexes of the relays for which the request failed.
+    pub fn failed(&self) -> Vec<RelayIndex> {
+        self.results.iter().filter(|(_, res)| res.is_err()).map(|(relay, _)| *relay).collect()
+    }
+
+    /// Returns the first successful result, or the last error if the request failed for all
+    /// relays.
+    pub fn into_any_ok(self) -> Result<T, BuilderApiError> {
+        self.results
+            .into_iter()
+            .map(|(_, res)| res)
+            .reduce(|acc, res| acc.or(res))
+            .unwrap_or_else(|| Err(BuilderApiError::Generic("no relays configured".to_string())))
+    }
+
+    /// Returns the successful results, or the first error if the request failed for any relay.
+    pub fn into_all_ok(self) -> Result<Vec<T>, BuilderApiError> {
+        self.results.into_iter().map(|(_, res)| res).collect()
+    }
+}
+
 /// Implements the builder API as defined in <https://ethereum.github.io/builder-specs>.
 ///
 /// The Builder API represents the specification for allowing proposers to request
@@ -159,6 +336,30 @@ pub trait ConstraintsApi: BuilderApi {
         constraints: &BatchedSignedConstraints,
     ) -> Result<(), BuilderApiError>;
 
+    /// Submits constraints to the relays at the given indexes only, and returns the result for
+    /// each of them. This allows to retry the relays for which a submission failed.
+    ///
+    /// Clients backed by a single relay only have the relay at index 0.
+    async fn submit_constraints_to(
+        &self,
+        constraints: &BatchedSignedConstraints,
+        relays: &[RelayIndex],
+    ) -> RelayResults<()>
+    where
+        Self: Sync,
+    {
+        let mut results = RelayResults::default();
+        if relays.contains(&0) {
+            results.push(0, self.submit_constraints(constraints).await);
+        }
+        results
+    }
+
+    /// Returns the number of relays behind this client.
+    fn relays_len(&self) -> usize {
+        1
+    }
+
     /// Implements: <https://docs.boltprotocol.xyz/technical-docs/api/builder#get_header_with_proofs>
     async fn get_header_with_proofs(
         &self,
@@ -171,3 +372,39 @@ pub trait ConstraintsApi: BuilderApi {
     /// Implements: <https://docs.boltprotocol.xyz/technical-docs/api/builder#revoke>
     async fn revoke(&self, signed_data: &[SignedRevocation]) -> Result<(), BuilderApiError>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn accept(value: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(ACCEPT, HeaderValue::from_static(value));
+        headers
+    }
+
+    #[test]
+    fn test_encoding_from_accept() {
+        assert_eq!(Encoding::from_accept(&HeaderMap::new()), Encoding::Json);
+        assert_eq!(Encoding::from_accept(&accept("application/json")), Encoding::Json);
+        assert_eq!(Encoding::from_accept(&accept("application/octet-stream")), Encoding::Ssz);
+        assert_eq!(Encoding::from_accept(&accept(ACCEPT_SSZ_OR_JSON)), Encoding::Ssz);
+        assert_eq!(
+            Encoding::from_accept(&accept("application/octet-stream;q=0.5,application/json")),
+            Encoding::Json
+        );
+        assert_eq!(Encoding::from_accept(&accept("text/html")), Encoding::Json);
+    }
+
+    #[test]
+    fn test_encoding_from_content_type() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(Encoding::from_content_type(&headers), Encoding::Json);
+
+        headers.insert(CONTENT_TYPE, Encoding::Ssz.header_value());
+        assert_eq!(Encoding::from_content_type(&headers), Encoding::Ssz);
+
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json; charset=utf-8"));
+        assert_eq!(Encoding::from_content_type(&headers), Encoding::Json);
+    }
+}
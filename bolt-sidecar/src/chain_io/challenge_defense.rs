@@ -0,0 +1,383 @@
+use std::{
+    collections::{HashMap, HashSet},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use alloy::{
+    consensus::{Header, TxEnvelope},
+    eips::eip2718::Encodable2718,
+    network::EthereumWallet,
+    primitives::{Address, Bytes, TxHash, B256, U256},
+    rlp,
+};
+use alloy_trie::{
+    proof::ProofRetainer, root::adjust_index_for_rlp, HashBuilder, Nibbles, EMPTY_ROOT_HASH,
+};
+use eyre::{bail, eyre};
+use tracing::{error, info, warn};
+
+use super::challenger::{
+    BoltChallenger,
+    BoltChallengerContract::{Challenge, ChallengeStatus, Proof},
+};
+use crate::client::{BeaconClient, ExecutionClient};
+
+/// Watches the BoltChallenger contract for challenges opened against the commitments signed by
+/// our operator, and defends them by submitting a proof of inclusion of the committed
+/// transactions before the challenge expires.
+///
+/// Challenges that can't be defended yet (e.g. because the execution client hasn't synced the
+/// inclusion block) are retried on every tick until they expire.
+#[derive(Debug)]
+pub struct ChallengeDefender {
+    challenger: BoltChallenger,
+    execution: ExecutionClient,
+    /// The beacon client, used to find the execution block of the challenged slots.
+    beacon: BeaconClient,
+    /// The wallet paying for the resolution transactions.
+    wallet: EthereumWallet,
+    /// The address of our operator, i.e. the commitment signer.
+    operator: Address,
+    /// The interval between two polls of the contract.
+    interval: Duration,
+    /// The last block whose `ChallengeOpened` events have been processed.
+    last_block: Option<u64>,
+    /// The IDs of the open challenges that haven't been defended yet.
+    pending: HashSet<B256>,
+}
+
+impl ChallengeDefender {
+    /// Creates a new defender for the challenges against the given operator.
+    pub fn new(
+        challenger: BoltChallenger,
+        execution: ExecutionClient,
+        beacon: BeaconClient,
+        wallet: EthereumWallet,
+        operator: Address,
+        interval: Duration,
+    ) -> Self {
+        Self {
+            challenger,
+            execution,
+            beacon,
+            wallet,
+            operator,
+            interval,
+            last_block: None,
+            pending: HashSet::new(),
+        }
+    }
+
+    /// Polls the contract for new challenges and tries to defend the pending ones.
+    pub async fn check(&mut self) -> eyre::Result<()> {
+        let head = self.execution.get_head().await?;
+
+        match self.last_block {
+            // On startup, pick up the challenges that were opened while we were offline.
+            None => {
+                let open = self.challenger.get_open_challenges(self.operator).await?;
+                self.pending.extend(open.into_iter().map(|c| c.id));
+            }
+            Some(last) if last < head => {
+                let ids =
+                    self.challenger.get_opened_challenge_ids(self.operator, last + 1, head).await?;
+                for id in &ids {
+                    warn!(%id, "New challenge opened against our commitments");
+                }
+                self.pending.extend(ids);
+            }
+            Some(_) => {}
+        }
+        self.last_block = Some(head);
+
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+
+        let max_duration = self.challenger.max_challenge_duration().await?;
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+
+        for id in self.pending.clone() {
+            let challenge = self.challenger.get_challenge(id).await?;
+
+            if challenge.status != ChallengeStatus::Open {
+                info!(%id, status = ?challenge.status, "Challenge has been resolved");
+                self.pending.remove(&id);
+                continue;
+            }
+
+            if challenge.openedAt + max_duration < now {
+                error!(%id, "Challenge expired before it could be defended");
+                self.pending.remove(&id);
+                continue;
+            }
+
+            match self.defend(&challenge).await {
+                Ok(tx_hash) => {
+                    info!(%id, %tx_hash, "Successfully defended challenge");
+                    self.pending.remove(&id);
+                }
+                Err(err) => {
+                    let expires_in = challenge.openedAt + max_duration - now;
+                    error!(%id, ?err, %expires_in, "Failed to defend challenge, retrying");
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Builds the inclusion proof of the committed transactions of the challenge and submits it.
+    async fn defend(&self, challenge: &Challenge) -> eyre::Result<TxHash> {
+        let proof = self.build_proof(challenge).await?;
+        self.challenger.resolve_open_challenge(self.wallet.clone(), challenge.id, proof).await
+    }
+
+    /// Builds the proof of inclusion of the committed transactions of the challenge.
+    ///
+    /// The inclusion block is the execution block of the target slot of the challenge, and the
+    /// account proof of the sender is taken at the block before it, i.e. at the top of the
+    /// inclusion block before any transaction is applied.
+    async fn build_proof(&self, challenge: &Challenge) -> eyre::Result<Proof> {
+        let target_slot = u64::try_from(challenge.targetSlot)
+            .map_err(|_| eyre!("Invalid target slot {}", challenge.targetSlot))?;
+
+        let Some(target_block) = self.beacon.get_execution_block_at_slot(target_slot).await? else {
+            bail!("No block was proposed at target slot {target_slot}");
+        };
+        let inclusion_block_number = target_block.number;
+
+        let previous_block = self.execution.get_block(Some(inclusion_block_number - 1), false);
+        let inclusion_block = self.execution.get_block(Some(inclusion_block_number), true);
+        let account_proof = self
+            .execution
+            .get_account_proof(&challenge.commitmentReceiver, inclusion_block_number - 1);
+        let (previous_block, inclusion_block, account_proof) =
+            tokio::try_join!(previous_block, inclusion_block, account_proof)?;
+
+        // The execution client may be on another fork than the beacon node
+        if inclusion_block.header.hash != target_block.hash {
+            bail!(
+                "Execution block {inclusion_block_number} doesn't match target slot {target_slot}"
+            );
+        }
+
+        let transactions = inclusion_block
+            .transactions
+            .as_transactions()
+            .ok_or(eyre!("Inclusion block {} has no full transactions", inclusion_block_number))?
+            .iter()
+            .map(|tx| tx.inner.clone())
+            .collect::<Vec<_>>();
+
+        let committed = challenge.committedTxs.iter().map(|tx| tx.txHash).collect::<Vec<_>>();
+
+        assemble_proof(
+            &committed,
+            &previous_block.header.inner,
+            &inclusion_block.header.inner,
+            &transactions,
+            &account_proof.account_proof,
+        )
+    }
+
+    /// Spawns a background task that periodically polls for challenges and defends them.
+    pub fn spawn(mut self) {
+        tokio::spawn(async move {
+            info!(challenger = %self.challenger.address(), "Watching for challenges");
+            let mut interval = tokio::time::interval(self.interval);
+            loop {
+                interval.tick().await;
+                if let Err(err) = self.check().await {
+                    error!(?err, "Failed to check for challenges");
+                }
+            }
+        });
+    }
+}
+
+/// Assembles the proof of inclusion of the committed transactions in the inclusion block.
+///
+/// Committed transactions that were not included (e.g. because the sender invalidated them
+/// with a higher nonce) get an empty proof: the contract considers the challenge defended
+/// as soon as the sender nonce or balance at the top of the block is shown to be invalid.
+fn assemble_proof(
+    committed: &[B256],
+    previous_header: &Header,
+    inclusion_header: &Header,
+    transactions: &[TxEnvelope],
+    account_proof: &[Bytes],
+) -> eyre::Result<Proof> {
+    let positions = transactions
+        .iter()
+        .enumerate()
+        .map(|(index, tx)| (*tx.tx_hash(), index))
+        .collect::<HashMap<_, _>>();
+    let indexes = committed.iter().map(|hash| positions.get(hash).copied()).collect::<Vec<_>>();
+
+    let encoded = transactions.iter().map(|tx| Bytes::from(tx.encoded_2718())).collect::<Vec<_>>();
+    let (tx_root, tx_proofs) =
+        transactions_trie_proofs(&encoded, indexes.iter().flatten().copied());
+    if tx_root != inclusion_header.transactions_root {
+        bail!("Transactions root mismatch in block {}", inclusion_header.number);
+    }
+
+    let mut tx_proofs = tx_proofs.into_iter();
+    let tx_merkle_proofs = indexes
+        .iter()
+        .map(|index| match index {
+            Some(_) => tx_proofs.next().map(|nodes| rlp::encode(nodes).into()),
+            None => Some(Bytes::new()),
+        })
+        .collect::<Option<Vec<_>>>()
+        .ok_or(eyre!("Missing transaction proof"))?;
+
+    Ok(Proof {
+        inclusionBlockNumber: U256::from(inclusion_header.number),
+        previousBlockHeaderRLP: rlp::encode(previous_header).into(),
+        inclusionBlockHeaderRLP: rlp::encode(inclusion_header).into(),
+        accountMerkleProof: rlp::encode(account_proof).into(),
+        txMerkleProofs: tx_merkle_proofs,
+        txIndexesInBlock: indexes
+            .into_iter()
+            .map(|index| U256::from(index.unwrap_or_default()))
+            .collect(),
+    })
+}
+
+/// Builds the transactions trie of a block from its EIP-2718 encoded transactions, returning its
+/// root and the Merkle proofs of the transactions at the given indexes, in the same order.
+///
+/// Each proof is the list of the RLP-encoded trie nodes from the root to the leaf, where the key
+/// of a transaction is its RLP-encoded index in the block.
+pub fn transactions_trie_proofs(
+    transactions: &[Bytes],
+    indexes: impl IntoIterator<Item = usize>,
+) -> (B256, Vec<Vec<Bytes>>) {
+    if transactions.is_empty() {
+        return (EMPTY_ROOT_HASH, Vec::new());
+    }
+
+    let targets = indexes
+        .into_iter()
+        .map(|index| Nibbles::unpack(rlp::encode_fixed_size(&index)))
+        .collect::<Vec<_>>();
+
+    let mut builder =
+        HashBuilder::default().with_proof_retainer(ProofRetainer::new(targets.clone()));
+
+    // Leaves must be added in the order of their keys, which is not the order of the indexes
+    // because of the RLP encoding.
+    for i in 0..transactions.len() {
+        let index = adjust_index_for_rlp(i, transactions.len());
+        let key = Nibbles::unpack(rlp::encode_fixed_size(&index));
+        builder.add_leaf(key, &transactions[index]);
+    }
+
+    let root = builder.root();
+    let nodes = builder.take_proof_nodes();
+    let proofs = targets
+        .iter()
+        .map(|target| {
+            nodes.matching_nodes_sorted(target).into_iter().map(|(_, node)| node).collect()
+        })
+        .collect();
+
+    (root, proofs)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::{
+        consensus::proofs::ordered_trie_root_with_encoder, network::TransactionBuilder,
+        rlp::Decodable, signers::local::PrivateKeySigner,
+    };
+    use alloy_trie::proof::verify_proof;
+
+    use super::*;
+    use crate::test_util::default_test_transaction;
+
+    /// Returns `count` signed transactions and the header of a block including them.
+    async fn block_with_transactions(count: u64) -> (Header, Vec<TxEnvelope>) {
+        let signer = PrivateKeySigner::random();
+        let wallet = EthereumWallet::from(signer.clone());
+
+        let mut transactions = Vec::new();
+        for nonce in 0..count {
+            let tx = default_test_transaction(signer.address(), Some(nonce));
+            transactions.push(tx.build(&wallet).await.unwrap());
+        }
+
+        let transactions_root =
+            ordered_trie_root_with_encoder(&transactions, |tx, buf| tx.encode_2718(buf));
+        let header = Header { number: 10, transactions_root, ..Default::default() };
+
+        (header, transactions)
+    }
+
+    #[test]
+    fn test_transactions_trie_proofs() {
+        let transactions =
+            (0..200u8).map(|i| Bytes::from(vec![i; 40 + i as usize])).collect::<Vec<_>>();
+        let indexes = vec![0, 1, 127, 128, 199];
+
+        let (root, proofs) = transactions_trie_proofs(&transactions, indexes.clone());
+
+        let expected_root =
+            ordered_trie_root_with_encoder(&transactions, |tx, buf| buf.extend_from_slice(tx));
+        assert_eq!(root, expected_root);
+
+        for (index, proof) in indexes.into_iter().zip(proofs) {
+            let key = Nibbles::unpack(rlp::encode_fixed_size(&index));
+            let value = transactions[index].to_vec();
+            verify_proof(root, key, Some(value), &proof).expect("valid proof");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_assemble_proof() {
+        let (header, transactions) = block_with_transactions(3).await;
+        let previous_header = Header { number: 9, ..Default::default() };
+        let account_proof = vec![Bytes::from_static(&[0xc0])];
+
+        // The second committed transaction was invalidated and never included
+        let committed =
+            [*transactions[2].tx_hash(), B256::repeat_byte(0x42), *transactions[0].tx_hash()];
+
+        let proof =
+            assemble_proof(&committed, &previous_header, &header, &transactions, &account_proof)
+                .unwrap();
+
+        assert_eq!(proof.inclusionBlockNumber, U256::from(10));
+        assert_eq!(proof.txIndexesInBlock, vec![U256::from(2), U256::ZERO, U256::ZERO]);
+        assert_eq!(Header::decode(&mut proof.inclusionBlockHeaderRLP.as_ref()).unwrap(), header);
+        assert_eq!(
+            Header::decode(&mut proof.previousBlockHeaderRLP.as_ref()).unwrap(),
+            previous_header
+        );
+        assert_eq!(
+            Vec::<Bytes>::decode(&mut proof.accountMerkleProof.as_ref()).unwrap(),
+            account_proof
+        );
+
+        assert!(proof.txMerkleProofs[1].is_empty());
+        for (position, index) in [(0, 2), (2, 0)] {
+            let nodes = Vec::<Bytes>::decode(&mut proof.txMerkleProofs[position].as_ref()).unwrap();
+            let key = Nibbles::unpack(rlp::encode_fixed_size(&index));
+            let value = transactions[index].encoded_2718();
+            verify_proof(header.transactions_root, key, Some(value), &nodes).expect("valid proof");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_assemble_proof_rejects_transactions_root_mismatch() {
+        let (header, mut transactions) = block_with_transactions(3).await;
+        let committed = [*transactions[0].tx_hash()];
+
+        // The transactions don't match the ones of the header anymore
+        transactions.pop();
+
+        let res = assemble_proof(&committed, &Header::default(), &header, &transactions, &[]);
+        assert!(res.is_err());
+    }
+}
@@ -0,0 +1,205 @@
+use alloy::{
+    network::EthereumWallet,
+    primitives::{Address, B256},
+    providers::{ProviderBuilder, RootProvider},
+    sol,
+    transports::http::Http,
+};
+use eyre::{bail, Context};
+use reqwest::{Client, Url};
+use tracing::debug;
+
+use BoltChallengerContract::{
+    BoltChallengerContractErrors, BoltChallengerContractInstance, Challenge, Proof,
+};
+
+use super::utils;
+
+/// A wrapper over a BoltChallengerContract that exposes various utility methods.
+#[derive(Debug, Clone)]
+pub struct BoltChallenger {
+    contract: BoltChallengerContractInstance<Http<Client>, RootProvider<Http<Client>>>,
+    /// The URL of the execution client, used to create signing providers for transactions.
+    execution_client_url: Url,
+}
+
+impl BoltChallenger {
+    /// Creates a new BoltChallenger instance.
+    pub fn from_address<U: Into<Url>>(
+        execution_client_url: U,
+        challenger_address: Address,
+    ) -> Self {
+        let execution_client_url = execution_client_url.into();
+        let provider = ProviderBuilder::new().on_http(execution_client_url.clone());
+        let contract = BoltChallengerContract::new(challenger_address, provider);
+
+        Self { contract, execution_client_url }
+    }
+
+    /// Returns the address of the BoltChallenger contract.
+    pub fn address(&self) -> Address {
+        *self.contract.address()
+    }
+
+    /// Fetch the open challenges against the commitments signed by the given signer.
+    pub async fn get_open_challenges(
+        &self,
+        commitment_signer: Address,
+    ) -> eyre::Result<Vec<Challenge>> {
+        let challenges = self.contract.getOpenChallenges().call().await?._0;
+
+        Ok(challenges.into_iter().filter(|c| c.commitmentSigner == commitment_signer).collect())
+    }
+
+    /// Fetch the challenge with the given ID.
+    pub async fn get_challenge(&self, challenge_id: B256) -> eyre::Result<Challenge> {
+        match self.contract.getChallengeByID(challenge_id).call().await {
+            Ok(challenge) => Ok(challenge._0),
+            Err(err) => match utils::try_parse_contract_error(err) {
+                Ok(BoltChallengerContractErrors::ChallengeDoesNotExist(_)) => {
+                    bail!("BoltChallenger::ChallengeDoesNotExist: {challenge_id}")
+                }
+                Ok(other) => bail!("Unexpected BoltChallenger error: {:?}", other),
+                Err(err) => Err(err.into()),
+            },
+        }
+    }
+
+    /// Fetch the IDs of the challenges opened against the commitments signed by the given signer
+    /// in the given (inclusive) range of blocks.
+    pub async fn get_opened_challenge_ids(
+        &self,
+        commitment_signer: Address,
+        from_block: u64,
+        to_block: u64,
+    ) -> eyre::Result<Vec<B256>> {
+        let logs = self
+            .contract
+            .ChallengeOpened_filter()
+            .from_block(from_block)
+            .to_block(to_block)
+            .topic3(commitment_signer.into_word())
+            .query()
+            .await?;
+
+        Ok(logs.into_iter().map(|(event, _)| event.challengeId).collect())
+    }
+
+    /// Fetch the maximum duration in seconds of a challenge from the BoltParameters contract.
+    pub async fn max_challenge_duration(&self) -> eyre::Result<u64> {
+        let parameters = self.contract.parameters().call().await?._0;
+        let parameters = BoltParametersContract::new(parameters, self.contract.provider().clone());
+
+        Ok(parameters.MAX_CHALLENGE_DURATION().call().await?._0)
+    }
+
+    /// Resolve an open challenge with the given proof, paying for the transaction with the given
+    /// wallet. The call is simulated first, so that invalid proofs don't waste gas.
+    ///
+    /// Returns the hash of the resolution transaction once it has been included.
+    pub async fn resolve_open_challenge(
+        &self,
+        wallet: EthereumWallet,
+        challenge_id: B256,
+        proof: Proof,
+    ) -> eyre::Result<B256> {
+        let provider = ProviderBuilder::new()
+            .with_recommended_fillers()
+            .wallet(wallet)
+            .on_http(self.execution_client_url.clone());
+        let contract = BoltChallengerContract::new(self.address(), provider);
+        let call = contract.resolveOpenChallenge(challenge_id, proof);
+
+        if let Err(err) = call.call().await {
+            match utils::try_parse_contract_error(err) {
+                Ok(BoltChallengerContractErrors::ChallengeAlreadyResolved(_)) => {
+                    bail!("BoltChallenger::ChallengeAlreadyResolved: {challenge_id}")
+                }
+                Ok(BoltChallengerContractErrors::ChallengeExpired(_)) => {
+                    bail!("BoltChallenger::ChallengeExpired: {challenge_id}")
+                }
+                Ok(other) => bail!("Challenge resolution would revert: {:?}", other),
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        let receipt = call
+            .send()
+            .await
+            .wrap_err("Failed to send challenge resolution transaction")?
+            .get_receipt()
+            .await?;
+        debug!(?receipt, "Challenge resolution transaction included");
+
+        if !receipt.status() {
+            bail!("Challenge resolution transaction {} reverted", receipt.transaction_hash);
+        }
+
+        Ok(receipt.transaction_hash)
+    }
+}
+
+sol! {
+    #[allow(missing_docs)]
+    #[sol(rpc, all_derives)]
+    interface BoltChallengerContract {
+        enum ChallengeStatus {
+            Open,
+            Defended,
+            Breached
+        }
+
+        struct Challenge {
+            bytes32 id;
+            uint48 openedAt;
+            ChallengeStatus status;
+            uint256 targetSlot;
+            address challenger;
+            address commitmentSigner;
+            address commitmentReceiver;
+            TransactionData[] committedTxs;
+        }
+
+        struct TransactionData {
+            bytes32 txHash;
+            uint256 nonce;
+            uint256 gasLimit;
+        }
+
+        struct Proof {
+            uint256 inclusionBlockNumber;
+            bytes previousBlockHeaderRLP;
+            bytes inclusionBlockHeaderRLP;
+            bytes accountMerkleProof;
+            bytes[] txMerkleProofs;
+            uint256[] txIndexesInBlock;
+        }
+
+        event ChallengeOpened(bytes32 indexed challengeId, address indexed challenger, address indexed commitmentSigner);
+
+        function parameters() external view returns (address);
+        function getOpenChallenges() external view returns (Challenge[] memory);
+        function getChallengeByID(bytes32 challengeID) external view returns (Challenge memory);
+        function resolveOpenChallenge(bytes32 challengeID, Proof calldata proof) external;
+
+        error ChallengeAlreadyResolved();
+        error ChallengeDoesNotExist();
+        error ChallengeExpired();
+        error BlockIsTooOld();
+        error InvalidBlockNumber();
+        error InvalidBlockHash();
+        error InvalidParentBlockHash();
+        error AccountDoesNotExist();
+        error TransactionNotIncluded();
+        error WrongTransactionHashProof();
+        error InvalidProofsLength();
+    }
+}
+
+sol! {
+    #[allow(missing_docs)]
+    #[sol(rpc)]
+    interface BoltParametersContract {
+        function MAX_CHALLENGE_DURATION() external view returns (uint48);
+    }
+}
@@ -1,4 +1,4 @@
-use std::time::Duration;
+use std::{collections::HashMap, time::Duration};
 
 use alloy::{
     contract::Error,
@@ -150,6 +150,74 @@ impl BoltManager {
 
         Ok(proposers_statuses)
     }
+
+    /// Fetch the current proposer statuses of the provided validator public keys.
+    ///
+    /// Unlike [`BoltManager::verify_validator_pubkeys`], this doesn't fail if some validators are
+    /// inactive or authorize another operator: the statuses are returned as-is so that the caller
+    /// can decide what to do with them. Validators that are not registered in Bolt (anymore) are
+    /// mapped to `None`.
+    pub async fn get_proposer_statuses(
+        &self,
+        keys: Vec<BlsPublicKey>,
+        commitment_signer_pubkey: Address,
+    ) -> eyre::Result<HashMap<BlsPublicKey, Option<ProposerStatus>>> {
+        let hashes_with_preimages = utils::pubkey_hashes(keys);
+        let hashes = hashes_with_preimages.keys().cloned().collect::<Vec<_>>();
+
+        let mut statuses = HashMap::with_capacity(hashes.len());
+        for chunk in hashes.chunks(MAX_CHUNK_SIZE) {
+            let chunk_statuses = match self.0.getProposerStatuses(chunk.to_vec()).call().await {
+                Ok(returndata) => returndata.statuses,
+                Err(err) => match utils::try_parse_contract_error(err) {
+                    // A single unregistered validator makes the whole query revert,
+                    // so we fall back to querying the validators of this chunk one by one.
+                    Ok(BoltManagerContractErrors::ValidatorDoesNotExist(_)) => {
+                        let mut chunk_statuses = Vec::with_capacity(chunk.len());
+                        for hash in chunk {
+                            match self.0.getProposerStatuses(vec![*hash]).call().await {
+                                Ok(returndata) => chunk_statuses.extend(returndata.statuses),
+                                Err(err) => match utils::try_parse_contract_error(err) {
+                                    Ok(BoltManagerContractErrors::ValidatorDoesNotExist(_)) => {
+                                        debug!(%hash, "Validator is not registered in Bolt");
+                                    }
+                                    Ok(decoded) => bail!(generate_bolt_manager_error(
+                                        decoded,
+                                        commitment_signer_pubkey
+                                    )),
+                                    Err(err) => return Err(err.into()),
+                                },
+                            }
+                        }
+                        chunk_statuses
+                    }
+                    // The operator of the validators is not registered in Bolt (anymore),
+                    // so none of them can be considered registered either.
+                    Ok(BoltManagerContractErrors::KeyNotFound(_)) => {
+                        warn!("Operator of the queried validators is not registered in Bolt");
+                        Vec::new()
+                    }
+                    Ok(decoded) => {
+                        bail!(generate_bolt_manager_error(decoded, commitment_signer_pubkey))
+                    }
+                    Err(err) => return Err(err.into()),
+                },
+            };
+
+            for status in chunk_statuses {
+                if let Some(pubkey) = hashes_with_preimages.get(&status.pubkeyHash) {
+                    statuses.insert(pubkey.clone(), Some(status));
+                }
+            }
+        }
+
+        // Every validator missing from the results is not registered in Bolt
+        for pubkey in hashes_with_preimages.into_values() {
+            statuses.entry(pubkey).or_insert(None);
+        }
+
+        Ok(statuses)
+    }
 }
 
 fn generate_bolt_manager_error(
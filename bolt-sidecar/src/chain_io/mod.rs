@@ -2,5 +2,17 @@
 pub mod manager;
 pub use manager::BoltManager;
 
+/// Wrapper over the BoltValidators contract
+pub mod validators;
+pub use validators::BoltValidators;
+
+/// Wrapper over the BoltChallenger contract
+pub mod challenger;
+pub use challenger::BoltChallenger;
+
+/// Automatic defense of the challenges opened against our commitments
+pub mod challenge_defense;
+pub use challenge_defense::ChallengeDefender;
+
 /// Utilities and functions used in the Bolt contracts
 pub mod utils;
@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+
+use alloy::{
+    primitives::Address,
+    providers::{ProviderBuilder, RootProvider},
+    sol,
+    transports::http::Http,
+};
+use ethereum_consensus::primitives::BlsPublicKey;
+use eyre::bail;
+use futures::{stream, StreamExt, TryStreamExt};
+use reqwest::{Client, Url};
+use tracing::debug;
+
+use BoltValidatorsContract::{BoltValidatorsContractErrors, BoltValidatorsContractInstance};
+
+use super::utils;
+use crate::config::chain::Chain;
+
+/// The maximum number of concurrent queries to the BoltValidators contract, so that checking
+/// many validators doesn't flood the execution client.
+const MAX_CONCURRENT_QUERIES: usize = 16;
+
+/// A wrapper over a BoltValidatorsContract that exposes various utility methods.
+#[derive(Debug, Clone)]
+pub struct BoltValidators(BoltValidatorsContractInstance<Http<Client>, RootProvider<Http<Client>>>);
+
+impl BoltValidators {
+    /// Creates a new BoltValidators instance. Returns `None` if a canonical BoltValidators
+    /// contract is not deployed on such chain.
+    pub fn from_chain<U: Into<Url>>(execution_client_url: U, chain: Chain) -> Option<Self> {
+        let address = chain.validators_address()?;
+        Some(Self::from_address(execution_client_url, address))
+    }
+
+    /// Creates a new BoltValidators instance.
+    pub fn from_address<U: Into<Url>>(
+        execution_client_url: U,
+        validators_address: Address,
+    ) -> Self {
+        let provider = ProviderBuilder::new().on_http(execution_client_url.into());
+        let registry = BoltValidatorsContract::new(validators_address, provider);
+
+        Self(registry)
+    }
+
+    /// Fetch the maximum committed gas limit registered on-chain for each of the provided
+    /// validator public keys. Validators that are not registered are left out of the result.
+    pub async fn get_max_committed_gas_limits(
+        &self,
+        keys: Vec<BlsPublicKey>,
+    ) -> eyre::Result<HashMap<BlsPublicKey, u64>> {
+        let requests = utils::pubkey_hashes(keys).into_iter().map(|(hash, pubkey)| async move {
+            match self.0.getValidatorByPubkeyHash(hash).call().await {
+                Ok(info) => Ok(Some((pubkey, u64::from(info._0.maxCommittedGasLimit)))),
+                Err(err) => match utils::try_parse_contract_error(err) {
+                    Ok(BoltValidatorsContractErrors::ValidatorDoesNotExist(_)) => {
+                        debug!(%hash, "Validator is not registered in BoltValidators");
+                        Ok(None)
+                    }
+                    Ok(BoltValidatorsContractErrors::InvalidQuery(_)) => {
+                        bail!("BoltValidators::InvalidQuery: invalid zero public key hash")
+                    }
+                    Err(err) => Err(err.into()),
+                },
+            }
+        });
+
+        let results = stream::iter(requests)
+            .buffer_unordered(MAX_CONCURRENT_QUERIES)
+            .try_collect::<Vec<_>>()
+            .await?;
+
+        Ok(results.into_iter().flatten().collect())
+    }
+}
+
+sol! {
+    #[allow(missing_docs)]
+    #[sol(rpc)]
+    interface BoltValidatorsContract {
+        #[derive(Debug, Default)]
+        struct ValidatorInfo {
+            bytes20 pubkeyHash;
+            uint32 maxCommittedGasLimit;
+            address authorizedOperator;
+            address controller;
+        }
+
+        function getValidatorByPubkeyHash(bytes20 pubkeyHash) public view returns (ValidatorInfo memory);
+
+        error InvalidQuery();
+        #[derive(Debug)]
+        error ValidatorDoesNotExist(bytes20 pubkeyHash);
+    }
+}
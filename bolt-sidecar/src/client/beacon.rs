@@ -8,6 +8,8 @@ use beacon_api_client::{BlockId, StateId};
 use reqwest::Url;
 use serde::{Deserialize, Serialize};
 
+use crate::primitives::SignedBlockContents;
+
 /// Errors that can occur while interacting with the beacon API.
 #[derive(Debug, thiserror::Error)]
 #[allow(missing_docs)]
@@ -26,6 +28,8 @@ pub enum BeaconClientError {
     Inner(#[from] beacon_api_client::Error),
     #[error("Failed to parse or build URL")]
     Url,
+    #[error("Failed to publish block: {0}")]
+    Publish(String),
 }
 
 pub type BeaconClientResult<T> = Result<T, BeaconClientError>;
@@ -107,6 +111,110 @@ impl BeaconClient {
         let res = self.inner.get_beacon_block_root(BlockId::Head).await?;
         Ok(B256::from_slice(res.as_slice()))
     }
+
+    /// Fetch the execution block included in the beacon block of the given slot.
+    ///
+    /// Returns `None` if the slot was missed, i.e. if there is no beacon block for it.
+    pub async fn get_execution_block_at_slot(
+        &self,
+        slot: u64,
+    ) -> BeaconClientResult<Option<ExecutionBlockRef>> {
+        let url = self.endpoint(&format!("eth/v2/beacon/blocks/{slot}"))?;
+
+        let response = self.client.get(url).send().await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        #[derive(Deserialize)]
+        struct Block {
+            message: Message,
+        }
+        #[derive(Deserialize)]
+        struct Message {
+            body: Body,
+        }
+        #[derive(Deserialize)]
+        struct Body {
+            execution_payload: Payload,
+        }
+        #[derive(Deserialize)]
+        struct Payload {
+            block_number: String,
+            block_hash: B256,
+        }
+
+        let payload = response
+            .error_for_status()?
+            .json::<ResponseData<Block>>()
+            .await?
+            .data
+            .message
+            .body
+            .execution_payload;
+
+        Ok(Some(ExecutionBlockRef {
+            number: payload.block_number.parse()?,
+            hash: payload.block_hash,
+        }))
+    }
+
+    /// Publish a full signed Deneb block along with its blobs to the beacon node.
+    ///
+    /// The beacon node is asked to perform consensus and equivocation checks before
+    /// broadcasting the block, so that a conflicting block is never gossiped.
+    /// Reference: <https://ethereum.github.io/beacon-APIs/#/Beacon/publishBlockV2>
+    pub async fn publish_block_contents(
+        &self,
+        block_contents: &SignedBlockContents,
+    ) -> BeaconClientResult<()> {
+        let mut url = self.endpoint("eth/v2/beacon/blocks")?;
+        url.set_query(Some("broadcast_validation=consensus_and_equivocation"));
+
+        let response = self
+            .client
+            .post(url)
+            .header("content-type", "application/json")
+            .header("eth-consensus-version", "deneb")
+            .json(block_contents)
+            .send()
+            .await?;
+
+        // 200: the block was broadcast and imported, 202: the block was broadcast
+        // but failed integration. Both mean the block has been published.
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(BeaconClientError::Publish(format!("{status}: {body}")));
+        }
+
+        Ok(())
+    }
+
+    /// Returns the URL of the given endpoint, relative to the beacon node URL so that any
+    /// path prefix in it is preserved.
+    fn endpoint(&self, path: &str) -> BeaconClientResult<Url> {
+        let mut base = self.beacon_rpc_url.clone();
+        if !base.path().ends_with('/') {
+            base.set_path(&format!("{}/", base.path()));
+        }
+
+        base.join(path).map_err(|_| BeaconClientError::Url)
+    }
+
+    /// Returns the URL of the beacon node.
+    pub fn url(&self) -> &Url {
+        &self.beacon_rpc_url
+    }
+}
+
+/// A reference to an execution block, as included in a beacon block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExecutionBlockRef {
+    /// The execution block number.
+    pub number: u64,
+    /// The execution block hash.
+    pub hash: B256,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -125,6 +233,17 @@ mod tests {
     use super::*;
     use std::str::FromStr;
 
+    #[test]
+    fn test_endpoint_keeps_path_prefix() {
+        let beacon_api = BeaconClient::new(Url::from_str("http://localhost:5052/beacon").unwrap());
+        let url = beacon_api.endpoint("eth/v2/beacon/blocks").unwrap();
+        assert_eq!(url.as_str(), "http://localhost:5052/beacon/eth/v2/beacon/blocks");
+
+        let beacon_api = BeaconClient::new(Url::from_str("http://localhost:5052").unwrap());
+        let url = beacon_api.endpoint("eth/v2/beacon/blocks").unwrap();
+        assert_eq!(url.as_str(), "http://localhost:5052/eth/v2/beacon/blocks");
+    }
+
     #[tokio::test]
     async fn test_get_prev_randao() {
         let url = Url::from_str("http://remotebeast:44400").unwrap();
@@ -166,4 +285,20 @@ mod tests {
 
         assert!(beacon_api.get_parent_beacon_block_root().await.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_get_execution_block_at_slot() {
+        let url = Url::from_str("http://remotebeast:44400").unwrap();
+
+        if reqwest::get(url.clone()).await.is_err_and(|err| err.is_timeout() || err.is_connect()) {
+            eprintln!("Skipping test because remotebeast is not reachable");
+            return;
+        }
+
+        let beacon_api = BeaconClient::new(url);
+        let head = beacon_api.get_beacon_header(BlockId::Head).await.unwrap().header.message.slot;
+
+        let block = beacon_api.get_execution_block_at_slot(head).await.unwrap();
+        assert!(block.is_some_and(|block| block.number > 0));
+    }
 }
@@ -1,28 +1,33 @@
 use std::collections::HashSet;
 
 use alloy::hex;
-use axum::http::StatusCode;
+use axum::http::{HeaderMap, StatusCode};
 use beacon_api_client::VersionedValue;
 use ethereum_consensus::{
-    builder::SignedValidatorRegistration, crypto::PublicKey as BlsPublicKey,
-    deneb::mainnet::SignedBlindedBeaconBlock, Fork,
+    builder::SignedValidatorRegistration,
+    crypto::PublicKey as BlsPublicKey,
+    deneb::mainnet::SignedBlindedBeaconBlock,
+    ssz::prelude::{deserialize, serialize},
+    Fork,
 };
 use reqwest::Url;
-use tracing::{error, span_enabled, trace, warn, Level};
+use tracing::{debug, error, instrument, span_enabled, trace, warn, Level};
 
 use crate::{
     api::{
         builder::GetHeaderParams,
         spec::{
-            BuilderApi, BuilderApiError, ConstraintsApi, ErrorResponse, DELEGATE_PATH,
-            GET_PAYLOAD_PATH, REGISTER_VALIDATORS_PATH, REVOKE_PATH, STATUS_PATH,
-            SUBMIT_CONSTRAINTS_PATH,
+            BuilderApi, BuilderApiError, ConstraintsApi, Encoding, ErrorResponse,
+            ACCEPT_SSZ_OR_JSON, CONSENSUS_VERSION_HEADER, DELEGATE_PATH, GET_PAYLOAD_PATH,
+            REGISTER_VALIDATORS_PATH, REVOKE_PATH, STATUS_PATH, SUBMIT_CONSTRAINTS_PATH,
         },
     },
     primitives::{
-        BatchedSignedConstraints, GetPayloadResponse, SignedBuilderBid, SignedDelegation,
-        SignedRevocation,
+        BatchedSignedConstraints, ExecutionPayloadAndBlobsBundle, GetPayloadResponse,
+        SignedBuilderBid, SignedBuilderBidWithInclusionProofs, SignedDelegation, SignedRevocation,
     },
+    state::delegations::DelegationStore,
+    telemetry::trace_context_headers,
 };
 
 /// A client for interacting with the Constraints client API.
@@ -30,7 +35,7 @@ use crate::{
 pub struct ConstraintsClient {
     url: Url,
     client: reqwest::Client,
-    delegations: Vec<SignedDelegation>,
+    delegations: DelegationStore,
 }
 
 impl ConstraintsClient {
@@ -39,13 +44,24 @@ impl ConstraintsClient {
         Self {
             url: url.into(),
             client: reqwest::ClientBuilder::new().user_agent("bolt-sidecar").build().unwrap(),
-            delegations: Vec::new(),
+            delegations: DelegationStore::default(),
         }
     }
 
+    /// Sets the delegation store of the client, to share delegations with other clients.
+    pub fn with_delegations(mut self, delegations: DelegationStore) -> Self {
+        self.delegations = delegations;
+        self
+    }
+
     /// Adds a list of delegations to the client.
     pub fn add_delegations(&mut self, delegations: Vec<SignedDelegation>) {
-        self.delegations.extend(delegations);
+        self.delegations.add_delegations(delegations, false);
+    }
+
+    /// Returns the delegation store of the client.
+    pub fn delegations(&self) -> &DelegationStore {
+        &self.delegations
     }
 
     /// Return a public key that can be used to sign constraints with for the given
@@ -54,6 +70,8 @@ impl ConstraintsClient {
     /// Rationale:
     /// - If there are no delegatee keys, try to use the validator key directly if available.
     /// - If there are delegatee keys, try to use the first one that is available in the list.
+    ///
+    /// Revoked delegatees are never returned.
     pub fn find_signing_key(
         &self,
         validator_pubkey: BlsPublicKey,
@@ -70,13 +88,9 @@ impl ConstraintsClient {
         delegatees.into_iter().find(|delegatee| available_pubkeys.contains(delegatee))
     }
 
-    /// Finds all delegations for the given validator public key.
+    /// Finds all active delegatees for the given validator public key.
     pub fn find_delegatees(&self, validator_pubkey: &BlsPublicKey) -> HashSet<BlsPublicKey> {
-        self.delegations
-            .iter()
-            .filter(|d| d.message.validator_pubkey == *validator_pubkey)
-            .map(|d| d.message.delegatee_pubkey.clone())
-            .collect::<HashSet<_>>()
+        self.delegations.delegatees(validator_pubkey)
     }
 
     /// Returns the URL of the target client.
@@ -84,6 +98,72 @@ impl ConstraintsClient {
         self.url.as_str()
     }
 
+    /// Returns the host of the target client, used to label relay metrics.
+    pub fn host(&self) -> &str {
+        self.url.host_str().unwrap_or("unknown")
+    }
+
+    /// Gets the header along with the inclusion proofs of the constraints for its slot.
+    ///
+    /// NOTE: inclusion proofs are only available in the JSON encoding of the response.
+    /// Reference: <https://docs.boltprotocol.xyz/technical-docs/api/builder#get_header_with_proofs>
+    pub async fn get_header_with_inclusion_proofs(
+        &self,
+        params: GetHeaderParams,
+    ) -> Result<VersionedValue<SignedBuilderBidWithInclusionProofs>, BuilderApiError> {
+        let parent_hash = hex::encode_prefixed(params.parent_hash.as_ref());
+        let public_key = hex::encode_prefixed(params.public_key.as_ref());
+
+        let response = self
+            .client
+            .get(self.endpoint(&format!(
+                "/eth/v1/builder/header_with_proofs/{}/{}/{}",
+                params.slot, parent_hash, public_key,
+            )))
+            .header("content-type", "application/json")
+            .send()
+            .await?;
+
+        if response.status() == StatusCode::NO_CONTENT {
+            return Err(BuilderApiError::NoBids(params.slot));
+        }
+
+        if response.status() != StatusCode::OK {
+            let error = response.json::<ErrorResponse>().await?;
+            return Err(BuilderApiError::FailedGettingHeader(error));
+        }
+
+        let header = response.json::<VersionedValue<SignedBuilderBidWithInclusionProofs>>().await?;
+
+        if !matches!(header.version, Fork::Deneb) {
+            return Err(BuilderApiError::InvalidFork(header.version.to_string()));
+        };
+
+        Ok(header)
+    }
+
+    /// Submits a signed blinded block to the relay with the given body encoding.
+    async fn submit_blinded_block(
+        &self,
+        signed_block: &SignedBlindedBeaconBlock,
+        encoding: Encoding,
+    ) -> Result<reqwest::Response, BuilderApiError> {
+        let body = match encoding {
+            Encoding::Json => serde_json::to_vec(signed_block)?,
+            Encoding::Ssz => serialize(signed_block)?,
+        };
+
+        Ok(self
+            .client
+            .post(self.endpoint(GET_PAYLOAD_PATH))
+            .header("content-type", encoding.content_type())
+            .header("accept", ACCEPT_SSZ_OR_JSON)
+            .header(CONSENSUS_VERSION_HEADER, "deneb")
+            .body(body)
+            .send()
+            .await?)
+    }
+
     /// Joins the given path with the client's URL.
     /// If the path is invalid, an error is logged and the client's URL is returned.
     fn endpoint(&self, path: &str) -> Url {
@@ -134,22 +214,13 @@ impl BuilderApi for ConstraintsClient {
         let validator_pubkeys =
             registrations.iter().map(|r| &r.message.public_key).collect::<HashSet<_>>();
 
-        let filtered_delegations = self
-            .delegations
-            .iter()
-            .filter(|d| validator_pubkeys.contains(&d.message.validator_pubkey))
-            .cloned()
-            .collect::<Vec<_>>();
+        let filtered_delegations = self.delegations.delegations_for(&validator_pubkeys);
 
         if filtered_delegations.is_empty() {
             warn!("No delegations found for the incoming validator registrations");
             // Works also with directives like `RUST_LOG=bolt_sidecar=trace`
             if span_enabled!(Level::TRACE) {
-                let delegations_pubkeys = self
-                    .delegations
-                    .iter()
-                    .map(|d| &d.message.validator_pubkey)
-                    .collect::<HashSet<_>>();
+                let delegations_pubkeys = self.delegations.validator_pubkeys();
                 let without_delegations = validator_pubkeys
                     .iter()
                     .filter(|p| !delegations_pubkeys.contains(*p))
@@ -181,6 +252,7 @@ impl BuilderApi for ConstraintsClient {
                 params.slot, parent_hash, public_key
             )))
             .header("content-type", "application/json")
+            .header("accept", ACCEPT_SSZ_OR_JSON)
             .send()
             .await?;
 
@@ -189,30 +261,46 @@ impl BuilderApi for ConstraintsClient {
             return Err(BuilderApiError::FailedGettingHeader(error));
         }
 
-        let header = response.json::<SignedBuilderBid>().await?;
+        let header = match Encoding::from_content_type(response.headers()) {
+            Encoding::Json => response.json::<SignedBuilderBid>().await?,
+            Encoding::Ssz => {
+                check_consensus_version(response.headers())?;
+                deserialize::<SignedBuilderBid>(&response.bytes().await?)?
+            }
+        };
 
         Ok(header)
     }
 
     /// Implements: <https://ethereum.github.io/builder-specs/#/Builder/submitBlindedBlock>
+    ///
+    /// The signed blinded block is sent SSZ-encoded first, falling back to JSON if the
+    /// relay rejects it with any client error. The header has already been signed at this
+    /// point, so we can't afford to lose the payload to an encoding mismatch.
     async fn get_payload(
         &self,
         signed_block: SignedBlindedBeaconBlock,
     ) -> Result<GetPayloadResponse, BuilderApiError> {
-        let response = self
-            .client
-            .post(self.endpoint(GET_PAYLOAD_PATH))
-            .header("content-type", "application/json")
-            .body(serde_json::to_vec(&signed_block)?)
-            .send()
-            .await?;
+        let mut response = self.submit_blinded_block(&signed_block, Encoding::Ssz).await?;
+
+        if response.status().is_client_error() {
+            let status = response.status();
+            debug!(%status, "SSZ-encoded blinded block rejected by the relay, falling back to JSON");
+            response = self.submit_blinded_block(&signed_block, Encoding::Json).await?;
+        }
 
         if response.status() != StatusCode::OK {
             let error = response.json::<ErrorResponse>().await?;
             return Err(BuilderApiError::FailedGettingPayload(error));
         }
 
-        let payload = response.json().await?;
+        let payload = match Encoding::from_content_type(response.headers()) {
+            Encoding::Json => response.json().await?,
+            Encoding::Ssz => {
+                check_consensus_version(response.headers())?;
+                deserialize::<ExecutionPayloadAndBlobsBundle>(&response.bytes().await?)?.into()
+            }
+        };
 
         Ok(payload)
     }
@@ -220,6 +308,7 @@ impl BuilderApi for ConstraintsClient {
 
 #[async_trait::async_trait]
 impl ConstraintsApi for ConstraintsClient {
+    #[instrument(skip_all, fields(target = %self.url, count = constraints.len()))]
     async fn submit_constraints(
         &self,
         constraints: &BatchedSignedConstraints,
@@ -227,6 +316,7 @@ impl ConstraintsApi for ConstraintsClient {
         let response = self
             .client
             .post(self.endpoint(SUBMIT_CONSTRAINTS_PATH))
+            .headers(trace_context_headers())
             .header("content-type", "application/json")
             .body(serde_json::to_vec(&constraints)?)
             .send()
@@ -240,45 +330,23 @@ impl ConstraintsApi for ConstraintsClient {
         Ok(())
     }
 
+    /// Gets the header of a single relay without its inclusion proofs, which can't be verified
+    /// without the constraints submitted for its slot. Use
+    /// [`ConstraintsClient::get_header_with_inclusion_proofs`] to get them.
     async fn get_header_with_proofs(
         &self,
         params: GetHeaderParams,
     ) -> Result<VersionedValue<SignedBuilderBid>, BuilderApiError> {
-        let parent_hash = hex::encode_prefixed(params.parent_hash.as_ref());
-        let public_key = hex::encode_prefixed(params.public_key.as_ref());
+        let header = self.get_header_with_inclusion_proofs(params).await?;
 
-        let response = self
-            .client
-            .get(self.endpoint(&format!(
-                "/eth/v1/builder/header_with_proofs/{}/{}/{}",
-                params.slot, parent_hash, public_key,
-            )))
-            .header("content-type", "application/json")
-            .send()
-            .await?;
-
-        if response.status() == StatusCode::NO_CONTENT {
-            return Err(BuilderApiError::NoBids(params.slot));
-        }
-
-        if response.status() != StatusCode::OK {
-            let error = response.json::<ErrorResponse>().await?;
-            return Err(BuilderApiError::FailedGettingHeader(error));
-        }
-
-        let header = response.json::<VersionedValue<SignedBuilderBid>>().await?;
-
-        if !matches!(header.version, Fork::Deneb) {
-            return Err(BuilderApiError::InvalidFork(header.version.to_string()));
-        };
-
-        Ok(header)
+        Ok(VersionedValue { version: header.version, data: header.data.bid, meta: header.meta })
     }
 
     async fn delegate(&self, signed_data: &[SignedDelegation]) -> Result<(), BuilderApiError> {
         let response = self
             .client
             .post(self.endpoint(DELEGATE_PATH))
+            .headers(trace_context_headers())
             .header("content-type", "application/json")
             .body(serde_json::to_string(signed_data)?)
             .send()
@@ -296,6 +364,7 @@ impl ConstraintsApi for ConstraintsClient {
         let response = self
             .client
             .post(self.endpoint(REVOKE_PATH))
+            .headers(trace_context_headers())
             .header("content-type", "application/json")
             .body(serde_json::to_string(signed_data)?)
             .send()
@@ -310,11 +379,63 @@ impl ConstraintsApi for ConstraintsClient {
     }
 }
 
+/// Checks that the consensus version of an SSZ-encoded response is supported.
+/// SSZ payloads are not self-describing, so the version header is required to decode them.
+fn check_consensus_version(headers: &HeaderMap) -> Result<(), BuilderApiError> {
+    match headers.get(CONSENSUS_VERSION_HEADER).and_then(|v| v.to_str().ok()) {
+        Some(version) if version.eq_ignore_ascii_case("deneb") => Ok(()),
+        version => Err(BuilderApiError::InvalidFork(version.unwrap_or("missing").to_string())),
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use axum::{
+        http::{header::CONTENT_TYPE, HeaderMap, StatusCode},
+        response::IntoResponse,
+        routing::post,
+        Json, Router,
+    };
     use reqwest::Url;
+    use tokio::net::TcpListener;
 
     use super::ConstraintsClient;
+    use crate::{
+        api::spec::{BuilderApi, GET_PAYLOAD_PATH},
+        primitives::{GetPayloadResponse, PayloadAndBlobs},
+    };
+
+    /// Spawns a relay stand-in that rejects SSZ-encoded blinded blocks with the given status.
+    async fn spawn_json_only_relay(ssz_status: StatusCode) -> Url {
+        let handler = move |headers: HeaderMap| async move {
+            let content_type = headers.get(CONTENT_TYPE).and_then(|v| v.to_str().ok());
+            if content_type != Some("application/json") {
+                return ssz_status.into_response();
+            }
+
+            Json(GetPayloadResponse::Deneb(PayloadAndBlobs::default())).into_response()
+        };
+        let router = Router::new().route(GET_PAYLOAD_PATH, post(handler));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+
+        Url::parse(&format!("http://{addr}")).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_get_payload_falls_back_to_json() {
+        for status in [
+            StatusCode::BAD_REQUEST,
+            StatusCode::NOT_ACCEPTABLE,
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+        ] {
+            let client = ConstraintsClient::new(spawn_json_only_relay(status).await);
+            let payload = client.get_payload(Default::default()).await.unwrap();
+            assert!(matches!(payload, GetPayloadResponse::Deneb(_)), "status {status}");
+        }
+    }
 
     #[test]
     fn test_join_endpoints() {
@@ -6,7 +6,7 @@ use alloy::{
     providers::{ProviderBuilder, RootProvider},
     rpc::{
         client::{BatchRequest, ClientBuilder, RpcClient},
-        types::{Block, FeeHistory, TransactionReceipt},
+        types::{Block, EIP1186AccountProofResponse, FeeHistory, TransactionReceipt},
     },
     transports::{http::Http, TransportErrorKind, TransportResult},
 };
@@ -142,6 +142,18 @@ impl ExecutionClient {
         self.rpc.request("eth_getBlockByNumber", (tag, full)).await
     }
 
+    /// Get the Merkle proof of the given account in the state trie of the given block,
+    /// without any storage proofs.
+    pub async fn get_account_proof(
+        &self,
+        address: &Address,
+        block_number: u64,
+    ) -> TransportResult<EIP1186AccountProofResponse> {
+        let tag = BlockNumberOrTag::Number(block_number);
+
+        self.rpc.request("eth_getProof", (address, &[] as &[B256], tag)).await
+    }
+
     /// Send a raw transaction to the network.
     #[allow(unused)]
     pub async fn send_raw_transaction(&self, raw: Bytes) -> TransportResult<B256> {
@@ -4,6 +4,14 @@
 pub mod constraints;
 pub use constraints::ConstraintsClient;
 
+/// Module for fanning out Builder API and Constraints API requests to multiple relays.
+pub mod multiplexer;
+pub use multiplexer::ConstraintsMultiplexer;
+
+/// Module for streaming signed constraints to the Constraints API as soon as they are signed.
+pub mod streamer;
+pub use streamer::ConstraintsStreamer;
+
 /// Module defining an execution layer client wrapper around [`alloy::rpc::client::RpcClient`]
 /// for extending the [`alloy::providers::RootProvider`] with methods relevant to the Bolt state.
 pub mod execution;
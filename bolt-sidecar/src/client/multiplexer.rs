@@ -0,0 +1,542 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    future::Future,
+    sync::Arc,
+    time::Instant,
+};
+
+use alloy::primitives::{TxHash, B256};
+use axum::http::StatusCode;
+use beacon_api_client::VersionedValue;
+use ethereum_consensus::{
+    builder::SignedValidatorRegistration, crypto::PublicKey as BlsPublicKey,
+    deneb::mainnet::SignedBlindedBeaconBlock,
+};
+use futures::future::{join_all, select_ok};
+use parking_lot::RwLock;
+use tracing::{debug, info, warn};
+
+use super::ConstraintsClient;
+use crate::{
+    api::{
+        builder::GetHeaderParams,
+        spec::{BuilderApi, BuilderApiError, ConstraintsApi, RelayIndex, RelayResults},
+    },
+    primitives::{
+        proofs::{constraints_proof_data, verify_multiproofs},
+        BatchedSignedConstraints, GetPayloadResponse, SignedBuilderBid, SignedDelegation,
+        SignedRevocation, Slot,
+    },
+    state::delegations::DelegationStore,
+    telemetry::ApiMetrics,
+};
+
+/// The number of slots for which submitted constraints are kept to verify the
+/// inclusion proofs of incoming bids.
+const CONSTRAINTS_CACHE_SLOTS: u64 = 64;
+
+/// The proof data of the constraints submitted for each slot: the transaction hashes and
+/// corresponding hash tree roots.
+type ConstraintsProofData = BTreeMap<Slot, Vec<(TxHash, B256)>>;
+
+/// A client that fans out Builder API and Constraints API requests to multiple relays
+/// concurrently, without the need of a multiplexer like bolt-boost or an MEV-Boost fork.
+///
+/// - Constraints, delegations, revocations and registrations are submitted to all relays.
+///   Constraints can also be resubmitted to the relays that failed only, see
+///   [`ConstraintsApi::submit_constraints_to`].
+/// - Bids are gathered from all relays, their inclusion proofs are verified against the constraints
+///   submitted through this client, and the best valid bid is returned.
+/// - Per-relay latency and failures are reported in logs and metrics, labeled by relay host.
+///
+/// NOTE: all the inner relay clients share the same delegations, which can be updated at runtime.
+#[derive(Debug, Clone)]
+pub struct ConstraintsMultiplexer {
+    relays: Vec<ConstraintsClient>,
+    /// The proof data of the constraints submitted for each slot. This is shared between
+    /// clones, so that bids requested by the builder proxy can be verified against the
+    /// constraints submitted by the driver.
+    constraints: Arc<RwLock<ConstraintsProofData>>,
+    /// The delegations shared by all the relay clients.
+    delegations: DelegationStore,
+}
+
+impl ConstraintsMultiplexer {
+    /// Creates a new multiplexer over the given relay clients.
+    pub fn new(relays: Vec<ConstraintsClient>) -> Self {
+        let delegations = DelegationStore::default();
+        let relays =
+            relays.into_iter().map(|relay| relay.with_delegations(delegations.clone())).collect();
+
+        Self { relays, constraints: Default::default(), delegations }
+    }
+
+    /// Adds a list of delegations to all the relay clients.
+    pub fn add_delegations(&mut self, delegations: Vec<SignedDelegation>) {
+        self.delegations.add_delegations(delegations, false);
+    }
+
+    /// Returns the delegation store shared by all the relay clients.
+    pub fn delegations(&self) -> &DelegationStore {
+        &self.delegations
+    }
+
+    /// Adds the given delegations at runtime and propagates the new ones to the relays.
+    /// If `force` is true, previously revoked delegatees are re-enabled.
+    ///
+    /// The delegations are applied locally even if propagating them to the relays fails.
+    /// Returns the delegations that were added.
+    pub async fn update_delegations(
+        &self,
+        delegations: Vec<SignedDelegation>,
+        force: bool,
+    ) -> Result<Vec<SignedDelegation>, BuilderApiError> {
+        let added = self.delegations.add_delegations(delegations, force);
+
+        if !added.is_empty() {
+            info!(count = added.len(), "Added new delegations, propagating to relays");
+            self.delegate(&added).await?;
+        }
+
+        Ok(added)
+    }
+
+    /// Applies the given revocations at runtime and propagates the new ones to the relays.
+    ///
+    /// The revocations are applied locally even if propagating them to the relays fails.
+    /// Returns the revocations that were applied.
+    pub async fn revoke_delegations(
+        &self,
+        revocations: Vec<SignedRevocation>,
+    ) -> Result<Vec<SignedRevocation>, BuilderApiError> {
+        let applied = self.delegations.revoke(revocations);
+
+        if !applied.is_empty() {
+            info!(count = applied.len(), "Revoked delegatees, propagating to relays");
+            self.revoke(&applied).await?;
+        }
+
+        Ok(applied)
+    }
+
+    /// Return a public key that can be used to sign constraints with for the given
+    /// validator public key. See [`ConstraintsClient::find_signing_key`].
+    pub fn find_signing_key(
+        &self,
+        validator_pubkey: BlsPublicKey,
+        available_pubkeys: HashSet<BlsPublicKey>,
+    ) -> Option<BlsPublicKey> {
+        self.relays.first()?.find_signing_key(validator_pubkey, available_pubkeys)
+    }
+
+    /// Returns the URLs of the relays.
+    pub fn targets(&self) -> Vec<&str> {
+        self.relays.iter().map(|relay| relay.target()).collect()
+    }
+
+    /// Saves the proof data of the given constraints, to verify inclusion proofs against later.
+    ///
+    /// Constraints whose proof data can't be computed are skipped: bids for their slot will
+    /// fail the inclusion proof verification.
+    fn save_constraints(&self, constraints: &BatchedSignedConstraints) {
+        let mut cache = self.constraints.write();
+
+        for signed_constraints in constraints {
+            let slot = signed_constraints.message.slot;
+            let proof_data = match constraints_proof_data(signed_constraints) {
+                Ok(proof_data) => proof_data,
+                Err(err) => {
+                    warn!(slot, ?err, "Failed to compute the proof data of constraints");
+                    continue;
+                }
+            };
+
+            let entry = cache.entry(slot).or_default();
+            for (hash, leaf) in proof_data {
+                if !entry.iter().any(|(saved_hash, _)| saved_hash == &hash) {
+                    entry.push((hash, leaf));
+                }
+            }
+        }
+
+        if let Some(latest_slot) = cache.keys().next_back().copied() {
+            let min_slot = latest_slot.saturating_sub(CONSTRAINTS_CACHE_SLOTS);
+            *cache = cache.split_off(&min_slot);
+        }
+    }
+
+    /// Runs the given request against all relays concurrently. See [`Self::fan_out_to`].
+    async fn fan_out<'a, T, F, Fut>(&'a self, method: &'static str, request: F) -> RelayResults<T>
+    where
+        F: Fn(&'a ConstraintsClient) -> Fut,
+        Fut: Future<Output = Result<T, BuilderApiError>>,
+    {
+        let relays = (0..self.relays.len()).collect::<Vec<_>>();
+        self.fan_out_to(method, &relays, request).await
+    }
+
+    /// Runs the given request against the relays at the given indexes concurrently, recording
+    /// per-relay latency and failures. Unknown indexes are ignored.
+    async fn fan_out_to<'a, T, F, Fut>(
+        &'a self,
+        method: &'static str,
+        relays: &[RelayIndex],
+        request: F,
+    ) -> RelayResults<T>
+    where
+        F: Fn(&'a ConstraintsClient) -> Fut,
+        Fut: Future<Output = Result<T, BuilderApiError>>,
+    {
+        let requests = relays.iter().filter_map(|&index| {
+            let relay = self.relays.get(index)?;
+            let fut = request(relay);
+            Some(async move {
+                let start = Instant::now();
+                let res = fut.await;
+                let elapsed = start.elapsed();
+
+                ApiMetrics::observe_relay_request(elapsed, relay.host().to_string(), method);
+                match &res {
+                    Ok(_) => {
+                        debug!(relay = relay.target(), method, ?elapsed, "Relay request succeeded")
+                    }
+                    Err(BuilderApiError::NoBids(_)) => {
+                        debug!(relay = relay.target(), method, ?elapsed, "No bids from relay")
+                    }
+                    Err(err) => {
+                        warn!(
+                            relay = relay.target(),
+                            method,
+                            ?elapsed,
+                            ?err,
+                            "Relay request failed"
+                        );
+                        ApiMetrics::increment_relay_request_failures(
+                            relay.host().to_string(),
+                            method,
+                        );
+                    }
+                }
+
+                (index, res)
+            })
+        });
+
+        let results: RelayResults<T> = join_all(requests).await.into_iter().collect();
+
+        let failed = results.failed();
+        if !failed.is_empty() {
+            let failed =
+                failed.iter().map(|&index| self.relays[index].target()).collect::<Vec<_>>();
+            let succeeded = results.succeeded().len();
+            warn!(method, ?failed, succeeded, "Request failed for some relays");
+        }
+
+        results
+    }
+}
+
+#[async_trait::async_trait]
+impl BuilderApi for ConstraintsMultiplexer {
+    /// Returns OK if at least one relay is available.
+    async fn status(&self) -> Result<StatusCode, BuilderApiError> {
+        let results = self.fan_out("status", |relay| relay.status()).await;
+
+        if results.iter().any(|(_, res)| res.as_ref().is_ok_and(|s| *s == StatusCode::OK)) {
+            Ok(StatusCode::OK)
+        } else {
+            Ok(StatusCode::SERVICE_UNAVAILABLE)
+        }
+    }
+
+    /// Returns OK if the registrations were accepted by at least one relay, like MEV-Boost
+    /// does, so that the beacon node doesn't retry them against the relays that accepted them.
+    /// Failures are logged and counted per relay.
+    async fn register_validators(
+        &self,
+        registrations: Vec<SignedValidatorRegistration>,
+    ) -> Result<(), BuilderApiError> {
+        self.fan_out("register_validators", |relay| {
+            relay.register_validators(registrations.clone())
+        })
+        .await
+        .into_any_ok()
+    }
+
+    /// Returns the highest bid among all relays.
+    async fn get_header(
+        &self,
+        params: GetHeaderParams,
+    ) -> Result<SignedBuilderBid, BuilderApiError> {
+        let slot = params.slot;
+        let results = self.fan_out("get_header", |relay| relay.get_header(params.clone())).await;
+
+        results
+            .into_iter()
+            .filter_map(|(_, res)| res.ok())
+            .max_by_key(|bid| bid.message.value)
+            .ok_or(BuilderApiError::NoBids(slot))
+    }
+
+    /// Submits the signed blinded block to all relays, and returns the first payload
+    /// matching the signed header.
+    async fn get_payload(
+        &self,
+        signed_block: SignedBlindedBeaconBlock,
+    ) -> Result<GetPayloadResponse, BuilderApiError> {
+        let block_hash = &signed_block.message.body.execution_payload_header.block_hash;
+
+        let requests = self.relays.iter().map(|relay| {
+            let signed_block = signed_block.clone();
+            Box::pin(async move {
+                let start = Instant::now();
+                let res = relay.get_payload(signed_block).await;
+                let elapsed = start.elapsed();
+
+                let host = relay.host().to_string();
+                ApiMetrics::observe_relay_request(elapsed, host.clone(), "get_payload");
+
+                let payload = res.inspect_err(|err| {
+                    warn!(relay = relay.target(), ?elapsed, ?err, "Failed to get payload");
+                    ApiMetrics::increment_relay_request_failures(host, "get_payload");
+                })?;
+
+                if payload.block_hash() != block_hash {
+                    warn!(relay = relay.target(), "Relay returned a payload for another block");
+                    return Err(BuilderApiError::Generic("payload block hash mismatch".to_string()));
+                }
+
+                Ok(payload)
+            })
+        });
+
+        if self.relays.is_empty() {
+            return Err(BuilderApiError::Generic("no relays configured".to_string()));
+        }
+
+        select_ok(requests).await.map(|(payload, _)| payload)
+    }
+}
+
+#[async_trait::async_trait]
+impl ConstraintsApi for ConstraintsMultiplexer {
+    /// Submits the constraints to all relays. Returns an error if any relay failed, use
+    /// [`ConstraintsApi::submit_constraints_to`] to know which ones.
+    async fn submit_constraints(
+        &self,
+        constraints: &BatchedSignedConstraints,
+    ) -> Result<(), BuilderApiError> {
+        self.save_constraints(constraints);
+
+        self.fan_out("submit_constraints", |relay| relay.submit_constraints(constraints))
+            .await
+            .into_all_ok()
+            .map(|_| ())
+    }
+
+    async fn submit_constraints_to(
+        &self,
+        constraints: &BatchedSignedConstraints,
+        relays: &[RelayIndex],
+    ) -> RelayResults<()> {
+        self.save_constraints(constraints);
+
+        self.fan_out_to("submit_constraints", relays, |relay| relay.submit_constraints(constraints))
+            .await
+    }
+
+    fn relays_len(&self) -> usize {
+        self.relays.len()
+    }
+
+    /// Gathers bids from all relays, verifies their inclusion proofs against the constraints
+    /// submitted for the slot, and returns the highest valid bid.
+    async fn get_header_with_proofs(
+        &self,
+        params: GetHeaderParams,
+    ) -> Result<VersionedValue<SignedBuilderBid>, BuilderApiError> {
+        let slot = params.slot;
+        let results = self
+            .fan_out("get_header_with_proofs", |relay| {
+                relay.get_header_with_inclusion_proofs(params.clone())
+            })
+            .await;
+
+        let proof_data = self.constraints.read().get(&slot).cloned().unwrap_or_default();
+
+        let mut best_bid: Option<VersionedValue<SignedBuilderBid>> = None;
+        let mut invalid_bids = 0;
+        for (index, res) in results {
+            let Ok(bid) = res else { continue };
+            let relay = &self.relays[index];
+
+            // If we didn't submit constraints for this slot, there is nothing to verify.
+            if !proof_data.is_empty() {
+                let root = B256::from(bid.data.bid.message.header.transactions_root.0);
+
+                let start = Instant::now();
+                if let Err(err) = verify_multiproofs(&proof_data, &bid.data.proofs, root) {
+                    warn!(relay = relay.target(), ?err, "Invalid inclusion proofs, skipping bid");
+                    ApiMetrics::increment_relay_invalid_bids(relay.host().to_string());
+                    invalid_bids += 1;
+                    continue;
+                }
+                debug!(relay = relay.target(), elapsed = ?start.elapsed(), "Verified inclusion proofs");
+            }
+
+            let value = bid.data.bid.message.value;
+            if best_bid.as_ref().is_none_or(|best| value > best.data.message.value) {
+                best_bid = Some(VersionedValue {
+                    version: bid.version,
+                    data: bid.data.bid,
+                    meta: bid.meta,
+                });
+            }
+        }
+
+        let Some(best_bid) = best_bid else {
+            if invalid_bids > 0 {
+                return Err(BuilderApiError::InvalidProofs(slot));
+            }
+            return Err(BuilderApiError::NoBids(slot));
+        };
+        info!(slot, value = %best_bid.data.message.value, "Selected best bid among relays");
+
+        Ok(best_bid)
+    }
+
+    /// Submits the delegations to all relays. Returns an error if any relay failed.
+    async fn delegate(&self, signed_data: &[SignedDelegation]) -> Result<(), BuilderApiError> {
+        self.fan_out("delegate", |relay| relay.delegate(signed_data))
+            .await
+            .into_all_ok()
+            .map(|_| ())
+    }
+
+    /// Submits the revocations to all relays. Returns an error if any relay failed.
+    async fn revoke(&self, signed_data: &[SignedRevocation]) -> Result<(), BuilderApiError> {
+        self.fan_out("revoke", |relay| relay.revoke(signed_data)).await.into_all_ok().map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::U256;
+    use axum::{
+        routing::{get, post},
+        Json, Router,
+    };
+    use ethereum_consensus::Fork;
+    use reqwest::Url;
+    use tokio::net::TcpListener;
+
+    use super::*;
+    use crate::{
+        api::spec::SUBMIT_CONSTRAINTS_PATH,
+        primitives::{ConstraintsMessage, SignedConstraints},
+        test_util::random_constraints,
+    };
+
+    /// Spawns a relay stand-in that always returns a bid with the given value.
+    async fn spawn_relay(value: u64) -> Url {
+        let mut bid = SignedBuilderBid::default();
+        bid.message.value = U256::from(value);
+        let response = VersionedValue { version: Fork::Deneb, data: bid, meta: Default::default() };
+        let response = serde_json::to_value(response).unwrap();
+
+        let router = Router::new().route(
+            "/eth/v1/builder/header_with_proofs/:slot/:parent_hash/:pubkey",
+            get(move || async move { Json(response.clone()) }),
+        );
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+
+        Url::parse(&format!("http://{addr}")).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_get_header_with_proofs_picks_best_bid() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let relays = vec![
+            ConstraintsClient::new(spawn_relay(1).await),
+            ConstraintsClient::new(spawn_relay(3).await),
+            ConstraintsClient::new(spawn_relay(2).await),
+            // An unreachable relay should not affect the result
+            ConstraintsClient::new(Url::parse("http://127.0.0.1:1").unwrap()),
+        ];
+
+        let multiplexer = ConstraintsMultiplexer::new(relays);
+        let params = GetHeaderParams {
+            slot: 1,
+            parent_hash: Default::default(),
+            public_key: Default::default(),
+        };
+
+        let bid = multiplexer.get_header_with_proofs(params).await.unwrap();
+        assert_eq!(bid.data.message.value, U256::from(3));
+    }
+
+    #[tokio::test]
+    async fn test_get_header_with_proofs_invalid_proofs() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let multiplexer =
+            ConstraintsMultiplexer::new(vec![ConstraintsClient::new(spawn_relay(1).await)]);
+
+        // The relay returns bids without proofs for the submitted constraints
+        let tx = random_constraints(1).remove(0);
+        let message = ConstraintsMessage::from_tx(Default::default(), 1, tx);
+        multiplexer
+            .save_constraints(&vec![SignedConstraints { message, signature: Default::default() }]);
+
+        let params = GetHeaderParams {
+            slot: 1,
+            parent_hash: Default::default(),
+            public_key: Default::default(),
+        };
+
+        let err = multiplexer.get_header_with_proofs(params).await.unwrap_err();
+        assert!(matches!(err, BuilderApiError::InvalidProofs(1)));
+        assert_eq!(err.fallback_reason(), "invalid_proofs");
+    }
+
+    #[tokio::test]
+    async fn test_submit_constraints_to_reports_failed_relays() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let router =
+            Router::new().route(SUBMIT_CONSTRAINTS_PATH, post(|| async { StatusCode::OK }));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+
+        let relays = vec![
+            ConstraintsClient::new(Url::parse(&format!("http://{addr}")).unwrap()),
+            ConstraintsClient::new(Url::parse("http://127.0.0.1:1").unwrap()),
+        ];
+        let multiplexer = ConstraintsMultiplexer::new(relays);
+        assert_eq!(multiplexer.relays_len(), 2);
+
+        let tx = random_constraints(1).remove(0);
+        let message = ConstraintsMessage::from_tx(Default::default(), 1, tx);
+        let constraints = vec![SignedConstraints { message, signature: Default::default() }];
+
+        // A single failed relay fails the whole submission
+        assert!(multiplexer.submit_constraints(&constraints).await.is_err());
+
+        let results = multiplexer.submit_constraints_to(&constraints, &[0, 1]).await;
+        assert_eq!(results.succeeded(), vec![0]);
+        assert_eq!(results.failed(), vec![1]);
+
+        // Only the requested relays are contacted
+        let results = multiplexer.submit_constraints_to(&constraints, &[0]).await;
+        assert_eq!(results.succeeded(), vec![0]);
+        assert!(results.failed().is_empty());
+
+        // The proof data is saved even if some relays failed
+        assert_eq!(multiplexer.constraints.read().get(&1).map(Vec::len), Some(1));
+    }
+}
@@ -0,0 +1,584 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::Arc,
+    time::Instant,
+};
+
+use parking_lot::Mutex;
+use tokio::{
+    sync::{mpsc, oneshot},
+    task::JoinSet,
+};
+use tracing::{debug, error, info, info_span, warn, Instrument, Span};
+
+use crate::{
+    api::spec::{ConstraintsApi, RelayIndex},
+    common::backoff::retry_with_backoff,
+    crypto::SignableBLS,
+    primitives::{BatchedSignedConstraints, Slot},
+    state::SidecarHealth,
+    telemetry::ApiMetrics,
+};
+
+/// The number of retries for constraints streamed as soon as they are signed. This is kept low
+/// to avoid stalling the queue, as any constraints that fail to be submitted here will be picked
+/// up by the reconciliation step at the commitment deadline.
+const STREAM_MAX_RETRIES: usize = 2;
+
+/// The number of retries for the reconciliation step at the commitment deadline.
+const RECONCILE_MAX_RETRIES: usize = 10;
+
+/// The number of slots for which the digests of submitted constraints are kept for deduplication.
+const SUBMITTED_CACHE_SLOTS: u64 = 64;
+
+/// The relays that accepted each submitted constraints, by slot and message digest.
+type SubmittedConstraints = BTreeMap<Slot, HashMap<[u8; 32], HashSet<RelayIndex>>>;
+
+/// A message for the constraints streaming task.
+#[derive(Debug)]
+enum StreamerMessage {
+    /// Newly signed constraints to submit as soon as possible, with the span of the request
+    /// for which they were signed.
+    Submit(BatchedSignedConstraints, Span),
+    /// The full list of constraints for a slot at the commitment deadline. Any constraints
+    /// that haven't been accepted by every relay yet are submitted again to the relays
+    /// missing them.
+    Reconcile(Slot, BatchedSignedConstraints),
+    /// A request to be notified once all the previously queued messages have been processed,
+    /// including any reconciliation in progress.
+    Flush(oneshot::Sender<()>),
+}
+
+/// A handle to a background task that streams signed constraints to the constraints API
+/// as soon as they are signed, instead of waiting for the commitment deadline.
+///
+/// Guarantees:
+/// - Ordering: streamed constraints are submitted in the same order they were signed, by a single
+///   task processing a FIFO queue. A batch is never streamed before the previous one has completed.
+/// - Deduplication: constraints are identified by their message digest, and the relays that
+///   accepted them are tracked. Constraints are never submitted again to a relay that already
+///   accepted them, and are only resubmitted to the relays that failed.
+///
+/// At the commitment deadline, [`ConstraintsStreamer::reconcile`] should be called with the full
+/// list of constraints for the slot, so that any constraints that failed to be streamed are
+/// submitted again with a more aggressive retry policy. Reconciliation runs in its own task, so
+/// that a slow relay doesn't delay the streaming of constraints for later slots.
+#[derive(Debug, Clone)]
+pub struct ConstraintsStreamer {
+    tx: mpsc::UnboundedSender<StreamerMessage>,
+}
+
+impl ConstraintsStreamer {
+    /// Spawns a new streaming task submitting constraints through the given client, recording
+    /// successful submissions in the given health.
+    pub fn spawn<C>(client: C, health: SidecarHealth) -> Self
+    where
+        C: ConstraintsApi + Send + Sync + 'static,
+    {
+        let (tx, rx) = mpsc::unbounded_channel();
+
+        let submitter =
+            Submitter { client: Arc::new(client), submitted: Default::default(), health };
+        let task = StreamerTask { submitter, rx, reconciling: JoinSet::new() };
+        tokio::spawn(task.run());
+
+        Self { tx }
+    }
+
+    /// Queues newly signed constraints for submission. The submission is traced as following
+    /// from the current span.
+    pub fn submit(&self, constraints: BatchedSignedConstraints) {
+        if constraints.is_empty() {
+            return;
+        }
+
+        if self.tx.send(StreamerMessage::Submit(constraints, Span::current())).is_err() {
+            error!("Constraints streaming task has stopped, constraints will not be streamed");
+        }
+    }
+
+    /// Queues the full list of constraints for the given slot for reconciliation.
+    pub fn reconcile(&self, slot: Slot, constraints: BatchedSignedConstraints) {
+        if self.tx.send(StreamerMessage::Reconcile(slot, constraints)).is_err() {
+            error!(slot, "Constraints streaming task has stopped, failed to reconcile constraints");
+        }
+    }
+
+    /// Waits until all the constraints queued so far have been processed, whether their
+    /// submission succeeded or not.
+    pub async fn flush(&self) {
+        let (tx, rx) = oneshot::channel();
+        if self.tx.send(StreamerMessage::Flush(tx)).is_err() {
+            error!("Constraints streaming task has stopped, nothing to flush");
+            return;
+        }
+
+        let _ = rx.await;
+    }
+}
+
+/// The background task processing the constraints streaming queue.
+struct StreamerTask<C> {
+    submitter: Submitter<C>,
+    rx: mpsc::UnboundedReceiver<StreamerMessage>,
+    /// The reconciliations in progress.
+    reconciling: JoinSet<()>,
+}
+
+impl<C: ConstraintsApi + Send + Sync + 'static> StreamerTask<C> {
+    /// Runs the task until all the handles are dropped.
+    async fn run(mut self) {
+        let mut next = None;
+
+        loop {
+            let message = match next.take() {
+                Some(message) => message,
+                None => tokio::select! {
+                    message = self.rx.recv() => match message {
+                        Some(message) => message,
+                        None => break,
+                    },
+                    Some(_) = self.reconciling.join_next() => continue,
+                },
+            };
+
+            match message {
+                StreamerMessage::Submit(mut constraints, origin) => {
+                    let span = info_span!("stream_constraints");
+                    span.follows_from(&origin);
+
+                    // Batch together any other constraints that are already queued, stopping
+                    // at the first other message to preserve ordering.
+                    while let Ok(message) = self.rx.try_recv() {
+                        match message {
+                            StreamerMessage::Submit(more, origin) => {
+                                span.follows_from(&origin);
+                                constraints.extend(more);
+                            }
+                            other => {
+                                next = Some(other);
+                                break;
+                            }
+                        }
+                    }
+
+                    self.submitter
+                        .submit(constraints, "stream", STREAM_MAX_RETRIES)
+                        .instrument(span)
+                        .await;
+                }
+                StreamerMessage::Reconcile(slot, constraints) => {
+                    let submitter = self.submitter.clone();
+                    self.reconciling.spawn(async move {
+                        let total = constraints.len();
+                        let pending =
+                            submitter.submit(constraints, "reconcile", RECONCILE_MAX_RETRIES).await;
+                        info!(
+                            slot,
+                            total, pending, "Reconciled constraints at commitment deadline"
+                        );
+                    });
+                }
+                StreamerMessage::Flush(tx) => {
+                    while self.reconciling.join_next().await.is_some() {}
+                    let _ = tx.send(());
+                }
+            }
+        }
+
+        while self.reconciling.join_next().await.is_some() {}
+        debug!("Constraints streaming task stopped");
+    }
+}
+
+/// Submits constraints to the relays that haven't accepted them yet, keeping track of the
+/// relays that did.
+struct Submitter<C> {
+    client: Arc<C>,
+    submitted: Arc<Mutex<SubmittedConstraints>>,
+    /// The health of the sidecar, exposing the last successful submission.
+    health: SidecarHealth,
+}
+
+impl<C> Clone for Submitter<C> {
+    fn clone(&self) -> Self {
+        Self {
+            client: self.client.clone(),
+            submitted: self.submitted.clone(),
+            health: self.health.clone(),
+        }
+    }
+}
+
+impl<C: ConstraintsApi + Send + Sync> Submitter<C> {
+    /// Submits the constraints to the relays that haven't accepted them yet, recording the
+    /// relays that accept them. Returns the number of constraints that had to be submitted.
+    ///
+    /// Constraints missing from the same relays are submitted together, and retried only
+    /// against the relays that failed. The outcome is recorded in the metrics under the given
+    /// mode.
+    async fn submit(
+        &self,
+        constraints: BatchedSignedConstraints,
+        mode: &'static str,
+        max_retries: usize,
+    ) -> usize {
+        let all_relays = (0..self.client.relays_len()).collect::<HashSet<_>>();
+
+        // Group the constraints by the relays missing them, preserving their order.
+        let mut groups: Vec<(Vec<RelayIndex>, BatchedSignedConstraints)> = Vec::new();
+        let mut seen = HashSet::new();
+        {
+            let submitted = self.submitted.lock();
+            for c in constraints {
+                let digest = c.message.digest();
+                if !seen.insert((c.message.slot, digest)) {
+                    continue;
+                }
+
+                let accepted = submitted.get(&c.message.slot).and_then(|s| s.get(&digest));
+                let mut missing = match accepted {
+                    Some(accepted) => all_relays.difference(accepted).copied().collect::<Vec<_>>(),
+                    None => all_relays.iter().copied().collect(),
+                };
+                if missing.is_empty() {
+                    continue;
+                }
+                missing.sort_unstable();
+
+                match groups.iter_mut().find(|(relays, _)| *relays == missing) {
+                    Some((_, batch)) => batch.push(c),
+                    None => groups.push((missing, vec![c])),
+                }
+            }
+        }
+
+        let mut pending = 0;
+        for (relays, batch) in groups {
+            pending += batch.len();
+            self.submit_to(batch, relays, mode, max_retries).await;
+        }
+
+        pending
+    }
+
+    /// Submits the batch to the given relays, retrying only the relays that failed.
+    async fn submit_to(
+        &self,
+        batch: BatchedSignedConstraints,
+        relays: Vec<RelayIndex>,
+        mode: &'static str,
+        max_retries: usize,
+    ) {
+        let start = Instant::now();
+        let remaining = Mutex::new(relays);
+        let (client, batch, remaining) = (&self.client, &batch, &remaining);
+        let result = retry_with_backoff(max_retries, || async move {
+            let relays = remaining.lock().clone();
+            let results = client.submit_constraints_to(batch, &relays).await;
+
+            self.record_accepted(batch, &results.succeeded());
+            let failed = results.failed();
+            *remaining.lock() = failed.clone();
+
+            if failed.is_empty() {
+                Ok(())
+            } else {
+                warn!(?failed, count = batch.len(), "Failed to submit constraints, retrying...");
+                Err(failed)
+            }
+        })
+        .await;
+        ApiMetrics::observe_constraints_submission(start.elapsed(), mode, result.is_ok());
+
+        match result {
+            Ok(()) => debug!(count = batch.len(), "Submitted constraints"),
+            Err(failed) => error!(?failed, count = batch.len(), "Failed to submit constraints"),
+        }
+    }
+
+    /// Records that the given relays accepted the batch.
+    fn record_accepted(&self, batch: &BatchedSignedConstraints, relays: &[RelayIndex]) {
+        if relays.is_empty() {
+            return;
+        }
+
+        self.health.record_constraints_success();
+
+        let mut submitted = self.submitted.lock();
+        for c in batch {
+            let accepted = submitted.entry(c.message.slot).or_default();
+            accepted.entry(c.message.digest()).or_default().extend(relays.iter().copied());
+        }
+
+        if let Some(latest_slot) = submitted.keys().next_back().copied() {
+            let min_slot = latest_slot.saturating_sub(SUBMITTED_CACHE_SLOTS);
+            *submitted = submitted.split_off(&min_slot);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use axum::http::StatusCode;
+    use beacon_api_client::VersionedValue;
+    use ethereum_consensus::{
+        builder::SignedValidatorRegistration, deneb::mainnet::SignedBlindedBeaconBlock,
+    };
+
+    use super::*;
+    use crate::{
+        api::{
+            builder::GetHeaderParams,
+            spec::{BuilderApi, BuilderApiError, RelayResults},
+        },
+        primitives::{
+            ConstraintsMessage, GetPayloadResponse, SignedBuilderBid, SignedConstraints,
+            SignedDelegation, SignedRevocation,
+        },
+        signer::local::LocalSigner,
+    };
+
+    /// The number of relays behind the mock client.
+    const MOCK_RELAYS: usize = 2;
+
+    /// A constraints client over [`MOCK_RELAYS`] relays that records the batches accepted by
+    /// each relay.
+    #[derive(Clone, Default)]
+    struct MockClient {
+        batches: Arc<Mutex<Vec<(RelayIndex, BatchedSignedConstraints)>>>,
+        /// The relays rejecting submissions.
+        failing: Arc<Mutex<HashSet<RelayIndex>>>,
+        /// A slot for which submissions never complete, like with an unresponsive relay.
+        hanging_slot: Arc<Mutex<Option<Slot>>>,
+    }
+
+    impl MockClient {
+        fn set_failing(&self, relays: &[RelayIndex]) {
+            *self.failing.lock() = relays.iter().copied().collect();
+        }
+
+        /// Returns the batches accepted by the given relay.
+        fn accepted(&self, relay: RelayIndex) -> Vec<BatchedSignedConstraints> {
+            let batches = self.batches.lock();
+            batches.iter().filter(|(r, _)| *r == relay).map(|(_, batch)| batch.clone()).collect()
+        }
+
+        /// Waits for the given relay to accept at least `count` batches.
+        async fn wait_for_batches(
+            &self,
+            relay: RelayIndex,
+            count: usize,
+        ) -> Vec<BatchedSignedConstraints> {
+            for _ in 0..100 {
+                if self.accepted(relay).len() >= count {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+
+            self.accepted(relay)
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl BuilderApi for MockClient {
+        async fn status(&self) -> Result<StatusCode, BuilderApiError> {
+            Ok(StatusCode::OK)
+        }
+
+        async fn register_validators(
+            &self,
+            _registrations: Vec<SignedValidatorRegistration>,
+        ) -> Result<(), BuilderApiError> {
+            Ok(())
+        }
+
+        async fn get_header(
+            &self,
+            params: GetHeaderParams,
+        ) -> Result<SignedBuilderBid, BuilderApiError> {
+            Err(BuilderApiError::NoBids(params.slot))
+        }
+
+        async fn get_payload(
+            &self,
+            _signed_block: SignedBlindedBeaconBlock,
+        ) -> Result<GetPayloadResponse, BuilderApiError> {
+            Err(BuilderApiError::Generic("no payload".to_string()))
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl ConstraintsApi for MockClient {
+        async fn submit_constraints(
+            &self,
+            constraints: &BatchedSignedConstraints,
+        ) -> Result<(), BuilderApiError> {
+            let relays = (0..MOCK_RELAYS).collect::<Vec<_>>();
+            self.submit_constraints_to(constraints, &relays).await.into_all_ok().map(|_| ())
+        }
+
+        async fn submit_constraints_to(
+            &self,
+            constraints: &BatchedSignedConstraints,
+            relays: &[RelayIndex],
+        ) -> RelayResults<()> {
+            let hanging_slot = *self.hanging_slot.lock();
+            if constraints.iter().any(|c| Some(c.message.slot) == hanging_slot) {
+                std::future::pending::<()>().await;
+            }
+
+            let failing = self.failing.lock().clone();
+            relays
+                .iter()
+                .map(|&relay| {
+                    if failing.contains(&relay) {
+                        return (relay, Err(BuilderApiError::Generic("relay unavailable".into())));
+                    }
+
+                    self.batches.lock().push((relay, constraints.clone()));
+                    (relay, Ok(()))
+                })
+                .collect()
+        }
+
+        fn relays_len(&self) -> usize {
+            MOCK_RELAYS
+        }
+
+        async fn get_header_with_proofs(
+            &self,
+            params: GetHeaderParams,
+        ) -> Result<VersionedValue<SignedBuilderBid>, BuilderApiError> {
+            Err(BuilderApiError::NoBids(params.slot))
+        }
+
+        async fn delegate(&self, _signed_data: &[SignedDelegation]) -> Result<(), BuilderApiError> {
+            Ok(())
+        }
+
+        async fn revoke(&self, _signed_data: &[SignedRevocation]) -> Result<(), BuilderApiError> {
+            Ok(())
+        }
+    }
+
+    fn constraints(slot: Slot) -> SignedConstraints {
+        let pubkey = LocalSigner::random().pubkey();
+        let message = ConstraintsMessage { pubkey, slot, top: false, transactions: vec![] };
+        SignedConstraints { message, signature: Default::default() }
+    }
+
+    #[tokio::test]
+    async fn test_streams_deduplicated_constraints_in_order() {
+        let client = MockClient::default();
+        let streamer = ConstraintsStreamer::spawn(client.clone(), SidecarHealth::default());
+
+        let (a, b, c) = (constraints(10), constraints(10), constraints(10));
+
+        streamer.submit(vec![a.clone()]);
+        for relay in 0..MOCK_RELAYS {
+            let batches = client.wait_for_batches(relay, 1).await;
+            assert_eq!(batches, vec![vec![a.clone()]]);
+        }
+
+        // Duplicates are not submitted again
+        streamer.submit(vec![a.clone(), b.clone()]);
+        for relay in 0..MOCK_RELAYS {
+            let batches = client.wait_for_batches(relay, 2).await;
+            assert_eq!(batches[1], vec![b.clone()]);
+        }
+
+        // Reconciliation only submits what's missing
+        streamer.reconcile(10, vec![a, b, c.clone()]);
+        for relay in 0..MOCK_RELAYS {
+            let batches = client.wait_for_batches(relay, 3).await;
+            assert_eq!(batches[2], vec![c.clone()]);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reconciles_failed_submissions() {
+        // The retry backoff elapses instantly while the runtime is idle
+        tokio::time::pause();
+
+        let client = MockClient::default();
+        client.set_failing(&[0, 1]);
+        let streamer = ConstraintsStreamer::spawn(client.clone(), SidecarHealth::default());
+
+        let a = constraints(10);
+        streamer.submit(vec![a.clone()]);
+
+        // Let the streaming attempt and its retries fail, then bring the relays back up
+        streamer.flush().await;
+        assert!(client.batches.lock().is_empty());
+        client.set_failing(&[]);
+
+        streamer.reconcile(10, vec![a.clone()]);
+        for relay in 0..MOCK_RELAYS {
+            let batches = client.wait_for_batches(relay, 1).await;
+            assert_eq!(batches, vec![vec![a.clone()]]);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reconciles_only_relays_that_failed() {
+        let client = MockClient::default();
+        client.set_failing(&[1]);
+        let streamer = ConstraintsStreamer::spawn(client.clone(), SidecarHealth::default());
+
+        // The constraints are accepted by relay 0 only
+        let a = constraints(10);
+        streamer.submit(vec![a.clone()]);
+        streamer.flush().await;
+        assert_eq!(client.accepted(0), vec![vec![a.clone()]]);
+        assert!(client.accepted(1).is_empty());
+
+        // Once relay 1 is back up, they are resubmitted to it and to it only
+        client.set_failing(&[]);
+        streamer.reconcile(10, vec![a.clone()]);
+        streamer.flush().await;
+        assert_eq!(client.accepted(0), vec![vec![a.clone()]]);
+        assert_eq!(client.accepted(1), vec![vec![a]]);
+    }
+
+    #[tokio::test]
+    async fn test_reconciliation_does_not_stall_streaming() {
+        let client = MockClient::default();
+        client.set_failing(&[1]);
+        let streamer = ConstraintsStreamer::spawn(client.clone(), SidecarHealth::default());
+
+        let a = constraints(10);
+        streamer.submit(vec![a.clone()]);
+        streamer.flush().await;
+        assert!(client.accepted(1).is_empty());
+
+        // Reconciliation of slot 10 never completes...
+        client.set_failing(&[]);
+        *client.hanging_slot.lock() = Some(10);
+        streamer.reconcile(10, vec![a]);
+
+        // ...but constraints for later slots are still streamed
+        let b = constraints(11);
+        streamer.submit(vec![b.clone()]);
+        assert_eq!(client.wait_for_batches(1, 1).await, vec![vec![b]]);
+    }
+
+    #[tokio::test]
+    async fn test_flush_waits_for_queued_constraints() {
+        let client = MockClient::default();
+        let streamer = ConstraintsStreamer::spawn(client.clone(), SidecarHealth::default());
+
+        let (a, b) = (constraints(10), constraints(11));
+        streamer.submit(vec![a.clone()]);
+        streamer.reconcile(11, vec![b.clone()]);
+
+        // All the queued messages are processed by the time the flush completes
+        streamer.flush().await;
+        for relay in 0..MOCK_RELAYS {
+            assert_eq!(client.accepted(relay), vec![vec![a.clone()], vec![b.clone()]]);
+        }
+    }
+}
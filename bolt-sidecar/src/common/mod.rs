@@ -9,6 +9,9 @@ pub mod score_cache;
 /// Secret key types wrappers for BLS, ECDSA and JWT.
 pub mod secrets;
 
+/// Listener for the signals requesting the sidecar to shut down.
+pub mod shutdown;
+
 /// Utility functions for working with transactions.
 pub mod transactions;
 
@@ -0,0 +1,38 @@
+use tokio::{
+    signal::unix::{signal, SignalKind},
+    sync::mpsc,
+};
+use tracing::error;
+
+/// Listens for the signals requesting the sidecar to shut down (SIGINT and SIGTERM) in a
+/// background task, returning a channel that receives the name of every signal received.
+///
+/// Signals are received for as long as the channel is open, so that callers can tell apart
+/// a first shutdown request from a repeated one.
+pub fn shutdown_signals() -> mpsc::UnboundedReceiver<&'static str> {
+    let (tx, rx) = mpsc::unbounded_channel();
+
+    tokio::spawn(async move {
+        let mut sigterm = match signal(SignalKind::terminate()) {
+            Ok(sigterm) => Some(sigterm),
+            Err(err) => {
+                error!(?err, "Failed to listen for SIGTERM, only listening for SIGINT");
+                None
+            }
+        };
+
+        loop {
+            let name = tokio::select! {
+                Ok(()) = tokio::signal::ctrl_c() => "SIGINT",
+                Some(_) = async { sigterm.as_mut()?.recv().await } => "SIGTERM",
+                else => break,
+            };
+
+            if tx.send(name).is_err() {
+                break;
+            }
+        }
+    });
+
+    rx
+}
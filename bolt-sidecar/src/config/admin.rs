@@ -0,0 +1,34 @@
+use std::{fmt, path::PathBuf};
+
+use clap::Parser;
+use serde::Deserialize;
+
+/// Default port for the admin API server, only reachable from localhost.
+pub const DEFAULT_ADMIN_PORT: u16 = 8018;
+
+/// Options for the admin API, used to manage the sidecar at runtime.
+#[derive(Parser, Clone, Deserialize)]
+pub struct AdminOpts {
+    /// The port on which to expose the admin API. The server only listens on localhost.
+    #[clap(long, env = "BOLT_SIDECAR_ADMIN_PORT", default_value_t = DEFAULT_ADMIN_PORT)]
+    pub admin_port: u16,
+    /// The bearer token required to authenticate requests to the admin API.
+    /// If not provided, the admin API is disabled.
+    #[clap(long, env = "BOLT_SIDECAR_ADMIN_TOKEN")]
+    pub admin_token: Option<String>,
+    /// Path to the file where the state set through the admin API, such as whether the
+    /// acceptance of commitments is paused, is persisted across restarts.
+    #[clap(long, env = "BOLT_SIDECAR_ADMIN_STATE_PATH", default_value = "./admin_state.json")]
+    pub admin_state_path: PathBuf,
+}
+
+// Implement Debug manually to hide the admin token
+impl fmt::Debug for AdminOpts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AdminOpts")
+            .field("admin_port", &self.admin_port)
+            .field("admin_token", &"********") // Hides the actual token
+            .field("admin_state_path", &self.admin_state_path)
+            .finish()
+    }
+}
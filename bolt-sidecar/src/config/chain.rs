@@ -43,6 +43,12 @@ pub const DEFAULT_CHAIN_CONFIG: ChainConfig = ChainConfig {
 /// https://holesky.etherscan.io/address/0x440202829b493F9FF43E730EB5e8379EEa3678CF
 pub const MANAGER_ADDRESS_HOLESKY: Address = address!("440202829b493F9FF43E730EB5e8379EEa3678CF");
 
+/// The address of the canonical BoltValidators contract for the Holesky chain.
+///
+/// https://holesky.etherscan.io/address/0x47D2DC1DE1eFEFA5e6944402f2eda3981D36a9c8
+pub const VALIDATORS_ADDRESS_HOLESKY: Address =
+    address!("47D2DC1DE1eFEFA5e6944402f2eda3981D36a9c8");
+
 /// Configuration for the chain the sidecar is running on.
 #[derive(Debug, Clone, Copy, Args, Deserialize)]
 pub struct ChainConfig {
@@ -137,6 +143,14 @@ impl Chain {
             _ => None,
         }
     }
+
+    /// Returns the address of the canonical BoltValidators contract for a given chain, if present
+    pub const fn validators_address(&self) -> Option<Address> {
+        match self {
+            Self::Holesky => Some(VALIDATORS_ADDRESS_HOLESKY),
+            _ => None,
+        }
+    }
 }
 
 impl Display for Chain {
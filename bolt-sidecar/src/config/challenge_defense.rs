@@ -0,0 +1,52 @@
+use std::fmt;
+
+use alloy::primitives::Address;
+use clap::Parser;
+use serde::Deserialize;
+
+use crate::common::secrets::EcdsaSecretKeyWrapper;
+
+/// Default interval for polling the BoltChallenger contract for new challenges: one slot.
+pub const DEFAULT_CHALLENGE_DEFENSE_POLL_INTERVAL_SECS: u64 = 12;
+
+/// Options for the automatic defense of the challenges opened against our commitments.
+#[derive(Parser, Clone, Deserialize)]
+pub struct ChallengeDefenseOpts {
+    /// The address of the BoltChallenger contract to watch for challenges against the commitments
+    /// signed by the sidecar. If not provided, challenges are not defended automatically.
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_BOLT_CHALLENGER_ADDRESS",
+        requires("challenge_defense_private_key")
+    )]
+    pub bolt_challenger_address: Option<Address>,
+    /// Secret ECDSA key of the account paying for the challenge resolution transactions.
+    /// It doesn't need to be the commitment signer key, but it must be funded.
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_CHALLENGE_DEFENSE_PRIVATE_KEY",
+        requires("bolt_challenger_address")
+    )]
+    pub challenge_defense_private_key: Option<EcdsaSecretKeyWrapper>,
+    /// The interval in seconds at which new challenges are polled for.
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_CHALLENGE_DEFENSE_POLL_INTERVAL",
+        default_value_t = DEFAULT_CHALLENGE_DEFENSE_POLL_INTERVAL_SECS
+    )]
+    pub challenge_defense_poll_interval_secs: u64,
+}
+
+// Implement Debug manually to hide the private key
+impl fmt::Debug for ChallengeDefenseOpts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChallengeDefenseOpts")
+            .field("bolt_challenger_address", &self.bolt_challenger_address)
+            .field("challenge_defense_private_key", &"********") // Hides the actual key
+            .field(
+                "challenge_defense_poll_interval_secs",
+                &self.challenge_defense_poll_interval_secs,
+            )
+            .finish()
+    }
+}
@@ -0,0 +1,80 @@
+use std::{fmt, path::PathBuf};
+
+use alloy::primitives::Address;
+use clap::{ArgGroup, Args};
+use reqwest::Url;
+use serde::Deserialize;
+
+use crate::common::secrets::EcdsaSecretKeyWrapper;
+
+/// Command-line options for signing commitment messages
+#[derive(Args, Deserialize)]
+#[clap(
+    group = ArgGroup::new("commitment-signing-opts").required(true)
+        .args(&["commitment_private_key", "commitment_keystore_path", "commitment_web3signer_url"])
+)]
+pub struct CommitmentSigningOpts {
+    /// Secret ECDSA key used to sign commitment messages on behalf of your validators.
+    /// This MUST be set to the private key of your operator address registered in a restaking protocol.
+    #[clap(long, env = "BOLT_SIDECAR_COMMITMENT_PRIVATE_KEY")]
+    pub commitment_private_key: Option<EcdsaSecretKeyWrapper>,
+    /// Path to an Ethereum JSON V3 encrypted keystore holding the commitment private key,
+    /// as an alternative to providing it in plaintext.
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_COMMITMENT_KEYSTORE_PATH",
+        requires("commitment_keystore_password_path")
+    )]
+    pub commitment_keystore_path: Option<PathBuf>,
+    /// Path to a file containing the password of the commitment keystore.
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_COMMITMENT_KEYSTORE_PASSWORD_PATH",
+        requires("commitment_keystore_path")
+    )]
+    pub commitment_keystore_password_path: Option<PathBuf>,
+    /// URL of a Web3Signer instance with the eth1 API enabled, holding the commitment key.
+    #[clap(long, env = "BOLT_SIDECAR_COMMITMENT_WEB3SIGNER_URL")]
+    pub commitment_web3signer_url: Option<Url>,
+    /// The address of the commitment key in the Web3Signer. Required if it holds more than
+    /// one ECDSA key.
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_COMMITMENT_WEB3SIGNER_ADDRESS",
+        requires("commitment_web3signer_url")
+    )]
+    pub commitment_web3signer_address: Option<Address>,
+    /// Path to the CA certificate file of the commitment Web3Signer TLS server. (.crt)
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_COMMITMENT_WEB3SIGNER_CA_CERT_PATH",
+        requires_all(["commitment_web3signer_url", "commitment_web3signer_combined_pem_path"])
+    )]
+    pub commitment_web3signer_ca_cert_path: Option<PathBuf>,
+    /// Path to the PEM encoded client private key and certificate file for the commitment
+    /// Web3Signer. (.pem)
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_COMMITMENT_WEB3SIGNER_COMBINED_PEM_PATH",
+        requires_all(["commitment_web3signer_url", "commitment_web3signer_ca_cert_path"])
+    )]
+    pub commitment_web3signer_combined_pem_path: Option<PathBuf>,
+}
+
+// Implement Debug manually to hide the commitment_private_key field
+impl fmt::Debug for CommitmentSigningOpts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CommitmentSigningOpts")
+            .field("commitment_private_key", &"********") // Hides the actual private key
+            .field("commitment_keystore_path", &self.commitment_keystore_path)
+            .field("commitment_keystore_password_path", &self.commitment_keystore_password_path)
+            .field("commitment_web3signer_url", &self.commitment_web3signer_url)
+            .field("commitment_web3signer_address", &self.commitment_web3signer_address)
+            .field("commitment_web3signer_ca_cert_path", &self.commitment_web3signer_ca_cert_path)
+            .field(
+                "commitment_web3signer_combined_pem_path",
+                &self.commitment_web3signer_combined_pem_path,
+            )
+            .finish()
+    }
+}
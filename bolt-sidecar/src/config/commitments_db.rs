@@ -0,0 +1,116 @@
+use std::{fs, path::PathBuf};
+
+use clap::{Parser, Subcommand};
+
+use crate::state::{commitments_db::CommitmentsInterchange, CommitmentsDb};
+
+use super::ChainConfig;
+
+/// Offline import and export of the database of signed commitments, run with
+/// `bolt-sidecar-commitments-db <export|import>`.
+///
+/// This must not be run while a sidecar is using the same database: use the admin API to
+/// export or import commitments at runtime instead.
+#[derive(Debug, Parser)]
+#[clap(name = "bolt-sidecar-commitments-db")]
+pub struct CommitmentsDbCommand {
+    /// Path to the file where the inclusion commitments signed by the sidecar are recorded.
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_COMMITMENTS_DB_PATH",
+        default_value = "./commitments_db.json"
+    )]
+    pub commitments_db_path: PathBuf,
+    /// Chain config for the chain on which the commitments were signed
+    #[clap(flatten)]
+    pub chain: ChainConfig,
+    /// The action to perform on the database.
+    #[clap(subcommand)]
+    pub action: CommitmentsDbAction,
+}
+
+/// The actions available on the database of signed commitments.
+#[derive(Debug, Clone, Subcommand)]
+pub enum CommitmentsDbAction {
+    /// Export the signed commitments to a file in the interchange format.
+    Export {
+        /// The output file for the interchange.
+        #[clap(long)]
+        out: PathBuf,
+    },
+    /// Import signed commitments from a file in the interchange format, for instance
+    /// exported from another sidecar instance.
+    Import {
+        /// The interchange file to import.
+        #[clap(long)]
+        file: PathBuf,
+    },
+}
+
+impl CommitmentsDbCommand {
+    /// Run the command.
+    pub fn run(self) -> eyre::Result<()> {
+        let mut db = CommitmentsDb::open(&self.commitments_db_path, self.chain.chain_id())?;
+
+        match self.action {
+            CommitmentsDbAction::Export { out } => {
+                fs::write(&out, serde_json::to_vec_pretty(&db.export())?)?;
+                println!("Exported signed commitments to {}", out.display());
+            }
+            CommitmentsDbAction::Import { file } => {
+                let interchange: CommitmentsInterchange =
+                    serde_json::from_slice(&fs::read(&file)?)?;
+                let imported = db.import(interchange)?;
+                println!("Imported {imported} signed commitments from {}", file.display());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::CommandFactory;
+
+    use super::*;
+    use crate::{state::commitments_db::SignedCommitmentRecord, test_util::temp_file_path};
+
+    #[test]
+    fn test_validate_cli_flags() {
+        CommitmentsDbCommand::command().debug_assert();
+    }
+
+    #[test]
+    fn test_export_and_import() -> eyre::Result<()> {
+        let (db_path, other_db_path) =
+            (temp_file_path("bolt_commitments_db"), temp_file_path("bolt_commitments_db"));
+        let interchange_path = temp_file_path("bolt_commitments_interchange");
+
+        let mut db = CommitmentsDb::open(&db_path, 1)?;
+        let record =
+            SignedCommitmentRecord { slot: 10, digest: Default::default(), transactions: vec![] };
+        db.insert(record, 9, 100_000)?;
+        drop(db);
+
+        let args = |db: &PathBuf, action: &[&str]| {
+            let mut args =
+                vec!["bolt-sidecar-commitments-db", "--commitments-db-path", db.to_str().unwrap()];
+            args.extend_from_slice(action);
+            CommitmentsDbCommand::parse_from(args)
+        };
+
+        let interchange = interchange_path.to_str().unwrap();
+        args(&db_path, &["export", "--out", interchange]).run()?;
+        args(&other_db_path, &["import", "--file", interchange]).run()?;
+
+        let other = CommitmentsDb::open(&other_db_path, 1)?;
+        assert_eq!(other.get(10).len(), 1);
+
+        for path in [db_path, other_db_path, interchange_path] {
+            fs::remove_file(path)?;
+        }
+
+        Ok(())
+    }
+}
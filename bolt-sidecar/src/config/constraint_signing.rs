@@ -7,11 +7,14 @@ use serde::Deserialize;
 
 use crate::common::secrets::{BlsSecretKeyWrapper, JwtSecretConfig};
 
+/// Default timeout for a single signing request to Dirk, in milliseconds.
+pub const DEFAULT_DIRK_SIGN_TIMEOUT_MS: u64 = 1000;
+
 /// Command-line options for signing constraint messages
 #[derive(Args, Deserialize)]
 #[clap(
     group = ArgGroup::new("signing-opts").required(true)
-        .args(&["constraint_private_key", "commit_boost_signer_url", "keystore_password", "keystore_secrets_path"])
+        .args(&["constraint_private_key", "commit_boost_signer_url", "keystore_password", "keystore_secrets_path", "web3signer_url", "dirk_url"])
 )]
 pub struct ConstraintSigningOpts {
     /// Private key to use for signing constraint messages
@@ -34,9 +37,70 @@ pub struct ConstraintSigningOpts {
     /// Path to the keystores folder. If not provided, the default path is used.
     #[clap(long, env = "BOLT_SIDECAR_KEYSTORE_PATH")]
     pub keystore_path: Option<PathBuf>,
+    /// URL of a Web3Signer instance with the commit-boost API enabled, holding the keys
+    /// to sign constraints with.
+    #[clap(long, env = "BOLT_SIDECAR_WEB3SIGNER_URL")]
+    pub web3signer_url: Option<Url>,
+    /// Path to the CA certificate file of the Web3Signer TLS server. (.crt)
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_WEB3SIGNER_CA_CERT_PATH",
+        requires_all(["web3signer_url", "web3signer_combined_pem_path"])
+    )]
+    pub web3signer_ca_cert_path: Option<PathBuf>,
+    /// Path to the PEM encoded client private key and certificate file for the Web3Signer. (.pem)
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_WEB3SIGNER_COMBINED_PEM_PATH",
+        requires_all(["web3signer_url", "web3signer_ca_cert_path"])
+    )]
+    pub web3signer_combined_pem_path: Option<PathBuf>,
+    /// URL of a Dirk instance holding the keys to sign constraints with.
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_DIRK_URL",
+        requires_all(["dirk_wallet_path", "dirk_passphrases", "dirk_client_cert_path", "dirk_client_key_path"])
+    )]
+    pub dirk_url: Option<Url>,
+    /// The path of the wallet in the Dirk keystore. Both regular and distributed accounts
+    /// under this path are used for signing.
+    #[clap(long, env = "BOLT_SIDECAR_DIRK_WALLET_PATH", requires("dirk_url"))]
+    pub dirk_wallet_path: Option<String>,
+    /// The passphrases to unlock the Dirk accounts with.
+    /// If multiple are provided, they are tried in order until one works.
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_DIRK_PASSPHRASES",
+        value_delimiter = ',',
+        hide_env_values = true,
+        requires("dirk_url")
+    )]
+    pub dirk_passphrases: Option<Vec<String>>,
+    /// Path to the client certificate file for Dirk. (.crt)
+    #[clap(long, env = "BOLT_SIDECAR_DIRK_CLIENT_CERT_PATH", requires("dirk_url"))]
+    pub dirk_client_cert_path: Option<PathBuf>,
+    /// Path to the client key file for Dirk. (.key)
+    #[clap(long, env = "BOLT_SIDECAR_DIRK_CLIENT_KEY_PATH", requires("dirk_url"))]
+    pub dirk_client_key_path: Option<PathBuf>,
+    /// Path to the CA certificate file of the Dirk server. (.crt)
+    #[clap(long, env = "BOLT_SIDECAR_DIRK_CA_CERT_PATH", requires("dirk_url"))]
+    pub dirk_ca_cert_path: Option<PathBuf>,
+    /// The maximum time to wait for a signature from Dirk, in milliseconds. Commitment requests
+    /// whose constraints can't be signed in time are rejected.
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_DIRK_SIGN_TIMEOUT_MS",
+        default_value_t = DEFAULT_DIRK_SIGN_TIMEOUT_MS
+    )]
+    pub dirk_sign_timeout_ms: u64,
     /// Path to the delegations file. If not provided, the default path is used.
     #[clap(long, env = "BOLT_SIDECAR_DELEGATIONS_PATH")]
     pub delegations_path: Option<PathBuf>,
+    /// Path to the revocations file. Revoked delegatees are never used to sign constraints.
+    ///
+    /// Both the delegations and revocations files are watched for changes at runtime.
+    #[clap(long, env = "BOLT_SIDECAR_REVOCATIONS_PATH")]
+    pub revocations_path: Option<PathBuf>,
 }
 
 // Implement Debug manually to hide the keystore_password field
@@ -49,7 +113,18 @@ impl fmt::Debug for ConstraintSigningOpts {
             .field("keystore_password", &"********") // Hides the actual password
             .field("keystore_path", &self.keystore_path)
             .field("keystore_secrets_path", &self.keystore_secrets_path)
+            .field("web3signer_url", &self.web3signer_url)
+            .field("web3signer_ca_cert_path", &self.web3signer_ca_cert_path)
+            .field("web3signer_combined_pem_path", &self.web3signer_combined_pem_path)
+            .field("dirk_url", &self.dirk_url)
+            .field("dirk_wallet_path", &self.dirk_wallet_path)
+            .field("dirk_passphrases", &"********") // Hides the actual passphrases
+            .field("dirk_client_cert_path", &self.dirk_client_cert_path)
+            .field("dirk_client_key_path", &self.dirk_client_key_path)
+            .field("dirk_ca_cert_path", &self.dirk_ca_cert_path)
+            .field("dirk_sign_timeout_ms", &self.dirk_sign_timeout_ms)
             .field("delegations_path", &self.delegations_path)
+            .field("revocations_path", &self.revocations_path)
             .finish()
     }
 }
@@ -0,0 +1,641 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    env,
+    ffi::OsString,
+    fmt, fs,
+    num::NonZero,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::{Duration, SystemTime},
+};
+
+use clap::{
+    error::{ContextKind, ContextValue},
+    parser::ValueSource,
+    ArgAction, ArgMatches, Command, CommandFactory,
+};
+use tokio::{
+    signal::unix::{signal, SignalKind},
+    sync::watch,
+};
+use toml::{Table, Value};
+use tracing::{error, info, warn};
+
+use super::{limits::LimitsOpts, Opts};
+
+/// The interval at which the configuration file is checked for changes.
+const WATCH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// The options that can be changed at runtime by editing the configuration file, without
+/// restarting the sidecar. Any other change is only applied on the next restart.
+pub const RELOADABLE_OPTIONS: &[&str] = &[
+    "max_commitments_per_slot",
+    "max_committed_gas_per_slot",
+    "min_priority_fee",
+    "delegations_path",
+];
+
+/// The option pointing to the configuration file, which can't be set from the file itself.
+const CONFIG_PATH_OPTION: &str = "config_path";
+
+/// Errors that can occur while loading the configuration file.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum ConfigFileError {
+    #[error("failed to read config file {0:?}: {1}")]
+    Read(PathBuf, std::io::Error),
+    #[error("failed to parse config file {0:?}: {1}")]
+    Parse(PathBuf, toml::de::Error),
+    #[error("unknown key `{0}` in config file")]
+    UnknownKey(String),
+    #[error("key `{0}` is set more than once in config file")]
+    DuplicateKey(String),
+    #[error("key `{0}` can't be set from the config file")]
+    NotConfigurable(String),
+    #[error("unsupported value for key `{0}` in config file: expected a string, number, boolean or array of them")]
+    UnsupportedValue(String),
+    #[error("invalid value for key `{key}` in config file: {reason}")]
+    InvalidValue { key: String, reason: String },
+}
+
+/// A value of the configuration file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ConfigEntry {
+    /// The full key of the value in the file, including its tables, used in errors
+    /// (e.g. `limits.min_priority_fee`).
+    key: String,
+    /// The value, formatted as it would be provided in an environment variable.
+    value: String,
+}
+
+/// A TOML configuration file, mapped onto the command-line options.
+///
+/// Each key is the name of an option (e.g. `min_priority_fee`), and can be grouped in tables
+/// which are only used for readability (e.g. `[limits]`). Arrays are joined with commas, as in
+/// environment variables.
+///
+/// Precedence: CLI flags > environment variables (and `.env` file) > config file > defaults.
+///
+/// The values of the file that are not overridden are passed to clap as command-line flags
+/// rather than default values, as clap ignores default values when checking required options,
+/// argument groups and `requires` relations between options.
+#[derive(Clone)]
+pub struct ConfigFile {
+    path: PathBuf,
+    /// The values of the file, indexed by option name.
+    entries: BTreeMap<String, ConfigEntry>,
+    /// The options set in the file that are overridden by CLI flags or environment variables,
+    /// and are therefore never reloaded from the file.
+    overridden: HashSet<String>,
+}
+
+// Implement Debug manually to hide the values, which may include secrets
+impl fmt::Debug for ConfigFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConfigFile")
+            .field("path", &self.path)
+            .field("keys", &self.entries.keys().collect::<Vec<_>>())
+            .field("overridden", &self.overridden)
+            .finish()
+    }
+}
+
+impl ConfigFile {
+    /// Reads and validates the configuration file at the given path.
+    ///
+    /// Every key must be the name of an option that can be set from an environment variable.
+    pub fn read(path: &Path) -> Result<Self, ConfigFileError> {
+        let contents =
+            fs::read_to_string(path).map_err(|e| ConfigFileError::Read(path.to_path_buf(), e))?;
+        let table =
+            contents.parse::<Table>().map_err(|e| ConfigFileError::Parse(path.to_path_buf(), e))?;
+
+        let mut values = Vec::new();
+        flatten_table(&table, "", &mut values)?;
+
+        // Keys are either option names or their long flag names, e.g. `constraints_api_urls`
+        // or `constraints_api_url`, and are indexed by option name.
+        let command = Opts::command();
+        let mut entries = BTreeMap::new();
+        for (name, entry) in values {
+            let arg = command
+                .get_arguments()
+                .find(|arg| {
+                    arg.get_id().as_str() == name ||
+                        arg.get_long().is_some_and(|long| long.replace('-', "_") == name)
+                })
+                .ok_or_else(|| ConfigFileError::UnknownKey(entry.key.clone()))?;
+
+            let id = arg.get_id().as_str();
+            if id == CONFIG_PATH_OPTION || arg.get_env().is_none() || arg.get_long().is_none() {
+                return Err(ConfigFileError::NotConfigurable(entry.key));
+            }
+
+            // Boolean flags don't take a value, so they are only passed when set to `true`
+            if matches!(arg.get_action(), ArgAction::SetTrue) &&
+                entry.value.parse::<bool>().is_err()
+            {
+                let reason = format!("expected a boolean, got `{}`", entry.value);
+                return Err(ConfigFileError::InvalidValue { key: entry.key, reason });
+            }
+
+            if let Some(previous) = entries.insert(id.to_string(), entry) {
+                return Err(ConfigFileError::DuplicateKey(previous.key));
+            }
+        }
+
+        Ok(Self { path: path.to_path_buf(), entries, overridden: HashSet::new() })
+    }
+
+    /// Returns the path of the configuration file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Records the options in the file that are already set by CLI flags or environment
+    /// variables, which take precedence over the file.
+    fn record_overrides(&mut self, matches: &ArgMatches) {
+        for (name, entry) in &self.entries {
+            match matches.value_source(name) {
+                Some(ValueSource::CommandLine) => {
+                    println!("Config key `{}` is overridden by a command-line flag", entry.key)
+                }
+                Some(ValueSource::EnvVariable) => {
+                    println!("Config key `{}` is overridden by an environment variable", entry.key)
+                }
+                _ => continue,
+            }
+            self.overridden.insert(name.clone());
+        }
+    }
+
+    /// Returns the command-line flags setting the options of the file that are not overridden.
+    fn to_args(&self, command: &Command) -> Vec<OsString> {
+        command
+            .get_arguments()
+            .filter_map(|arg| {
+                let name = arg.get_id().as_str();
+                let entry = self.entries.get(name).filter(|_| !self.overridden.contains(name))?;
+                let long = arg.get_long()?;
+
+                match arg.get_action() {
+                    ArgAction::SetTrue => (entry.value == "true").then(|| format!("--{long}")),
+                    _ => Some(format!("--{long}={}", entry.value)),
+                }
+            })
+            .map(OsString::from)
+            .collect()
+    }
+
+    /// Parses the given command-line arguments, using the options in the file that are not
+    /// set by CLI flags or environment variables as fallback values.
+    fn try_get_matches_from(
+        &mut self,
+        command: &mut Command,
+        args: Vec<OsString>,
+    ) -> Result<ArgMatches, clap::Error> {
+        let matches = command.clone().ignore_errors(true).try_get_matches_from(&args)?;
+        self.record_overrides(&matches);
+
+        // The flags of the file are inserted right after the binary name, so that they can't
+        // end up after a `--` separator.
+        let mut args = args.into_iter();
+        let args = args.next().into_iter().chain(self.to_args(command)).chain(args);
+        command.try_get_matches_from_mut(args)
+    }
+
+    /// Maps a CLI parsing error to the key of the file holding the invalid value, if any.
+    fn map_parse_error(&self, command: &Command, err: &clap::Error) -> Option<ConfigFileError> {
+        let Some(ContextValue::String(invalid)) = err.get(ContextKind::InvalidArg) else {
+            return None;
+        };
+        let flag = invalid.split_whitespace().next()?;
+
+        let arg = command
+            .get_arguments()
+            .find(|arg| arg.get_long().is_some_and(|long| flag == format!("--{long}")))?;
+        let name = arg.get_id().as_str();
+        let entry = self.entries.get(name).filter(|_| !self.overridden.contains(name))?;
+
+        // Only attribute the error to the file if the invalid value comes from it
+        let Some(ContextValue::String(value)) = err.get(ContextKind::InvalidValue) else {
+            return None;
+        };
+        let from_file = match arg.get_value_delimiter() {
+            Some(delimiter) => entry.value.split(delimiter).any(|v| v == value),
+            None => entry.value == *value,
+        };
+        if !from_file {
+            return None;
+        }
+
+        let reason = err.to_string().lines().next().unwrap_or_default().to_string();
+        Some(ConfigFileError::InvalidValue { key: entry.key.clone(), reason })
+    }
+
+    /// Parses the value of the given option from the file, if set and not overridden.
+    fn parse<T>(&self, name: &str) -> Result<Option<T>, ConfigFileError>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        let Some(entry) = self.entries.get(name).filter(|_| !self.overridden.contains(name)) else {
+            return Ok(None);
+        };
+
+        entry.value.parse().map(Some).map_err(|err: T::Err| ConfigFileError::InvalidValue {
+            key: entry.key.clone(),
+            reason: err.to_string(),
+        })
+    }
+
+    /// Applies the reloadable options in the file on top of the given ones. Options that are
+    /// missing from the file or overridden keep their current value.
+    pub fn reloadable_opts(
+        &self,
+        current: &ReloadableOpts,
+    ) -> Result<ReloadableOpts, ConfigFileError> {
+        let mut limits = current.limits;
+        if let Some(max) = self.parse::<NonZero<usize>>("max_commitments_per_slot")? {
+            limits.max_commitments_per_slot = max;
+        }
+        if let Some(max) = self.parse::<NonZero<u64>>("max_committed_gas_per_slot")? {
+            limits.max_committed_gas_per_slot = max;
+        }
+        if let Some(min) = self.parse::<u128>("min_priority_fee")? {
+            limits.min_priority_fee = min;
+        }
+
+        let delegations_path =
+            self.parse::<PathBuf>("delegations_path")?.or_else(|| current.delegations_path.clone());
+
+        Ok(ReloadableOpts { limits, delegations_path })
+    }
+
+    /// Returns the options that changed from the given file but can't be reloaded.
+    fn restart_required<'a>(&'a self, previous: &'a Self) -> BTreeSet<&'a str> {
+        self.entries
+            .keys()
+            .chain(previous.entries.keys())
+            .filter(|name| !RELOADABLE_OPTIONS.contains(&name.as_str()))
+            .filter(|name| self.entries.get(*name) != previous.entries.get(*name))
+            .map(|name| name.as_str())
+            .collect()
+    }
+}
+
+/// Flattens the given TOML table into a list of option names and values.
+fn flatten_table(
+    table: &Table,
+    prefix: &str,
+    values: &mut Vec<(String, ConfigEntry)>,
+) -> Result<(), ConfigFileError> {
+    for (name, value) in table {
+        let key = if prefix.is_empty() { name.clone() } else { format!("{prefix}.{name}") };
+
+        let value = match value {
+            Value::Table(table) => {
+                flatten_table(table, &key, values)?;
+                continue;
+            }
+            Value::Array(values) => values
+                .iter()
+                .map(scalar_to_string)
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(|| ConfigFileError::UnsupportedValue(key.clone()))?
+                .join(","),
+            value => scalar_to_string(value)
+                .ok_or_else(|| ConfigFileError::UnsupportedValue(key.clone()))?,
+        };
+
+        values.push((name.replace('-', "_"), ConfigEntry { key, value }));
+    }
+
+    Ok(())
+}
+
+/// Formats a scalar TOML value as it would be provided in an environment variable.
+fn scalar_to_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Integer(i) => Some(i.to_string()),
+        Value::Float(f) => Some(f.to_string()),
+        Value::Boolean(b) => Some(b.to_string()),
+        Value::Datetime(_) | Value::Array(_) | Value::Table(_) => None,
+    }
+}
+
+/// Parses the command-line options, using the configuration file given with `--config` (if any)
+/// as fallback values for the options not set by CLI flags or environment variables.
+///
+/// Invalid values in the configuration file are reported with their key.
+pub(super) fn parse_with_config_file() -> eyre::Result<(ArgMatches, Option<ConfigFile>)> {
+    let mut command = Opts::command();
+
+    // Look up the config path first, as required options may only be set in the file.
+    let config_path = command
+        .clone()
+        .ignore_errors(true)
+        .get_matches()
+        .get_one::<PathBuf>(CONFIG_PATH_OPTION)
+        .cloned();
+
+    let Some(path) = config_path else {
+        return Ok((command.get_matches(), None));
+    };
+
+    let mut config = ConfigFile::read(&path)?;
+    println!("Loaded configuration file from path: {:?}", config.path());
+
+    let matches = match config.try_get_matches_from(&mut command, env::args_os().collect()) {
+        Ok(matches) => matches,
+        Err(err) => match config.map_parse_error(&command, &err) {
+            Some(config_err) => return Err(config_err.into()),
+            None => err.exit(),
+        },
+    };
+
+    Ok((matches, Some(config)))
+}
+
+/// The options that can be reloaded at runtime from the configuration file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReloadableOpts {
+    /// The operating limits, including the minimum priority fee.
+    pub limits: LimitsOpts,
+    /// The path to the signed delegations file.
+    pub delegations_path: Option<PathBuf>,
+}
+
+impl From<&Opts> for ReloadableOpts {
+    fn from(opts: &Opts) -> Self {
+        Self {
+            limits: opts.limits,
+            delegations_path: opts.constraint_signing.delegations_path.clone(),
+        }
+    }
+}
+
+/// Watches the configuration file, reloading the [`ReloadableOpts`] when it changes or when the
+/// process receives a SIGHUP signal, and publishing them to the subscribers.
+///
+/// A file that fails to be read or validated is ignored, keeping the current options.
+#[derive(Debug)]
+pub struct ConfigWatcher {
+    config: ConfigFile,
+    last_modified: Option<SystemTime>,
+    tx: watch::Sender<ReloadableOpts>,
+}
+
+impl ConfigWatcher {
+    /// Creates a new watcher for the given file, starting from the given options.
+    pub fn new(config: ConfigFile, opts: ReloadableOpts) -> Self {
+        let last_modified = fs::metadata(config.path()).and_then(|m| m.modified()).ok();
+        let (tx, _) = watch::channel(opts);
+        Self { config, last_modified, tx }
+    }
+
+    /// Returns a new receiver of the reloaded options.
+    pub fn subscribe(&self) -> watch::Receiver<ReloadableOpts> {
+        self.tx.subscribe()
+    }
+
+    /// Reloads the configuration file and publishes the reloadable options if they changed.
+    pub fn reload(&mut self) -> Result<(), ConfigFileError> {
+        let mut config = ConfigFile::read(self.config.path())?;
+        config.overridden = std::mem::take(&mut self.config.overridden);
+
+        let result = config.reloadable_opts(&self.tx.borrow());
+        let restart_required = config.restart_required(&self.config);
+        self.config = config;
+        let opts = result?;
+
+        if !restart_required.is_empty() {
+            warn!(keys = ?restart_required, "Config file changes require a restart to be applied");
+        }
+
+        self.tx.send_if_modified(|current| {
+            if *current == opts {
+                return false;
+            }
+            info!(?opts, "Reloaded options from config file");
+            *current = opts;
+            true
+        });
+
+        Ok(())
+    }
+
+    /// Spawns a background task that reloads the file when it changes or on SIGHUP.
+    pub fn spawn(mut self) {
+        tokio::spawn(async move {
+            let mut sighup = match signal(SignalKind::hangup()) {
+                Ok(sighup) => Some(sighup),
+                Err(err) => {
+                    error!(?err, "Failed to listen for SIGHUP, only watching the config file");
+                    None
+                }
+            };
+
+            let mut interval = tokio::time::interval(WATCH_INTERVAL);
+            loop {
+                tokio::select! {
+                    _ = interval.tick() => {
+                        let modified =
+                            fs::metadata(self.config.path()).and_then(|m| m.modified()).ok();
+                        if modified == self.last_modified {
+                            continue;
+                        }
+                        self.last_modified = modified;
+                    }
+                    Some(_) = async { sighup.as_mut()?.recv().await } => {
+                        info!("Received SIGHUP, reloading config file");
+                    }
+                }
+
+                if let Err(err) = self.reload() {
+                    error!(?err, "Failed to reload config file, keeping the current options");
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::{Arg, ArgGroup};
+
+    use super::*;
+
+    fn read_config(contents: &str) -> Result<ConfigFile, ConfigFileError> {
+        let path =
+            env::temp_dir().join(format!("bolt_sidecar_config_{}.toml", rand::random::<u64>()));
+        fs::write(&path, contents).unwrap();
+
+        let config = ConfigFile::read(&path);
+        fs::remove_file(path).unwrap();
+        config
+    }
+
+    #[test]
+    fn test_config_file_keys() {
+        let config = read_config(
+            r#"
+            port = 9000
+            constraints-api-url = ["http://localhost:1", "http://localhost:2"]
+
+            [limits]
+            min_priority_fee = "2000000000"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.entries["port"].value, "9000");
+        assert_eq!(
+            config.entries["constraints_api_urls"].value,
+            "http://localhost:1,http://localhost:2"
+        );
+        assert_eq!(config.entries["min_priority_fee"].key, "limits.min_priority_fee");
+
+        let err = read_config("[limits]\nmin_priority_fe = 1").unwrap_err();
+        assert!(matches!(err, ConfigFileError::UnknownKey(key) if key == "limits.min_priority_fe"));
+
+        let err = read_config("port = 1\n[server]\nport = 2").unwrap_err();
+        assert!(matches!(err, ConfigFileError::DuplicateKey(key) if key == "port"));
+
+        let err = read_config("config_path = \"./config.toml\"").unwrap_err();
+        assert!(matches!(err, ConfigFileError::NotConfigurable(_)));
+    }
+
+    #[test]
+    fn test_reloadable_opts() {
+        let mut config = read_config(
+            r#"
+            [limits]
+            max_commitments_per_slot = 0
+            min_priority_fee = 2000000000
+            "#,
+        )
+        .unwrap();
+
+        let err = config.reloadable_opts(&ReloadableOpts::default()).unwrap_err();
+        assert!(
+            matches!(err, ConfigFileError::InvalidValue { key, .. } if key == "limits.max_commitments_per_slot")
+        );
+
+        // Overridden options are never reloaded
+        config.overridden.insert("max_commitments_per_slot".to_string());
+        let opts = config.reloadable_opts(&ReloadableOpts::default()).unwrap();
+        assert_eq!(opts.limits.min_priority_fee, 2_000_000_000);
+        assert_eq!(
+            opts.limits.max_commitments_per_slot,
+            LimitsOpts::default().max_commitments_per_slot
+        );
+
+        config.overridden.insert("min_priority_fee".to_string());
+        let opts = config.reloadable_opts(&ReloadableOpts::default()).unwrap();
+        assert_eq!(opts, ReloadableOpts::default());
+    }
+
+    /// A command with the same kinds of constraints between options as [`Opts`].
+    fn test_command() -> Command {
+        Command::new("test")
+            .arg(Arg::new("url").long("url").env("BOLT_TEST_CONFIG_URL").requires("jwt"))
+            .arg(Arg::new("jwt").long("jwt").env("BOLT_TEST_CONFIG_JWT").requires("url"))
+            .arg(Arg::new("private_key").long("private-key").env("BOLT_TEST_CONFIG_PRIVATE_KEY"))
+            .group(ArgGroup::new("signing").args(["url", "private_key"]).required(true))
+            .arg(
+                Arg::new("port")
+                    .long("port")
+                    .env("BOLT_TEST_CONFIG_PORT")
+                    .value_parser(clap::value_parser!(u16))
+                    .required(true),
+            )
+            .arg(
+                Arg::new("relays")
+                    .long("relay")
+                    .env("BOLT_TEST_CONFIG_RELAYS")
+                    .action(ArgAction::Append)
+                    .value_delimiter(','),
+            )
+            .arg(
+                Arg::new("shadow_mode")
+                    .long("shadow-mode")
+                    .env("BOLT_TEST_CONFIG_SHADOW_MODE")
+                    .action(ArgAction::SetTrue),
+            )
+    }
+
+    fn test_config(values: &[(&str, &str)]) -> ConfigFile {
+        let entries = values
+            .iter()
+            .map(|(name, value)| {
+                let entry = ConfigEntry { key: name.to_string(), value: value.to_string() };
+                (name.to_string(), entry)
+            })
+            .collect();
+        ConfigFile { path: PathBuf::from("config.toml"), entries, overridden: HashSet::new() }
+    }
+
+    fn args(args: &[&str]) -> Vec<OsString> {
+        args.iter().map(OsString::from).collect()
+    }
+
+    #[test]
+    fn test_config_file_values_are_fallbacks() {
+        let mut command = test_command();
+        let mut config = test_config(&[
+            ("private_key", "0x01"),
+            ("port", "9000"),
+            ("relays", "http://localhost:1,http://localhost:2"),
+            ("shadow_mode", "true"),
+        ]);
+
+        // Required options and groups can be satisfied from the file alone
+        let matches = config.try_get_matches_from(&mut command, args(&["test"])).unwrap();
+        assert_eq!(matches.get_one::<String>("private_key").unwrap(), "0x01");
+        assert_eq!(matches.get_one::<u16>("port"), Some(&9000));
+        assert_eq!(matches.get_many::<String>("relays").unwrap().count(), 2);
+        assert!(matches.get_flag("shadow_mode"));
+        assert!(config.overridden.is_empty());
+
+        // CLI flags take precedence over the file
+        let mut config = test_config(&[("private_key", "0x01"), ("port", "9000")]);
+        let matches =
+            config.try_get_matches_from(&mut command, args(&["test", "--port", "9001"])).unwrap();
+        assert_eq!(matches.get_one::<u16>("port"), Some(&9001));
+        assert_eq!(config.overridden, HashSet::from(["port".to_string()]));
+    }
+
+    #[test]
+    fn test_config_file_satisfies_requirements() {
+        // An option set from the CLI can require an option set in the file
+        let mut command = test_command();
+        let mut config = test_config(&[("jwt", "0xdead"), ("port", "9000")]);
+        let matches = config
+            .try_get_matches_from(&mut command, args(&["test", "--url", "http://localhost:1"]))
+            .unwrap();
+        assert_eq!(matches.get_one::<String>("jwt").unwrap(), "0xdead");
+
+        // And requirements between options of the file are enforced
+        let mut config = test_config(&[("url", "http://localhost:1"), ("port", "9000")]);
+        let err = config.try_get_matches_from(&mut command, args(&["test"])).unwrap_err();
+        assert_eq!(err.kind(), clap::error::ErrorKind::MissingRequiredArgument);
+    }
+
+    #[test]
+    fn test_config_file_invalid_value() {
+        let mut command = test_command();
+        let mut config = test_config(&[("private_key", "0x01"), ("port", "not-a-port")]);
+
+        let err = config.try_get_matches_from(&mut command, args(&["test"])).unwrap_err();
+        let err = config.map_parse_error(&command, &err).unwrap();
+        assert!(matches!(err, ConfigFileError::InvalidValue { key, .. } if key == "port"));
+
+        // Invalid values from the CLI are not attributed to the file
+        let mut config = test_config(&[("private_key", "0x01"), ("port", "9000")]);
+        let err = config
+            .try_get_matches_from(&mut command, args(&["test", "--port", "not-a-port"]))
+            .unwrap_err();
+        assert!(config.map_parse_error(&command, &err).is_none());
+    }
+}
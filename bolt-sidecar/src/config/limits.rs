@@ -15,8 +15,7 @@ pub const DEFAULT_MIN_PRIORITY_FEE: u128 = 1_000_000_000; // 1 Gwei
 pub const DEFAULT_MAX_ACCOUNT_STATES_SIZE: u64 = 1_024;
 
 /// Limits for the sidecar.
-#[cfg_attr(test, derive(PartialEq))]
-#[derive(Debug, Parser, Clone, Copy, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Parser, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub struct LimitsOpts {
     /// Max number of commitments to accept per block
     #[clap(
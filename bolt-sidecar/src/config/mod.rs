@@ -1,7 +1,7 @@
-use std::env;
+use std::{env, path::PathBuf};
 
 use alloy::primitives::Address;
-use clap::Parser;
+use clap::{FromArgMatches, Parser};
 use eyre::bail;
 use reqwest::Url;
 use serde::Deserialize;
@@ -10,10 +10,14 @@ use serde::Deserialize;
 pub mod chain;
 pub use chain::ChainConfig;
 
-/// Commitment and constraint signing related options.
+/// Constraint signing related options.
 pub mod constraint_signing;
 pub use constraint_signing::ConstraintSigningOpts;
 
+/// Commitment signing related options.
+pub mod commitment_signing;
+pub use commitment_signing::CommitmentSigningOpts;
+
 /// Telemetry and metrics related options.
 pub mod telemetry;
 use telemetry::TelemetryOpts;
@@ -22,7 +26,27 @@ use telemetry::TelemetryOpts;
 pub mod limits;
 use limits::LimitsOpts;
 
-use crate::common::secrets::{BlsSecretKeyWrapper, EcdsaSecretKeyWrapper, JwtSecretConfig};
+/// Rate limits and quotas for the commitments API.
+pub mod rate_limits;
+use rate_limits::RateLimitOpts;
+
+/// Admin API related options.
+pub mod admin;
+use admin::AdminOpts;
+
+/// Automatic challenge defense related options.
+pub mod challenge_defense;
+use challenge_defense::ChallengeDefenseOpts;
+
+/// Offline import and export of the database of signed commitments.
+pub mod commitments_db;
+pub use commitments_db::CommitmentsDbCommand;
+
+/// TOML configuration file support, with hot reload of the limits and delegations path.
+pub mod file;
+pub use file::{ConfigFile, ConfigWatcher, ReloadableOpts};
+
+use crate::common::secrets::{BlsSecretKeyWrapper, JwtSecretConfig};
 
 /// Default port for the JSON-RPC server exposed by the sidecar supporting the Commitments API.
 ///
@@ -32,9 +56,20 @@ pub const DEFAULT_RPC_PORT: u16 = 8017;
 /// Default port for the Constraints proxy server, binded to the default port used by MEV-Boost.
 pub const DEFAULT_CONSTRAINTS_PROXY_PORT: u16 = 18550;
 
+/// Default interval for re-checking the on-chain validator statuses: one mainnet epoch.
+pub const DEFAULT_ONCHAIN_CHECKS_INTERVAL_SECS: u64 = 384;
+
 /// Command-line options for the Bolt sidecar
 #[derive(Debug, Parser, Deserialize)]
 pub struct Opts {
+    /// Path to a TOML configuration file, whose keys are the names of the options below
+    /// (e.g. `min_priority_fee`), optionally grouped in tables (e.g. `[limits]`).
+    ///
+    /// Values in the file are overridden by CLI flags and environment variables. Changes to the
+    /// limits and to the delegations path are applied at runtime when the file changes or on
+    /// SIGHUP, while other changes require a restart.
+    #[clap(long = "config", env = "BOLT_SIDECAR_CONFIG")]
+    pub config_path: Option<PathBuf>,
     /// Port to listen on for incoming JSON-RPC requests of the Commitments API.
     /// This port should be open on your firewall in order to receive external requests!
     #[clap(long, env = "BOLT_SIDECAR_PORT", default_value_t = DEFAULT_RPC_PORT)]
@@ -49,14 +84,28 @@ pub struct Opts {
     /// synced Geth node.
     #[clap(long, env = "BOLT_SIDECAR_ENGINE_API_URL", default_value = "http://localhost:8551")]
     pub engine_api_url: Url,
-    /// URL to forward the constraints produced by the Bolt sidecar to a server supporting the
-    /// Constraints API, such as an MEV-Boost fork.
+    /// Comma-separated list of URLs to forward the constraints produced by the Bolt sidecar to.
+    /// These can be relays supporting the Constraints API directly, or a server multiplexing
+    /// requests to them, such as bolt-boost or an MEV-Boost fork.
+    ///
+    /// If more than one URL is provided, requests are fanned out to all of them concurrently
+    /// and the best bid with valid inclusion proofs is selected.
     #[clap(
-        long,
+        long = "constraints-api-url",
         env = "BOLT_SIDECAR_CONSTRAINTS_API_URL",
-        default_value = "http://localhost:18551"
+        default_value = "http://localhost:18551",
+        value_delimiter = ','
     )]
-    pub constraints_api_url: Url,
+    pub constraints_api_urls: Vec<Url>,
+    /// Comma-separated list of other relays supporting the Constraints API. If the main
+    /// constraints API fails to return the payload for a signed header, these are queried
+    /// for the same payload so that the proposal is not lost.
+    #[clap(long, env = "BOLT_SIDECAR_FALLBACK_RELAY_URLS", value_delimiter = ',')]
+    pub fallback_relay_urls: Vec<Url>,
+    /// Comma-separated list of additional beacon node URLs to which a block recovered from
+    /// a fallback relay is published, along with the main beacon API URL.
+    #[clap(long, env = "BOLT_SIDECAR_PUBLISH_BEACON_API_URLS", value_delimiter = ',')]
+    pub publish_beacon_api_urls: Vec<Url>,
     /// The port from which the Bolt sidecar will receive Builder-API requests from the
     /// Beacon client
     #[clap(
@@ -65,6 +114,26 @@ pub struct Opts {
         default_value_t = DEFAULT_CONSTRAINTS_PROXY_PORT
     )]
     pub constraints_proxy_port: u16,
+    /// Path to the file where headers signed by the proposers are recorded.
+    ///
+    /// This is used to refuse serving conflicting headers or payloads for a slot
+    /// in which a header has already been signed, even across restarts.
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_SIGNED_HEADERS_PATH",
+        default_value = "./signed_headers.json"
+    )]
+    pub signed_headers_path: PathBuf,
+    /// Path to the file where the inclusion commitments signed by the sidecar are recorded.
+    ///
+    /// This is used to refuse signing commitments that conflict with the ones already signed
+    /// for pending slots, even across restarts.
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_COMMITMENTS_DB_PATH",
+        default_value = "./commitments_db.json"
+    )]
+    pub commitments_db_path: PathBuf,
     /// The JWT secret token to authenticate calls to the engine API.
     ///
     /// It can either be a hex-encoded string or a file path to a file
@@ -79,10 +148,6 @@ pub struct Opts {
     /// one is created. You can generate one with the `bolt` CLI tool, using `bolt generate bls`.
     #[clap(long, env = "BOLT_SIDECAR_BUILDER_PRIVATE_KEY", default_value_t = BlsSecretKeyWrapper::random())]
     pub builder_private_key: BlsSecretKeyWrapper,
-    /// Secret ECDSA key used to sign commitment messages on behalf of your validators.
-    /// This MUST be set to the private key of your operator address registered in a restaking protocol.
-    #[clap(long, env = "BOLT_SIDECAR_COMMITMENT_PRIVATE_KEY")]
-    pub commitment_private_key: EcdsaSecretKeyWrapper,
     /// Unsafely disables consensus checks when validating commitments.
     ///
     /// If enabled, the sidecar will sign every commitment request with the first private key
@@ -92,18 +157,48 @@ pub struct Opts {
     /// Unsafely disables on-chain checks of validators and operator when starting the sidecar
     #[clap(long, env = "BOLT_SIDECAR_UNSAFE_DISABLE_ONCHAIN_CHECKS", default_value_t = false)]
     pub unsafe_disable_onchain_checks: bool,
+    /// Runs the sidecar in shadow mode, to rehearse it against live traffic.
+    ///
+    /// If enabled, commitment requests go through the full validation but the sidecar never
+    /// signs commitments or constraints: it responds with unsigned "would-commit" responses.
+    #[clap(long, env = "BOLT_SIDECAR_SHADOW_MODE", default_value_t = false)]
+    pub shadow_mode: bool,
+    /// The interval in seconds at which the on-chain statuses of the validators and operator
+    /// are re-checked while running. Commitments for validators that became inactive are paused.
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_ONCHAIN_CHECKS_INTERVAL",
+        default_value_t = DEFAULT_ONCHAIN_CHECKS_INTERVAL_SECS
+    )]
+    pub onchain_checks_interval_secs: u64,
     /// Operating limits for the sidecar
     #[clap(flatten)]
     pub limits: LimitsOpts,
+    /// Rate limits for the commitments API
+    #[clap(flatten)]
+    pub rate_limits: RateLimitOpts,
     /// Chain config for the chain on which the sidecar is running
     #[clap(flatten)]
     pub chain: ChainConfig,
     /// Constraint signing options
     #[clap(flatten)]
     pub constraint_signing: ConstraintSigningOpts,
+    /// Commitment signing options
+    #[clap(flatten)]
+    pub commitment_signing: CommitmentSigningOpts,
     /// Telemetry options
     #[clap(flatten)]
     pub telemetry: TelemetryOpts,
+    /// Admin API options
+    #[clap(flatten)]
+    pub admin: AdminOpts,
+    /// Automatic challenge defense options
+    #[clap(flatten)]
+    pub challenge_defense: ChallengeDefenseOpts,
+    /// The configuration file loaded from `config_path`, if any.
+    #[clap(skip)]
+    #[serde(skip)]
+    pub config_file: Option<ConfigFile>,
 
     /// Additional unrecognized arguments. Useful for CI and testing
     /// to avoid issues on potential extra flags provided (e.g. "--exact" from cargo nextest).
@@ -114,12 +209,16 @@ pub struct Opts {
 }
 
 impl Opts {
-    /// Parse the command-line arguments into the `Opts` struct, using
-    /// environment variables as fallback values when not provided via CLI.
+    /// Parse the command-line arguments into the `Opts` struct, using environment variables
+    /// and then the configuration file as fallback values when not provided via CLI.
     pub fn try_parse() -> eyre::Result<Self> {
         read_env_file()?;
 
-        Ok(Self::parse())
+        let (matches, config_file) = file::parse_with_config_file()?;
+        let mut opts = Self::from_arg_matches(&matches)?;
+        opts.config_file = config_file;
+
+        Ok(opts)
     }
 }
 
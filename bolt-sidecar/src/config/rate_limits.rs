@@ -0,0 +1,78 @@
+use std::num::NonZero;
+
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+
+/// Default max commitment requests to accept per second overall.
+pub const DEFAULT_MAX_REQUESTS_PER_SECOND: u32 = 100;
+
+/// Default max commitment requests to accept per second from a single IP address.
+pub const DEFAULT_MAX_REQUESTS_PER_IP: u32 = 20;
+
+/// Default max commitment requests to accept per second from a single signer.
+pub const DEFAULT_MAX_REQUESTS_PER_SIGNER: u32 = 5;
+
+/// Default max pending commitments per signer for a single slot.
+pub const DEFAULT_MAX_PENDING_PER_SIGNER: usize = 16;
+
+/// Default capacity of the queue of commitment requests waiting to be processed.
+pub const DEFAULT_REQUEST_QUEUE_SIZE: usize = 1_024;
+
+/// Rate limits and quotas for the commitments API.
+#[derive(Debug, Parser, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimitOpts {
+    /// Max number of commitment requests to accept per second overall
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_MAX_REQUESTS_PER_SECOND",
+        default_value_t = RateLimitOpts::default().max_requests_per_second
+    )]
+    pub max_requests_per_second: NonZero<u32>,
+    /// Max number of commitment requests to accept per second from a single IP address.
+    ///
+    /// The address is the one of the TCP connection: behind a reverse proxy, all requests share
+    /// the address of the proxy, which should then enforce this limit instead.
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_MAX_REQUESTS_PER_IP",
+        default_value_t = RateLimitOpts::default().max_requests_per_ip
+    )]
+    pub max_requests_per_ip: NonZero<u32>,
+    /// Max number of commitment requests to accept per second from a single signer
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_MAX_REQUESTS_PER_SIGNER",
+        default_value_t = RateLimitOpts::default().max_requests_per_signer
+    )]
+    pub max_requests_per_signer: NonZero<u32>,
+    /// Max number of pending commitments per signer for a single slot
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_MAX_PENDING_PER_SIGNER",
+        default_value_t = RateLimitOpts::default().max_pending_per_signer
+    )]
+    pub max_pending_per_signer: NonZero<usize>,
+    /// Max number of commitment requests waiting to be processed. Requests are rejected when
+    /// the queue is full instead of waiting for room.
+    #[clap(
+        long,
+        env = "BOLT_SIDECAR_REQUEST_QUEUE_SIZE",
+        default_value_t = RateLimitOpts::default().request_queue_size
+    )]
+    pub request_queue_size: NonZero<usize>,
+}
+
+impl Default for RateLimitOpts {
+    fn default() -> Self {
+        Self {
+            max_requests_per_second: NonZero::new(DEFAULT_MAX_REQUESTS_PER_SECOND)
+                .expect("Valid non-zero"),
+            max_requests_per_ip: NonZero::new(DEFAULT_MAX_REQUESTS_PER_IP).expect("Valid non-zero"),
+            max_requests_per_signer: NonZero::new(DEFAULT_MAX_REQUESTS_PER_SIGNER)
+                .expect("Valid non-zero"),
+            max_pending_per_signer: NonZero::new(DEFAULT_MAX_PENDING_PER_SIGNER)
+                .expect("Valid non-zero"),
+            request_queue_size: NonZero::new(DEFAULT_REQUEST_QUEUE_SIZE).expect("Valid non-zero"),
+        }
+    }
+}
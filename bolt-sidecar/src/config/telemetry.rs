@@ -1,4 +1,5 @@
 use clap::Parser;
+use reqwest::Url;
 use serde::Deserialize;
 
 /// Telemetry and metrics related options.
@@ -9,6 +10,10 @@ pub struct TelemetryOpts {
     metrics_port: u16,
     #[clap(long, env = "BOLT_SIDECAR_DISABLE_METRICS", default_value_t = false)]
     disable_metrics: bool,
+    /// The OTLP gRPC endpoint to export traces to, e.g. `http://localhost:4317`.
+    /// If not provided, traces are not exported.
+    #[clap(long, env = "BOLT_SIDECAR_OTLP_ENDPOINT")]
+    otlp_endpoint: Option<Url>,
 }
 
 impl TelemetryOpts {
@@ -20,4 +25,9 @@ impl TelemetryOpts {
             Some(self.metrics_port)
         }
     }
+
+    /// Get the OTLP endpoint to export traces to, if any.
+    pub fn otlp_endpoint(&self) -> Option<&Url> {
+        self.otlp_endpoint.as_ref()
+    }
 }
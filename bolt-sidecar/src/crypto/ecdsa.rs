@@ -1,7 +1,7 @@
 use std::fmt::Debug;
 
 use alloy::{
-    primitives::Address,
+    primitives::{keccak256, Address},
     signers::{local::PrivateKeySigner, Signature as AlloySignature, Signer},
 };
 use secp256k1::{ecdsa::Signature, Message, PublicKey, SecretKey};
@@ -57,11 +57,29 @@ impl ECDSASigner {
     }
 }
 
-/// A generic signing trait to generate ECDSA signatures.
+/// A generic signing trait to generate ECDSA signatures over the keccak256 hash of some data,
+/// which is how commitments are signed.
+///
+/// Signers must be `Sync`, as they are shared by reference across concurrent signing requests.
 #[async_trait::async_trait]
-pub trait SignerECDSA: Send + Debug {
+pub trait SignerECDSA: Send + Sync + Debug {
     /// Returns the public key of the signer.
     fn public_key(&self) -> Address;
+    /// Sign the keccak256 hash of the given data and return the signature.
+    async fn sign_keccak256(&self, data: &[u8]) -> eyre::Result<AlloySignature>;
+    /// Checks that the signer can be used. Signers holding their key in memory are always
+    /// reachable, remote signers must override this to probe the remote service.
+    async fn check_reachable(&self) -> eyre::Result<()> {
+        Ok(())
+    }
+}
+
+/// A signing trait to generate ECDSA signatures over arbitrary hashes.
+///
+/// Remote signers that always hash the data before signing it, such as Web3Signer, can't
+/// implement this trait and only implement [`SignerECDSA`].
+#[async_trait::async_trait]
+pub trait SignerHashECDSA: SignerECDSA {
     /// Sign the given hash and return the signature.
     async fn sign_hash(&self, hash: &[u8; 32]) -> eyre::Result<AlloySignature>;
 }
@@ -72,6 +90,13 @@ impl SignerECDSA for PrivateKeySigner {
         self.address()
     }
 
+    async fn sign_keccak256(&self, data: &[u8]) -> eyre::Result<AlloySignature> {
+        SignerHashECDSA::sign_hash(self, &keccak256(data)).await
+    }
+}
+
+#[async_trait::async_trait]
+impl SignerHashECDSA for PrivateKeySigner {
     async fn sign_hash(&self, hash: &[u8; 32]) -> eyre::Result<AlloySignature> {
         let sig = Signer::sign_hash(self, hash.into()).await?;
 
@@ -84,7 +109,8 @@ impl SignerECDSA for PrivateKeySigner {
 mod tests {
     use crate::test_util::TestSignableData;
 
-    use super::ECDSASigner;
+    use super::{ECDSASigner, SignerECDSA, SignerHashECDSA};
+    use alloy::{primitives::keccak256, signers::local::PrivateKeySigner};
     use rand::Rng;
     use secp256k1::{PublicKey, SecretKey};
 
@@ -104,4 +130,17 @@ mod tests {
 
         assert!(signer.verify_ecdsa(&message, &signature, &pubkey));
     }
+
+    #[tokio::test]
+    async fn test_private_key_signer_sign_keccak256() -> eyre::Result<()> {
+        let signer = PrivateKeySigner::random();
+        let data = b"commitment digest data";
+
+        // Signing some data is the same as signing its keccak256 hash
+        let signature = signer.sign_keccak256(data).await?;
+        assert_eq!(signature, SignerHashECDSA::sign_hash(&signer, &keccak256(data)).await?);
+        assert_eq!(signature.recover_address_from_prehash(&keccak256(data))?, signer.public_key());
+
+        Ok(())
+    }
 }
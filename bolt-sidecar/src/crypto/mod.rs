@@ -4,4 +4,4 @@ pub use bls::SignableBLS;
 
 /// ECDSA signatures and verification functions.
 pub mod ecdsa;
-pub use ecdsa::SignerECDSA;
+pub use ecdsa::{SignerECDSA, SignerHashECDSA};
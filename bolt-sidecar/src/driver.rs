@@ -1,7 +1,13 @@
-use std::{fmt, sync::Arc, time::Instant};
+use std::{
+    fmt, fs,
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use alloy::{
     consensus::{TxType, Typed2718},
+    network::EthereumWallet,
     rpc::types::beacon::events::HeadEvent,
     signers::local::PrivateKeySigner,
 };
@@ -11,43 +17,72 @@ use ethereum_consensus::{
 };
 use eyre::Context;
 use futures::StreamExt;
-use tokio::sync::mpsc;
-use tracing::{debug, error, info, warn};
+use parking_lot::Mutex;
+use tokio::sync::{mpsc, watch};
+use tracing::{debug, error, info, info_span, warn, Instrument};
 
 use crate::{
     api::{
+        admin::{
+            start_admin_server, AdminApiConfig, AdminApiError, AdminRequest, CommitmentsStatus,
+            SignersInfo,
+        },
         builder::{start_builder_proxy_server, BuilderProxyConfig},
         commitments::{
+            rate_limit::PendingCommitments,
             server::{CommitmentEvent, CommitmentsApiServer},
             spec::CommitmentError,
         },
-        spec::ConstraintsApi,
     },
     builder::payload_fetcher::LocalPayloadFetcher,
-    chain_io::BoltManager,
-    client::{BeaconClient, ConstraintsClient},
-    common::backoff::retry_with_backoff,
-    config::Opts,
-    crypto::{SignableBLS, SignerECDSA},
+    chain_io::{BoltChallenger, BoltManager, BoltValidators, ChallengeDefender},
+    client::{
+        BeaconClient, ConstraintsClient, ConstraintsMultiplexer, ConstraintsStreamer,
+        ExecutionClient,
+    },
+    common::shutdown::shutdown_signals,
+    config::{ConfigWatcher, Opts, ReloadableOpts},
+    crypto::{bls::BLSSig, SignableBLS, SignerECDSA},
     primitives::{
-        commitment::SignedCommitment, read_signed_delegations_from_file, CommitmentRequest,
-        ConstraintsMessage, FetchPayloadRequest, SignedConstraints,
+        commitment::{CommitmentResponse, RequestDomain, SignedCommitment},
+        CommitmentRequest, ConstraintsMessage, FetchPayloadRequest, SignedConstraints, Slot,
+    },
+    signer::{
+        dirk::DirkTlsCredentials, keystore::KeystoreSigner, local::LocalSigner,
+        web3signer::Web3SignerTlsCredentials, CommitBoostSigner, CommitmentSigner, DirkSigner,
+        SignerBLS, Web3Signer, Web3SignerEcdsa,
+    },
+    state::{
+        commitments_db::SignedCommitmentRecord, consensus::ConsensusError, fetcher::StateFetcher,
+        CommitmentsDb, ConsensusState, DelegationsWatcher, ExecutionState, FulfillmentAudits,
+        HeadTracker, HealthMonitor, SidecarHealth, SignedHeadersDb, StateClient,
+        ValidatorStatusMonitor, ValidatorStatuses,
     },
-    signer::{keystore::KeystoreSigner, local::LocalSigner, CommitBoostSigner, SignerBLS},
-    state::{fetcher::StateFetcher, ConsensusState, ExecutionState, HeadTracker, StateClient},
     telemetry::ApiMetrics,
     LocalBuilder,
 };
 
+/// The number of upcoming slots in which a proposal of one of our validators defers shutdown.
+const SHUTDOWN_GUARD_SLOTS: u64 = 2;
+
+/// The maximum time to wait for the commitment requests in flight to complete on shutdown.
+/// This is longer than the timeout of the commitments API requests.
+const SHUTDOWN_DRAIN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The maximum time to wait for the queued constraints to be submitted on shutdown.
+const SHUTDOWN_FLUSH_TIMEOUT: Duration = Duration::from_secs(30);
+
 /// The driver for the sidecar, responsible for managing the main event loop.
 ///
 /// The reponsibilities of the driver include:
 /// - Handling incoming API events
 /// - Updating the execution state based on new beacon chain heads
-/// - Submitting constraints to the constraints service at the commitment deadline
+/// - Streaming constraints to the constraints service as soon as they are signed, and reconciling
+///   them at the commitment deadline
 /// - Building local payloads for the beacon chain
 /// - Responding to requests to fetch a local payload
 /// - Updating the consensus state based on the beacon chain clock
+/// - Shutting down gracefully, handing off in-flight commitments
 pub struct SidecarDriver<C, ECDSA> {
     /// Head tracker for monitoring the beacon chain clock
     head_tracker: HeadTracker,
@@ -61,19 +96,43 @@ pub struct SidecarDriver<C, ECDSA> {
     commitment_signer: ECDSA,
     /// Local block builder for creating local payloads
     local_builder: LocalBuilder,
-    /// Client for interacting with the constraints service
-    constraints_client: ConstraintsClient,
+    /// Client for interacting with the constraints services
+    constraints_client: ConstraintsMultiplexer,
+    /// Handle to the task streaming signed constraints to the constraints services
+    constraints_streamer: ConstraintsStreamer,
+    /// Last known on-chain statuses of the validators, used to pause inactive ones
+    validator_statuses: ValidatorStatuses,
+    /// Record of the signed commitments, consulted before signing new ones
+    commitments_db: Arc<Mutex<CommitmentsDb>>,
+    /// The pending commitments per signer for the upcoming slots
+    pending_commitments: PendingCommitments,
     /// Channel for receiving incoming API events
     api_events_rx: mpsc::Receiver<CommitmentEvent>,
     /// Channel for receiving requests to fetch a local payload
     payload_requests_rx: mpsc::Receiver<FetchPayloadRequest>,
+    /// Channel for receiving requests from the admin API
+    admin_requests_rx: mpsc::Receiver<AdminRequest>,
+    /// Whether the acceptance of new commitments has been paused through the admin API
+    commitments_paused: bool,
+    /// The file in which the pause set through the admin API is persisted
+    admin_state_path: PathBuf,
+    /// Channel for receiving the options reloaded from the configuration file, if any
+    reloads: Option<watch::Receiver<ReloadableOpts>>,
     /// Stream of slots made from the consensus clock
     slot_stream: SlotStream<SystemTimeProvider>,
+    /// Channel for receiving the signals requesting the sidecar to shut down
+    shutdown_signals: mpsc::UnboundedReceiver<&'static str>,
+    /// Channel for stopping the commitments API server from accepting new requests
+    stop_api: watch::Sender<bool>,
+    /// The proposal slot after which a shutdown that has been requested is completed, if any
+    shutdown_after: Option<Slot>,
     /// Whether to skip consensus checks (should only be used for testing)
     unsafe_skip_consensus_checks: bool,
+    /// Whether to run in shadow mode, validating requests without signing anything
+    shadow_mode: bool,
 }
 
-impl SidecarDriver<StateClient, PrivateKeySigner> {
+impl SidecarDriver<StateClient, CommitmentSigner> {
     /// Create a new sidecar driver with the given [Opts] and private key signer.
     pub async fn with_local_signer(opts: &Opts) -> eyre::Result<Self> {
         // The default state client simply uses the execution API URL to fetch state updates.
@@ -89,9 +148,8 @@ impl SidecarDriver<StateClient, PrivateKeySigner> {
             opts.chain,
         ));
 
-        // Commitment responses are signed with a regular Ethereum wallet private key.
-        let commitment_key = opts.commitment_private_key.0.clone();
-        let commitment_signer = PrivateKeySigner::from_signing_key(commitment_key);
+        // Commitment responses are signed with a regular Ethereum wallet key.
+        let commitment_signer = commitment_signer(opts).await?;
 
         Self::from_components(opts, constraint_signer, commitment_signer, state_client)
             .await
@@ -99,7 +157,7 @@ impl SidecarDriver<StateClient, PrivateKeySigner> {
     }
 }
 
-impl SidecarDriver<StateClient, PrivateKeySigner> {
+impl SidecarDriver<StateClient, CommitmentSigner> {
     /// Create a new sidecar driver with the given [Opts] and keystore signer.
     pub async fn with_keystore_signer(opts: &Opts) -> eyre::Result<Self> {
         // The default state client simply uses the execution API URL to fetch state updates.
@@ -121,9 +179,8 @@ impl SidecarDriver<StateClient, PrivateKeySigner> {
 
         let keystore_signer = SignerBLS::Keystore(keystore);
 
-        // Commitment responses are signed with a regular Ethereum wallet private key.
-        let commitment_key = opts.commitment_private_key.0.clone();
-        let commitment_signer = PrivateKeySigner::from_signing_key(commitment_key);
+        // Commitment responses are signed with a regular Ethereum wallet key.
+        let commitment_signer = commitment_signer(opts).await?;
 
         Self::from_components(opts, keystore_signer, commitment_signer, state_client)
             .await
@@ -131,6 +188,70 @@ impl SidecarDriver<StateClient, PrivateKeySigner> {
     }
 }
 
+impl SidecarDriver<StateClient, CommitmentSigner> {
+    /// Create a new sidecar driver with the given [Opts] and Web3Signer signer.
+    pub async fn with_web3signer(opts: &Opts) -> eyre::Result<Self> {
+        // The default state client simply uses the execution API URL to fetch state updates.
+        let state_client = StateClient::new(opts.execution_api_url.clone());
+
+        let signing = &opts.constraint_signing;
+        let credentials = signing
+            .web3signer_ca_cert_path
+            .clone()
+            .zip(signing.web3signer_combined_pem_path.clone());
+        let web3signer = Web3Signer::connect(
+            signing.web3signer_url.clone().expect("Web3Signer URL"),
+            credentials.map(|(ca_cert_path, combined_pem_path)| Web3SignerTlsCredentials {
+                ca_cert_path,
+                combined_pem_path,
+            }),
+        )
+        .await?;
+
+        let web3signer = SignerBLS::Web3Signer(web3signer);
+
+        // Commitment responses are signed with a regular Ethereum wallet key.
+        let commitment_signer = commitment_signer(opts).await?;
+
+        Self::from_components(opts, web3signer, commitment_signer, state_client)
+            .await
+            .wrap_err("Failed to initialize sidecar with Web3Signer signer")
+    }
+}
+
+impl SidecarDriver<StateClient, CommitmentSigner> {
+    /// Create a new sidecar driver with the given [Opts] and Dirk signer.
+    pub async fn with_dirk_signer(opts: &Opts) -> eyre::Result<Self> {
+        // The default state client simply uses the execution API URL to fetch state updates.
+        let state_client = StateClient::new(opts.execution_api_url.clone());
+
+        let signing = &opts.constraint_signing;
+        let credentials = DirkTlsCredentials {
+            client_cert_path: signing.dirk_client_cert_path.clone().expect("Dirk client cert"),
+            client_key_path: signing.dirk_client_key_path.clone().expect("Dirk client key"),
+            ca_cert_path: signing.dirk_ca_cert_path.clone(),
+        };
+        let dirk = DirkSigner::connect(
+            signing.dirk_url.clone().expect("Dirk URL"),
+            credentials,
+            signing.dirk_wallet_path.clone().expect("Dirk wallet path"),
+            signing.dirk_passphrases.as_deref().expect("Dirk passphrases"),
+            opts.chain,
+            Duration::from_millis(signing.dirk_sign_timeout_ms),
+        )
+        .await?;
+
+        let dirk_signer = SignerBLS::Dirk(dirk);
+
+        // Commitment responses are signed with a regular Ethereum wallet key.
+        let commitment_signer = commitment_signer(opts).await?;
+
+        Self::from_components(opts, dirk_signer, commitment_signer, state_client)
+            .await
+            .wrap_err("Failed to initialize sidecar with Dirk signer")
+    }
+}
+
 impl SidecarDriver<StateClient, CommitBoostSigner> {
     /// Create a new sidecar driver with the given [Opts] and commit-boost signer.
     pub async fn with_commit_boost_signer(opts: &Opts) -> eyre::Result<Self> {
@@ -150,7 +271,7 @@ impl SidecarDriver<StateClient, CommitBoostSigner> {
     }
 }
 
-impl<C: StateFetcher, ECDSA: SignerECDSA> SidecarDriver<C, ECDSA> {
+impl<C: StateFetcher, ECDSA: SignerECDSA + Clone + 'static> SidecarDriver<C, ECDSA> {
     /// Create a new sidecar driver with the given components
     pub async fn from_components(
         opts: &Opts,
@@ -158,22 +279,46 @@ impl<C: StateFetcher, ECDSA: SignerECDSA> SidecarDriver<C, ECDSA> {
         commitment_signer: ECDSA,
         fetcher: C,
     ) -> eyre::Result<Self> {
-        let mut constraints_client = ConstraintsClient::new(opts.constraints_api_url.clone());
+        let constraints_client = ConstraintsMultiplexer::new(
+            opts.constraints_api_urls.iter().cloned().map(ConstraintsClient::new).collect(),
+        );
 
-        // read the delegations from disk if they exist and add them to the constraints client.
-        let validator_pubkeys = if let Some(delegations_path) =
-            &opts.constraint_signing.delegations_path
-        {
-            info!("Reading signed delegations from disk");
-            let delegations = read_signed_delegations_from_file(delegations_path)?;
-            let keys = delegations.iter().map(|d| d.validator_pubkey.clone()).collect::<Vec<_>>();
-            constraints_client.add_delegations(delegations);
-            keys
+        // the limits and the delegations path are reloaded at runtime from the configuration
+        // file, if any
+        let config_watcher = opts
+            .config_file
+            .clone()
+            .map(|config| ConfigWatcher::new(config, ReloadableOpts::from(opts)));
+        let reloads = config_watcher.as_ref().map(ConfigWatcher::subscribe);
+
+        // read the delegations and revocations from disk if they exist and add them to the
+        // constraints client. Their signatures are verified and every delegatee must be available
+        // in the constraint signer, otherwise the sidecar refuses to start. The files are then
+        // watched for changes at runtime.
+        let mut delegations_watcher = DelegationsWatcher::new(
+            constraints_client.clone(),
+            opts.constraint_signing.delegations_path.clone(),
+            opts.constraint_signing.revocations_path.clone(),
+        )
+        .with_verification(opts.chain, constraint_signer.available_pubkeys());
+        if let Some(reloads) = reloads.clone() {
+            delegations_watcher = delegations_watcher.with_reloads(reloads);
+        }
+        delegations_watcher.reload().await.wrap_err("Failed to load signed delegations")?;
+
+        let validator_pubkeys = if opts.constraint_signing.delegations_path.is_some() {
+            info!("Using validator public keys from the signed delegations");
+            Vec::from_iter(constraints_client.delegations().validator_pubkeys())
         } else {
             info!("No delegations provided, using public keys from the provided signer");
             Vec::from_iter(constraint_signer.available_pubkeys())
         };
 
+        // The on-chain statuses are re-checked periodically after startup, so that commitments
+        // for validators that become inactive while running are paused.
+        let validator_statuses = ValidatorStatuses::default();
+        let mut status_monitor = None;
+
         if opts.unsafe_disable_onchain_checks {
             warn!("Skipping validators and operator public keys verification: --unsafe-disable-onchain-checks is 'true'");
         } else if let Some(manager) =
@@ -185,10 +330,27 @@ impl<C: StateFetcher, ECDSA: SignerECDSA> SidecarDriver<C, ECDSA> {
             );
 
             manager
-                .verify_validator_pubkeys(validator_pubkeys, commitment_signer.public_key())
+                .verify_validator_pubkeys(validator_pubkeys.clone(), commitment_signer.public_key())
                 .await?;
 
             info!("Successfully verified validators and operator keys with BoltManager");
+
+            let mut monitor = ValidatorStatusMonitor::new(
+                manager,
+                commitment_signer.public_key(),
+                validator_statuses.clone(),
+                validator_pubkeys,
+                Duration::from_secs(opts.onchain_checks_interval_secs),
+            );
+            // Delegations can be added at runtime through the admin API or the delegations file,
+            // so the validators with active delegations are always checked as well.
+            monitor = monitor.with_delegations(constraints_client.delegations().clone());
+            if let Some(validators) =
+                BoltValidators::from_chain(opts.execution_api_url.clone(), *opts.chain)
+            {
+                monitor = monitor.with_validators_registry(validators);
+            }
+            status_monitor = Some(monitor);
         } else {
             warn!(
                 "BoltManager is not deployed on {}, skipping validators and operator public keys verification",
@@ -196,8 +358,25 @@ impl<C: StateFetcher, ECDSA: SignerECDSA> SidecarDriver<C, ECDSA> {
             );
         }
 
+        let commitments_db = CommitmentsDb::open(&opts.commitments_db_path, opts.chain.chain_id())
+            .wrap_err("Failed to open signed commitments database")?;
+        let commitments_db = Arc::new(Mutex::new(commitments_db));
+
+        let signed_headers = SignedHeadersDb::open(&opts.signed_headers_path)
+            .wrap_err("Failed to open signed headers database")?;
+        let signed_headers = Arc::new(Mutex::new(signed_headers));
         let beacon_client = BeaconClient::new(opts.beacon_api_url.clone());
-        let execution = ExecutionState::new(fetcher, opts.limits, opts.chain.gas_limit).await?;
+        let fulfillment = FulfillmentAudits::default()
+            .with_signed_headers(signed_headers.clone())
+            .with_beacon_client(beacon_client.clone());
+
+        // shadow templates are never submitted, so their inclusion isn't audited
+        let mut execution = ExecutionState::new(fetcher, opts.limits, opts.chain.gas_limit)
+            .await?
+            .with_validator_statuses(validator_statuses.clone());
+        if !opts.shadow_mode {
+            execution = execution.with_fulfillment_audits(fulfillment.clone());
+        }
 
         let genesis_time = beacon_client.get_genesis_details().await?.genesis_time;
         let slot_stream =
@@ -207,6 +386,19 @@ impl<C: StateFetcher, ECDSA: SignerECDSA> SidecarDriver<C, ECDSA> {
         let local_builder = LocalBuilder::new(opts, genesis_time);
         let head_tracker = HeadTracker::start(beacon_client.clone());
 
+        // check the health of the sidecar and its dependencies once per slot
+        let health = SidecarHealth::default();
+        HealthMonitor::new(
+            ExecutionClient::new(opts.execution_api_url.clone()),
+            beacon_client.clone(),
+            constraint_signer.clone(),
+            commitment_signer.clone(),
+            health.clone(),
+            genesis_time,
+            opts.chain.slot_time(),
+        )
+        .spawn();
+
         let consensus = ConsensusState::new(
             beacon_client,
             opts.chain.commitment_deadline(),
@@ -217,6 +409,15 @@ impl<C: StateFetcher, ECDSA: SignerECDSA> SidecarDriver<C, ECDSA> {
         let builder_proxy_cfg = BuilderProxyConfig {
             constraints_client: constraints_client.clone(),
             server_port: opts.constraints_proxy_port,
+            signed_headers,
+            fallback_relays: (!opts.fallback_relay_urls.is_empty()).then(|| {
+                ConstraintsMultiplexer::new(
+                    opts.fallback_relay_urls.iter().cloned().map(ConstraintsClient::new).collect(),
+                )
+            }),
+            beacon_api_urls: std::iter::once(opts.beacon_api_url.clone())
+                .chain(opts.publish_beacon_api_urls.iter().cloned())
+                .collect(),
         };
 
         // start the builder api proxy server
@@ -227,37 +428,133 @@ impl<C: StateFetcher, ECDSA: SignerECDSA> SidecarDriver<C, ECDSA> {
             }
         });
 
+        // watch the delegations and revocations files for changes
+        delegations_watcher.spawn();
+
+        // watch the configuration file for changes, if any
+        if let Some(watcher) = config_watcher {
+            watcher.spawn();
+        }
+
+        // periodically re-check the on-chain statuses of the validators, if enabled
+        if let Some(monitor) = status_monitor {
+            monitor.spawn();
+        }
+
+        // defend the challenges opened against our commitments, if enabled
+        if let (Some(address), Some(key)) = (
+            opts.challenge_defense.bolt_challenger_address,
+            opts.challenge_defense.challenge_defense_private_key.clone(),
+        ) {
+            let wallet = EthereumWallet::from(PrivateKeySigner::from_signing_key(key.0));
+            ChallengeDefender::new(
+                BoltChallenger::from_address(opts.execution_api_url.clone(), address),
+                ExecutionClient::new(opts.execution_api_url.clone()),
+                BeaconClient::new(opts.beacon_api_url.clone()),
+                wallet,
+                commitment_signer.public_key(),
+                Duration::from_secs(opts.challenge_defense.challenge_defense_poll_interval_secs),
+            )
+            .spawn();
+        }
+
+        // start the admin api server, if enabled
+        let (admin_requests_tx, admin_requests_rx) = mpsc::channel(16);
+        let mut commitments_paused = false;
+        if let Some(token) = opts.admin.admin_token.clone() {
+            // restore the pause set through the admin API before the restart, if any
+            commitments_paused = CommitmentsStatus::read_from_file(&opts.admin.admin_state_path)
+                .wrap_err("Failed to read the admin state")?
+                .paused;
+            if commitments_paused {
+                warn!("Commitment acceptance is paused, resume it through the admin API");
+            }
+
+            let admin_cfg = AdminApiConfig {
+                port: opts.admin.admin_port,
+                token,
+                constraints_client: constraints_client.clone(),
+                chain: opts.chain,
+                available_pubkeys: constraint_signer.available_pubkeys(),
+                revocations_path: opts.constraint_signing.revocations_path.clone(),
+                commitments_db: commitments_db.clone(),
+                fulfillment,
+                driver: admin_requests_tx,
+            };
+
+            tokio::spawn(async move {
+                if let Err(err) = start_admin_server(admin_cfg).await {
+                    error!(?err, "Admin API server failed");
+                }
+            });
+        }
+
         // start the commitments api server
         let api_addr = format!("0.0.0.0:{}", opts.port);
-        let (api_events_tx, api_events_rx) = mpsc::channel(1024);
-        CommitmentsApiServer::new(api_addr).run(api_events_tx, opts.limits).await;
+        let (api_events_tx, api_events_rx) =
+            mpsc::channel(opts.rate_limits.request_queue_size.get());
+        let (stop_api, mut api_stopped) = watch::channel(false);
+        let api_stopped = async move {
+            let _ = api_stopped.wait_for(|stopped| *stopped).await;
+        };
+        let mut api_server = CommitmentsApiServer::new(&api_addr)
+            .with_shutdown(&api_addr, api_stopped)
+            .with_validator_statuses(validator_statuses.clone())
+            .with_health(health.clone())
+            .with_rate_limits(opts.rate_limits)
+            .with_request_domain(RequestDomain {
+                chain_id: opts.chain.chain_id(),
+                operator: commitment_signer.public_key(),
+            });
+        if let Some(reloads) = reloads.clone() {
+            api_server = api_server.with_reloads(reloads);
+        }
+        api_server.run(api_events_tx, opts.limits).await;
 
         let unsafe_skip_consensus_checks = opts.unsafe_disable_consensus_checks;
 
+        if opts.shadow_mode {
+            warn!("Running in shadow mode: requests are validated but never signed or submitted");
+        }
+
         Ok(Self {
             unsafe_skip_consensus_checks,
+            shadow_mode: opts.shadow_mode,
             head_tracker,
             execution,
             consensus,
             constraint_signer,
             commitment_signer,
             local_builder,
+            constraints_streamer: ConstraintsStreamer::spawn(constraints_client.clone(), health),
             constraints_client,
+            validator_statuses,
+            commitments_db,
+            pending_commitments: PendingCommitments::new(opts.rate_limits.max_pending_per_signer),
             api_events_rx,
             payload_requests_rx,
+            admin_requests_rx,
+            commitments_paused,
+            admin_state_path: opts.admin.admin_state_path.clone(),
+            reloads,
             slot_stream,
+            shutdown_signals: shutdown_signals(),
+            stop_api,
+            shutdown_after: None,
         })
     }
 
-    /// Run the main event loop endlessly for the sidecar driver.
+    /// Run the main event loop for the sidecar driver until a shutdown signal is received,
+    /// then shut down gracefully. See [`SidecarDriver::handle_shutdown_signal`].
     ///
     /// Any errors encountered are contained to the specific `handler` in which
     /// they occurred, and the driver will continue to run as long as possible.
-    pub async fn run_forever(mut self) -> ! {
+    pub async fn run(mut self) {
         loop {
             tokio::select! {
                 Some(api_event) = self.api_events_rx.recv() => {
-                    self.handle_incoming_api_event(api_event).await;
+                    let span = info_span!(parent: &api_event.span, "handle_commitment");
+                    self.handle_incoming_api_event(api_event).instrument(span).await;
                 }
                 Ok(head_event) = self.head_tracker.next_head() => {
                     self.handle_new_head_event(head_event).await;
@@ -268,22 +565,47 @@ impl<C: StateFetcher, ECDSA: SignerECDSA> SidecarDriver<C, ECDSA> {
                 Some(payload_request) = self.payload_requests_rx.recv() => {
                     self.handle_fetch_payload_request(payload_request);
                 }
+                Some(admin_request) = self.admin_requests_rx.recv() => {
+                    self.handle_admin_request(admin_request);
+                }
+                Some(Ok(())) = async { Some(self.reloads.as_mut()?.changed().await) } => {
+                    self.handle_reloaded_opts();
+                }
                 Some(slot) = self.slot_stream.next() => {
                     if let Err(e) = self.consensus.update_slot(slot).await {
                         error!(err = ?e, "Failed to update consensus state slot");
                     }
+                    self.pending_commitments.prune(slot);
+
+                    if self.shutdown_after.is_some_and(|proposal_slot| slot > proposal_slot) {
+                        info!(slot, "Proposal slot has passed, resuming shutdown");
+                        break;
+                    }
+                }
+                Some(signal) = self.shutdown_signals.recv() => {
+                    if self.handle_shutdown_signal(signal) {
+                        break;
+                    }
                 }
             }
         }
+
+        self.shutdown().await;
     }
 
     /// Handle an incoming API event, validating the request and responding with a commitment.
     async fn handle_incoming_api_event(&mut self, event: CommitmentEvent) {
-        let CommitmentEvent { request, response } = event;
+        let CommitmentEvent { request, response, .. } = event;
 
         info!("Received new commitment request: {:?}", request);
         ApiMetrics::increment_inclusion_commitments_received();
 
+        if self.commitments_paused {
+            warn!("Commitment acceptance is paused, rejecting request");
+            let _ = response.send(Err(CommitmentError::Paused));
+            return;
+        }
+
         let start = Instant::now();
 
         // When we'll add more commitment types, we'll need to match on the request type here.
@@ -298,10 +620,14 @@ impl<C: StateFetcher, ECDSA: SignerECDSA> SidecarDriver<C, ECDSA> {
         // - On regular operation, we need to validate the request against the consensus state to
         //   determine if the sidecar is the proposer for the given slot. If so, we use the
         //   validator pubkey or any of its active delegatees to sign constraints.
-        let signing_pubkey = if self.unsafe_skip_consensus_checks {
-            available_pubkeys.iter().min().cloned().expect("at least one available pubkey")
+        let (proposer, signing_pubkey) = if self.unsafe_skip_consensus_checks {
+            (None, available_pubkeys.iter().min().cloned().expect("at least one available pubkey"))
         } else {
-            let validator_pubkey = match self.consensus.validate_request(&inclusion_request) {
+            let validation_start = Instant::now();
+            let validation = self.consensus.validate_request(&inclusion_request);
+            ApiMetrics::observe_validation(validation_start.elapsed(), "consensus");
+
+            let validator_pubkey = match validation {
                 Ok(pubkey) => pubkey,
                 Err(err) => {
                     warn!(?err, "Consensus: failed to validate request");
@@ -310,20 +636,54 @@ impl<C: StateFetcher, ECDSA: SignerECDSA> SidecarDriver<C, ECDSA> {
                 }
             };
 
+            // Don't accept commitments for validators that aren't active in Bolt anymore.
+            if self.validator_statuses.is_paused(&validator_pubkey) {
+                warn!(%target_slot, ?validator_pubkey, "Validator is paused, rejecting request");
+                let err = ConsensusError::ValidatorPaused(validator_pubkey);
+                let _ = response.send(Err(CommitmentError::Consensus(err)));
+                return;
+            }
+
             // Find a public key to sign new constraints with for this slot.
             // This can either be the validator pubkey or a delegatee (if one is available).
-            let Some(signing_key) =
-                self.constraints_client.find_signing_key(validator_pubkey, available_pubkeys)
+            let Some(signing_key) = self
+                .constraints_client
+                .find_signing_key(validator_pubkey.clone(), available_pubkeys)
             else {
                 error!(%target_slot, "No available public key to sign constraints with");
                 let _ = response.send(Err(CommitmentError::Internal));
                 return;
             };
 
-            signing_key
+            // Never sign with a delegatee that has been revoked at runtime.
+            if self.constraints_client.delegations().is_revoked(&validator_pubkey, &signing_key) {
+                error!(%target_slot, ?signing_key, "Refusing to sign constraints with revoked delegatee");
+                let _ = response.send(Err(CommitmentError::Internal));
+                return;
+            }
+
+            (Some(validator_pubkey), signing_key)
         };
 
-        if let Err(err) = self.execution.validate_request(&mut inclusion_request).await {
+        // Pending commitments are only reserved for target slots that passed the consensus
+        // checks, and released if the request isn't committed to.
+        let signer = inclusion_request.signer.unwrap_or_default();
+        let reservation = match self.pending_commitments.reserve(signer, target_slot) {
+            Ok(reservation) => reservation,
+            Err(err) => {
+                warn!(?err, "Too many pending commitments for signer, rejecting request");
+                ApiMetrics::increment_rate_limited_requests(err.reason());
+                let _ = response.send(Err(err.into()));
+                return;
+            }
+        };
+
+        let validation_start = Instant::now();
+        let validation =
+            self.execution.validate_request(&mut inclusion_request, proposer.as_ref()).await;
+        ApiMetrics::observe_validation(validation_start.elapsed(), "execution");
+
+        if let Err(err) = validation {
             warn!(?err, "Execution: failed to validate request");
             ApiMetrics::increment_validation_errors(err.to_tag_str().to_owned());
             let _ = response.send(Err(CommitmentError::Validation(err)));
@@ -336,25 +696,76 @@ impl<C: StateFetcher, ECDSA: SignerECDSA> SidecarDriver<C, ECDSA> {
             "Validation against execution state passed"
         );
 
+        // Refuse the commitment before signing anything if it conflicts with the commitments
+        // already signed for pending slots, even in previous runs of the sidecar.
+        let record = match SignedCommitmentRecord::from_request(&inclusion_request) {
+            Ok(record) => record,
+            Err(err) => {
+                error!(?err, "Failed to create signed commitment record");
+                let _ = response.send(Err(CommitmentError::Internal));
+                return;
+            }
+        };
+        let head_slot = self.consensus.latest_slot();
+        let max_committed_gas = self.execution.max_committed_gas_for(proposer.as_ref());
+        let checked = self.commitments_db.lock().check(&record, head_slot, max_committed_gas);
+        if let Err(err) = checked {
+            warn!(?err, "Commitment conflicts with a previously signed commitment");
+            let _ = response.send(Err(CommitmentError::Conflict(err)));
+            return;
+        }
+
+        // In shadow mode, we stop right before signing: the constraints are added unsigned to the
+        // block template, so that the next requests are validated against them, but they are
+        // never signed nor streamed to the constraints service.
+        if self.shadow_mode {
+            for tx in &inclusion_request.txs {
+                let message =
+                    ConstraintsMessage::from_tx(signing_pubkey.clone(), target_slot, tx.clone());
+                let unsigned = SignedConstraints { message, signature: BLSSig::default() };
+                self.execution.add_constraint(target_slot, unsigned);
+            }
+
+            debug!(target_slot, elapsed = ?start.elapsed(), "Shadow mode: would commit to request");
+            ApiMetrics::increment_shadow_commitments();
+            reservation.keep();
+            let _ = response
+                .send(Ok(CommitmentResponse::WouldCommit(inclusion_request.into_would_commit())));
+            return;
+        }
+
         // NOTE: we iterate over the transactions in the request and generate a signed constraint
         // for each one. This is because the transactions in the commitment request are not supposed
         // to be treated as a relative-ordering bundle, but a batch with no ordering guarantees.
         //
         // For more information, check out the constraints API docs:
         // https://docs.boltprotocol.xyz/technical-docs/api/builder#constraints
+        let mut new_constraints = Vec::with_capacity(inclusion_request.txs.len());
         for tx in &inclusion_request.txs {
             let tx_type = TxType::try_from(tx.ty()).expect("valid tx type");
             let message =
                 ConstraintsMessage::from_tx(signing_pubkey.clone(), target_slot, tx.clone());
             let digest = message.digest();
 
-            let signature_result = match &self.constraint_signer {
-                SignerBLS::Local(signer) => signer.sign_commit_boost_root(digest),
-                SignerBLS::CommitBoost(signer) => signer.sign_commit_boost_root(digest).await,
-                SignerBLS::Keystore(signer) => {
-                    signer.sign_commit_boost_root(digest, &signing_pubkey)
+            let signing_start = Instant::now();
+            let signature_result = async {
+                match &self.constraint_signer {
+                    SignerBLS::Local(signer) => signer.sign_commit_boost_root(digest),
+                    SignerBLS::CommitBoost(signer) => signer.sign_commit_boost_root(digest).await,
+                    SignerBLS::Keystore(signer) => {
+                        signer.sign_commit_boost_root(digest, &signing_pubkey)
+                    }
+                    SignerBLS::Web3Signer(signer) => {
+                        signer.sign_commit_boost_root(digest, &signing_pubkey).await
+                    }
+                    SignerBLS::Dirk(signer) => {
+                        signer.sign_commit_boost_root(digest, &signing_pubkey).await
+                    }
                 }
-            };
+            }
+            .instrument(info_span!("sign_constraints", signer = self.constraint_signer.kind()))
+            .await;
+            ApiMetrics::observe_signing(signing_start.elapsed(), "constraints");
 
             let signed_constraints = match signature_result {
                 Ok(signature) => SignedConstraints { message, signature },
@@ -365,22 +776,54 @@ impl<C: StateFetcher, ECDSA: SignerECDSA> SidecarDriver<C, ECDSA> {
                 }
             };
 
-            ApiMetrics::increment_transactions_preconfirmed(tx_type);
-            self.execution.add_constraint(target_slot, signed_constraints);
+            new_constraints.push((tx_type, signed_constraints));
         }
 
         // Create a commitment by signing the request
-        match inclusion_request.commit_and_sign(&self.commitment_signer).await {
-            Ok(commitment) => {
-                debug!(target_slot, elapsed = ?start.elapsed(), "Commitment signed and sent");
-                response.send(Ok(SignedCommitment::Inclusion(commitment))).ok()
-            }
+        let signing_start = Instant::now();
+        let signing = inclusion_request.commit_and_sign(&self.commitment_signer);
+        let signing = signing.instrument(info_span!("sign_commitment")).await;
+        ApiMetrics::observe_signing(signing_start.elapsed(), "commitment");
+
+        let commitment = match signing {
+            Ok(commitment) => commitment,
             Err(err) => {
                 error!(?err, "Failed to sign commitment");
-                response.send(Err(CommitmentError::Internal)).ok()
+                let _ = response.send(Err(CommitmentError::Internal));
+                return;
             }
         };
 
+        // Durably record the signed commitment before anything signed leaves the sidecar. If it
+        // can't be recorded, e.g. because a concurrent commitment conflicts with it, the
+        // signatures are discarded.
+        let recorded = self.commitments_db.lock().insert(record, head_slot, max_committed_gas);
+        if let Err(err) = recorded {
+            if err.is_conflict() {
+                warn!(?err, "Commitment conflicts with a previously signed commitment");
+                let _ = response.send(Err(CommitmentError::Conflict(err)));
+            } else {
+                error!(?err, "Failed to record signed commitment");
+                let _ = response.send(Err(CommitmentError::Internal));
+            }
+            return;
+        }
+
+        // Only add the constraints to the block template once the commitment has been signed,
+        // and stream them to the constraints service right away, so that builders can start
+        // including them before the commitment deadline.
+        let mut streamed = Vec::with_capacity(new_constraints.len());
+        for (tx_type, signed_constraints) in new_constraints {
+            ApiMetrics::increment_transactions_preconfirmed(tx_type);
+            self.execution.add_constraint(target_slot, signed_constraints.clone());
+            streamed.push(signed_constraints);
+        }
+        self.constraints_streamer.submit(streamed);
+
+        reservation.keep();
+        debug!(target_slot, elapsed = ?start.elapsed(), "Commitment signed and sent");
+        let _ = response.send(Ok(SignedCommitment::Inclusion(commitment).into()));
+
         ApiMetrics::increment_inclusion_commitments_accepted();
     }
 
@@ -405,29 +848,187 @@ impl<C: StateFetcher, ECDSA: SignerECDSA> SidecarDriver<C, ECDSA> {
             return;
         };
 
+        // Shadow templates hold unsigned constraints, which are never built nor submitted.
+        if self.shadow_mode {
+            debug!(slot, "Shadow mode: commitment deadline reached, nothing to submit");
+            return;
+        }
+
         info!(slot, "Commitment deadline reached, building local block");
+        ApiMetrics::set_slot_commitments(
+            template.committed_gas(),
+            template.blob_count(),
+            template.transactions_len(),
+        );
 
         if let Err(e) = self.local_builder.build_new_local_payload(slot, template).await {
             error!(err = ?e, "Error while building local payload at deadline for slot {slot}");
         };
 
-        let constraints = Arc::new(template.signed_constraints_list.clone());
-        let constraints_client = Arc::new(self.constraints_client.clone());
-
-        // Submit constraints to the constraints service with an exponential retry mechanism.
-        tokio::spawn(retry_with_backoff(10, move || {
-            let constraints_client = Arc::clone(&constraints_client);
-            let constraints = Arc::clone(&constraints);
-            async move {
-                match constraints_client.submit_constraints(constraints.as_ref()).await {
-                    Ok(_) => Ok(()),
-                    Err(e) => {
-                        error!(err = ?e, "Failed to submit constraints, retrying...");
-                        Err(e)
-                    }
+        // Constraints are streamed as soon as they are signed. Reconcile the full list for the
+        // slot to make sure that any failed submissions are retried before block building ends.
+        self.constraints_streamer.reconcile(slot, template.signed_constraints_list.clone());
+    }
+
+    /// Handle a signal requesting the sidecar to shut down, returning whether to shut down now.
+    ///
+    /// The commitments API server stops accepting new requests right away. If one of our
+    /// validators is about to propose, or commitments have been made for an upcoming slot, the
+    /// shutdown is deferred until that slot has passed so that the commitments can be honored.
+    /// Receiving the signal again forces the shutdown.
+    fn handle_shutdown_signal(&mut self, signal: &'static str) -> bool {
+        if self.shutdown_after.is_some() {
+            warn!(signal, "Received shutdown signal again, forcing shutdown");
+            return true;
+        }
+
+        info!(signal, "Received shutdown signal, no longer accepting commitment requests");
+        let _ = self.stop_api.send(true);
+
+        let Some(proposal_slot) = self.imminent_proposal_slot() else { return true };
+        warn!(
+            signal,
+            proposal_slot,
+            "Deferring shutdown until the proposal slot has passed, send again to force shutdown"
+        );
+        self.shutdown_after = Some(proposal_slot);
+        false
+    }
+
+    /// Returns the last upcoming slot for which we have made commitments, or in which one of
+    /// our validators is about to propose, if any. Shadow templates aren't commitments.
+    fn imminent_proposal_slot(&self) -> Option<Slot> {
+        let latest_slot = self.consensus.latest_slot();
+        let available_pubkeys = self.constraint_signer.available_pubkeys();
+
+        let committed = self
+            .execution
+            .block_template_slots()
+            .into_iter()
+            .filter(|s| !self.shadow_mode && *s > latest_slot);
+        let proposing = self
+            .consensus
+            .upcoming_proposers(SHUTDOWN_GUARD_SLOTS)
+            .into_iter()
+            .filter(|(_, pubkey)| {
+                self.constraints_client
+                    .find_signing_key(pubkey.clone(), available_pubkeys.clone())
+                    .is_some()
+            })
+            .map(|(slot, _)| slot);
+
+        committed.chain(proposing).max()
+    }
+
+    /// Shut down the sidecar gracefully:
+    /// 1. Finish processing the commitment requests in flight, now that the commitments API server
+    ///    no longer accepts new ones.
+    /// 2. Submit the constraints of the block templates whose commitment deadline has passed, and
+    ///    wait for all the queued constraints to be submitted.
+    /// 3. Persist the signed commitments database.
+    async fn shutdown(mut self) {
+        info!("Shutting down the sidecar");
+        let _ = self.stop_api.send(true);
+
+        // The events channel is closed once the server has completed all in-flight requests.
+        let drain = async {
+            while let Some(api_event) = self.api_events_rx.recv().await {
+                let span = info_span!(parent: &api_event.span, "handle_commitment");
+                self.handle_incoming_api_event(api_event).instrument(span).await;
+            }
+        };
+        if tokio::time::timeout(SHUTDOWN_DRAIN_TIMEOUT, drain).await.is_err() {
+            warn!("Timed out waiting for in-flight commitment requests to complete");
+        }
+
+        if let Some(proposal_slot) = self.imminent_proposal_slot() {
+            warn!(proposal_slot, "Shutting down with commitments or a proposal for upcoming slots");
+        }
+
+        // Constraints are reconciled at the commitment deadline, but a deadline may have passed
+        // while shutting down. Reconciliation is deduplicated, so this is a no-op otherwise.
+        for slot in self.execution.block_template_slots() {
+            if self.shadow_mode || !self.consensus.is_past_commitment_deadline(slot) {
+                continue;
+            }
+
+            if let Some(template) = self.execution.get_block_template(slot) {
+                self.constraints_streamer.reconcile(slot, template.signed_constraints_list.clone());
+            }
+        }
+
+        let flush = self.constraints_streamer.flush();
+        if tokio::time::timeout(SHUTDOWN_FLUSH_TIMEOUT, flush).await.is_err() {
+            warn!("Timed out waiting for queued constraints to be submitted");
+        }
+
+        if let Err(err) = self.commitments_db.lock().persist() {
+            error!(?err, "Failed to persist signed commitments database");
+        }
+
+        info!("Sidecar shut down");
+    }
+
+    /// Apply the limits reloaded from the configuration file to the validation of new
+    /// commitments. Commitments already accepted are not affected.
+    fn handle_reloaded_opts(&mut self) {
+        let Some(reloads) = &mut self.reloads else { return };
+
+        let limits = reloads.borrow_and_update().limits;
+        info!(?limits, "Applying limits reloaded from the configuration file");
+        self.execution.set_limits(limits);
+    }
+
+    /// Handle a request from the admin API, responding with a view of the sidecar state or
+    /// applying the requested action.
+    fn handle_admin_request(&mut self, request: AdminRequest) {
+        debug!(?request, "Received admin request");
+
+        // Responses are dropped if the admin API handler has timed out in the meantime.
+        match request {
+            AdminRequest::Consensus(tx) => {
+                let _ = tx.send(self.consensus.snapshot());
+            }
+            AdminRequest::Execution(tx) => {
+                let _ = tx.send(self.execution.snapshot());
+            }
+            AdminRequest::Accounts(tx) => {
+                let _ = tx.send(self.execution.account_states_snapshot());
+            }
+            AdminRequest::Signers(tx) => {
+                let mut constraint_pubkeys =
+                    self.constraint_signer.available_pubkeys().into_iter().collect::<Vec<_>>();
+                constraint_pubkeys.sort();
+
+                let _ = tx.send(SignersInfo {
+                    constraint_signer: self.constraint_signer.kind(),
+                    constraint_pubkeys,
+                    commitment_signer: self.commitment_signer.public_key(),
+                });
+            }
+            AdminRequest::SetPaused(paused, tx) => {
+                if paused != self.commitments_paused {
+                    warn!(paused, "Commitment acceptance status changed through the admin API");
                 }
+                self.commitments_paused = paused;
+
+                let status = CommitmentsStatus { paused };
+                let persisted = status.write_to_file(&self.admin_state_path).map_err(|err| {
+                    error!(?err, "Failed to persist the commitments status");
+                    AdminApiError::StatusPersistence(err.to_string())
+                });
+                let _ = tx.send(persisted.map(|_| status));
             }
-        }));
+            AdminRequest::Constraints(slot, tx) => {
+                // Shadow templates hold unsigned constraints, which must never be resubmitted.
+                let constraints = self
+                    .execution
+                    .get_block_template(slot)
+                    .filter(|_| !self.shadow_mode)
+                    .map(|template| template.signed_constraints_list.clone());
+                let _ = tx.send(constraints);
+            }
+        }
     }
 
     /// Handle a fetch payload request, responding with the local payload if available.
@@ -446,7 +1047,45 @@ impl<C: StateFetcher, ECDSA: SignerECDSA> SidecarDriver<C, ECDSA> {
     }
 }
 
-impl fmt::Debug for SidecarDriver<StateClient, PrivateKeySigner> {
+/// Create the signer for commitment responses from the given [Opts]. The key is either provided
+/// in plaintext, decrypted from a JSON V3 keystore, or held by a remote Web3Signer.
+async fn commitment_signer(opts: &Opts) -> eyre::Result<CommitmentSigner> {
+    let signing = &opts.commitment_signing;
+
+    if let Some(key) = &signing.commitment_private_key {
+        return Ok(CommitmentSigner::Local(PrivateKeySigner::from_signing_key(key.0.clone())));
+    }
+
+    if let Some(path) = &signing.commitment_keystore_path {
+        let password_path =
+            signing.commitment_keystore_password_path.as_ref().expect("keystore password path");
+        let password = fs::read_to_string(password_path)
+            .wrap_err("Failed to read commitment keystore password file")?;
+        let signer =
+            PrivateKeySigner::decrypt_keystore(path, password.trim_end_matches(['\r', '\n']))
+                .wrap_err("Failed to decrypt commitment keystore")?;
+        return Ok(CommitmentSigner::Local(signer));
+    }
+
+    let credentials = signing
+        .commitment_web3signer_ca_cert_path
+        .clone()
+        .zip(signing.commitment_web3signer_combined_pem_path.clone())
+        .map(|(ca_cert_path, combined_pem_path)| Web3SignerTlsCredentials {
+            ca_cert_path,
+            combined_pem_path,
+        });
+    let signer = Web3SignerEcdsa::connect(
+        signing.commitment_web3signer_url.clone().expect("commitment Web3Signer URL"),
+        credentials,
+        signing.commitment_web3signer_address,
+    )
+    .await?;
+
+    Ok(CommitmentSigner::Web3Signer(signer))
+}
+
+impl fmt::Debug for SidecarDriver<StateClient, CommitmentSigner> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("SidecarDriver")
             .field("head_tracker", &self.head_tracker)
@@ -456,8 +1095,325 @@ impl fmt::Debug for SidecarDriver<StateClient, PrivateKeySigner> {
             .field("commitment_signer", &self.commitment_signer)
             .field("local_builder", &self.local_builder)
             .field("constraints_client", &self.constraints_client)
+            .field("constraints_streamer", &self.constraints_streamer)
+            .field("validator_statuses", &self.validator_statuses)
+            .field("commitments_db", &self.commitments_db)
+            .field("pending_commitments", &self.pending_commitments)
             .field("api_events_rx", &self.api_events_rx)
             .field("payload_requests_rx", &self.payload_requests_rx)
+            .field("admin_requests_rx", &self.admin_requests_rx)
+            .field("commitments_paused", &self.commitments_paused)
+            .field("reloads", &self.reloads)
+            .field("shadow_mode", &self.shadow_mode)
+            .field("shutdown_after", &self.shutdown_after)
             .finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use alloy::primitives::{Address, PrimitiveSignature};
+    use alloy_node_bindings::AnvilInstance;
+    use axum::{http::StatusCode, routing::post, Json, Router};
+    use clap::Parser;
+    use reqwest::Url;
+    use tokio::{net::TcpListener, sync::oneshot};
+    use tracing::Span;
+
+    use super::*;
+    use crate::{
+        api::spec::SUBMIT_CONSTRAINTS_PATH,
+        common::secrets::{BlsSecretKeyWrapper, EcdsaSecretKeyWrapper, JwtSecretConfig},
+        primitives::InclusionRequest,
+        signer::web3signer::test_util::start_counting_web3signer_test_server,
+        test_util::{create_signed_inclusion_request, default_test_transaction, launch_anvil},
+    };
+
+    /// A commitment signer counting the signatures it produces.
+    #[derive(Debug, Clone)]
+    struct CountingSigner {
+        signer: PrivateKeySigner,
+        signatures: Arc<AtomicUsize>,
+    }
+
+    #[async_trait::async_trait]
+    impl SignerECDSA for CountingSigner {
+        fn public_key(&self) -> Address {
+            self.signer.address()
+        }
+
+        async fn sign_keccak256(&self, data: &[u8]) -> eyre::Result<PrimitiveSignature> {
+            self.signatures.fetch_add(1, Ordering::SeqCst);
+            SignerECDSA::sign_keccak256(&self.signer, data).await
+        }
+    }
+
+    /// Spawns a relay stand-in recording the slots of the constraints it accepts.
+    async fn spawn_relay() -> eyre::Result<(Url, Arc<Mutex<Vec<Slot>>>)> {
+        let slots = Arc::new(Mutex::new(Vec::new()));
+        let recorded = slots.clone();
+        let router = Router::new().route(
+            SUBMIT_CONSTRAINTS_PATH,
+            post(move |Json(batch): Json<Vec<serde_json::Value>>| async move {
+                for constraints in batch {
+                    let slot = constraints["message"]["slot"].as_u64().expect("slot");
+                    recorded.lock().push(slot);
+                }
+                StatusCode::OK
+            }),
+        );
+
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let url = Url::parse(&format!("http://{}", listener.local_addr()?))?;
+        tokio::spawn(async move { axum::serve(listener, router).await });
+
+        Ok((url, slots))
+    }
+
+    /// Creates a driver over the given Anvil instance, signers and relay, without any of the
+    /// servers and background tasks started by [`SidecarDriver::from_components`].
+    async fn test_driver<ECDSA: SignerECDSA + Clone + 'static>(
+        anvil: &AnvilInstance,
+        constraint_signer: SignerBLS,
+        commitment_signer: ECDSA,
+        relay: Url,
+    ) -> eyre::Result<SidecarDriver<StateClient, ECDSA>> {
+        let opts = Opts::try_parse_from([
+            "bolt-sidecar".to_string(),
+            format!("--engine-jwt-hex={}", JwtSecretConfig::default().to_hex()),
+            format!("--fee-recipient={}", Address::ZERO),
+            format!("--constraint-private-key={}", BlsSecretKeyWrapper::random()),
+            format!("--commitment-private-key={}", EcdsaSecretKeyWrapper::random()),
+        ])?;
+
+        let client = StateClient::new(anvil.endpoint_url());
+        let mut execution =
+            ExecutionState::new(client.clone(), opts.limits, opts.chain.gas_limit).await?;
+        execution.update_head(None, client.get_head().await?).await?;
+
+        // The beacon node is never reached in these tests
+        let beacon_client = BeaconClient::new("http://127.0.0.1:1".parse()?);
+        let constraints_client = ConstraintsMultiplexer::new(vec![ConstraintsClient::new(relay)]);
+        let (_, api_events_rx) = mpsc::channel(1);
+        let (_, payload_requests_rx) = mpsc::channel(1);
+        let (_, admin_requests_rx) = mpsc::channel(1);
+        let (_, shutdown_signals) = mpsc::unbounded_channel();
+
+        Ok(SidecarDriver {
+            head_tracker: HeadTracker::start(beacon_client.clone()),
+            execution,
+            consensus: ConsensusState::new(
+                beacon_client,
+                opts.chain.commitment_deadline(),
+                opts.chain.enable_unsafe_lookahead,
+            ),
+            constraint_signer,
+            commitment_signer,
+            local_builder: LocalBuilder::new(&opts, 0),
+            constraints_streamer: ConstraintsStreamer::spawn(
+                constraints_client.clone(),
+                SidecarHealth::default(),
+            ),
+            constraints_client,
+            validator_statuses: ValidatorStatuses::default(),
+            commitments_db: Arc::new(Mutex::new(CommitmentsDb::in_memory(opts.chain.chain_id()))),
+            pending_commitments: PendingCommitments::new(opts.rate_limits.max_pending_per_signer),
+            api_events_rx,
+            payload_requests_rx,
+            admin_requests_rx,
+            commitments_paused: false,
+            admin_state_path: PathBuf::from("./admin_state.json"),
+            reloads: None,
+            slot_stream: clock::from_system_time(0, opts.chain.slot_time(), SLOTS_PER_EPOCH)
+                .into_stream(),
+            shutdown_signals,
+            stop_api: watch::channel(false).0,
+            shutdown_after: None,
+            unsafe_skip_consensus_checks: true,
+            shadow_mode: false,
+        })
+    }
+
+    /// Sends the given request to the driver and returns its response.
+    async fn send_request<ECDSA: SignerECDSA + Clone + 'static>(
+        driver: &mut SidecarDriver<StateClient, ECDSA>,
+        request: InclusionRequest,
+    ) -> Result<CommitmentResponse, CommitmentError> {
+        let (response, rx) = oneshot::channel();
+        let request = CommitmentRequest::Inclusion(request);
+        let event = CommitmentEvent { request, response, span: Span::none() };
+
+        driver.handle_incoming_api_event(event).await;
+        rx.await.expect("response sent")
+    }
+
+    #[tokio::test]
+    async fn test_shadow_mode_never_signs() -> eyre::Result<()> {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let anvil = launch_anvil();
+        let (url, bls_signatures) =
+            start_counting_web3signer_test_server(vec![LocalSigner::random()]).await?;
+        let constraint_signer = SignerBLS::Web3Signer(Web3Signer::connect(url, None).await?);
+        let commitment_signer = CountingSigner {
+            signer: PrivateKeySigner::random(),
+            signatures: Arc::new(AtomicUsize::new(0)),
+        };
+
+        let (relay, _) = spawn_relay().await?;
+        let mut driver =
+            test_driver(&anvil, constraint_signer, commitment_signer.clone(), relay).await?;
+        driver.shadow_mode = true;
+
+        let sender = anvil.addresses()[0];
+        let sender_pk = &anvil.keys()[0];
+        let target_slot = 10;
+
+        let tx = default_test_transaction(sender, Some(0));
+        let request = create_signed_inclusion_request(&[tx], sender_pk, target_slot).await?;
+        let response = send_request(&mut driver, request).await;
+        assert!(matches!(response, Ok(CommitmentResponse::WouldCommit(_))));
+
+        // The unsigned constraints are kept in a shadow template...
+        let template = driver.execution.get_block_template(target_slot).expect("template");
+        assert_eq!(template.transactions_len(), 1);
+        assert!(template.signed_constraints_list[0].signature.is_zero());
+
+        // ...which the next requests are validated against
+        let tx = default_test_transaction(sender, Some(0));
+        let request = create_signed_inclusion_request(&[tx], sender_pk, target_slot).await?;
+        let response = send_request(&mut driver, request).await;
+        assert!(matches!(response, Err(CommitmentError::Validation(_))));
+
+        // The shadow template is never submitted
+        driver.handle_commitment_deadline(target_slot).await;
+        let (tx, rx) = oneshot::channel();
+        driver.handle_admin_request(AdminRequest::Constraints(target_slot, tx));
+        assert_eq!(rx.await?, None);
+
+        assert_eq!(bls_signatures.load(Ordering::SeqCst), 0);
+        assert_eq!(commitment_signer.signatures.load(Ordering::SeqCst), 0);
+
+        // Both signers are called outside of shadow mode
+        driver.shadow_mode = false;
+        let tx = default_test_transaction(sender, Some(1));
+        let request = create_signed_inclusion_request(&[tx], sender_pk, target_slot).await?;
+        let response = send_request(&mut driver, request).await;
+        assert!(matches!(response, Ok(CommitmentResponse::Signed(_))));
+
+        assert_eq!(bls_signatures.load(Ordering::SeqCst), 1);
+        assert_eq!(commitment_signer.signatures.load(Ordering::SeqCst), 1);
+
+        Ok(())
+    }
+
+    /// Creates a driver with a local constraint signer, whose validator proposes at the given
+    /// slots after the latest slot 10.
+    async fn test_proposer_driver(
+        anvil: &AnvilInstance,
+        relay: Url,
+        commitment_deadline: Duration,
+        proposal_slots: &[Slot],
+    ) -> eyre::Result<SidecarDriver<StateClient, PrivateKeySigner>> {
+        let signer = LocalSigner::random();
+        let pubkey = signer.pubkey();
+
+        let mut driver =
+            test_driver(anvil, SignerBLS::Local(signer), PrivateKeySigner::random(), relay).await?;
+        driver.unsafe_skip_consensus_checks = false;
+        driver.consensus = ConsensusState::new(
+            BeaconClient::new("http://127.0.0.1:1".parse()?),
+            commitment_deadline,
+            false,
+        );
+        let duties = proposal_slots.iter().map(|slot| (*slot, pubkey.clone())).collect();
+        driver.consensus.set_proposer_duties(10, duties);
+
+        Ok(driver)
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_deferred_on_imminent_proposal() -> eyre::Result<()> {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let anvil = launch_anvil();
+        let (relay, _) = spawn_relay().await?;
+
+        // Proposals beyond the guard slots don't defer shutdown
+        let mut driver =
+            test_proposer_driver(&anvil, relay.clone(), Duration::from_secs(8), &[13]).await?;
+        assert_eq!(driver.imminent_proposal_slot(), None);
+        assert!(driver.handle_shutdown_signal("SIGTERM"));
+
+        let mut driver = test_proposer_driver(&anvil, relay, Duration::from_secs(8), &[12]).await?;
+        let api_stopped = driver.stop_api.subscribe();
+        assert_eq!(driver.imminent_proposal_slot(), Some(12));
+
+        // The API stops accepting requests, but shutdown waits for the proposal
+        assert!(!driver.handle_shutdown_signal("SIGTERM"));
+        assert!(*api_stopped.borrow());
+        assert_eq!(driver.shutdown_after, Some(12));
+
+        // Receiving the signal again forces the shutdown
+        assert!(driver.handle_shutdown_signal("SIGINT"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_deferred_on_pending_commitments() -> eyre::Result<()> {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let anvil = launch_anvil();
+        let (relay, _) = spawn_relay().await?;
+        let mut driver = test_proposer_driver(&anvil, relay, Duration::from_secs(8), &[]).await?;
+
+        let sender = anvil.addresses()[0];
+        let tx = default_test_transaction(sender, Some(0));
+        let request = create_signed_inclusion_request(&[tx], &anvil.keys()[0], 20).await?;
+        let message = ConstraintsMessage::from_tx(
+            driver.constraint_signer.available_pubkeys().into_iter().next().expect("pubkey"),
+            20,
+            request.txs[0].clone(),
+        );
+        let constraints = SignedConstraints { message, signature: BLSSig::default() };
+        driver.execution.add_constraint(20, constraints);
+
+        assert_eq!(driver.imminent_proposal_slot(), Some(20));
+        assert!(!driver.handle_shutdown_signal("SIGTERM"));
+        assert_eq!(driver.shutdown_after, Some(20));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_reconciles_templates_past_deadline() -> eyre::Result<()> {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let anvil = launch_anvil();
+        let (relay, submitted) = spawn_relay().await?;
+        let deadline = Duration::from_millis(100);
+        let mut driver = test_proposer_driver(&anvil, relay, deadline, &[11, 12]).await?;
+        let pubkey = driver.constraint_signer.available_pubkeys().into_iter().next().unwrap();
+
+        // Constraints for the next two slots, that haven't been submitted yet
+        let sender = anvil.addresses()[0];
+        for (nonce, slot) in [(0, 11), (1, 12)] {
+            let tx = default_test_transaction(sender, Some(nonce));
+            let request = create_signed_inclusion_request(&[tx], &anvil.keys()[0], slot).await?;
+            let message = ConstraintsMessage::from_tx(pubkey.clone(), slot, request.txs[0].clone());
+            let constraints = SignedConstraints { message, signature: BLSSig::default() };
+            driver.execution.add_constraint(slot, constraints);
+        }
+
+        // The deadline of the next slot passes while shutting down, before it is handled
+        tokio::time::sleep(deadline * 2).await;
+        driver.shutdown().await;
+
+        assert_eq!(*submitted.lock(), vec![11]);
+
+        Ok(())
+    }
+}
@@ -45,6 +45,9 @@ pub mod signer;
 /// Utilities and contracts wrappers for interacting with the Bolt registry
 pub mod chain_io;
 
+/// Generated protobuf types for the Dirk remote signer gRPC API
+mod pb;
+
 /// Utilities for testing
 #[cfg(test)]
 mod test_util;
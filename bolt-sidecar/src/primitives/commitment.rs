@@ -1,9 +1,10 @@
-use std::str::FromStr;
+use std::{borrow::Cow, str::FromStr};
 
 use alloy::{
     consensus::Transaction,
     hex,
-    primitives::{keccak256, Address, PrimitiveSignature as Signature, B256},
+    primitives::{keccak256, Address, PrimitiveSignature as Signature, B256, U256},
+    sol_types::{Eip712Domain, SolStruct},
 };
 use serde::{de, Deserialize, Deserializer, Serialize};
 
@@ -16,6 +17,60 @@ use super::{deserialize_txs, serialize_txs, FullTransaction, TransactionExt};
 #[error("Invalid signature")]
 pub struct SignatureError;
 
+/// The name of the EIP-712 domain of commitment requests.
+pub const REQUEST_DOMAIN_NAME: &str = "Bolt";
+
+/// The version of the EIP-712 domain of commitment requests. It must be bumped whenever the
+/// typed data of the requests changes.
+pub const REQUEST_DOMAIN_VERSION: &str = "1";
+
+/// The EIP-712 typed data of commitment requests.
+mod typed {
+    alloy::sol! {
+        /// An inclusion request, as displayed by wallets when signing it.
+        struct InclusionRequest {
+            uint64 slot;
+            bytes32[] transactionHashes;
+        }
+    }
+}
+
+/// The scheme used by users to sign commitment requests.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RequestSigningScheme {
+    /// A signature over the raw request digest, see [`InclusionRequest::digest`].
+    #[default]
+    Digest,
+    /// A signature over the version 1 of the EIP-712 typed data of the request, see
+    /// [`InclusionRequest::eip712_signing_hash`].
+    Eip712V1,
+}
+
+/// The parameters of the EIP-712 domain of the requests sent to a sidecar. Signatures are
+/// bound to the chain and to the operator of the sidecar, so that they can't be replayed
+/// against another sidecar.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RequestDomain {
+    /// The chain ID of the chain on which the sidecar is running.
+    pub chain_id: u64,
+    /// The operator address of the sidecar, i.e. the address of its commitment signer. It is
+    /// used as the verifying contract of the domain.
+    pub operator: Address,
+}
+
+impl RequestDomain {
+    /// Returns the EIP-712 domain of the requests.
+    pub fn eip712_domain(&self) -> Eip712Domain {
+        Eip712Domain::new(
+            Some(Cow::Borrowed(REQUEST_DOMAIN_NAME)),
+            Some(Cow::Borrowed(REQUEST_DOMAIN_VERSION)),
+            Some(U256::from(self.chain_id)),
+            Some(self.operator),
+            None,
+        )
+    }
+}
+
 /// Commitment requests sent by users or RPC proxies to the sidecar.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(untagged)]
@@ -32,6 +87,35 @@ pub enum SignedCommitment {
     Inclusion(InclusionCommitment),
 }
 
+/// The response to a commitment request.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum CommitmentResponse {
+    /// A signed commitment.
+    Signed(SignedCommitment),
+    /// An unsigned response for a request that passed validation in shadow mode.
+    WouldCommit(WouldCommitResponse),
+}
+
+impl From<SignedCommitment> for CommitmentResponse {
+    fn from(commitment: SignedCommitment) -> Self {
+        Self::Signed(commitment)
+    }
+}
+
+/// An unsigned response to an inclusion request that passed validation in shadow mode: the
+/// sidecar would have committed to it, but neither the commitment nor its constraints were
+/// signed.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WouldCommitResponse {
+    #[serde(flatten)]
+    request: InclusionRequest,
+    /// The digest that would have been signed in the commitment.
+    digest: B256,
+    /// Always true, to tell this response apart from a signed commitment.
+    would_commit: bool,
+}
+
 /// A signed inclusion commitment with a generic signature.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct InclusionCommitment {
@@ -101,12 +185,16 @@ impl InclusionRequest {
         self,
         signer: &S,
     ) -> eyre::Result<InclusionCommitment> {
-        let digest = self.digest();
-        let signature = signer.sign_hash(&digest).await?;
+        let signature = signer.sign_keccak256(&self.digest_data()).await?;
         let signature = Signature::try_from(signature.as_bytes().as_ref())?;
         Ok(InclusionCommitment { request: self, signature })
     }
 
+    /// Returns the unsigned response to the request in shadow mode, without signing it.
+    pub fn into_would_commit(self) -> WouldCommitResponse {
+        WouldCommitResponse { digest: self.digest(), request: self, would_commit: true }
+    }
+
     /// Validates the transaction fees against a minimum basefee.
     /// Returns true if the fee is greater than or equal to the min, false otherwise.
     pub fn validate_basefee(&self, min: u128) -> bool {
@@ -232,6 +320,21 @@ impl InclusionRequest {
     /// Returns the digest of the request.
     /// digest = keccak256(bytes(tx_hash1) | bytes(tx_hash2) | ... | le_bytes(target_slot))
     pub fn digest(&self) -> B256 {
+        keccak256(self.digest_data())
+    }
+
+    /// Returns the EIP-712 signing hash of the typed data of the request in the given domain.
+    pub fn eip712_signing_hash(&self, domain: &RequestDomain) -> B256 {
+        let typed = typed::InclusionRequest {
+            slot: self.slot,
+            transactionHashes: self.txs.iter().map(|tx| *tx.hash()).collect(),
+        };
+
+        typed.eip712_signing_hash(&domain.eip712_domain())
+    }
+
+    /// Returns the data hashed to compute the digest of the request.
+    fn digest_data(&self) -> Vec<u8> {
         let mut data = Vec::new();
         // First field is the concatenation of all the transaction hashes
         data.extend_from_slice(
@@ -241,7 +344,7 @@ impl InclusionRequest {
         // Second field is the little endian encoding of the target slot
         data.extend_from_slice(&self.slot.to_le_bytes());
 
-        keccak256(&data)
+        data
     }
 }
 
@@ -281,10 +384,10 @@ mod tests {
 
     use alloy::{
         hex,
-        primitives::{Address, PrimitiveSignature as Signature},
+        primitives::{b256, Address, PrimitiveSignature as Signature},
     };
 
-    use super::{CommitmentRequest, InclusionRequest};
+    use super::{CommitmentRequest, CommitmentResponse, InclusionRequest, RequestDomain};
 
     #[test]
     fn test_create_digest() {
@@ -343,4 +446,49 @@ mod tests {
             panic!("Expected Inclusion request");
         }
     }
+
+    #[test]
+    fn test_would_commit_response() {
+        let json_req = r#"{
+            "slot": 633067,
+            "txs": ["0xf86b82016e84042343e0830f424094deaddeaddeaddeaddeaddeaddeaddeaddeaddead0780850344281a21a0e525fc31b5574722ff064bdd127c4441b0fc66de7dc44928e163cb68e9d807e5a00b3ec02fc1e34b0209f252369ad10b745cd5a51c88384a340f7a150d0e45e471"]
+        }"#;
+
+        let req: InclusionRequest = serde_json::from_str(json_req).unwrap();
+        let digest = req.digest();
+        let response = CommitmentResponse::WouldCommit(req.into_would_commit());
+
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(json["slot"], 633067);
+        assert_eq!(json["digest"], digest.to_string());
+        assert_eq!(json["would_commit"], true);
+        assert!(json.get("signature").is_none());
+
+        // An unsigned response must never be mistaken for a signed commitment
+        let deser: CommitmentResponse = serde_json::from_value(json).unwrap();
+        assert_eq!(deser, response);
+    }
+
+    #[test]
+    fn test_eip712_signing_hash() {
+        let json_req = r#"{
+            "slot": 633067,
+            "txs": ["0xf86b82016e84042343e0830f424094deaddeaddeaddeaddeaddeaddeaddeaddeaddead0780850344281a21a0e525fc31b5574722ff064bdd127c4441b0fc66de7dc44928e163cb68e9d807e5a00b3ec02fc1e34b0209f252369ad10b745cd5a51c88384a340f7a150d0e45e471"]
+        }"#;
+
+        let req: InclusionRequest = serde_json::from_str(json_req).unwrap();
+        let domain = RequestDomain { chain_id: 17000, operator: Address::repeat_byte(1) };
+        let hash = req.eip712_signing_hash(&domain);
+        assert_ne!(hash, req.digest());
+
+        // Fixed vector, shared with the `send` command of bolt-cli which signs the requests
+        // with its own copy of the typed data.
+        assert_eq!(hash, b256!("cbed59f0d3ff554624dcd53972c76b2e365b866d250c909599b17c5a5cc3ed62"));
+
+        // Signatures are bound to the chain and to the operator of the sidecar
+        let other_chain = RequestDomain { chain_id: 1, ..domain };
+        let other_operator = RequestDomain { operator: Address::repeat_byte(2), ..domain };
+        assert_ne!(hash, req.eip712_signing_hash(&other_chain));
+        assert_ne!(hash, req.eip712_signing_hash(&other_operator));
+    }
 }
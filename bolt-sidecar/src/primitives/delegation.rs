@@ -1,10 +1,18 @@
-use std::{fs, ops::Deref, path::PathBuf};
+use std::{collections::HashSet, fs, ops::Deref, path::PathBuf};
 
 use alloy::signers::k256::sha2::{Digest, Sha256};
-use ethereum_consensus::crypto::{PublicKey as BlsPublicKey, Signature as BlsSignature};
+use blst::BLST_ERROR;
+use ethereum_consensus::{
+    crypto::{PublicKey as BlsPublicKey, Signature as BlsSignature},
+    deneb::compute_signing_root,
+};
 use eyre::bail;
+use tracing::{error, info};
 
-use crate::crypto::SignableBLS;
+use crate::{
+    config::ChainConfig,
+    crypto::{bls::BLS_DST_PREFIX, SignableBLS},
+};
 
 /// Event types that can be emitted by the validator pubkey to
 /// signal some action on the Bolt protocol.
@@ -29,6 +37,19 @@ pub struct SignedDelegation {
     pub signature: BlsSignature,
 }
 
+impl SignedDelegation {
+    /// Verify that the delegation is signed by the validator with the commit-boost domain
+    /// of the given chain.
+    pub fn verify(&self, chain: &ChainConfig) -> bool {
+        verify_commit_boost_signature(
+            chain,
+            &self.message.validator_pubkey,
+            self.message.digest(),
+            &self.signature,
+        )
+    }
+}
+
 impl Deref for SignedDelegation {
     type Target = DelegationMessage;
 
@@ -78,11 +99,35 @@ pub fn read_signed_delegations_from_file(
     }
 }
 
+/// Read the signed revocations from disk.
+pub fn read_signed_revocations_from_file(
+    file_path: &PathBuf,
+) -> eyre::Result<Vec<SignedRevocation>> {
+    match fs::read_to_string(file_path) {
+        Ok(contents) => match serde_json::from_str::<Vec<SignedRevocation>>(&contents) {
+            Ok(revocations) => Ok(revocations),
+            Err(err) => bail!("Failed to parse signed revocations from disk: {:?}", err),
+        },
+        Err(err) => bail!("Failed to read signed revocations from disk: {:?}", err),
+    }
+}
+
+/// Write the signed revocations to disk, replacing the contents of the file atomically.
+pub fn write_signed_revocations_to_file(
+    file_path: &PathBuf,
+    revocations: &[SignedRevocation],
+) -> eyre::Result<()> {
+    let tmp_path = file_path.with_extension("tmp");
+    fs::write(&tmp_path, serde_json::to_vec_pretty(revocations)?)?;
+    fs::rename(&tmp_path, file_path)?;
+    Ok(())
+}
+
 /// A signed revocation message.
 ///
 /// This is a message that is signed by a validator to revoke its
 /// constraint signing power from another key (delegatee).
-#[derive(Debug, Clone, serde::Serialize, PartialEq, Eq)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
 pub struct SignedRevocation {
     /// The revocation message.
     pub message: RevocationMessage,
@@ -90,6 +135,19 @@ pub struct SignedRevocation {
     pub signature: BlsSignature,
 }
 
+impl SignedRevocation {
+    /// Verify that the revocation is signed by the validator with the commit-boost domain
+    /// of the given chain.
+    pub fn verify(&self, chain: &ChainConfig) -> bool {
+        verify_commit_boost_signature(
+            chain,
+            &self.message.validator_pubkey,
+            self.message.digest(),
+            &self.signature,
+        )
+    }
+}
+
 impl Deref for SignedRevocation {
     type Target = RevocationMessage;
 
@@ -99,7 +157,7 @@ impl Deref for SignedRevocation {
 }
 
 /// A revocation message.
-#[derive(Debug, Clone, serde::Serialize, PartialEq, Eq)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
 pub struct RevocationMessage {
     action: u8,
     /// The validator pubkey that is revoking a delegatee's power.
@@ -126,9 +184,132 @@ impl SignableBLS for RevocationMessage {
     }
 }
 
+/// Verify a BLS signature over the given digest with the commit-boost domain of the given chain.
+/// Malformed public keys and signatures are considered invalid.
+fn verify_commit_boost_signature(
+    chain: &ChainConfig,
+    pubkey: &BlsPublicKey,
+    digest: [u8; 32],
+    signature: &BlsSignature,
+) -> bool {
+    let Ok(signing_root) = compute_signing_root(&digest, chain.commit_boost_domain()) else {
+        return false;
+    };
+    let Ok(pk) = blst::min_pk::PublicKey::from_bytes(pubkey.as_ref()) else {
+        return false;
+    };
+    let Ok(sig) = blst::min_pk::Signature::from_bytes(signature.as_ref()) else {
+        return false;
+    };
+
+    let res = sig.verify(true, signing_root.as_ref(), BLS_DST_PREFIX, &[], &pk, true);
+    res == BLST_ERROR::BLST_SUCCESS
+}
+
+/// The outcome of checking a single signed delegation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DelegationStatus {
+    /// The signature is valid and the delegatee is available in the signer.
+    Valid,
+    /// The signature doesn't match the validator public key and the commit-boost domain.
+    InvalidSignature,
+    /// The delegatee public key is not one of the available signer public keys.
+    UnavailableDelegatee,
+}
+
+/// A per-validator report of the checks on a list of signed delegations.
+#[derive(Debug, Default)]
+pub struct DelegationsReport {
+    /// The validator public keys, in the order they first appear in the delegations.
+    validators: Vec<BlsPublicKey>,
+    /// The (validator, delegatee, status) result of each delegation.
+    checks: Vec<(BlsPublicKey, BlsPublicKey, DelegationStatus)>,
+}
+
+impl DelegationsReport {
+    /// Check the signature of each delegation against the commit-boost domain of the given
+    /// chain, and that each delegatee is among the available signer public keys.
+    pub fn new(
+        chain: &ChainConfig,
+        delegations: &[SignedDelegation],
+        available_pubkeys: &HashSet<BlsPublicKey>,
+    ) -> Self {
+        let mut report = Self::default();
+
+        for delegation in delegations {
+            let status = if !delegation.verify(chain) {
+                DelegationStatus::InvalidSignature
+            } else if !available_pubkeys.contains(&delegation.delegatee_pubkey) {
+                DelegationStatus::UnavailableDelegatee
+            } else {
+                DelegationStatus::Valid
+            };
+
+            if !report.validators.contains(&delegation.validator_pubkey) {
+                report.validators.push(delegation.validator_pubkey.clone());
+            }
+            report.checks.push((
+                delegation.validator_pubkey.clone(),
+                delegation.delegatee_pubkey.clone(),
+                status,
+            ));
+        }
+
+        report
+    }
+
+    /// Returns true if all the delegations are valid.
+    pub fn is_valid(&self) -> bool {
+        self.checks.iter().all(|(_, _, status)| *status == DelegationStatus::Valid)
+    }
+
+    /// Log the report, one line per validator and one line per invalid delegation.
+    pub fn log(&self) {
+        for validator in &self.validators {
+            let checks = self.checks.iter().filter(|(v, _, _)| v == validator);
+            let (total, valid) = checks.clone().fold((0, 0), |(total, valid), (_, _, status)| {
+                (total + 1, valid + usize::from(*status == DelegationStatus::Valid))
+            });
+
+            info!(?validator, total, valid, "Delegations report");
+            for (_, delegatee, status) in checks.filter(|(_, _, s)| *s != DelegationStatus::Valid) {
+                error!(?validator, ?delegatee, ?status, "Invalid delegation");
+            }
+        }
+    }
+
+    /// Returns an error describing all the invalid delegations, if any.
+    pub fn into_result(self) -> eyre::Result<()> {
+        let invalid = self
+            .checks
+            .iter()
+            .filter_map(|(validator, delegatee, status)| match status {
+                DelegationStatus::Valid => None,
+                DelegationStatus::InvalidSignature => Some(format!(
+                    "invalid signature for delegation {validator:?} -> {delegatee:?}"
+                )),
+                DelegationStatus::UnavailableDelegatee => Some(format!(
+                    "delegatee {delegatee:?} of validator {validator:?} is not available in the signer"
+                )),
+            })
+            .collect::<Vec<_>>();
+
+        if !invalid.is_empty() {
+            bail!("Found {} invalid delegations:\n- {}", invalid.len(), invalid.join("\n- "));
+        }
+
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use std::path::PathBuf;
+    use std::{collections::HashSet, path::PathBuf};
+
+    use ethereum_consensus::crypto::Signature as BlsSignature;
+
+    use super::{DelegationMessage, DelegationStatus, DelegationsReport, SignedDelegation};
+    use crate::{config::ChainConfig, crypto::SignableBLS, signer::local::LocalSigner};
 
     #[test]
     fn test_read_signed_delegations_from_file() {
@@ -144,4 +325,47 @@ mod tests {
             "0x83b85769a8f2a1a6bd3a609e51b460f6fb897daff1157991479421493926faeffa6670152524403929a8a7e551d345f3"
         );
     }
+
+    #[test]
+    fn test_delegations_report() {
+        let chain = ChainConfig::mainnet();
+        let validator = LocalSigner::random();
+        let delegatee = LocalSigner::random().pubkey();
+        let unavailable = LocalSigner::random().pubkey();
+
+        let sign = |delegatee: &ethereum_consensus::crypto::PublicKey| {
+            let message = DelegationMessage::new(validator.pubkey(), delegatee.clone());
+            let signature = validator.sign_commit_boost_root(message.digest()).unwrap();
+            SignedDelegation {
+                message,
+                signature: BlsSignature::try_from(signature.as_ref()).unwrap(),
+            }
+        };
+
+        let valid = sign(&delegatee);
+        assert!(valid.verify(&chain));
+
+        let mut forged = sign(&delegatee);
+        forged.message.delegatee_pubkey = unavailable.clone();
+        assert!(!forged.verify(&chain));
+
+        let available = HashSet::from([delegatee]);
+
+        let report = DelegationsReport::new(&chain, &[valid.clone()], &available);
+        assert!(report.is_valid());
+        assert!(report.into_result().is_ok());
+
+        let report =
+            DelegationsReport::new(&chain, &[valid, forged, sign(&unavailable)], &available);
+        assert!(!report.is_valid());
+        assert_eq!(
+            report.checks.iter().map(|(_, _, s)| *s).collect::<Vec<_>>(),
+            vec![
+                DelegationStatus::Valid,
+                DelegationStatus::InvalidSignature,
+                DelegationStatus::UnavailableDelegatee
+            ]
+        );
+        assert!(report.into_result().is_err());
+    }
 }
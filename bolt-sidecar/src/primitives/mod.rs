@@ -1,9 +1,12 @@
 use alloy::primitives::U256;
 use ethereum_consensus::{
-    crypto::KzgCommitment,
+    crypto::{KzgCommitment, KzgProof},
     deneb::{
         self,
-        mainnet::{BlobsBundle, MAX_BLOB_COMMITMENTS_PER_BLOCK},
+        mainnet::{
+            Blob, BlobsBundle, ExecutionPayload as DenebExecutionPayload, SignedBeaconBlock,
+            MAX_BLOB_COMMITMENTS_PER_BLOCK,
+        },
         presets::mainnet::ExecutionPayloadHeader,
         Hash32,
     },
@@ -28,10 +31,15 @@ pub use constraint::{BatchedSignedConstraints, ConstraintsMessage, SignedConstra
 /// Delegation and revocation signed message types and utilities.
 pub mod delegation;
 pub use delegation::{
-    read_signed_delegations_from_file, DelegationMessage, RevocationMessage, SignedDelegation,
-    SignedRevocation,
+    read_signed_delegations_from_file, read_signed_revocations_from_file,
+    write_signed_revocations_to_file, DelegationMessage, DelegationsReport, RevocationMessage,
+    SignedDelegation, SignedRevocation,
 };
 
+/// Inclusion proof types and verification for bids received via the constraints API.
+pub mod proofs;
+pub use proofs::{InclusionProofs, SignedBuilderBidWithInclusionProofs};
+
 /// Transaction types and extension utilities.
 pub mod transaction;
 pub use transaction::{deserialize_txs, serialize_txs, FullTransaction, TransactionExt};
@@ -174,6 +182,72 @@ impl GetPayloadResponse {
     }
 }
 
+/// SSZ-encodable blobs bundle, as defined in the builder specs.
+#[derive(Debug, Default, Clone, SimpleSerialize)]
+#[allow(missing_docs)]
+pub struct SszBlobsBundle {
+    pub commitments: List<KzgCommitment, MAX_BLOB_COMMITMENTS_PER_BLOCK>,
+    pub proofs: List<KzgProof, MAX_BLOB_COMMITMENTS_PER_BLOCK>,
+    pub blobs: List<Blob, MAX_BLOB_COMMITMENTS_PER_BLOCK>,
+}
+
+/// SSZ-encodable Deneb payload with its blobs bundle, returned by `submitBlindedBlock`
+/// when the `application/octet-stream` content type is negotiated.
+#[derive(Debug, Default, Clone, SimpleSerialize)]
+#[allow(missing_docs)]
+pub struct ExecutionPayloadAndBlobsBundle {
+    pub execution_payload: DenebExecutionPayload,
+    pub blobs_bundle: SszBlobsBundle,
+}
+
+impl GetPayloadResponse {
+    /// Converts the response into its SSZ-encodable Deneb representation.
+    /// Returns `None` if the payload is not a Deneb payload.
+    pub fn to_ssz_deneb(&self) -> Option<ExecutionPayloadAndBlobsBundle> {
+        let Self::Deneb(payload_and_blobs) = self else {
+            return None;
+        };
+
+        let ExecutionPayload::Deneb(execution_payload) = &payload_and_blobs.execution_payload
+        else {
+            return None;
+        };
+
+        let bundle = &payload_and_blobs.blobs_bundle;
+        Some(ExecutionPayloadAndBlobsBundle {
+            execution_payload: execution_payload.clone(),
+            blobs_bundle: SszBlobsBundle {
+                commitments: List::try_from(bundle.commitments.clone()).ok()?,
+                proofs: List::try_from(bundle.proofs.clone()).ok()?,
+                blobs: List::try_from(bundle.blobs.clone()).ok()?,
+            },
+        })
+    }
+}
+
+/// A full signed Deneb block along with its blobs, as published to the beacon node.
+/// Reference: <https://ethereum.github.io/beacon-APIs/#/Beacon/publishBlockV2>
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[allow(missing_docs)]
+pub struct SignedBlockContents {
+    pub signed_block: SignedBeaconBlock,
+    pub kzg_proofs: Vec<KzgProof>,
+    pub blobs: Vec<Blob>,
+}
+
+impl From<ExecutionPayloadAndBlobsBundle> for GetPayloadResponse {
+    fn from(value: ExecutionPayloadAndBlobsBundle) -> Self {
+        Self::Deneb(PayloadAndBlobs {
+            execution_payload: ExecutionPayload::Deneb(value.execution_payload),
+            blobs_bundle: BlobsBundle {
+                commitments: value.blobs_bundle.commitments.to_vec(),
+                proofs: value.blobs_bundle.proofs.to_vec(),
+                blobs: value.blobs_bundle.blobs.to_vec(),
+            },
+        })
+    }
+}
+
 impl From<PayloadAndBlobs> for GetPayloadResponse {
     fn from(payload_and_blobs: PayloadAndBlobs) -> Self {
         match payload_and_blobs.execution_payload.version() {
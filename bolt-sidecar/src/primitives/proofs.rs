@@ -0,0 +1,201 @@
+use alloy::{
+    eips::eip2718::Encodable2718,
+    primitives::{TxHash, B256},
+};
+use ethereum_consensus::{bellatrix::mainnet::Transaction, ssz::prelude::HashTreeRoot};
+use serde::{Deserialize, Serialize};
+
+use super::{SignedBuilderBid, SignedConstraints};
+
+/// Errors that can occur when computing or verifying inclusion proofs.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum ProofError {
+    #[error("Leaves and indices length mismatch")]
+    LengthMismatch,
+    #[error("Mismatch in provided leaves and leaves to prove")]
+    LeavesMismatch,
+    #[error("Hash not found in constraints: {0:?}")]
+    MissingHash(TxHash),
+    #[error("Proof verification failed")]
+    VerificationFailed,
+    #[error("Failed to compute the hash tree root of a transaction: {0}")]
+    InvalidTransaction(String),
+}
+
+/// Merkle multiproof that a set of constrained transactions is included in the
+/// transactions tree of a bid.
+///
+/// Reference: <https://docs.boltprotocol.xyz/technical-docs/api/builder#get_header_with_proofs>
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InclusionProofs {
+    /// The transaction hashes these inclusion proofs are for. The hash tree roots of
+    /// these transactions are the leaves of the transactions tree.
+    pub transaction_hashes: Vec<TxHash>,
+    /// The generalized indexes of the nodes in the transactions tree.
+    pub generalized_indexes: Vec<usize>,
+    /// The proof hashes for the transactions tree.
+    pub merkle_hashes: Vec<B256>,
+}
+
+impl InclusionProofs {
+    /// Returns the total number of leaves in the tree.
+    pub fn total_leaves(&self) -> usize {
+        self.transaction_hashes.len()
+    }
+}
+
+/// Signed builder bid along with the inclusion proofs of the constraints for its slot,
+/// as returned by the `header_with_proofs` endpoint.
+///
+/// Reference: <https://docs.boltprotocol.xyz/technical-docs/api/builder#get_header_with_proofs>
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct SignedBuilderBidWithInclusionProofs {
+    /// The signed builder bid.
+    #[serde(flatten)]
+    pub bid: SignedBuilderBid,
+    /// The inclusion proofs of the constrained transactions.
+    #[serde(default)]
+    pub proofs: InclusionProofs,
+}
+
+/// Returns the proof data of the transactions in the given constraints: a list of transaction
+/// hashes and corresponding hash tree roots, which are the leaves of the transactions tree.
+///
+/// For blob transactions, the hash tree root is computed without the blob sidecar.
+pub fn constraints_proof_data(
+    constraints: &SignedConstraints,
+) -> Result<Vec<(TxHash, B256)>, ProofError> {
+    constraints
+        .message
+        .transactions
+        .iter()
+        .map(|tx| {
+            let raw_tx = tx.clone().into_signed().encoded_2718();
+            Ok((*tx.hash(), transaction_leaf(&raw_tx)?))
+        })
+        .collect()
+}
+
+/// Returns the leaf of a transaction in the transactions tree of a block: the hash tree root
+/// of its EIP-2718 encoding.
+fn transaction_leaf(raw_tx: &[u8]) -> Result<B256, ProofError> {
+    let tx = Transaction::try_from(raw_tx)
+        .map_err(|_| ProofError::InvalidTransaction(format!("too large: {}", raw_tx.len())))?;
+    let root =
+        tx.hash_tree_root().map_err(|err| ProofError::InvalidTransaction(format!("{err:?}")))?;
+
+    Ok(B256::from(root.0))
+}
+
+/// Verifies the provided multiproofs against the proof data of the constraints
+/// and the transactions root of the bid.
+pub fn verify_multiproofs(
+    proof_data: &[(TxHash, B256)],
+    proofs: &InclusionProofs,
+    root: B256,
+) -> Result<(), ProofError> {
+    // Check if the length of the leaves and indices match
+    if proofs.transaction_hashes.len() != proofs.generalized_indexes.len() {
+        return Err(ProofError::LengthMismatch);
+    }
+
+    // Check if the total leaves matches the proofs provided
+    if proof_data.len() != proofs.total_leaves() {
+        return Err(ProofError::LeavesMismatch);
+    }
+
+    // Get the leaves from the constraints by matching the proven hashes, in the same
+    // order as the generalized indexes.
+    let mut leaves = Vec::with_capacity(proofs.total_leaves());
+    for hash in &proofs.transaction_hashes {
+        let Some((_, leaf)) = proof_data.iter().find(|(saved_hash, _)| saved_hash == hash) else {
+            return Err(ProofError::MissingHash(*hash));
+        };
+
+        leaves.push(*leaf);
+    }
+
+    ssz_rs::multiproofs::verify_merkle_multiproof(
+        &leaves,
+        &proofs.merkle_hashes,
+        &proofs.generalized_indexes,
+        root,
+    )
+    .map_err(|_| ProofError::VerificationFailed)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::b256;
+
+    use super::*;
+    use crate::{primitives::ConstraintsMessage, test_util::random_constraints};
+
+    #[test]
+    fn test_verify_merkle_multiproof_blob() {
+        // Proof generated from bolt-builder code for a blob transaction.
+        let root = b256!("085f9483581f0302fd8a5a7b03e5aa9f110d4548bd679bedc04764dc9405a700");
+
+        let merkle_hashes = vec![
+            b256!("8c0bd07dcc7050700654b730d245db145c92ad92ef6ac81e2361533c66ee9688"),
+            b256!("ee38e5ba99fa98c9c8963c7e9c59e3128f285454f27daf9549d19c4bb98039fd"),
+            b256!("af0302f3b715a72dab24a7590f01dc5717c642a39fc5a92bc09518b24e05d56c"),
+            b256!("c78009fdf07fc56a11f122370658a353aaa542ed63e44c4bc15ff4cd105ab33c"),
+            b256!("536d98837f2dd165a55d5eeae91485954472d56f246df256bf3cae19352a123c"),
+            b256!("9efde052aa15429fae05bad4d0b1d7c64da64d03d7a1854a588c2cb8430c0d30"),
+            b256!("d88ddfeed400a8755596b21942c1497e114c302e6118290f91e6772976041fa1"),
+            b256!("87eb0ddba57e35f6d286673802a4af5975e22506c7cf4c64bb6be5ee11527f2c"),
+            b256!("26846476fd5fc54a5d43385167c95144f2643f533cc85bb9d16b782f8d7db193"),
+            b256!("506d86582d252405b840018792cad2bf1259f1ef5aa5f887e13cb2f0094f51e1"),
+            b256!("ffff0ad7e659772f9534c195c815efc4014ef1e1daed4404c06385d11192e92b"),
+            b256!("6cf04127db05441cd833107a52be852868890e4317e6a02ab47683aa75964220"),
+            b256!("b7d05f875f140027ef5118a2247bbb84ce8f2f0f1123623085daf7960c329f5f"),
+            b256!("df6af5f5bbdb6be9ef8aa618e4bf8073960867171e29676f8b284dea6a08a85e"),
+            b256!("b58d900f5e182e3c50ef74969ea16c7726c549757cc23523c369587da7293784"),
+            b256!("d49a7502ffcfb0340b1d7885688500ca308161a7f96b62df9d083b71fcc8f2bb"),
+            b256!("8fe6b1689256c0d385f42f5bbe2027a22c1996e110ba97c171d3e5948de92beb"),
+            b256!("8d0d63c39ebade8509e0ae3c9c3876fb5fa112be18f905ecacfecb92057603ab"),
+            b256!("95eec8b2e541cad4e91de38385f2e046619f54496c2382cb6cacd5b98c26f5a4"),
+            b256!("f893e908917775b62bff23294dbbe3a1cd8e6cc1c35b4801887b646a6f81f17f"),
+            b256!("0600000000000000000000000000000000000000000000000000000000000000"),
+        ];
+
+        let tx_hash = b256!("00724d63ef8a791110a66d6e7433d097637aec698f5cf81c44446e1ea5c45a1a");
+        let leaf = b256!("b4bb948e1cfc750a20fa08d6661d3f0717ca367eec45d81fcf92e8f1ae1fe688");
+
+        let proofs = InclusionProofs {
+            transaction_hashes: vec![tx_hash],
+            generalized_indexes: vec![2097152],
+            merkle_hashes,
+        };
+
+        assert!(verify_multiproofs(&[(tx_hash, leaf)], &proofs, root).is_ok());
+
+        // A proof for a different leaf must fail
+        let wrong_leaf = B256::repeat_byte(0x42);
+        assert!(matches!(
+            verify_multiproofs(&[(tx_hash, wrong_leaf)], &proofs, root),
+            Err(ProofError::VerificationFailed)
+        ));
+
+        // Missing constraints must fail
+        assert!(matches!(verify_multiproofs(&[], &proofs, root), Err(ProofError::LeavesMismatch)));
+    }
+
+    #[test]
+    fn test_constraints_proof_data() {
+        let transactions = random_constraints(3);
+        let hashes = transactions.iter().map(|tx| *tx.hash()).collect::<Vec<_>>();
+
+        let message =
+            ConstraintsMessage { pubkey: Default::default(), slot: 10, top: false, transactions };
+        let constraints = SignedConstraints { message, signature: Default::default() };
+
+        let proof_data = constraints_proof_data(&constraints).unwrap();
+        assert_eq!(proof_data.iter().map(|(hash, _)| *hash).collect::<Vec<_>>(), hashes);
+
+        // Each transaction is a different leaf of the transactions tree
+        assert_ne!(proof_data[0].1, proof_data[1].1);
+    }
+}
@@ -1,7 +1,7 @@
 use std::{str::FromStr, sync::Arc};
 
 use alloy::{
-    primitives::{Address, FixedBytes},
+    primitives::{keccak256, Address, FixedBytes},
     rpc::types::beacon::BlsSignature,
     signers::Signature,
 };
@@ -22,7 +22,10 @@ use tracing::{debug, error, info};
 
 use crate::{
     common::secrets::JwtSecretConfig,
-    crypto::{bls::BLS_DST_PREFIX, ecdsa::SignerECDSA},
+    crypto::{
+        bls::BLS_DST_PREFIX,
+        ecdsa::{SignerECDSA, SignerHashECDSA},
+    },
 };
 
 use super::SignerResult;
@@ -160,6 +163,12 @@ impl CommitBoostSigner {
 
         Ok(sig)
     }
+
+    /// Check that the Commit-Boost signer module is reachable, by listing its public keys.
+    pub async fn check_reachable(&self) -> SignerResult<()> {
+        self.signer_client.get_pubkeys().await.map_err(CommitBoostError::SignerClientError)?;
+        Ok(())
+    }
 }
 
 #[async_trait::async_trait]
@@ -168,6 +177,17 @@ impl SignerECDSA for CommitBoostSigner {
         Address::try_from(self.get_proxy_ecdsa_pubkey().as_ref()).expect("valid address")
     }
 
+    async fn sign_keccak256(&self, data: &[u8]) -> eyre::Result<Signature> {
+        self.sign_hash(&keccak256(data)).await
+    }
+
+    async fn check_reachable(&self) -> eyre::Result<()> {
+        Ok(CommitBoostSigner::check_reachable(self).await?)
+    }
+}
+
+#[async_trait::async_trait]
+impl SignerHashECDSA for CommitBoostSigner {
     async fn sign_hash(&self, hash: &[u8; 32]) -> eyre::Result<Signature> {
         let request = SignProxyRequest::builder(
             *self.proxy_ecdsa.read().first().expect("proxy ecdsa key loaded"),
@@ -0,0 +1,253 @@
+use std::time::Duration;
+
+use alloy::primitives::B256;
+use blst::BLST_ERROR;
+use ethereum_consensus::crypto::PublicKey as BlsPublicKey;
+use futures::{stream::FuturesUnordered, StreamExt};
+use tracing::{debug, warn};
+
+use crate::{
+    builder::signature::compute_signing_root,
+    crypto::bls::{BLSSig, BLS_DST_PREFIX},
+    pb::eth2_signer_api::DistributedAccount,
+};
+
+use super::{
+    recover_signature::recover_signature_from_shards, Dirk, DirkError, DirkTlsCredentials,
+};
+
+/// A Dirk distributed account, whose key is split in shards across a quorum of participants.
+///
+/// Connections to the participants are established and their accounts unlocked once, so that
+/// signing at commitment time only costs a round-trip to the fastest `threshold` participants.
+#[derive(Debug, Clone)]
+pub struct DistributedDirkAccount {
+    name: String,
+    participants: Vec<(u64, Dirk)>,
+    threshold: usize,
+    composite_public_key: BlsPublicKey,
+}
+
+impl DistributedDirkAccount {
+    /// Connect to the participants of the given distributed account and unlock their shards
+    /// with the provided passphrases. Participants that are unreachable or that cannot be
+    /// unlocked are skipped, as long as enough of them remain to reach the signing threshold.
+    pub async fn connect(
+        acc: DistributedAccount,
+        credentials: &DirkTlsCredentials,
+        passphrases: &[String],
+    ) -> Result<Self, DirkError> {
+        let composite_public_key = BlsPublicKey::try_from(acc.composite_public_key.as_ref())
+            .map_err(|_| DirkError::InvalidPubkey(acc.name.clone()))?;
+        let threshold = acc.signing_threshold as usize;
+
+        let mut participants = Vec::with_capacity(acc.participants.len());
+        for participant in acc.participants {
+            // Note: the Dirk endpoint address must be parsed as "https://name:port".
+            // Sauce: https://github.com/wealdtech/go-eth2-wallet-dirk/blob/263190301ef3352fbda43f91363145f175a12cf6/grpc.go#L1706
+            let url = format!("https://{}:{}", participant.name, participant.port);
+
+            let mut conn = match Dirk::connect(&url, credentials).await {
+                Ok(conn) => conn,
+                Err(err) => {
+                    warn!(?err, %url, account = %acc.name, "Failed to connect to Dirk participant");
+                    continue;
+                }
+            };
+
+            if let Err(err) =
+                conn.try_unlock_account_with_passphrases(acc.name.clone(), passphrases).await
+            {
+                warn!(?err, %url, account = %acc.name, "Failed to unlock Dirk participant shard");
+                continue;
+            }
+
+            participants.push((participant.id, conn));
+        }
+
+        if participants.len() < threshold {
+            return Err(DirkError::InsufficientShards { got: participants.len(), threshold });
+        }
+
+        Ok(Self { name: acc.name, participants, threshold, composite_public_key })
+    }
+
+    /// Obtain the composite public key of the distributed account.
+    pub fn composite_public_key(&self) -> &BlsPublicKey {
+        &self.composite_public_key
+    }
+
+    /// Checks that enough participants are reachable to reach the signing threshold, by listing
+    /// the account on each of them concurrently within the given timeout.
+    pub async fn check_reachable(&self, timeout: Duration) -> Result<(), DirkError> {
+        let reachable = self
+            .participants
+            .iter()
+            .map(|(id, conn)| {
+                let mut conn = conn.clone();
+                let name = self.name.clone();
+                async move {
+                    let res = tokio::time::timeout(timeout, conn.list_accounts(name)).await;
+                    let reachable = matches!(res, Ok(Ok(_)));
+                    if !reachable {
+                        warn!(id, account = %self.name, "Dirk participant is unreachable");
+                    }
+                    reachable
+                }
+            })
+            .collect::<FuturesUnordered<_>>()
+            .filter(|reachable| futures::future::ready(*reachable))
+            .count()
+            .await;
+
+        if reachable < self.threshold {
+            return Err(DirkError::InsufficientShards { got: reachable, threshold: self.threshold });
+        }
+
+        Ok(())
+    }
+
+    /// Obtain a threshold signature from the quorum of participants in the distributed account.
+    ///
+    /// All participants are asked to sign concurrently, and the signature is recovered as soon
+    /// as the first `threshold` shards are received. The recovered signature is verified against
+    /// the composite public key, so that a faulty shard is never used as a valid signature.
+    pub async fn threshold_sign(&self, root: B256, domain: B256) -> Result<BLSSig, DirkError> {
+        let mut requests = self
+            .participants
+            .iter()
+            .map(|(id, conn)| {
+                let mut conn = conn.clone();
+                let name = self.name.clone();
+                async move { (*id, conn.request_signature(name, root, domain).await) }
+            })
+            .collect::<FuturesUnordered<_>>();
+
+        // Short-circuit when we have enough signatures to reach the threshold
+        let (mut sigs, mut ids) = (Vec::with_capacity(self.threshold), Vec::new());
+        while let Some((id, result)) = requests.next().await {
+            match result {
+                Ok(signature) => {
+                    sigs.push(signature);
+                    ids.push(id);
+                    if sigs.len() >= self.threshold {
+                        break;
+                    }
+                }
+                Err(err) => {
+                    warn!(?err, id, account = %self.name, "Dirk participant failed to sign")
+                }
+            }
+        }
+
+        debug!(shards = sigs.len(), account = %self.name, "Received Dirk signature shards");
+
+        if sigs.len() < self.threshold {
+            return Err(DirkError::InsufficientShards {
+                got: sigs.len(),
+                threshold: self.threshold,
+            });
+        }
+
+        let signature =
+            recover_signature_from_shards(&sigs, &ids).ok_or(DirkError::ShardRecovery)?;
+
+        if !verify_signature(&self.composite_public_key, root, domain, &signature) {
+            return Err(DirkError::InvalidRecoveredSignature(self.name.clone()));
+        }
+
+        Ok(signature)
+    }
+}
+
+/// Verify a BLS signature over the signing root of the given object root and domain.
+/// Malformed public keys and signatures are considered invalid.
+fn verify_signature(pubkey: &BlsPublicKey, root: B256, domain: B256, signature: &BLSSig) -> bool {
+    let signing_root = compute_signing_root(root.0, domain.0);
+    let Ok(pk) = blst::min_pk::PublicKey::from_bytes(pubkey.as_ref()) else {
+        return false;
+    };
+    let Ok(sig) = blst::min_pk::Signature::from_bytes(signature.as_ref()) else {
+        return false;
+    };
+
+    let res = sig.verify(true, signing_root.as_ref(), BLS_DST_PREFIX, &[], &pk, true);
+    res == BLST_ERROR::BLST_SUCCESS
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::B256;
+    use bls12_381::Scalar;
+    use ethereum_consensus::crypto::PublicKey as BlsPublicKey;
+
+    use super::{verify_signature, DistributedDirkAccount};
+    use crate::{
+        builder::signature::compute_signing_root,
+        crypto::bls::{BLSSig, BLS_DST_PREFIX},
+        signer::dirk::{recover_signature::recover_signature_from_shards, DirkError},
+    };
+
+    /// Convert a scalar to a BLS secret key. Scalars are little-endian, secret keys big-endian.
+    fn secret_key(scalar: Scalar) -> blst::min_pk::SecretKey {
+        let mut bytes = scalar.to_bytes();
+        bytes.reverse();
+        blst::min_pk::SecretKey::from_bytes(&bytes).expect("valid secret key")
+    }
+
+    fn sign(key: &blst::min_pk::SecretKey, root: B256, domain: B256) -> BLSSig {
+        let signing_root = compute_signing_root(root.0, domain.0);
+        BLSSig::from_slice(&key.sign(&signing_root, BLS_DST_PREFIX, &[]).to_bytes())
+    }
+
+    /// Split a secret key in shards with a 2-of-3 threshold, as Dirk does for distributed
+    /// accounts: shard `i` is the evaluation of `f(x) = secret + coeff * x` at `x = i`.
+    /// Returns the composite public key and the shards with their identifiers.
+    fn threshold_keys() -> (BlsPublicKey, Vec<(u64, blst::min_pk::SecretKey)>) {
+        let (secret, coeff) = (Scalar::from(0xb017u64), Scalar::from(0xd1e4u64));
+
+        let composite = secret_key(secret).sk_to_pk().to_bytes();
+        let composite = BlsPublicKey::try_from(composite.as_ref()).unwrap();
+        let shards = (1..=3u64).map(|id| (id, secret_key(secret + coeff * Scalar::from(id))));
+
+        (composite, shards.collect())
+    }
+
+    #[test]
+    fn test_verify_recovered_threshold_signature() {
+        let (composite, shards) = threshold_keys();
+        let (root, domain) = (B256::repeat_byte(0x42), B256::repeat_byte(0x01));
+
+        // Any 2 of the 3 shards recover a signature valid for the composite public key
+        for pair in [[0, 1], [0, 2], [1, 2]] {
+            let sigs = pair.map(|i| sign(&shards[i].1, root, domain));
+            let ids = pair.map(|i| shards[i].0);
+
+            let recovered = recover_signature_from_shards(&sigs, &ids).unwrap();
+            assert!(verify_signature(&composite, root, domain, &recovered));
+        }
+
+        // Shards attributed to the wrong participants recover an invalid signature
+        let sigs = [sign(&shards[0].1, root, domain), sign(&shards[1].1, root, domain)];
+        let recovered = recover_signature_from_shards(&sigs, &[2, 1]).unwrap();
+        assert!(!verify_signature(&composite, root, domain, &recovered));
+
+        // The recovered signature doesn't verify over a different domain
+        let recovered = recover_signature_from_shards(&sigs, &[1, 2]).unwrap();
+        assert!(!verify_signature(&composite, root, B256::ZERO, &recovered));
+    }
+
+    #[tokio::test]
+    async fn test_threshold_sign_insufficient_shards() {
+        let (composite, _) = threshold_keys();
+        let account = DistributedDirkAccount {
+            name: "wallet/distributed".to_string(),
+            participants: Vec::new(),
+            threshold: 2,
+            composite_public_key: composite,
+        };
+
+        let err = account.threshold_sign(B256::ZERO, B256::ZERO).await.unwrap_err();
+        assert!(matches!(err, DirkError::InsufficientShards { got: 0, threshold: 2 }));
+    }
+}
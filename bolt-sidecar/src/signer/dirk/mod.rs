@@ -0,0 +1,429 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt, fs,
+    path::PathBuf,
+    time::Duration,
+};
+
+use alloy::primitives::B256;
+use ethereum_consensus::crypto::PublicKey as BlsPublicKey;
+use reqwest::Url;
+use tonic::transport::{Certificate, Channel, ClientTlsConfig, Identity, Uri};
+use tracing::{debug, info};
+
+use crate::{
+    config::ChainConfig,
+    crypto::bls::BLSSig,
+    pb::eth2_signer_api::{
+        AccountManagerClient, ListAccountsRequest, ListAccountsResponse, ListerClient,
+        ResponseState, SignRequest, SignRequestId, SignerClient, UnlockAccountRequest,
+    },
+};
+
+use super::SignerResult;
+
+/// Utility to recover a threshold signature from partial signature shards.
+mod recover_signature;
+
+/// Module to work with Dirk distributed accounts.
+pub mod distributed;
+use distributed::DistributedDirkAccount;
+
+/// The maximum time to wait for a connection to a Dirk server to be established.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Error in the Dirk signer.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum DirkError {
+    #[error("failed to read TLS credentials: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("invalid Dirk URL: {0}")]
+    InvalidUrl(String),
+    #[error("failed to connect to Dirk: {0}")]
+    Transport(#[from] tonic::transport::Error),
+    #[error("Dirk request failed: {0}")]
+    Status(#[from] tonic::Status),
+    #[error("Dirk request was not successful: {0}")]
+    Unsuccessful(String),
+    #[error("failed to unlock account {0} with the provided passphrases")]
+    Locked(String),
+    #[error("Dirk returned an invalid public key for account {0}")]
+    InvalidPubkey(String),
+    #[error("Dirk returned an invalid signature")]
+    InvalidSignature,
+    #[error("insufficient signature shards: got {got}, expected {threshold}")]
+    InsufficientShards { got: usize, threshold: usize },
+    #[error("failed to recover the combined signature from the signature shards")]
+    ShardRecovery,
+    #[error("recovered signature is invalid for the composite public key of account {0}")]
+    InvalidRecoveredSignature(String),
+    #[error("public key {0:?} is not available in Dirk")]
+    UnknownPubkey(BlsPublicKey),
+    #[error("signing request timed out after {0:?}")]
+    Timeout(Duration),
+}
+
+/// TLS credentials for connecting to a remote Dirk server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirkTlsCredentials {
+    /// Path to the client certificate file. (.crt)
+    pub client_cert_path: PathBuf,
+    /// Path to the client key file. (.key)
+    pub client_key_path: PathBuf,
+    /// Path to the CA certificate file. (.crt)
+    pub ca_cert_path: Option<PathBuf>,
+}
+
+/// A Dirk remote signer client.
+///
+/// Available services:
+/// - `Lister`: List accounts in the keystore.
+/// - `Signer`: Request a signature from the remote signer.
+/// - `AccountManager`: Manage accounts in the keystore (lock and unlock accounts).
+///
+/// Reference: https://github.com/attestantio/dirk
+#[derive(Debug, Clone)]
+pub struct Dirk {
+    lister: ListerClient<Channel>,
+    signer: SignerClient<Channel>,
+    account_mng: AccountManagerClient<Channel>,
+}
+
+impl Dirk {
+    /// Connect to the Dirk server with the given address and TLS credentials.
+    pub async fn connect(addr: &str, credentials: &DirkTlsCredentials) -> Result<Self, DirkError> {
+        let addr = addr.parse::<Uri>().map_err(|_| DirkError::InvalidUrl(addr.to_string()))?;
+        let tls_config = compose_credentials(credentials)?;
+        let conn = Channel::builder(addr)
+            .tls_config(tls_config)?
+            .connect_timeout(CONNECT_TIMEOUT)
+            .connect()
+            .await?;
+
+        let lister = ListerClient::new(conn.clone());
+        let signer = SignerClient::new(conn.clone());
+        let account_mng = AccountManagerClient::new(conn);
+
+        Ok(Self { lister, signer, account_mng })
+    }
+
+    /// List all accounts in the keystore under the given wallet path.
+    pub async fn list_accounts(
+        &mut self,
+        wallet_path: String,
+    ) -> Result<ListAccountsResponse, DirkError> {
+        // Request all accounts in the given path. Only one path at a time
+        // as done in https://github.com/wealdtech/go-eth2-wallet-dirk/blob/182f99b22b64d01e0d4ae67bf47bb055763465d7/grpc.go#L121
+        let req = ListAccountsRequest { paths: vec![wallet_path] };
+        let res = self.lister.list_accounts(req).await?.into_inner();
+
+        if !matches!(res.state(), ResponseState::Succeeded) {
+            return Err(DirkError::Unsuccessful(format!("list accounts: {:?}", res.state())));
+        }
+
+        debug!(
+            accounts = %res.accounts.len(),
+            distributed_accounts = %res.distributed_accounts.len(),
+            "List accounts request succeeded"
+        );
+
+        Ok(res)
+    }
+
+    /// Try to unlock an account using the provided passphrases, in order.
+    pub async fn try_unlock_account_with_passphrases(
+        &mut self,
+        account_name: String,
+        passphrases: &[String],
+    ) -> Result<(), DirkError> {
+        for passphrase in passphrases {
+            if self.unlock_account(account_name.clone(), passphrase.clone()).await? {
+                return Ok(());
+            }
+        }
+
+        Err(DirkError::Locked(account_name))
+    }
+
+    /// Unlock an account in the keystore with the given passphrase.
+    /// Returns `false` if the passphrase was denied.
+    pub async fn unlock_account(
+        &mut self,
+        account_name: String,
+        passphrase: String,
+    ) -> Result<bool, DirkError> {
+        let pf_bytes = passphrase.as_bytes().to_vec();
+        let req = UnlockAccountRequest { account: account_name.clone(), passphrase: pf_bytes };
+        let res = self.account_mng.unlock(req).await?.into_inner();
+
+        match res.state() {
+            ResponseState::Succeeded => {
+                debug!("Unlock request succeeded for account {}", account_name);
+                Ok(true)
+            }
+            ResponseState::Denied => {
+                debug!("Unlock request denied for account {}", account_name);
+                Ok(false)
+            }
+            state => Err(DirkError::Unsuccessful(format!("unlock account: {state:?}"))),
+        }
+    }
+
+    /// Request a signature over an object root with the given domain from the remote signer.
+    /// Dirk computes the signing root internally.
+    pub async fn request_signature(
+        &mut self,
+        account_name: String,
+        root: B256,
+        domain: B256,
+    ) -> Result<BLSSig, DirkError> {
+        let req = SignRequest {
+            data: root.to_vec(),
+            domain: domain.to_vec(),
+            id: Some(SignRequestId::Account(account_name)),
+        };
+
+        let res = self.signer.sign(req).await?.into_inner();
+
+        if !matches!(res.state(), ResponseState::Succeeded) {
+            return Err(DirkError::Unsuccessful(format!("sign: {:?}", res.state())));
+        }
+        if res.signature.len() != 96 {
+            return Err(DirkError::InvalidSignature);
+        }
+
+        Ok(BLSSig::from_slice(&res.signature))
+    }
+}
+
+/// Compose the TLS credentials for Dirk from the given paths.
+fn compose_credentials(creds: &DirkTlsCredentials) -> Result<ClientTlsConfig, DirkError> {
+    let client_cert = fs::read(&creds.client_cert_path)?;
+    let client_key = fs::read(&creds.client_key_path)?;
+
+    // Create client identity (certificate + key)
+    let identity = Identity::from_pem(&client_cert, &client_key);
+
+    // Configure the TLS client
+    let mut tls_config = ClientTlsConfig::new().identity(identity);
+
+    // Add CA certificate if provided
+    if let Some(ca_path) = &creds.ca_cert_path {
+        let ca_cert = fs::read(ca_path)?;
+        tls_config = tls_config.ca_certificate(Certificate::from_pem(&ca_cert));
+    }
+
+    Ok(tls_config)
+}
+
+/// A Dirk account that can be used to sign constraints.
+#[derive(Debug, Clone)]
+enum DirkAccount {
+    /// A regular account, signed with by the connected Dirk server.
+    Regular(String),
+    /// A distributed account, signed with by a quorum of Dirk servers.
+    Distributed(DistributedDirkAccount),
+}
+
+/// A BLS signer backed by a remote Dirk keystore, supporting both regular and distributed
+/// (threshold) accounts.
+///
+/// All accounts are unlocked once at startup and kept unlocked, and every signing request is
+/// bounded by a timeout so that a slow or unreachable Dirk doesn't stall commitment requests.
+#[derive(Clone)]
+pub struct DirkSigner {
+    client: Dirk,
+    /// The wallet path under which the accounts were listed, probed by health checks.
+    wallet_path: String,
+    accounts: HashMap<BlsPublicKey, DirkAccount>,
+    chain: ChainConfig,
+    sign_timeout: Duration,
+}
+
+impl fmt::Debug for DirkSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DirkSigner")
+            .field("accounts", &self.accounts.len())
+            .field("chain", &self.chain.name())
+            .field("sign_timeout", &self.sign_timeout)
+            .finish()
+    }
+}
+
+impl DirkSigner {
+    /// Connect to the Dirk server at the given URL, list the accounts under the given wallet
+    /// path and unlock all of them with the provided passphrases.
+    pub async fn connect(
+        url: Url,
+        credentials: DirkTlsCredentials,
+        wallet_path: String,
+        passphrases: &[String],
+        chain: ChainConfig,
+        sign_timeout: Duration,
+    ) -> SignerResult<Self> {
+        let mut client = Dirk::connect(url.as_str(), &credentials).await?;
+        let response = client.list_accounts(wallet_path.clone()).await?;
+
+        let mut accounts = HashMap::new();
+
+        for account in response.accounts {
+            let pubkey = BlsPublicKey::try_from(account.public_key.as_slice())
+                .map_err(|_| DirkError::InvalidPubkey(account.name.clone()))?;
+
+            client.try_unlock_account_with_passphrases(account.name.clone(), passphrases).await?;
+            accounts.insert(pubkey, DirkAccount::Regular(account.name));
+        }
+
+        for account in response.distributed_accounts {
+            let account =
+                DistributedDirkAccount::connect(account, &credentials, passphrases).await?;
+            let pubkey = account.composite_public_key().clone();
+            accounts.insert(pubkey, DirkAccount::Distributed(account));
+        }
+
+        info!(accounts = accounts.len(), %url, "Connected to Dirk and unlocked accounts");
+
+        Ok(Self { client, wallet_path, accounts, chain, sign_timeout })
+    }
+
+    /// Checks that Dirk is reachable by listing the accounts of the wallet, and that enough
+    /// participants of each distributed account are reachable to reach its signing threshold.
+    ///
+    /// Each probe is bounded by the signing timeout, as a slower Dirk can't sign in time anyway.
+    pub async fn check_reachable(&self) -> SignerResult<()> {
+        let mut client = self.client.clone();
+        let request = client.list_accounts(self.wallet_path.clone());
+        tokio::time::timeout(self.sign_timeout, request)
+            .await
+            .map_err(|_| DirkError::Timeout(self.sign_timeout))??;
+
+        for account in self.accounts.values() {
+            if let DirkAccount::Distributed(account) = account {
+                account.check_reachable(self.sign_timeout).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the public keys available for signing.
+    pub fn pubkeys(&self) -> HashSet<BlsPublicKey> {
+        self.accounts.keys().cloned().collect()
+    }
+
+    /// Sign an object root with the Commit Boost domain, using the given public key.
+    ///
+    /// Fails with [`DirkError::Timeout`] if the signature isn't obtained within the
+    /// configured signing timeout.
+    pub async fn sign_commit_boost_root(
+        &self,
+        root: [u8; 32],
+        pubkey: &BlsPublicKey,
+    ) -> SignerResult<BLSSig> {
+        let account =
+            self.accounts.get(pubkey).ok_or_else(|| DirkError::UnknownPubkey(pubkey.clone()))?;
+
+        let root = B256::from(root);
+        let domain = B256::from(self.chain.commit_boost_domain());
+
+        let request = async {
+            match account {
+                DirkAccount::Regular(name) => {
+                    self.client.clone().request_signature(name.clone(), root, domain).await
+                }
+                DirkAccount::Distributed(account) => account.threshold_sign(root, domain).await,
+            }
+        };
+
+        let signature = tokio::time::timeout(self.sign_timeout, request)
+            .await
+            .map_err(|_| DirkError::Timeout(self.sign_timeout))??;
+
+        Ok(signature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, time::Duration};
+
+    use ethereum_consensus::crypto::PublicKey as BlsPublicKey;
+    use tonic::transport::Channel;
+
+    use super::{Dirk, DirkAccount, DirkError, DirkSigner};
+    use crate::{
+        common::secrets::BlsSecretKeyWrapper,
+        config::ChainConfig,
+        pb::eth2_signer_api::{AccountManagerClient, ListerClient, SignerClient},
+        signer::SignerError,
+    };
+
+    /// Create a Dirk client to the given address that only connects on its first request.
+    fn lazy_client(addr: &str) -> Dirk {
+        let conn = Channel::from_shared(format!("http://{addr}")).unwrap().connect_lazy();
+        Dirk {
+            lister: ListerClient::new(conn.clone()),
+            signer: SignerClient::new(conn.clone()),
+            account_mng: AccountManagerClient::new(conn),
+        }
+    }
+
+    fn random_pubkey() -> BlsPublicKey {
+        let sk = BlsSecretKeyWrapper::random().0;
+        BlsPublicKey::try_from(sk.sk_to_pk().to_bytes().as_slice()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_sign_with_unknown_pubkey() {
+        let signer = DirkSigner {
+            client: lazy_client("127.0.0.1:1"),
+            wallet_path: "wallet".into(),
+            accounts: HashMap::new(),
+            chain: ChainConfig::mainnet(),
+            sign_timeout: Duration::from_secs(1),
+        };
+
+        let pubkey = random_pubkey();
+        let err = signer.sign_commit_boost_root([0; 32], &pubkey).await.unwrap_err();
+        assert!(matches!(err, SignerError::Dirk(DirkError::UnknownPubkey(pk)) if pk == pubkey));
+    }
+
+    #[tokio::test]
+    async fn test_sign_times_out_on_unresponsive_dirk() {
+        // A listener that never accepts connections: the gRPC handshake never completes
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+
+        let pubkey = random_pubkey();
+        let sign_timeout = Duration::from_millis(100);
+        let signer = DirkSigner {
+            client: lazy_client(&addr),
+            wallet_path: "wallet".into(),
+            accounts: HashMap::from([(pubkey.clone(), DirkAccount::Regular("wallet/1".into()))]),
+            chain: ChainConfig::mainnet(),
+            sign_timeout,
+        };
+
+        let err = signer.sign_commit_boost_root([1; 32], &pubkey).await.unwrap_err();
+        assert!(matches!(err, SignerError::Dirk(DirkError::Timeout(t)) if t == sign_timeout));
+
+        // The health check probes Dirk, with the same timeout
+        let err = signer.check_reachable().await.unwrap_err();
+        assert!(matches!(err, SignerError::Dirk(DirkError::Timeout(t)) if t == sign_timeout));
+    }
+
+    #[tokio::test]
+    async fn test_check_reachable_fails_on_unreachable_dirk() {
+        let signer = DirkSigner {
+            client: lazy_client("127.0.0.1:1"),
+            wallet_path: "wallet".into(),
+            accounts: HashMap::new(),
+            chain: ChainConfig::mainnet(),
+            sign_timeout: Duration::from_secs(1),
+        };
+
+        let err = signer.check_reachable().await.unwrap_err();
+        assert!(matches!(err, SignerError::Dirk(DirkError::Status(_))));
+    }
+}
@@ -1,11 +1,21 @@
 use std::collections::HashSet;
 
+use alloy::{
+    primitives::Address,
+    signers::{local::PrivateKeySigner, Signature},
+};
 use ethereum_consensus::crypto::bls::PublicKey as BlsPublicKey;
 
+use crate::crypto::SignerECDSA;
+
 /// Commit-Boost remote signer client wrapper.
 pub mod commit_boost;
 pub use commit_boost::CommitBoostSigner;
 
+/// Dirk remote signer implementation, including distributed accounts.
+pub mod dirk;
+pub use dirk::DirkSigner;
+
 /// EIP-2335 keystore signer implementation.
 pub mod keystore;
 pub use keystore::KeystoreSigner;
@@ -14,6 +24,10 @@ pub use keystore::KeystoreSigner;
 pub mod local;
 pub use local::LocalSigner;
 
+/// Web3Signer remote signer implementation.
+pub mod web3signer;
+pub use web3signer::{Web3Signer, Web3SignerEcdsa};
+
 /// Error in the signer.
 #[derive(Debug, thiserror::Error)]
 #[allow(missing_docs)]
@@ -24,6 +38,10 @@ pub enum SignerError {
     CommitBoost(#[from] commit_boost::CommitBoostError),
     #[error("keystore signer error: {0}")]
     Keystore(#[from] keystore::KeystoreError),
+    #[error("dirk signer error: {0}")]
+    Dirk(#[from] dirk::DirkError),
+    #[error("web3signer error: {0}")]
+    Web3Signer(#[from] web3signer::Web3SignerError),
 }
 
 /// Result type for the signer.
@@ -38,6 +56,10 @@ pub enum SignerBLS {
     CommitBoost(CommitBoostSigner),
     /// Signer consisting of multiple keypairs loaded from ERC-2335 keystores files.
     Keystore(KeystoreSigner),
+    /// Remote signer backed by a Web3Signer instance.
+    Web3Signer(Web3Signer),
+    /// Remote signer backed by a Dirk instance, supporting distributed accounts.
+    Dirk(DirkSigner),
 }
 
 impl SignerBLS {
@@ -47,6 +69,65 @@ impl SignerBLS {
             Self::Local(signer) => [signer.pubkey()].into(),
             Self::CommitBoost(signer) => [signer.pubkey()].into(),
             Self::Keystore(signer) => signer.pubkeys(),
+            Self::Web3Signer(signer) => signer.pubkeys(),
+            Self::Dirk(signer) => signer.pubkeys(),
+        }
+    }
+
+    /// Checks that the signer can be used, i.e. that remote signers are reachable.
+    ///
+    /// Local and keystore signers hold their keys in memory and are always reachable.
+    pub async fn check_reachable(&self) -> SignerResult<()> {
+        match self {
+            Self::Local(_) | Self::Keystore(_) => Ok(()),
+            Self::CommitBoost(signer) => signer.check_reachable().await,
+            Self::Web3Signer(signer) => signer.list_accounts().await.map(|_| ()),
+            Self::Dirk(signer) => signer.check_reachable().await,
+        }
+    }
+
+    /// Returns the kind of signer, used in traces.
+    pub const fn kind(&self) -> &'static str {
+        match self {
+            Self::Local(_) => "local",
+            Self::CommitBoost(_) => "commit_boost",
+            Self::Keystore(_) => "keystore",
+            Self::Web3Signer(_) => "web3signer",
+            Self::Dirk(_) => "dirk",
+        }
+    }
+}
+
+/// Signer for the ECDSA signatures of commitments.
+#[derive(Debug, Clone)]
+pub enum CommitmentSigner {
+    /// Local signer with an ECDSA private key, provided in plaintext or decrypted from a
+    /// JSON V3 keystore.
+    Local(PrivateKeySigner),
+    /// Remote signer backed by a Web3Signer instance.
+    Web3Signer(Web3SignerEcdsa),
+}
+
+#[async_trait::async_trait]
+impl SignerECDSA for CommitmentSigner {
+    fn public_key(&self) -> Address {
+        match self {
+            Self::Local(signer) => signer.public_key(),
+            Self::Web3Signer(signer) => signer.public_key(),
+        }
+    }
+
+    async fn sign_keccak256(&self, data: &[u8]) -> eyre::Result<Signature> {
+        match self {
+            Self::Local(signer) => signer.sign_keccak256(data).await,
+            Self::Web3Signer(signer) => signer.sign_keccak256(data).await,
+        }
+    }
+
+    async fn check_reachable(&self) -> eyre::Result<()> {
+        match self {
+            Self::Local(_) => Ok(()),
+            Self::Web3Signer(signer) => signer.check_reachable().await,
         }
     }
 }
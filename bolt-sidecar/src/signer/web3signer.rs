@@ -0,0 +1,515 @@
+use std::{collections::HashSet, fmt, fs, path::PathBuf};
+
+use alloy::{
+    hex,
+    primitives::{keccak256, Address},
+    signers::Signature,
+};
+use ethereum_consensus::crypto::PublicKey as BlsPublicKey;
+use eyre::bail;
+use reqwest::{Certificate, Identity, Url};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info};
+
+use crate::crypto::{bls::BLSSig, ecdsa::SignerECDSA};
+
+use super::SignerResult;
+
+/// The path to list the consensus keys with the Web3Signer commit-boost API.
+const GET_PUBKEYS_PATH: &str = "/signer/v1/get_pubkeys";
+/// The path to request a signature with the Web3Signer commit-boost API.
+const REQUEST_SIGNATURE_PATH: &str = "/signer/v1/request_signature";
+/// The path to list the SECP256K1 public keys with the Web3Signer eth1 API.
+const ETH1_PUBLIC_KEYS_PATH: &str = "/api/v1/eth1/publicKeys";
+/// The path to request an ECDSA signature with the Web3Signer eth1 API, followed by the
+/// public key to sign with.
+const ETH1_SIGN_PATH: &str = "/api/v1/eth1/sign/";
+
+/// Error in the Web3Signer signer.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Web3SignerError {
+    #[error("failed to read TLS credentials: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("request to Web3Signer failed: {0}")]
+    Reqwest(#[from] reqwest::Error),
+    #[error("Web3Signer returned an invalid public key: {0}")]
+    InvalidPubkey(String),
+    #[error("Web3Signer returned an invalid signature: {0}")]
+    InvalidSignature(String),
+    #[error("public key {0:?} is not available in Web3Signer")]
+    UnknownPubkey(BlsPublicKey),
+    #[error("no ECDSA key for address {0} is available in Web3Signer")]
+    UnknownAddress(Address),
+    #[error("Web3Signer holds {0} ECDSA keys, the address to sign with must be specified")]
+    AmbiguousAddress(usize),
+}
+
+/// TLS credentials for connecting to a remote Web3Signer server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Web3SignerTlsCredentials {
+    /// Path to the CA certificate file. (.crt)
+    pub ca_cert_path: PathBuf,
+    /// Path to the PEM encoded private key and certificate file. (.pem)
+    pub combined_pem_path: PathBuf,
+}
+
+/// A BLS signer backed by a remote Web3Signer instance, using its commit-boost API.
+///
+/// Functionality:
+/// - List the consensus keys available in the remote keystore.
+/// - Sign roots over the consensus type with the commit-boost domain.
+///
+/// Reference: <https://docs.web3signer.consensys.io/reference>
+#[derive(Clone)]
+pub struct Web3Signer {
+    base_url: Url,
+    client: reqwest::Client,
+    pubkeys: HashSet<BlsPublicKey>,
+}
+
+impl fmt::Debug for Web3Signer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Web3Signer")
+            .field("base_url", &self.base_url.as_str())
+            .field("pubkeys", &self.pubkeys.len())
+            .finish()
+    }
+}
+
+impl Web3Signer {
+    /// Connect to a remote Web3Signer instance, with optional TLS client credentials,
+    /// and discover the consensus public keys available for signing.
+    pub async fn connect(
+        base_url: Url,
+        credentials: Option<Web3SignerTlsCredentials>,
+    ) -> SignerResult<Self> {
+        let client = build_client(credentials)?;
+
+        let mut signer = Self { base_url, client, pubkeys: HashSet::new() };
+        signer.pubkeys = signer.list_accounts().await?;
+        info!(pubkeys = signer.pubkeys.len(), url = %signer.base_url, "Connected to Web3Signer");
+
+        Ok(signer)
+    }
+
+    /// List the consensus accounts of the remote keystore.
+    ///
+    /// Only the consensus keys are returned, as signing is only over the consensus type.
+    pub async fn list_accounts(&self) -> SignerResult<HashSet<BlsPublicKey>> {
+        let response = self
+            .client
+            .get(self.endpoint(GET_PUBKEYS_PATH))
+            .send()
+            .await
+            .and_then(|res| res.error_for_status())
+            .map_err(Web3SignerError::Reqwest)?
+            .json::<CommitBoostKeys>()
+            .await
+            .map_err(Web3SignerError::Reqwest)?;
+
+        let pubkeys = response
+            .keys
+            .into_iter()
+            .map(|keys| {
+                let bytes = hex::decode(&keys.consensus)
+                    .map_err(|_| Web3SignerError::InvalidPubkey(keys.consensus.clone()))?;
+                BlsPublicKey::try_from(bytes.as_slice())
+                    .map_err(|_| Web3SignerError::InvalidPubkey(keys.consensus))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(pubkeys)
+    }
+
+    /// Returns the public keys available for signing.
+    pub fn pubkeys(&self) -> HashSet<BlsPublicKey> {
+        self.pubkeys.clone()
+    }
+
+    /// Sign an object root with the Commit Boost domain, using the given public key.
+    pub async fn sign_commit_boost_root(
+        &self,
+        root: [u8; 32],
+        pubkey: &BlsPublicKey,
+    ) -> SignerResult<BLSSig> {
+        if !self.pubkeys.contains(pubkey) {
+            return Err(Web3SignerError::UnknownPubkey(pubkey.clone()).into());
+        }
+
+        let request = CommitBoostSignatureRequest {
+            type_: "consensus".to_string(),
+            pubkey: hex::encode_prefixed(pubkey.as_ref()),
+            object_root: hex::encode_prefixed(root),
+        };
+
+        debug!(pubkey = %request.pubkey, root = %request.object_root, "Requesting signature from Web3Signer");
+
+        let signature = self
+            .client
+            .post(self.endpoint(REQUEST_SIGNATURE_PATH))
+            .json(&request)
+            .send()
+            .await
+            .and_then(|res| res.error_for_status())
+            .map_err(Web3SignerError::Reqwest)?
+            .json::<String>()
+            .await
+            .map_err(Web3SignerError::Reqwest)?;
+
+        let bytes = hex::decode(&signature)
+            .map_err(|_| Web3SignerError::InvalidSignature(signature.clone()))?;
+        if bytes.len() != 96 {
+            return Err(Web3SignerError::InvalidSignature(signature).into());
+        }
+
+        Ok(BLSSig::from_slice(&bytes))
+    }
+
+    /// Join the given path to the base URL of the Web3Signer.
+    fn endpoint(&self, path: &str) -> Url {
+        self.base_url.join(path).expect("valid endpoint path")
+    }
+}
+
+/// Build the HTTP client for the Web3Signer, with the given TLS client credentials if any.
+fn build_client(
+    credentials: Option<Web3SignerTlsCredentials>,
+) -> Result<reqwest::Client, Web3SignerError> {
+    let mut builder = reqwest::Client::builder().user_agent("bolt-sidecar");
+
+    if let Some(credentials) = credentials {
+        let ca_cert = Certificate::from_pem(&fs::read(credentials.ca_cert_path)?)?;
+        let identity = Identity::from_pem(&fs::read(credentials.combined_pem_path)?)?;
+
+        builder = builder.add_root_certificate(ca_cert).identity(identity).use_rustls_tls();
+    }
+
+    Ok(builder.build()?)
+}
+
+/// An ECDSA signer backed by a remote Web3Signer instance, using its eth1 signing API.
+///
+/// Web3Signer always hashes the data it's given with keccak256 before signing it, so this
+/// signer only implements [`SignerECDSA`] and can't be used to sign arbitrary hashes.
+///
+/// Reference: <https://docs.web3signer.consensys.io/reference/api/rest>
+#[derive(Clone)]
+pub struct Web3SignerEcdsa {
+    base_url: Url,
+    client: reqwest::Client,
+    /// The public key to sign with, as returned by Web3Signer.
+    public_key: String,
+    address: Address,
+}
+
+impl fmt::Debug for Web3SignerEcdsa {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Web3SignerEcdsa")
+            .field("base_url", &self.base_url.as_str())
+            .field("address", &self.address)
+            .finish()
+    }
+}
+
+impl Web3SignerEcdsa {
+    /// Connect to a remote Web3Signer instance, with optional TLS client credentials, and
+    /// select the ECDSA key of the given address. If no address is given, Web3Signer must
+    /// hold exactly one ECDSA key.
+    pub async fn connect(
+        base_url: Url,
+        credentials: Option<Web3SignerTlsCredentials>,
+        address: Option<Address>,
+    ) -> SignerResult<Self> {
+        let client = build_client(credentials)?;
+
+        let mut accounts = eth1_public_keys(&client, &base_url)
+            .await?
+            .into_iter()
+            .map(|public_key| Ok((eth1_address(&public_key)?, public_key)))
+            .collect::<Result<Vec<_>, Web3SignerError>>()?;
+
+        let (address, public_key) = match address {
+            Some(address) => accounts
+                .into_iter()
+                .find(|(a, _)| *a == address)
+                .ok_or(Web3SignerError::UnknownAddress(address))?,
+            None if accounts.len() == 1 => accounts.remove(0),
+            None => return Err(Web3SignerError::AmbiguousAddress(accounts.len()).into()),
+        };
+
+        info!(%address, url = %base_url, "Connected to Web3Signer for commitment signing");
+
+        Ok(Self { base_url, client, public_key, address })
+    }
+}
+
+/// Fetch the public keys of the SECP256K1 keys held by the Web3Signer instance.
+async fn eth1_public_keys(
+    client: &reqwest::Client,
+    base_url: &Url,
+) -> Result<Vec<String>, Web3SignerError> {
+    client
+        .get(base_url.join(ETH1_PUBLIC_KEYS_PATH).expect("valid endpoint path"))
+        .send()
+        .await
+        .and_then(|res| res.error_for_status())?
+        .json::<Vec<String>>()
+        .await
+        .map_err(Web3SignerError::Reqwest)
+}
+
+#[async_trait::async_trait]
+impl SignerECDSA for Web3SignerEcdsa {
+    fn public_key(&self) -> Address {
+        self.address
+    }
+
+    async fn sign_keccak256(&self, data: &[u8]) -> eyre::Result<Signature> {
+        let url = self.base_url.join(&format!("{ETH1_SIGN_PATH}{}", self.public_key))?;
+        let request = Eth1SignRequest { data: hex::encode_prefixed(data) };
+
+        let signature = self
+            .client
+            .post(url)
+            .json(&request)
+            .send()
+            .await
+            .and_then(|res| res.error_for_status())?
+            .text()
+            .await?;
+
+        let bytes = hex::decode(signature.trim())
+            .map_err(|_| Web3SignerError::InvalidSignature(signature.clone()))?;
+        let signature = Signature::try_from(bytes.as_slice())?;
+
+        // Sanity check: make sure the remote key is the one we expect
+        let recovered = signature.recover_address_from_prehash(&keccak256(data))?;
+        if recovered != self.address {
+            bail!("Web3Signer signature recovered to {recovered}, expected {}", self.address);
+        }
+
+        Ok(signature)
+    }
+
+    /// Checks that Web3Signer is reachable and still holds the key of our address.
+    async fn check_reachable(&self) -> eyre::Result<()> {
+        let public_keys = eth1_public_keys(&self.client, &self.base_url).await?;
+        if !public_keys.contains(&self.public_key) {
+            return Err(Web3SignerError::UnknownAddress(self.address).into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Derive the Ethereum address of a SECP256K1 public key returned by Web3Signer, which is
+/// hex encoded in uncompressed form, with or without the `0x04` prefix.
+fn eth1_address(public_key: &str) -> Result<Address, Web3SignerError> {
+    let invalid = || Web3SignerError::InvalidPubkey(public_key.to_string());
+
+    let bytes = hex::decode(public_key).map_err(|_| invalid())?;
+    let raw = match bytes.len() {
+        64 => &bytes[..],
+        65 if bytes[0] == 0x04 => &bytes[1..],
+        _ => return Err(invalid()),
+    };
+
+    Ok(Address::from_raw_public_key(raw))
+}
+
+/// The keys associated to a consensus key in the Web3Signer commit-boost API.
+#[derive(Serialize, Deserialize)]
+struct Keys {
+    /// The consensus keys stored in the Web3Signer.
+    consensus: String,
+    /// The two below proxy fields are here for deserialisation purposes.
+    /// They are not used as signing is only over the consensus type.
+    #[allow(unused)]
+    #[serde(default)]
+    proxy_bls: Vec<String>,
+    #[allow(unused)]
+    #[serde(default)]
+    proxy_ecdsa: Vec<String>,
+}
+
+/// Outer container for the get pubkeys response.
+#[derive(Serialize, Deserialize)]
+struct CommitBoostKeys {
+    keys: Vec<Keys>,
+}
+
+/// Request signature from the Web3Signer.
+#[derive(Serialize, Deserialize)]
+struct CommitBoostSignatureRequest {
+    #[serde(rename = "type")]
+    type_: String,
+    pubkey: String,
+    object_root: String,
+}
+
+/// Request an ECDSA signature from the Web3Signer eth1 API.
+#[derive(Serialize, Deserialize)]
+struct Eth1SignRequest {
+    data: String,
+}
+
+/// Test utilities for the Web3Signer signer.
+#[cfg(test)]
+pub mod test_util {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use alloy::signers::{local::PrivateKeySigner, SignerSync};
+    use axum::{
+        extract::{Path, State},
+        routing::{get, post},
+        Json, Router,
+    };
+    use tokio::net::TcpListener;
+
+    use super::*;
+    use crate::signer::local::LocalSigner;
+
+    /// The state of the commit-boost API stand-in: the signers and the number of signing requests.
+    type SignersState = (Arc<Vec<LocalSigner>>, Arc<AtomicUsize>);
+
+    /// Start a local stand-in for the Web3Signer commit-boost API, signing with the given
+    /// local signers. Returns the URL of the server.
+    pub async fn start_web3signer_test_server(signers: Vec<LocalSigner>) -> eyre::Result<Url> {
+        Ok(start_counting_web3signer_test_server(signers).await?.0)
+    }
+
+    /// Like [`start_web3signer_test_server`], also returning the number of signing requests
+    /// received by the server.
+    pub async fn start_counting_web3signer_test_server(
+        signers: Vec<LocalSigner>,
+    ) -> eyre::Result<(Url, Arc<AtomicUsize>)> {
+        let signers = Arc::new(signers);
+        let requests = Arc::new(AtomicUsize::new(0));
+
+        let router = Router::new()
+            .route(
+                GET_PUBKEYS_PATH,
+                get(|State((signers, _)): State<SignersState>| async move {
+                    let keys = signers
+                        .iter()
+                        .map(|s| Keys {
+                            consensus: hex::encode_prefixed(s.pubkey().as_ref()),
+                            proxy_bls: vec![],
+                            proxy_ecdsa: vec![],
+                        })
+                        .collect();
+                    Json(CommitBoostKeys { keys })
+                }),
+            )
+            .route(
+                REQUEST_SIGNATURE_PATH,
+                post(
+                    |State((signers, requests)): State<SignersState>,
+                     Json(req): Json<CommitBoostSignatureRequest>| async move {
+                        requests.fetch_add(1, Ordering::SeqCst);
+                        let signer = signers
+                            .iter()
+                            .find(|s| hex::encode_prefixed(s.pubkey().as_ref()) == req.pubkey)
+                            .expect("known pubkey");
+                        let root: [u8; 32] = hex::decode(&req.object_root)
+                            .expect("valid hex")
+                            .try_into()
+                            .expect("32 bytes root");
+                        let sig = signer.sign_commit_boost_root(root).expect("signature");
+                        Json(hex::encode_prefixed(sig))
+                    },
+                ),
+            )
+            .with_state((signers, requests.clone()));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let url = Url::parse(&format!("http://{}", listener.local_addr()?))?;
+        tokio::spawn(async move { axum::serve(listener, router).await });
+
+        Ok((url, requests))
+    }
+
+    /// Start a local stand-in for the Web3Signer eth1 API, signing with the given private key.
+    /// Returns the URL of the server.
+    pub async fn start_web3signer_eth1_test_server(signer: PrivateKeySigner) -> eyre::Result<Url> {
+        let public_key = signer.credential().verifying_key().to_encoded_point(false);
+        let public_key = hex::encode_prefixed(&public_key.as_bytes()[1..]);
+
+        let router = Router::new()
+            .route(ETH1_PUBLIC_KEYS_PATH, get(move || async move { Json(vec![public_key]) }))
+            .route(
+                &format!("{ETH1_SIGN_PATH}:identifier"),
+                post(move |Path(_): Path<String>, Json(req): Json<Eth1SignRequest>| async move {
+                    let data = hex::decode(&req.data).expect("valid hex");
+                    let sig = signer.sign_hash_sync(&keccak256(data)).expect("signature");
+                    hex::encode_prefixed(sig.as_bytes())
+                }),
+            );
+
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let url = Url::parse(&format!("http://{}", listener.local_addr()?))?;
+        tokio::spawn(async move { axum::serve(listener, router).await });
+
+        Ok(url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::signers::local::PrivateKeySigner;
+
+    use super::*;
+    use crate::signer::local::LocalSigner;
+
+    #[tokio::test]
+    async fn test_web3signer_sign_commit_boost_root() -> eyre::Result<()> {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let local = LocalSigner::random();
+        let url = test_util::start_web3signer_test_server(vec![local.clone()]).await?;
+
+        let signer = Web3Signer::connect(url, None).await?;
+        assert_eq!(signer.pubkeys(), HashSet::from([local.pubkey()]));
+
+        let root = [42u8; 32];
+        let signature = signer.sign_commit_boost_root(root, &local.pubkey()).await?;
+        let sig = blst::min_pk::Signature::from_bytes(signature.as_ref()).unwrap();
+        assert!(local.verify_commit_boost_root(root, &sig).is_ok());
+
+        // Signing with a key that is not in the remote keystore must fail
+        let unknown = LocalSigner::random().pubkey();
+        assert!(signer.sign_commit_boost_root(root, &unknown).await.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_web3signer_ecdsa_sign_keccak256() -> eyre::Result<()> {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let local = PrivateKeySigner::random();
+        let url = test_util::start_web3signer_eth1_test_server(local.clone()).await?;
+
+        // Selecting an address that is not in the remote keystore must fail
+        assert!(Web3SignerEcdsa::connect(url.clone(), None, Some(Address::ZERO)).await.is_err());
+
+        let signer = Web3SignerEcdsa::connect(url, None, None).await?;
+        assert_eq!(signer.public_key(), local.address());
+
+        let data = b"commitment digest data";
+        let signature = signer.sign_keccak256(data).await?;
+        assert_eq!(signature.recover_address_from_prehash(&keccak256(data))?, local.address());
+
+        // The health check probes Web3Signer for our key
+        signer.check_reachable().await?;
+        let unreachable =
+            Web3SignerEcdsa { base_url: Url::parse("http://127.0.0.1:1")?, ..signer.clone() };
+        assert!(unreachable.check_reachable().await.is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,489 @@
+use std::{collections::BTreeMap, io, path::PathBuf};
+
+use alloy::{
+    consensus::Transaction,
+    primitives::{Address, B256},
+};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+use crate::primitives::{InclusionRequest, Slot};
+
+use super::journal::Journal;
+
+/// The number of slots for which signed commitments are retained on disk (~1 day on mainnet).
+pub const COMMITMENTS_RETENTION_SLOTS: u64 = 8192;
+
+/// The version of the commitments interchange format produced and accepted by the sidecar.
+pub const INTERCHANGE_FORMAT_VERSION: u64 = 1;
+
+/// A transaction that is part of a signed commitment.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommittedTransaction {
+    /// The hash of the transaction.
+    pub hash: B256,
+    /// The recovered sender of the transaction.
+    pub sender: Address,
+    /// The nonce of the transaction.
+    pub nonce: u64,
+    /// The gas limit of the transaction.
+    pub gas_limit: u64,
+}
+
+/// A record of an inclusion commitment signed by the sidecar.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedCommitmentRecord {
+    /// The target slot of the commitment.
+    pub slot: Slot,
+    /// The digest of the commitment request.
+    pub digest: B256,
+    /// The committed transactions.
+    pub transactions: Vec<CommittedTransaction>,
+}
+
+impl SignedCommitmentRecord {
+    /// Create a record from an inclusion request whose senders have been recovered.
+    pub fn from_request(request: &InclusionRequest) -> Result<Self, CommitmentsDbError> {
+        let transactions = request
+            .txs
+            .iter()
+            .map(|tx| {
+                let sender =
+                    tx.sender().ok_or(CommitmentsDbError::UnrecoveredSender(*tx.hash()))?;
+                Ok(CommittedTransaction {
+                    hash: *tx.hash(),
+                    sender: *sender,
+                    nonce: tx.nonce(),
+                    gas_limit: tx.gas_limit(),
+                })
+            })
+            .collect::<Result<_, CommitmentsDbError>>()?;
+
+        Ok(Self { slot: request.slot, digest: request.digest(), transactions })
+    }
+
+    /// Returns the total gas limit of the committed transactions.
+    pub fn gas_limit(&self) -> u64 {
+        self.transactions.iter().map(|tx| tx.gas_limit).sum()
+    }
+}
+
+/// Metadata of a commitments interchange file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InterchangeMetadata {
+    /// The version of the interchange format.
+    pub interchange_format_version: u64,
+    /// The chain ID the commitments were signed for.
+    pub chain_id: u64,
+}
+
+/// The interchange format of the commitments database, similar in spirit to the EIP-3076
+/// slashing protection interchange format.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommitmentsInterchange {
+    /// The metadata of the interchange file.
+    pub metadata: InterchangeMetadata,
+    /// The signed commitment records.
+    pub commitments: Vec<SignedCommitmentRecord>,
+}
+
+/// An entry of the journal backing the commitments database on disk. The interchange metadata
+/// is always the first entry, followed by the signed commitment records.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum JournalEntry {
+    Metadata(InterchangeMetadata),
+    Commitment(SignedCommitmentRecord),
+}
+
+/// Errors that can occur when interacting with the commitments database.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum CommitmentsDbError {
+    #[error("Transaction {hash} was already committed for slot {slot}")]
+    DuplicateTransaction { slot: Slot, hash: B256 },
+    #[error("Nonce {nonce} of sender {sender} was already committed for slot {slot}")]
+    NonceConflict { slot: Slot, sender: Address, nonce: u64 },
+    #[error(
+        "Committed gas for slot {slot} would exceed the limit: {committed} + {requested} > {limit}"
+    )]
+    GasLimitExceeded { slot: Slot, committed: u64, requested: u64, limit: u64 },
+    #[error("Interchange chain ID {have} doesn't match the sidecar chain ID {expected}")]
+    ChainIdMismatch { expected: u64, have: u64 },
+    #[error("Unsupported interchange format version {0}")]
+    UnsupportedVersion(u64),
+    #[error("Commitments database is missing its metadata")]
+    MissingMetadata,
+    #[error("Sender of transaction {0} was not recovered")]
+    UnrecoveredSender(B256),
+    #[error("I/O error on commitments database: {0}")]
+    Io(#[from] io::Error),
+    #[error("Failed to (de)serialize commitments database: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+impl CommitmentsDbError {
+    /// Returns true if the error is caused by a conflict with a previously signed commitment.
+    pub fn is_conflict(&self) -> bool {
+        matches!(
+            self,
+            Self::DuplicateTransaction { .. } |
+                Self::NonceConflict { .. } |
+                Self::GasLimitExceeded { .. }
+        )
+    }
+}
+
+/// A persisted record of the inclusion commitments signed by the sidecar, similar in spirit
+/// to an EIP-3076 slashing protection database.
+///
+/// Every commitment is recorded once signed and before it is returned, and new commitments are
+/// refused if they can't be satisfied together with the ones that were already signed for
+/// pending slots, even across restarts of the sidecar. A commitment conflicts if:
+/// - One of its transactions was already committed.
+/// - One of its (sender, nonce) pairs was already committed with a different transaction.
+/// - The total committed gas for its slot would exceed the limit.
+#[derive(Debug)]
+pub struct CommitmentsDb {
+    /// The journal backing the database. If `None`, records are kept in memory.
+    journal: Option<Journal>,
+    /// The chain ID the commitments are signed for.
+    chain_id: u64,
+    /// The signed commitment records, indexed by slot.
+    records: BTreeMap<Slot, Vec<SignedCommitmentRecord>>,
+}
+
+impl CommitmentsDb {
+    /// Open the database at the given path, loading any existing records from it.
+    /// If the file does not exist, it is created.
+    pub fn open(path: impl Into<PathBuf>, chain_id: u64) -> Result<Self, CommitmentsDbError> {
+        let path = path.into();
+
+        let mut db = Self::in_memory(chain_id);
+
+        let (mut metadata, mut commitments) = (None, Vec::new());
+        for entry in Journal::read::<JournalEntry, CommitmentsDbError>(&path)? {
+            match entry {
+                JournalEntry::Metadata(m) => metadata = Some(m),
+                JournalEntry::Commitment(record) => commitments.push(record),
+            }
+        }
+        match metadata {
+            Some(metadata) => db.merge(CommitmentsInterchange { metadata, commitments })?,
+            None if commitments.is_empty() => 0,
+            None => return Err(CommitmentsDbError::MissingMetadata),
+        };
+
+        db.journal = Some(Journal::create(&path, entries(chain_id, &db.records))?);
+
+        debug!(?path, slots = db.records.len(), "Opened commitments database");
+        Ok(db)
+    }
+
+    /// Create a new database that is not persisted to disk.
+    pub fn in_memory(chain_id: u64) -> Self {
+        Self { journal: None, chain_id, records: BTreeMap::new() }
+    }
+
+    /// Get the signed commitment records for the given slot.
+    pub fn get(&self, slot: Slot) -> &[SignedCommitmentRecord] {
+        self.records.get(&slot).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Check that signing the given commitment would not conflict with the commitments
+    /// already signed for slots after `head_slot`.
+    pub fn check(
+        &self,
+        record: &SignedCommitmentRecord,
+        head_slot: Slot,
+        max_committed_gas: u64,
+    ) -> Result<(), CommitmentsDbError> {
+        for (&slot, records) in self.records.range(head_slot.saturating_add(1)..) {
+            for tx in records.iter().flat_map(|r| &r.transactions) {
+                for new in &record.transactions {
+                    if tx.hash == new.hash {
+                        return Err(CommitmentsDbError::DuplicateTransaction {
+                            slot,
+                            hash: new.hash,
+                        });
+                    }
+
+                    if tx.sender == new.sender && tx.nonce == new.nonce {
+                        return Err(CommitmentsDbError::NonceConflict {
+                            slot,
+                            sender: new.sender,
+                            nonce: new.nonce,
+                        });
+                    }
+                }
+            }
+        }
+
+        let committed = self.get(record.slot).iter().map(SignedCommitmentRecord::gas_limit).sum();
+        let requested = record.gas_limit();
+        if committed + requested > max_committed_gas {
+            return Err(CommitmentsDbError::GasLimitExceeded {
+                slot: record.slot,
+                committed,
+                requested,
+                limit: max_committed_gas,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Record a signed commitment and persist it to disk. This must be done before the signed
+    /// commitment is returned, and the signed commitment must be discarded if this fails.
+    ///
+    /// Returns an error if it conflicts with a commitment already signed for a slot after
+    /// `head_slot`. Recording the same commitment twice is a no-op.
+    pub fn insert(
+        &mut self,
+        record: SignedCommitmentRecord,
+        head_slot: Slot,
+        max_committed_gas: u64,
+    ) -> Result<(), CommitmentsDbError> {
+        if self.get(record.slot).iter().any(|r| r.digest == record.digest) {
+            return Ok(());
+        }
+
+        self.check(&record, head_slot, max_committed_gas)?;
+
+        let slot = record.slot;
+        self.records.entry(slot).or_default().push(record.clone());
+        self.prune(slot);
+
+        if let Some(journal) = &mut self.journal {
+            let live_count = self.records.values().map(Vec::len).sum::<usize>() + 1;
+            let live = || entries(self.chain_id, &self.records);
+            let entry = JournalEntry::Commitment(record);
+            if let Err(err) = journal.record(&entry, live_count, live) {
+                warn!(?err, slot, "Failed to persist signed commitment record");
+                if let Some(records) = self.records.get_mut(&slot) {
+                    records.pop();
+                }
+                return Err(err.into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Export all the records in the interchange format.
+    pub fn export(&self) -> CommitmentsInterchange {
+        CommitmentsInterchange {
+            metadata: InterchangeMetadata {
+                interchange_format_version: INTERCHANGE_FORMAT_VERSION,
+                chain_id: self.chain_id,
+            },
+            commitments: self.records.values().flatten().cloned().collect(),
+        }
+    }
+
+    /// Import the records of an interchange file, for instance exported from another sidecar
+    /// instance, and persist them to disk. Records are imported as-is, even if they conflict
+    /// with existing ones, as they represent commitments that have already been signed.
+    ///
+    /// Returns the number of records that were not already present in the database.
+    pub fn import(
+        &mut self,
+        interchange: CommitmentsInterchange,
+    ) -> Result<usize, CommitmentsDbError> {
+        let records = self.records.clone();
+        let imported = self.merge(interchange)?;
+        if let Err(err) = self.persist() {
+            self.records = records;
+            return Err(err);
+        }
+
+        info!(imported, "Imported signed commitment records");
+        Ok(imported)
+    }
+
+    /// Merge the records of an interchange file into the database, without persisting them.
+    fn merge(&mut self, interchange: CommitmentsInterchange) -> Result<usize, CommitmentsDbError> {
+        let metadata = interchange.metadata;
+        if metadata.interchange_format_version != INTERCHANGE_FORMAT_VERSION {
+            return Err(CommitmentsDbError::UnsupportedVersion(metadata.interchange_format_version));
+        }
+        if metadata.chain_id != self.chain_id {
+            return Err(CommitmentsDbError::ChainIdMismatch {
+                expected: self.chain_id,
+                have: metadata.chain_id,
+            });
+        }
+
+        let mut imported = 0;
+        for record in interchange.commitments {
+            let records = self.records.entry(record.slot).or_default();
+            if !records.iter().any(|r| r.digest == record.digest) {
+                records.push(record);
+                imported += 1;
+            }
+        }
+
+        if let Some(latest_slot) = self.records.keys().next_back().copied() {
+            self.prune(latest_slot);
+        }
+
+        Ok(imported)
+    }
+
+    /// Remove all records older than the retention window relative to the given slot.
+    fn prune(&mut self, latest_slot: Slot) {
+        let min_slot = latest_slot.saturating_sub(COMMITMENTS_RETENTION_SLOTS);
+        self.records = self.records.split_off(&min_slot);
+    }
+
+    /// Atomically rewrite the records to disk if the database is persisted, compacting its
+    /// journal to the records that are still retained.
+    pub fn persist(&mut self) -> Result<(), CommitmentsDbError> {
+        if let Some(journal) = &mut self.journal {
+            journal.compact(entries(self.chain_id, &self.records))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the journal entries of the given records: the metadata, followed by the records.
+fn entries(
+    chain_id: u64,
+    records: &BTreeMap<Slot, Vec<SignedCommitmentRecord>>,
+) -> impl Iterator<Item = JournalEntry> + '_ {
+    let metadata =
+        InterchangeMetadata { interchange_format_version: INTERCHANGE_FORMAT_VERSION, chain_id };
+
+    std::iter::once(JournalEntry::Metadata(metadata))
+        .chain(records.values().flatten().cloned().map(JournalEntry::Commitment))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::test_util::{random_constraints, temp_file_path};
+
+    fn tx(hash_byte: u8, sender_byte: u8, nonce: u64, gas_limit: u64) -> CommittedTransaction {
+        CommittedTransaction {
+            hash: B256::repeat_byte(hash_byte),
+            sender: Address::repeat_byte(sender_byte),
+            nonce,
+            gas_limit,
+        }
+    }
+
+    fn record(slot: Slot, transactions: Vec<CommittedTransaction>) -> SignedCommitmentRecord {
+        let digest = B256::repeat_byte(transactions.first().map_or(0, |tx| tx.hash[0]));
+        SignedCommitmentRecord { slot, digest, transactions }
+    }
+
+    #[test]
+    fn test_refuses_conflicting_commitments() {
+        let mut db = CommitmentsDb::in_memory(1);
+
+        db.insert(record(10, vec![tx(1, 1, 0, 21_000)]), 9, 100_000).unwrap();
+
+        // Same commitment is fine
+        db.insert(record(10, vec![tx(1, 1, 0, 21_000)]), 9, 100_000).unwrap();
+        assert_eq!(db.get(10).len(), 1);
+
+        // Same transaction in another pending slot must be refused
+        let err = db.insert(record(11, vec![tx(1, 1, 0, 21_000)]), 9, 100_000).unwrap_err();
+        assert!(matches!(err, CommitmentsDbError::DuplicateTransaction { slot: 10, .. }));
+
+        // Same sender and nonce with a different transaction must be refused
+        let err = db.insert(record(11, vec![tx(2, 1, 0, 21_000)]), 9, 100_000).unwrap_err();
+        assert!(matches!(err, CommitmentsDbError::NonceConflict { slot: 10, nonce: 0, .. }));
+
+        // Exceeding the committed gas limit for the slot must be refused
+        let err = db.insert(record(10, vec![tx(3, 2, 0, 80_000)]), 9, 100_000).unwrap_err();
+        assert!(matches!(err, CommitmentsDbError::GasLimitExceeded { slot: 10, .. }));
+
+        // Once the slot has passed, its commitments don't conflict anymore
+        db.insert(record(11, vec![tx(2, 1, 0, 21_000)]), 10, 100_000).unwrap();
+    }
+
+    #[test]
+    fn test_persists_across_restarts() {
+        let path = temp_file_path("bolt_commitments_db");
+
+        {
+            let mut db = CommitmentsDb::open(&path, 1).unwrap();
+            db.insert(record(42, vec![tx(1, 1, 0, 21_000)]), 41, 100_000).unwrap();
+        }
+
+        let db = CommitmentsDb::open(&path, 1).unwrap();
+        assert_eq!(db.get(42), &[record(42, vec![tx(1, 1, 0, 21_000)])]);
+        assert!(db.check(&record(42, vec![tx(2, 1, 0, 21_000)]), 41, 100_000).is_err());
+
+        // The database can't be opened for another chain
+        assert!(matches!(
+            CommitmentsDb::open(&path, 17000).unwrap_err(),
+            CommitmentsDbError::ChainIdMismatch { expected: 17000, have: 1 }
+        ));
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_record_requires_recovered_senders() {
+        let txs = random_constraints(2);
+        let mut request = InclusionRequest { slot: 10, txs, signature: None, signer: None };
+
+        let hash = *request.txs[0].hash();
+        let err = SignedCommitmentRecord::from_request(&request).unwrap_err();
+        assert!(matches!(err, CommitmentsDbError::UnrecoveredSender(h) if h == hash));
+
+        request.recover_signers().unwrap();
+        let record = SignedCommitmentRecord::from_request(&request).unwrap();
+        assert_eq!(record.transactions.len(), 2);
+        assert_eq!(record.digest, request.digest());
+    }
+
+    #[test]
+    fn test_persists_journal_entries() {
+        let path = temp_file_path("bolt_commitments_db");
+
+        let mut db = CommitmentsDb::open(&path, 1).unwrap();
+        db.insert(record(10, vec![tx(1, 1, 0, 21_000)]), 9, 100_000).unwrap();
+        db.insert(record(11, vec![tx(2, 2, 0, 21_000)]), 9, 100_000).unwrap();
+
+        // Each insert appends a single entry after the metadata
+        let entries = Journal::read::<JournalEntry, CommitmentsDbError>(&path).unwrap();
+        assert!(matches!(entries[0], JournalEntry::Metadata(ref m) if m.chain_id == 1));
+        assert!(matches!(
+            &entries[1..],
+            [JournalEntry::Commitment(_), JournalEntry::Commitment(_)]
+        ));
+
+        // Persisting compacts the journal, dropping pruned records
+        db.insert(record(COMMITMENTS_RETENTION_SLOTS + 11, vec![]), 9, 100_000).unwrap();
+        db.persist().unwrap();
+        let entries = Journal::read::<JournalEntry, CommitmentsDbError>(&path).unwrap();
+        assert_eq!(entries.len(), 3);
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_import_export() {
+        let mut db = CommitmentsDb::in_memory(1);
+        db.insert(record(10, vec![tx(1, 1, 0, 21_000)]), 9, 100_000).unwrap();
+        db.insert(record(11, vec![tx(2, 2, 0, 21_000)]), 9, 100_000).unwrap();
+
+        let interchange = db.export();
+        assert_eq!(interchange.metadata.chain_id, 1);
+        assert_eq!(interchange.commitments.len(), 2);
+
+        let mut other = CommitmentsDb::in_memory(1);
+        other.insert(record(10, vec![tx(1, 1, 0, 21_000)]), 9, 100_000).unwrap();
+        assert_eq!(other.import(interchange.clone()).unwrap(), 1);
+        assert_eq!(other.export(), interchange);
+
+        let mut wrong_chain = CommitmentsDb::in_memory(17000);
+        assert!(wrong_chain.import(interchange).is_err());
+    }
+}
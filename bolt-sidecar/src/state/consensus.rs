@@ -5,6 +5,7 @@ use std::{
 
 use beacon_api_client::ProposerDuty;
 use ethereum_consensus::{crypto::PublicKey as BlsPublicKey, phase0::mainnet::SLOTS_PER_EPOCH};
+use serde::Serialize;
 use tokio::join;
 use tracing::debug;
 
@@ -28,6 +29,8 @@ pub enum ConsensusError {
     DeadlineExceeded,
     #[error("Validator not found in the slot")]
     ValidatorNotFound,
+    #[error("Validator {0:?} is not active in Bolt, commitments are paused")]
+    ValidatorPaused(BlsPublicKey),
 }
 
 /// Represents an epoch in the beacon chain.
@@ -87,6 +90,32 @@ impl fmt::Debug for ConsensusState {
     }
 }
 
+/// A snapshot of the consensus state, exposed in the admin API.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConsensusSnapshot {
+    /// The current epoch.
+    pub epoch: u64,
+    /// The start slot of the current epoch.
+    pub epoch_start_slot: Slot,
+    /// The latest slot received.
+    pub latest_slot: Slot,
+    /// Whether the next epoch's proposer duties are also considered.
+    pub unsafe_lookahead_enabled: bool,
+    /// The known proposer duties.
+    pub proposer_duties: Vec<ProposerDutySnapshot>,
+}
+
+/// A proposer duty of a validator.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProposerDutySnapshot {
+    /// The slot of the duty.
+    pub slot: Slot,
+    /// The index of the proposer.
+    pub validator_index: usize,
+    /// The public key of the proposer.
+    pub pubkey: BlsPublicKey,
+}
+
 impl ConsensusState {
     /// Create a new `ConsensusState` with the given configuration.
     pub fn new(
@@ -204,6 +233,51 @@ impl ConsensusState {
             .ok_or(ConsensusError::ValidatorNotFound)
     }
 
+    /// Returns the latest slot of the beacon chain clock.
+    pub fn latest_slot(&self) -> Slot {
+        self.latest_slot
+    }
+
+    /// Returns whether the commitment deadline for the given slot has passed, meaning that
+    /// its constraints should already have been submitted.
+    pub fn is_past_commitment_deadline(&self, slot: Slot) -> bool {
+        slot <= self.latest_slot ||
+            (slot == self.latest_slot + 1 &&
+                self.latest_slot_timestamp + self.commitment_deadline_duration <
+                    Instant::now())
+    }
+
+    /// Returns the known proposers of the given number of slots following the latest slot.
+    pub fn upcoming_proposers(&self, slots: u64) -> Vec<(Slot, BlsPublicKey)> {
+        let range = self.latest_slot + 1..=self.latest_slot + slots;
+        self.epoch
+            .proposer_duties
+            .iter()
+            .filter(|duty| range.contains(&duty.slot))
+            .map(|duty| (duty.slot, duty.public_key.clone()))
+            .collect()
+    }
+
+    /// Returns a snapshot of the current epoch and proposer duties.
+    pub fn snapshot(&self) -> ConsensusSnapshot {
+        ConsensusSnapshot {
+            epoch: self.epoch.value,
+            epoch_start_slot: self.epoch.start_slot,
+            latest_slot: self.latest_slot,
+            unsafe_lookahead_enabled: self.unsafe_lookahead_enabled,
+            proposer_duties: self
+                .epoch
+                .proposer_duties
+                .iter()
+                .map(|duty| ProposerDutySnapshot {
+                    slot: duty.slot,
+                    validator_index: duty.validator_index,
+                    pubkey: duty.public_key.clone(),
+                })
+                .collect(),
+        }
+    }
+
     /// Returns the furthest slot for which a commitment request is considered valid, whether in
     /// the current epoch or next epoch (if unsafe lookahead is enabled)
     fn furthest_slot(&self) -> u64 {
@@ -213,6 +287,24 @@ impl ConsensusState {
     }
 }
 
+#[cfg(test)]
+impl ConsensusState {
+    /// Sets the latest slot, received now, and the proposer duties of its epoch without fetching
+    /// them from the beacon chain.
+    pub fn set_proposer_duties(&mut self, latest_slot: Slot, duties: Vec<(Slot, BlsPublicKey)>) {
+        self.latest_slot = latest_slot;
+        self.latest_slot_timestamp = Instant::now();
+        self.epoch = Epoch {
+            value: latest_slot / SLOTS_PER_EPOCH,
+            start_slot: latest_slot / SLOTS_PER_EPOCH * SLOTS_PER_EPOCH,
+            proposer_duties: duties
+                .into_iter()
+                .map(|(slot, public_key)| ProposerDuty { public_key, validator_index: 0, slot })
+                .collect(),
+        };
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use beacon_api_client::BlockId;
@@ -220,7 +312,42 @@ mod tests {
     use tracing::warn;
 
     use super::*;
-    use crate::test_util::try_get_beacon_api_url;
+    use crate::{signer::local::LocalSigner, test_util::try_get_beacon_api_url};
+
+    fn test_consensus_state(commitment_deadline_duration: Duration) -> ConsensusState {
+        let beacon_client = BeaconClient::new(Url::parse("http://127.0.0.1:1").unwrap());
+        ConsensusState::new(beacon_client, commitment_deadline_duration, false)
+    }
+
+    #[test]
+    fn test_is_past_commitment_deadline() {
+        let mut state = test_consensus_state(Duration::from_millis(50));
+        state.set_proposer_duties(10, Vec::new());
+
+        assert!(state.is_past_commitment_deadline(9));
+        assert!(state.is_past_commitment_deadline(10));
+        assert!(!state.is_past_commitment_deadline(11));
+        assert!(!state.is_past_commitment_deadline(12));
+
+        // Only the deadline of the next slot passes during the latest slot
+        std::thread::sleep(Duration::from_millis(100));
+        assert!(state.is_past_commitment_deadline(11));
+        assert!(!state.is_past_commitment_deadline(12));
+    }
+
+    #[test]
+    fn test_upcoming_proposers() {
+        let mut state = test_consensus_state(Duration::from_secs(1));
+        let (first, second) = (LocalSigner::random().pubkey(), LocalSigner::random().pubkey());
+        state.set_proposer_duties(
+            10,
+            vec![(10, first.clone()), (11, first.clone()), (13, second.clone())],
+        );
+
+        assert_eq!(state.upcoming_proposers(2), vec![(11, first)]);
+        assert_eq!(state.upcoming_proposers(3).last(), Some(&(13, second)));
+        assert!(state.upcoming_proposers(0).is_empty());
+    }
 
     #[tokio::test]
     #[ignore = "TODO: fix"]
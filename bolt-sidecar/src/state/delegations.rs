@@ -0,0 +1,402 @@
+use std::{
+    collections::HashSet,
+    fs,
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use ethereum_consensus::crypto::PublicKey as BlsPublicKey;
+use eyre::bail;
+use parking_lot::RwLock;
+use tokio::sync::watch;
+use tracing::{error, info, warn};
+
+use crate::{
+    client::ConstraintsMultiplexer,
+    config::{ChainConfig, ReloadableOpts},
+    primitives::{
+        read_signed_delegations_from_file, read_signed_revocations_from_file, DelegationsReport,
+        SignedDelegation, SignedRevocation,
+    },
+};
+
+/// The interval at which the delegations and revocations files are checked for changes.
+const WATCH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// A (validator, delegatee) public key pair.
+type DelegationPair = (BlsPublicKey, BlsPublicKey);
+
+/// The set of active delegations and revoked delegatees.
+#[derive(Debug, Default)]
+struct DelegationSet {
+    /// The active delegations.
+    delegations: Vec<SignedDelegation>,
+    /// The (validator, delegatee) pairs that have been revoked.
+    revoked: HashSet<DelegationPair>,
+}
+
+/// A store of the signed delegations of our validators, shared between all the clients
+/// and tasks that need it, so that it can be updated at runtime.
+///
+/// Revocations are sticky: once a delegatee has been revoked for a validator, delegations to
+/// it are ignored unless explicitly re-enabled with [`DelegationStore::add_delegations`] and
+/// `force` set to `true`. This prevents stale delegation files from re-enabling revoked keys.
+#[derive(Debug, Clone, Default)]
+pub struct DelegationStore {
+    inner: Arc<RwLock<DelegationSet>>,
+}
+
+impl DelegationStore {
+    /// Adds a list of delegations to the store. If `force` is true, delegations to previously
+    /// revoked delegatees are re-enabled, otherwise they are ignored.
+    ///
+    /// Returns the delegations that were not already present in the store.
+    pub fn add_delegations(
+        &self,
+        delegations: Vec<SignedDelegation>,
+        force: bool,
+    ) -> Vec<SignedDelegation> {
+        let mut set = self.inner.write();
+
+        let mut added = Vec::new();
+        for delegation in delegations {
+            let pair = (delegation.validator_pubkey.clone(), delegation.delegatee_pubkey.clone());
+
+            if set.revoked.contains(&pair) {
+                if !force {
+                    continue;
+                }
+                set.revoked.remove(&pair);
+            }
+
+            if set.delegations.iter().any(|d| d == &delegation) {
+                continue;
+            }
+
+            set.delegations.push(delegation.clone());
+            added.push(delegation);
+        }
+
+        added
+    }
+
+    /// Applies a list of revocations, removing the matching delegations from the store.
+    ///
+    /// Returns the revocations that were not already applied.
+    pub fn revoke(&self, revocations: Vec<SignedRevocation>) -> Vec<SignedRevocation> {
+        let mut set = self.inner.write();
+
+        let mut applied = Vec::new();
+        for revocation in revocations {
+            let pair = (revocation.validator_pubkey.clone(), revocation.delegatee_pubkey.clone());
+
+            set.delegations.retain(|d| {
+                d.validator_pubkey != revocation.validator_pubkey ||
+                    d.delegatee_pubkey != revocation.delegatee_pubkey
+            });
+
+            if set.revoked.insert(pair) {
+                applied.push(revocation);
+            }
+        }
+
+        applied
+    }
+
+    /// Removes the given delegations from the store, without revoking their delegatees.
+    ///
+    /// Returns the number of delegations that were removed.
+    pub fn remove_delegations(&self, delegations: &[SignedDelegation]) -> usize {
+        let mut set = self.inner.write();
+
+        let before = set.delegations.len();
+        set.delegations.retain(|d| !delegations.contains(d));
+        before - set.delegations.len()
+    }
+
+    /// Returns true if the delegatee has been revoked for the given validator.
+    pub fn is_revoked(&self, validator_pubkey: &BlsPublicKey, delegatee: &BlsPublicKey) -> bool {
+        self.inner.read().revoked.contains(&(validator_pubkey.clone(), delegatee.clone()))
+    }
+
+    /// Returns the active delegatees of the given validator public key.
+    pub fn delegatees(&self, validator_pubkey: &BlsPublicKey) -> HashSet<BlsPublicKey> {
+        self.inner
+            .read()
+            .delegations
+            .iter()
+            .filter(|d| d.validator_pubkey == *validator_pubkey)
+            .map(|d| d.delegatee_pubkey.clone())
+            .collect()
+    }
+
+    /// Returns the active delegations of the given validator public keys.
+    pub fn delegations_for(
+        &self,
+        validator_pubkeys: &HashSet<&BlsPublicKey>,
+    ) -> Vec<SignedDelegation> {
+        self.inner
+            .read()
+            .delegations
+            .iter()
+            .filter(|d| validator_pubkeys.contains(&d.validator_pubkey))
+            .cloned()
+            .collect()
+    }
+
+    /// Returns all the active delegations.
+    pub fn all(&self) -> Vec<SignedDelegation> {
+        self.inner.read().delegations.clone()
+    }
+
+    /// Returns the validator public keys with at least one active delegation.
+    pub fn validator_pubkeys(&self) -> HashSet<BlsPublicKey> {
+        self.inner.read().delegations.iter().map(|d| d.validator_pubkey.clone()).collect()
+    }
+
+    /// Returns true if there are no active delegations.
+    pub fn is_empty(&self) -> bool {
+        self.inner.read().delegations.is_empty()
+    }
+}
+
+/// Watches the delegations and revocations files for changes, loading new delegations and
+/// revocations at runtime and propagating them to the relays.
+#[derive(Debug)]
+pub struct DelegationsWatcher {
+    client: ConstraintsMultiplexer,
+    delegations_path: Option<PathBuf>,
+    revocations_path: Option<PathBuf>,
+    /// The chain and available signer public keys to verify loaded delegations against.
+    verification: Option<(ChainConfig, HashSet<BlsPublicKey>)>,
+    /// The last modification times of the delegations and revocations files.
+    last_modified: (Option<SystemTime>, Option<SystemTime>),
+    /// The options reloaded at runtime, from which the delegations path is followed.
+    reloads: Option<watch::Receiver<ReloadableOpts>>,
+    /// The delegations loaded from the current delegations path.
+    loaded: Vec<SignedDelegation>,
+}
+
+impl DelegationsWatcher {
+    /// Creates a new watcher for the given files, updating the delegations of the given client.
+    pub fn new(
+        client: ConstraintsMultiplexer,
+        delegations_path: Option<PathBuf>,
+        revocations_path: Option<PathBuf>,
+    ) -> Self {
+        Self {
+            client,
+            delegations_path,
+            revocations_path,
+            verification: None,
+            last_modified: (None, None),
+            reloads: None,
+            loaded: Vec::new(),
+        }
+    }
+
+    /// Verifies the signatures of all loaded delegations and revocations against the
+    /// commit-boost domain of the given chain, and that every delegatee is among the given
+    /// available signer public keys. Files that fail verification are rejected as a whole.
+    pub fn with_verification(
+        mut self,
+        chain: ChainConfig,
+        available_pubkeys: HashSet<BlsPublicKey>,
+    ) -> Self {
+        self.verification = Some((chain, available_pubkeys));
+        self
+    }
+
+    /// Follows the delegations path reloaded at runtime from the given channel. Delegations
+    /// loaded from a previous path are dropped from the store when the path changes, but the
+    /// relays keep them until they are revoked.
+    pub fn with_reloads(mut self, reloads: watch::Receiver<ReloadableOpts>) -> Self {
+        self.reloads = Some(reloads);
+        self
+    }
+
+    /// Switches to the given delegations path, dropping the delegations loaded from the
+    /// previous one and loading the new one on the next reload.
+    fn set_delegations_path(&mut self, path: Option<PathBuf>) {
+        if path != self.delegations_path {
+            let dropped = self.client.delegations().remove_delegations(&self.loaded);
+            info!(?path, previous = ?self.delegations_path, dropped, "Delegations path changed");
+
+            self.delegations_path = path;
+            self.last_modified.0 = None;
+            self.loaded.clear();
+        }
+    }
+
+    /// Reloads the delegations and revocations files if they changed since the last call.
+    /// Files that fail to be read or verified are ignored until they change again.
+    ///
+    /// Revocations are applied after delegations, so that a delegatee present in both files is
+    /// always considered revoked. Failures to propagate changes to the relays are only logged.
+    pub async fn reload(&mut self) -> eyre::Result<()> {
+        if let Some(path) = &self.delegations_path {
+            if let Some(modified) = changed_since(path, self.last_modified.0)? {
+                self.last_modified.0 = Some(modified);
+
+                let delegations = read_signed_delegations_from_file(path)?;
+                info!(count = delegations.len(), ?path, "Loaded signed delegations from disk");
+
+                if let Some((chain, available_pubkeys)) = &self.verification {
+                    let report = DelegationsReport::new(chain, &delegations, available_pubkeys);
+                    report.log();
+                    report.into_result()?;
+                }
+
+                self.loaded.clone_from(&delegations);
+                if let Err(err) = self.client.update_delegations(delegations, false).await {
+                    warn!(?err, "Failed to propagate new delegations to relays");
+                }
+            }
+        }
+
+        if let Some(path) = &self.revocations_path {
+            if let Some(modified) = changed_since(path, self.last_modified.1)? {
+                self.last_modified.1 = Some(modified);
+
+                let revocations = read_signed_revocations_from_file(path)?;
+                info!(count = revocations.len(), ?path, "Loaded signed revocations from disk");
+
+                if let Some((chain, _)) = &self.verification {
+                    if let Some(invalid) = revocations.iter().find(|r| !r.verify(chain)) {
+                        bail!(
+                            "invalid signature for revocation {:?} -> {:?}",
+                            invalid.validator_pubkey,
+                            invalid.delegatee_pubkey
+                        );
+                    }
+                }
+
+                if let Err(err) = self.client.revoke_delegations(revocations).await {
+                    warn!(?err, "Failed to propagate new revocations to relays");
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Spawns a background task that periodically reloads the files when they change, or when
+    /// the delegations path is changed at runtime.
+    pub fn spawn(mut self) {
+        if self.delegations_path.is_none() &&
+            self.revocations_path.is_none() &&
+            self.reloads.is_none()
+        {
+            return;
+        }
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(WATCH_INTERVAL);
+            loop {
+                tokio::select! {
+                    _ = interval.tick() => {}
+                    Some(Ok(())) = async { Some(self.reloads.as_mut()?.changed().await) } => {
+                        if let Some(reloads) = &mut self.reloads {
+                            let path = reloads.borrow_and_update().delegations_path.clone();
+                            self.set_delegations_path(path);
+                        }
+                    }
+                }
+
+                if let Err(err) = self.reload().await {
+                    error!(?err, "Failed to reload delegations and revocations from disk");
+                }
+            }
+        });
+    }
+}
+
+/// Returns the modification time of the file at the given path if it changed since `last`.
+fn changed_since(path: &PathBuf, last: Option<SystemTime>) -> eyre::Result<Option<SystemTime>> {
+    let modified = fs::metadata(path)?.modified()?;
+    Ok((last != Some(modified)).then_some(modified))
+}
+
+#[cfg(test)]
+mod tests {
+    use ethereum_consensus::crypto::Signature as BlsSignature;
+
+    use super::*;
+    use crate::{
+        primitives::{DelegationMessage, RevocationMessage},
+        signer::local::LocalSigner,
+    };
+
+    fn delegation(validator: &BlsPublicKey, delegatee: &BlsPublicKey) -> SignedDelegation {
+        SignedDelegation {
+            message: DelegationMessage::new(validator.clone(), delegatee.clone()),
+            signature: BlsSignature::default(),
+        }
+    }
+
+    fn revocation(validator: &BlsPublicKey, delegatee: &BlsPublicKey) -> SignedRevocation {
+        SignedRevocation {
+            message: RevocationMessage::new(validator.clone(), delegatee.clone()),
+            signature: BlsSignature::default(),
+        }
+    }
+
+    #[test]
+    fn test_revocations_are_sticky() {
+        let store = DelegationStore::default();
+        let validator = LocalSigner::random().pubkey();
+        let delegatee = LocalSigner::random().pubkey();
+
+        let added = store.add_delegations(vec![delegation(&validator, &delegatee)], false);
+        assert_eq!(added.len(), 1);
+        assert!(store.delegatees(&validator).contains(&delegatee));
+
+        // Adding the same delegation again is a no-op
+        assert!(store.add_delegations(vec![delegation(&validator, &delegatee)], false).is_empty());
+
+        let applied = store.revoke(vec![revocation(&validator, &delegatee)]);
+        assert_eq!(applied.len(), 1);
+        assert!(store.is_revoked(&validator, &delegatee));
+        assert!(store.delegatees(&validator).is_empty());
+
+        // Reloading a stale delegation doesn't re-enable the delegatee
+        assert!(store.add_delegations(vec![delegation(&validator, &delegatee)], false).is_empty());
+        assert!(store.delegatees(&validator).is_empty());
+
+        // Unless explicitly forced
+        assert_eq!(store.add_delegations(vec![delegation(&validator, &delegatee)], true).len(), 1);
+        assert!(!store.is_revoked(&validator, &delegatee));
+        assert!(store.delegatees(&validator).contains(&delegatee));
+    }
+
+    #[tokio::test]
+    async fn test_delegations_path_change_drops_previous_delegations() {
+        let validator = LocalSigner::random().pubkey();
+        let (first, second) = (LocalSigner::random().pubkey(), LocalSigner::random().pubkey());
+
+        let write_delegations = |delegatee: &BlsPublicKey| {
+            let path = std::env::temp_dir()
+                .join(format!("bolt_sidecar_delegations_{}.json", rand::random::<u64>()));
+            let delegations = vec![delegation(&validator, delegatee)];
+            fs::write(&path, serde_json::to_string(&delegations).unwrap()).unwrap();
+            path
+        };
+        let (first_path, second_path) = (write_delegations(&first), write_delegations(&second));
+
+        let client = ConstraintsMultiplexer::new(Vec::new());
+        let mut watcher = DelegationsWatcher::new(client.clone(), Some(first_path.clone()), None);
+        watcher.reload().await.unwrap();
+        assert_eq!(client.delegations().delegatees(&validator), HashSet::from([first.clone()]));
+
+        watcher.set_delegations_path(Some(second_path.clone()));
+        assert!(client.delegations().is_empty());
+
+        watcher.reload().await.unwrap();
+        assert_eq!(client.delegations().delegatees(&validator), HashSet::from([second]));
+        assert!(!client.delegations().is_revoked(&validator, &first));
+
+        fs::remove_file(first_path).unwrap();
+        fs::remove_file(second_path).unwrap();
+    }
+}
@@ -3,9 +3,11 @@ use alloy::{
     consensus::{BlobTransactionValidationError, EnvKzgSettings},
     eips::eip4844::MAX_BLOBS_PER_BLOCK,
     primitives::Address,
-    primitives::U256,
+    primitives::{TxHash, U256},
     transports::TransportError,
 };
+use ethereum_consensus::crypto::PublicKey as BlsPublicKey;
+use serde::Serialize;
 use std::collections::HashMap;
 use thiserror::Error;
 use tracing::{debug, error, trace, warn};
@@ -21,7 +23,10 @@ use crate::{
     telemetry::ApiMetrics,
 };
 
-use super::{account_state::AccountStateCache, fetcher::StateFetcher};
+use super::{
+    account_state::AccountStateCache, fetcher::StateFetcher, fulfillment::FulfillmentAudits,
+    ValidatorStatuses,
+};
 
 /// Possible commitment validation errors.
 ///
@@ -160,6 +165,10 @@ pub struct ExecutionState<C> {
     chain_id: u64,
     /// The limits set for the sidecar.
     limits: LimitsOpts,
+    /// The on-chain statuses of the validators, holding their maximum committed gas limits.
+    validator_statuses: ValidatorStatuses,
+    /// The audits of the commitments made for past slots.
+    fulfillment: FulfillmentAudits,
     /// The KZG settings for validating blobs.
     kzg_settings: EnvKzgSettings,
     /// The state fetcher client.
@@ -214,6 +223,8 @@ impl<C: StateFetcher> ExecutionState<C> {
             block_number,
             chain_id,
             limits,
+            validator_statuses: ValidatorStatuses::default(),
+            fulfillment: FulfillmentAudits::default(),
             client,
             slot: 0,
             account_states: AccountStateCache(ScoreCache::with_max_len(num_accounts)),
@@ -225,11 +236,40 @@ impl<C: StateFetcher> ExecutionState<C> {
         })
     }
 
+    /// Applies the maximum committed gas limits registered on-chain by the validators, on top of
+    /// the local [`LimitsOpts::max_committed_gas_per_slot`].
+    pub fn with_validator_statuses(mut self, validator_statuses: ValidatorStatuses) -> Self {
+        self.validator_statuses = validator_statuses;
+        self
+    }
+
+    /// Records the audits of the commitments made for past slots in the given store.
+    pub fn with_fulfillment_audits(mut self, fulfillment: FulfillmentAudits) -> Self {
+        self.fulfillment = fulfillment;
+        self
+    }
+
+    /// Replaces the operating limits, e.g. when they are reloaded from the configuration file.
+    /// Changes to the maximum size of the account states cache are ignored.
+    pub fn set_limits(&mut self, limits: LimitsOpts) {
+        self.limits =
+            LimitsOpts { max_account_states_size: self.limits.max_account_states_size, ..limits };
+    }
+
     /// Returns the current base fee in gwei
     pub fn basefee(&self) -> u128 {
         self.basefee
     }
 
+    /// Returns the maximum gas that can be committed in a slot of the given proposer: the smaller
+    /// of the local limit and the one registered on-chain by the proposer, if any.
+    pub fn max_committed_gas_for(&self, proposer: Option<&BlsPublicKey>) -> u64 {
+        let local = self.limits.max_committed_gas_per_slot.get();
+        proposer
+            .and_then(|pubkey| self.validator_statuses.max_committed_gas_limit(pubkey))
+            .map_or(local, |onchain| onchain.min(local))
+    }
+
     /// Validates the commitment request against state (historical + intermediate).
     ///
     /// NOTE: This function only simulates against execution state, it does not consider
@@ -240,10 +280,14 @@ impl<C: StateFetcher> ExecutionState<C> {
     /// will be cached. If this is succesful, any callers can be sure that the commitment is valid
     /// and SHOULD sign it and respond to the requester.
     ///
+    /// The `proposer` of the target slot, if known, is used to apply its on-chain maximum
+    /// committed gas limit.
+    ///
     /// TODO: should also validate everything in https://github.com/paradigmxyz/reth/blob/9aa44e1a90b262c472b14cd4df53264c649befc2/crates/transaction-pool/src/validate/eth.rs#L153
     pub async fn validate_request(
         &mut self,
         req: &mut InclusionRequest,
+        proposer: Option<&BlsPublicKey>,
     ) -> Result<(), ValidationError> {
         req.recover_signers()?;
 
@@ -268,11 +312,11 @@ impl<C: StateFetcher> ExecutionState<C> {
         let template_committed_gas =
             self.get_block_template(target_slot).map(|t| t.committed_gas()).unwrap_or(0);
 
-        if template_committed_gas + req.gas_limit() >= self.limits.max_committed_gas_per_slot.get()
-        {
+        let max_committed_gas = self.max_committed_gas_for(proposer);
+        if template_committed_gas + req.gas_limit() >= max_committed_gas {
             return Err(ValidationError::MaxCommittedGasReachedForSlot(
                 self.slot,
-                self.limits.max_committed_gas_per_slot.get(),
+                max_committed_gas,
             ));
         }
 
@@ -417,6 +461,13 @@ impl<C: StateFetcher> ExecutionState<C> {
         Ok(())
     }
 
+    /// Returns the slots for which a block template exists, in ascending order.
+    pub fn block_template_slots(&self) -> Vec<Slot> {
+        let mut slots = self.block_templates.keys().copied().collect::<Vec<_>>();
+        slots.sort();
+        slots
+    }
+
     /// Commits the transaction to the target block. Initializes a new block template
     /// if one does not exist for said block number.
     pub fn add_constraint(&mut self, target_slot: u64, signed_constraints: SignedConstraints) {
@@ -446,13 +497,18 @@ impl<C: StateFetcher> ExecutionState<C> {
         // Remove any block templates that are no longer valid
         // NOTE: this needs to be called BEFORE applying the state update or we might remove
         // constraints for which we need to get the receipts.
-        for template in self.remove_block_templates_until(slot) {
-            debug!(%slot, "Removed block template for slot");
+        for (template_slot, template) in self.remove_block_templates_until(slot) {
+            debug!(%slot, %template_slot, "Removed block template for slot");
             let hashes = template.transaction_hashes();
             let receipts = self.client.get_receipts_unordered(hashes.as_ref()).await?;
 
+            let mut inclusions = HashMap::with_capacity(receipts.len());
             let mut receipts_len = 0;
             for receipt in receipts.iter().flatten() {
+                if let (Some(hash), Some(number)) = (receipt.block_hash, receipt.block_number) {
+                    inclusions.insert(receipt.transaction_hash, (hash, number));
+                }
+
                 // Calculate the total tip revenue for this transaction:
                 // (effective_gas_price - basefee) * gas_used
                 let tip_per_gas = receipt.effective_gas_price - self.basefee;
@@ -478,6 +534,8 @@ impl<C: StateFetcher> ExecutionState<C> {
                     }
                 });
             }
+
+            self.fulfillment.audit(template_slot, &hashes, &inclusions).await;
         }
 
         self.apply_state_update(update);
@@ -535,7 +593,50 @@ impl<C: StateFetcher> ExecutionState<C> {
         self.block_templates.get(&slot)
     }
 
-    /// Removes all the block templates which slot is less then or equal `slot`, and returns them.
+    /// Returns a snapshot of the execution state and of the pending block templates.
+    pub fn snapshot(&self) -> ExecutionSnapshot {
+        let mut block_templates = self
+            .block_templates
+            .iter()
+            .map(|(slot, template)| BlockTemplateSnapshot {
+                slot: *slot,
+                transactions: template.transaction_hashes(),
+                constraints: template.signed_constraints_list.len(),
+                committed_gas: template.committed_gas(),
+                blob_count: template.blob_count(),
+            })
+            .collect::<Vec<_>>();
+        block_templates.sort_by_key(|template| template.slot);
+
+        ExecutionSnapshot {
+            chain_id: self.chain_id,
+            block_number: self.block_number,
+            slot: self.slot,
+            basefee: self.basefee,
+            blob_basefee: self.blob_basefee,
+            block_templates,
+        }
+    }
+
+    /// Returns the cached account states, sorted by address.
+    pub fn account_states_snapshot(&self) -> Vec<AccountSnapshot> {
+        let mut accounts = self
+            .account_states
+            .iter()
+            .map(|(address, (state, _))| AccountSnapshot {
+                address: *address,
+                nonce: state.transaction_count,
+                balance: state.balance,
+                has_code: state.has_code,
+            })
+            .collect::<Vec<_>>();
+        accounts.sort_by_key(|account| account.address);
+
+        accounts
+    }
+
+    /// Removes all the block templates which slot is less then or equal `slot`, and returns them
+    /// along with their slot, in ascending slot order.
     ///
     /// This should be called when we need to propose a block for the given slot, or when a new
     /// head comes in which makes an older block templates useless.
@@ -543,7 +644,7 @@ impl<C: StateFetcher> ExecutionState<C> {
     /// NOTE: We remove all previous block templates to ensure that, when a new head is received
     /// from the beacon client, all stale template are cleared. This prevents outdated templates
     /// from persisting in cases of missed slots, where such events are not emitted.
-    pub fn remove_block_templates_until(&mut self, slot: u64) -> Vec<BlockTemplate> {
+    pub fn remove_block_templates_until(&mut self, slot: u64) -> Vec<(Slot, BlockTemplate)> {
         let mut slots_to_remove =
             self.block_templates.keys().filter(|s| **s <= slot).copied().collect::<Vec<_>>();
         slots_to_remove.sort();
@@ -551,7 +652,7 @@ impl<C: StateFetcher> ExecutionState<C> {
         let mut templates = Vec::with_capacity(slots_to_remove.len());
         for s in slots_to_remove {
             if let Some(template) = self.block_templates.remove(&s) {
-                templates.push(template);
+                templates.push((s, template));
             }
         }
 
@@ -559,6 +660,51 @@ impl<C: StateFetcher> ExecutionState<C> {
     }
 }
 
+/// A snapshot of the execution state, exposed in the admin API.
+#[derive(Debug, Clone, Serialize)]
+pub struct ExecutionSnapshot {
+    /// The chain ID.
+    pub chain_id: u64,
+    /// The latest block number.
+    pub block_number: u64,
+    /// The latest slot number.
+    pub slot: Slot,
+    /// The basefee at the head block.
+    pub basefee: u128,
+    /// The blob basefee at the head block.
+    pub blob_basefee: u128,
+    /// The pending block templates, sorted by slot.
+    pub block_templates: Vec<BlockTemplateSnapshot>,
+}
+
+/// A summary of a pending block template.
+#[derive(Debug, Clone, Serialize)]
+pub struct BlockTemplateSnapshot {
+    /// The target slot of the template.
+    pub slot: Slot,
+    /// The hashes of the committed transactions.
+    pub transactions: Vec<TxHash>,
+    /// The number of signed constraints.
+    pub constraints: usize,
+    /// The gas committed in the template.
+    pub committed_gas: u64,
+    /// The number of blobs committed in the template.
+    pub blob_count: usize,
+}
+
+/// A cached account state.
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountSnapshot {
+    /// The account address.
+    pub address: Address,
+    /// The nonce of the account.
+    pub nonce: u64,
+    /// The balance of the account in wei.
+    pub balance: U256,
+    /// Whether the account is a smart contract.
+    pub has_code: bool,
+}
+
 #[derive(Debug, Clone)]
 pub struct StateUpdate {
     pub account_states: HashMap<Address, AccountState>,
@@ -685,7 +831,7 @@ mod tests {
 
         let mut request = create_signed_inclusion_request(&[tx], sender_pk, 10).await?;
 
-        assert!(state.validate_request(&mut request).await.is_ok());
+        assert!(state.validate_request(&mut request, None).await.is_ok());
 
         Ok(())
     }
@@ -722,7 +868,7 @@ mod tests {
         state.update_head(None, 11).await?;
 
         assert!(matches!(
-            state.validate_request(&mut request).await,
+            state.validate_request(&mut request, None).await,
             Err(ValidationError::SlotTooLow(11))
         ));
 
@@ -760,7 +906,7 @@ mod tests {
         let mut request = create_signed_inclusion_request(&[tx], sender_pk, 10).await?;
 
         assert!(matches!(
-            state.validate_request(&mut request).await,
+            state.validate_request(&mut request, None).await,
             Err(ValidationError::NonceTooLow(1, 0))
         ));
 
@@ -772,7 +918,7 @@ mod tests {
         let mut request = create_signed_inclusion_request(&[tx], sender_pk, 10).await?;
 
         assert!(matches!(
-            state.validate_request(&mut request).await,
+            state.validate_request(&mut request, None).await,
             Err(ValidationError::NonceTooHigh(1, 2))
         ));
 
@@ -803,7 +949,7 @@ mod tests {
         let mut request = create_signed_inclusion_request(&[tx], sender_pk, 10).await?;
 
         assert!(matches!(
-            state.validate_request(&mut request).await,
+            state.validate_request(&mut request, None).await,
             Err(ValidationError::InsufficientBalance)
         ));
 
@@ -848,7 +994,7 @@ mod tests {
         let tx = default_test_transaction(*sender, Some(1));
         let mut request = create_signed_inclusion_request(&[tx], sender_pk, 10).await?;
 
-        assert!(state.validate_request(&mut request).await.is_ok());
+        assert!(state.validate_request(&mut request, None).await.is_ok());
 
         let message = ConstraintsMessage::build(Default::default(), request.clone());
         let signature = signer.sign_commit_boost_root(message.digest())?;
@@ -862,7 +1008,7 @@ mod tests {
         // this should fail because the balance is insufficient as we spent
         // all of it on the previous preconfirmation
         assert!(matches!(
-            state.validate_request(&mut request).await,
+            state.validate_request(&mut request, None).await,
             Err(ValidationError::InsufficientBalance)
         ));
 
@@ -896,7 +1042,7 @@ mod tests {
         let mut request = create_signed_inclusion_request(&[tx], sender_pk, 10).await?;
 
         assert!(matches!(
-            state.validate_request(&mut request).await,
+            state.validate_request(&mut request, None).await,
             Err(ValidationError::BaseFeeTooLow(_))
         ));
 
@@ -928,13 +1074,54 @@ mod tests {
         let mut request = create_signed_inclusion_request(&[tx], sender_pk, 10).await?;
 
         assert!(matches!(
-            state.validate_request(&mut request).await,
+            state.validate_request(&mut request, None).await,
             Err(ValidationError::MaxCommittedGasReachedForSlot(_, 5_000_000))
         ));
 
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_invalid_inclusion_request_with_excess_onchain_gas() -> eyre::Result<()> {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let anvil = launch_anvil();
+        let client = StateClient::new(anvil.endpoint_url());
+
+        let proposer = LocalSigner::random().pubkey();
+        let validator_statuses = ValidatorStatuses::default();
+        validator_statuses
+            .update_max_committed_gas_limits(HashMap::from([(proposer.clone(), 5_000_000)]));
+
+        let limits = LimitsOpts::default();
+        let mut state = ExecutionState::new(client.clone(), limits, DEFAULT_GAS_LIMIT)
+            .await?
+            .with_validator_statuses(validator_statuses);
+
+        let sender = anvil.addresses().first().unwrap();
+        let sender_pk = anvil.keys().first().unwrap();
+
+        // initialize the state by updating the head once
+        let slot = client.get_head().await?;
+        state.update_head(None, slot).await?;
+
+        let tx = default_test_transaction(*sender, None).with_gas_limit(6_000_000);
+
+        // The on-chain limit of the proposer is lower than the local one
+        let mut request = create_signed_inclusion_request(&[tx.clone()], sender_pk, 10).await?;
+        assert!(matches!(
+            state.validate_request(&mut request, Some(&proposer)).await,
+            Err(ValidationError::MaxCommittedGasReachedForSlot(_, 5_000_000))
+        ));
+
+        // Other proposers are only bound by the local limit
+        let mut request = create_signed_inclusion_request(&[tx], sender_pk, 10).await?;
+        let other = LocalSigner::random().pubkey();
+        assert!(state.validate_request(&mut request, Some(&other)).await.is_ok());
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_invalid_inclusion_request_min_priority_fee() -> eyre::Result<()> {
         let anvil = launch_anvil();
@@ -958,7 +1145,7 @@ mod tests {
         let mut request = create_signed_inclusion_request(&[tx], sender_pk, 10).await?;
 
         assert!(matches!(
-            state.validate_request(&mut request).await,
+            state.validate_request(&mut request, None).await,
             Err(ValidationError::MaxPriorityFeePerGasTooLow)
         ));
 
@@ -968,7 +1155,7 @@ mod tests {
 
         let mut request = create_signed_inclusion_request(&[tx], sender_pk, 10).await?;
 
-        assert!(state.validate_request(&mut request).await.is_ok());
+        assert!(state.validate_request(&mut request, None).await.is_ok());
 
         Ok(())
     }
@@ -1001,7 +1188,7 @@ mod tests {
         let mut request = create_signed_inclusion_request(&[tx], sender_pk, 10).await?;
 
         assert!(matches!(
-            state.validate_request(&mut request).await,
+            state.validate_request(&mut request, None).await,
             Err(ValidationError::MaxPriorityFeePerGasTooLow)
         ));
 
@@ -1011,7 +1198,7 @@ mod tests {
 
         let mut request = create_signed_inclusion_request(&[tx], sender_pk, 10).await?;
 
-        assert!(state.validate_request(&mut request).await.is_ok());
+        assert!(state.validate_request(&mut request, None).await.is_ok());
 
         Ok(())
     }
@@ -1043,7 +1230,7 @@ mod tests {
 
         let mut request = create_signed_inclusion_request(&[tx.clone(), tx], sender_pk, 10).await?;
 
-        let response = state.validate_request(&mut request).await;
+        let response = state.validate_request(&mut request, None).await;
         println!("{response:?}");
 
         assert!(matches!(response, Err(ValidationError::NonceTooLow(_, _))));
@@ -1080,7 +1267,7 @@ mod tests {
         let mut request = create_signed_inclusion_request(&[tx], sender_pk, target_slot).await?;
         let inclusion_request = request.clone();
 
-        assert!(state.validate_request(&mut request).await.is_ok());
+        assert!(state.validate_request(&mut request, None).await.is_ok());
 
         let bls_signer = LocalSigner::random();
         let message = ConstraintsMessage::build(Default::default(), inclusion_request);
@@ -1129,7 +1316,7 @@ mod tests {
         let mut request = create_signed_inclusion_request(&[tx], sender_pk, target_slot).await?;
         let inclusion_request = request.clone();
 
-        assert!(state.validate_request(&mut request).await.is_ok());
+        assert!(state.validate_request(&mut request, None).await.is_ok());
 
         let bls_signer = LocalSigner::random();
         let message = ConstraintsMessage::build(Default::default(), inclusion_request);
@@ -1173,7 +1360,7 @@ mod tests {
         let mut request = create_signed_inclusion_request(&[tx], sender_pk, target_slot).await?;
         let inclusion_request = request.clone();
 
-        assert!(state.validate_request(&mut request).await.is_ok());
+        assert!(state.validate_request(&mut request, None).await.is_ok());
 
         let bls_signer = LocalSigner::random();
         let message = ConstraintsMessage::build(Default::default(), inclusion_request);
@@ -1190,7 +1377,7 @@ mod tests {
         let mut request = create_signed_inclusion_request(&[tx], sender_pk, 10).await?;
 
         assert!(matches!(
-            state.validate_request(&mut request).await,
+            state.validate_request(&mut request, None).await,
             Err(ValidationError::MaxCommittedGasReachedForSlot(_, DEFAULT_MAX_COMMITTED_GAS))
         ));
 
@@ -1220,7 +1407,7 @@ mod tests {
 
         let mut request = create_signed_inclusion_request(&[tx1, tx2, tx3], sender_pk, 10).await?;
 
-        assert!(state.validate_request(&mut request).await.is_ok());
+        assert!(state.validate_request(&mut request, None).await.is_ok());
 
         Ok(())
     }
@@ -1249,7 +1436,7 @@ mod tests {
         let mut request = create_signed_inclusion_request(&[tx1, tx2, tx3], sender_pk, 10).await?;
 
         assert!(matches!(
-            state.validate_request(&mut request).await,
+            state.validate_request(&mut request, None).await,
             Err(ValidationError::NonceTooHigh(2, 3))
         ));
 
@@ -1281,7 +1468,7 @@ mod tests {
         let mut request = create_signed_inclusion_request(&[tx1, tx2, tx3], sender_pk, 10).await?;
 
         assert!(matches!(
-            state.validate_request(&mut request).await,
+            state.validate_request(&mut request, None).await,
             Err(ValidationError::InsufficientBalance)
         ));
 
@@ -1315,7 +1502,7 @@ mod tests {
         let mut request = create_signed_inclusion_request(&[tx], sender_pk, target_slot).await?;
         let inclusion_request = request.clone();
 
-        let request_validation = state.validate_request(&mut request).await;
+        let request_validation = state.validate_request(&mut request, None).await;
         println!("request validation = {:?}", request_validation);
         assert!(request_validation.is_ok());
 
@@ -1336,7 +1523,7 @@ mod tests {
         let mut request = create_signed_inclusion_request(&[tx], sender_pk, target_slot).await?;
         let inclusion_request = request.clone();
 
-        let request_validation = state.validate_request(&mut request).await;
+        let request_validation = state.validate_request(&mut request, None).await;
         println!("request validation = {:?}", request_validation);
         assert!(request_validation.is_ok());
 
@@ -0,0 +1,303 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
+
+use alloy::primitives::{TxHash, B256};
+use parking_lot::{Mutex, RwLock};
+use serde::Serialize;
+use tracing::{error, info, warn};
+
+use crate::{
+    client::{beacon::BeaconClientResult, BeaconClient},
+    primitives::Slot,
+    telemetry::ApiMetrics,
+};
+
+use super::signed_headers::{HeaderSource, SignedHeadersDb};
+
+/// The number of slots for which fulfillment audits are retained in memory (~1 day on mainnet).
+pub const FULFILLMENT_RETENTION_SLOTS: u64 = 8192;
+
+/// The outcome of the commitments made for a slot, once the slot is over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FulfillmentOutcome {
+    /// The committed transactions were included in the block of the slot.
+    Fulfilled,
+    /// A block was proposed for the slot, but without the committed transactions.
+    Breached,
+    /// No block was proposed for the slot.
+    SlotMissed,
+}
+
+impl FulfillmentOutcome {
+    /// Returns the outcome as a string, used as a metrics label.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Fulfilled => "fulfilled",
+            Self::Breached => "breached",
+            Self::SlotMissed => "slot_missed",
+        }
+    }
+}
+
+/// The execution block proposed for a slot.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ProposedBlock {
+    /// The block number.
+    pub number: u64,
+    /// The block hash.
+    pub hash: B256,
+    /// Where the block was sourced from, if it matches a header signed through the sidecar.
+    /// If `None`, the block was not proposed through the sidecar.
+    pub source: Option<HeaderSource>,
+}
+
+/// The audit of a transaction committed for a slot.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CommittedTxAudit {
+    /// The transaction hash.
+    pub hash: TxHash,
+    /// The number of the block in which the transaction landed, if any.
+    pub block_number: Option<u64>,
+    /// The outcome of the commitment for this transaction.
+    pub outcome: FulfillmentOutcome,
+}
+
+/// The audit of the commitments made for a past slot.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SlotAudit {
+    /// The audited slot.
+    pub slot: Slot,
+    /// The block proposed for the slot, or `None` if the slot was missed.
+    pub block: Option<ProposedBlock>,
+    /// The overall outcome for the slot: breached if any committed transaction is breached.
+    pub outcome: FulfillmentOutcome,
+    /// The audits of the committed transactions.
+    pub transactions: Vec<CommittedTxAudit>,
+}
+
+impl SlotAudit {
+    /// Audits the transactions committed for a slot, given the hash and number of the block in
+    /// which each of them landed, as found in their receipts. A transaction is fulfilled only if
+    /// it landed in the exact block proposed for the slot.
+    pub fn new(
+        slot: Slot,
+        block: Option<ProposedBlock>,
+        hashes: &[TxHash],
+        inclusions: &HashMap<TxHash, (B256, u64)>,
+    ) -> Self {
+        let transactions = hashes
+            .iter()
+            .map(|hash| {
+                let inclusion = inclusions.get(hash);
+                let outcome = match &block {
+                    None => FulfillmentOutcome::SlotMissed,
+                    Some(block) if inclusion.is_some_and(|(h, _)| *h == block.hash) => {
+                        FulfillmentOutcome::Fulfilled
+                    }
+                    Some(_) => FulfillmentOutcome::Breached,
+                };
+
+                CommittedTxAudit {
+                    hash: *hash,
+                    block_number: inclusion.map(|(_, number)| *number),
+                    outcome,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let outcome = if block.is_none() {
+            FulfillmentOutcome::SlotMissed
+        } else if transactions.iter().any(|tx| tx.outcome == FulfillmentOutcome::Breached) {
+            FulfillmentOutcome::Breached
+        } else {
+            FulfillmentOutcome::Fulfilled
+        };
+
+        Self { slot, block, outcome, transactions }
+    }
+}
+
+/// The audits of the commitments made for past slots, shared between the execution state that
+/// records them and the admin API that exposes them.
+///
+/// Breached commitments and missed slots are reported as errors, so that operators are alerted
+/// before a challenger opens a challenge against the commitments.
+#[derive(Debug, Clone, Default)]
+pub struct FulfillmentAudits {
+    audits: Arc<RwLock<BTreeMap<Slot, SlotAudit>>>,
+    /// The headers signed by our proposers, used to tell whether a block was proposed through
+    /// the sidecar.
+    signed_headers: Option<Arc<Mutex<SignedHeadersDb>>>,
+    /// The beacon client, used to find the execution block proposed for an audited slot.
+    /// Slots are not audited without it.
+    beacon: Option<BeaconClient>,
+}
+
+impl FulfillmentAudits {
+    /// Uses the given signed headers database to find the source of the proposed blocks.
+    pub fn with_signed_headers(mut self, signed_headers: Arc<Mutex<SignedHeadersDb>>) -> Self {
+        self.signed_headers = Some(signed_headers);
+        self
+    }
+
+    /// Uses the given beacon client to find the execution block proposed for the audited slots.
+    pub fn with_beacon_client(mut self, beacon: BeaconClient) -> Self {
+        self.beacon = Some(beacon);
+        self
+    }
+
+    /// Audits the transactions committed for a past slot and records the audit, given the hash
+    /// and number of the block in which each of them landed.
+    ///
+    /// The block proposed for the slot is the execution block of its beacon block, so that the
+    /// audit doesn't depend on the execution client being synced to the slot.
+    pub async fn audit(
+        &self,
+        slot: Slot,
+        hashes: &[TxHash],
+        inclusions: &HashMap<TxHash, (B256, u64)>,
+    ) {
+        let Some(beacon) = &self.beacon else {
+            warn!(slot, "No beacon client configured, skipping fulfillment audit");
+            return;
+        };
+
+        match self.proposed_block(beacon, slot).await {
+            Ok(block) => self.record(SlotAudit::new(slot, block, hashes, inclusions)),
+            Err(err) => error!(slot, ?err, "Failed to fetch the block proposed for the slot"),
+        }
+    }
+
+    /// Returns the execution block proposed for the given slot, or `None` if it was missed.
+    async fn proposed_block(
+        &self,
+        beacon: &BeaconClient,
+        slot: Slot,
+    ) -> BeaconClientResult<Option<ProposedBlock>> {
+        let block = beacon.get_execution_block_at_slot(slot).await?;
+
+        Ok(block.map(|block| ProposedBlock {
+            number: block.number,
+            hash: block.hash,
+            source: self.header_source(slot, &block.hash),
+        }))
+    }
+
+    /// Returns where the block with the given hash was sourced from, if it matches the header
+    /// signed through the sidecar for the slot.
+    pub fn header_source(&self, slot: Slot, block_hash: &B256) -> Option<HeaderSource> {
+        let signed_headers = self.signed_headers.as_ref()?.lock();
+        let record = signed_headers.get(slot)?;
+        (record.block_hash.as_ref() == block_hash.as_slice()).then_some(record.source)
+    }
+
+    /// Records the audit of a slot, updating the metrics and alerting on breaches.
+    pub fn record(&self, audit: SlotAudit) {
+        for tx in &audit.transactions {
+            ApiMetrics::increment_committed_transactions_fulfillment(tx.outcome.as_str());
+        }
+
+        let slot = audit.slot;
+        let txs = audit.transactions.len();
+        match audit.outcome {
+            FulfillmentOutcome::Fulfilled => {
+                info!(slot, txs, "All committed transactions were included in the slot block");
+            }
+            FulfillmentOutcome::Breached => {
+                let missing = audit
+                    .transactions
+                    .iter()
+                    .filter(|tx| tx.outcome == FulfillmentOutcome::Breached)
+                    .map(|tx| tx.hash)
+                    .collect::<Vec<_>>();
+                error!(
+                    slot,
+                    block = ?audit.block,
+                    ?missing,
+                    "Committed transactions were not included in the slot block, our commitments can be challenged"
+                );
+            }
+            FulfillmentOutcome::SlotMissed => {
+                error!(
+                    slot,
+                    txs, "Slot with commitments was missed, our commitments can be challenged"
+                );
+            }
+        }
+
+        let mut audits = self.audits.write();
+        audits.insert(slot, audit);
+
+        let min_slot = slot.saturating_sub(FULFILLMENT_RETENTION_SLOTS);
+        *audits = audits.split_off(&min_slot);
+    }
+
+    /// Returns the audit of the given slot, if any.
+    pub fn get(&self, slot: Slot) -> Option<SlotAudit> {
+        self.audits.read().get(&slot).cloned()
+    }
+
+    /// Returns the most recent audits, up to the given limit, from the latest slot.
+    pub fn recent(&self, limit: usize) -> Vec<SlotAudit> {
+        self.audits.read().values().rev().take(limit).cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethereum_consensus::primitives::Hash32;
+
+    use super::*;
+    use crate::state::signed_headers::SignedHeaderRecord;
+
+    #[test]
+    fn test_slot_audit_outcomes() {
+        let (tx1, tx2) = (TxHash::repeat_byte(1), TxHash::repeat_byte(2));
+        let block_hash = B256::repeat_byte(0xaa);
+        let block = ProposedBlock { number: 10, hash: block_hash, source: None };
+
+        // Both transactions landed in the slot block
+        let inclusions = HashMap::from([(tx1, (block_hash, 10)), (tx2, (block_hash, 10))]);
+        let audit = SlotAudit::new(5, Some(block.clone()), &[tx1, tx2], &inclusions);
+        assert_eq!(audit.outcome, FulfillmentOutcome::Fulfilled);
+
+        // One transaction landed in a later block, the other one didn't land at all
+        let inclusions = HashMap::from([(tx1, (B256::repeat_byte(0xbb), 11))]);
+        let audit = SlotAudit::new(5, Some(block), &[tx1, tx2], &inclusions);
+        assert_eq!(audit.outcome, FulfillmentOutcome::Breached);
+        assert_eq!(audit.transactions[0].block_number, Some(11));
+        assert_eq!(audit.transactions[1].block_number, None);
+
+        // No block was proposed for the slot
+        let audit = SlotAudit::new(5, None, &[tx1], &inclusions);
+        assert_eq!(audit.outcome, FulfillmentOutcome::SlotMissed);
+        assert!(audit.transactions.iter().all(|tx| tx.outcome == FulfillmentOutcome::SlotMissed));
+
+        let audits = FulfillmentAudits::default();
+        audits.record(audit.clone());
+        assert_eq!(audits.get(5), Some(audit));
+        assert_eq!(audits.recent(10).len(), 1);
+
+        // Blocks are attributed to the sidecar only if they match the signed header
+        let mut db = SignedHeadersDb::in_memory();
+        let record = SignedHeaderRecord {
+            block_hash: Hash32::try_from(block_hash.as_slice()).unwrap(),
+            parent_hash: Hash32::default(),
+            source: HeaderSource::Local,
+        };
+        db.insert(5, record).unwrap();
+        let audits = audits.with_signed_headers(Arc::new(Mutex::new(db)));
+        assert_eq!(audits.header_source(5, &block_hash), Some(HeaderSource::Local));
+        assert_eq!(audits.header_source(5, &B256::ZERO), None);
+    }
+
+    #[tokio::test]
+    async fn test_audit_without_beacon_client_is_skipped() {
+        let audits = FulfillmentAudits::default();
+        audits.audit(5, &[TxHash::repeat_byte(1)], &HashMap::new()).await;
+        assert_eq!(audits.get(5), None);
+    }
+}
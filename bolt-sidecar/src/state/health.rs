@@ -0,0 +1,350 @@
+use std::{
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use alloy::{primitives::Address, providers::Provider, rpc::types::SyncStatus};
+use parking_lot::RwLock;
+use serde::Serialize;
+use tracing::{info, warn};
+
+use crate::{
+    client::{BeaconClient, ExecutionClient},
+    crypto::SignerECDSA,
+    signer::SignerBLS,
+};
+
+/// The maximum age of the execution head, in slots, for it to be considered fresh.
+/// This leaves room for a few consecutive missed slots.
+const MAX_HEAD_AGE_SLOTS: u64 = 4;
+
+/// The maximum difference, in slots, between the local slot clock and the beacon node head.
+const MAX_SLOT_DRIFT: u64 = 4;
+
+/// The sync status and head freshness of the execution client.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ExecutionHealth {
+    /// Whether the execution client is synced and its head is fresh.
+    pub ok: bool,
+    /// Whether the execution client reports to be syncing.
+    pub syncing: bool,
+    /// The number of the head block.
+    pub head_block: Option<u64>,
+    /// The number of seconds elapsed since the timestamp of the head block.
+    pub head_age_secs: Option<u64>,
+    /// The error encountered while querying the execution client, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// The sync status of the beacon node.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ConsensusHealth {
+    /// Whether the beacon node is synced.
+    pub ok: bool,
+    /// Whether the beacon node reports to be syncing.
+    pub syncing: bool,
+    /// The slot of the head of the beacon node.
+    pub head_slot: Option<u64>,
+    /// The number of slots the beacon node is behind its peers.
+    pub sync_distance: Option<u64>,
+    /// The error encountered while querying the beacon node, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// The drift between the local slot clock and the head of the beacon node.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SlotClockHealth {
+    /// Whether the drift is within bounds.
+    pub ok: bool,
+    /// The current slot according to the local clock.
+    pub local_slot: u64,
+    /// The local slot minus the beacon node head slot. A negative drift means that the local
+    /// clock is behind the chain.
+    pub drift_slots: Option<i64>,
+}
+
+/// The reachability of the constraint signer.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SignerHealth {
+    /// Whether the signer is reachable and has keys available.
+    pub ok: bool,
+    /// The kind of signer.
+    pub kind: &'static str,
+    /// The number of public keys available for signing.
+    pub available_keys: usize,
+    /// The error encountered while reaching the signer, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// The reachability of the commitment signer.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CommitmentSignerHealth {
+    /// Whether the signer is reachable.
+    pub ok: bool,
+    /// The address of the signer.
+    pub address: Address,
+    /// The error encountered while reaching the signer, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// The last successful submission of constraints to the constraints API.
+///
+/// This is informational only: constraints are only submitted when we are proposing.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ConstraintsApiHealth {
+    /// The UNIX timestamp in seconds of the last successful submission, if any.
+    pub last_success: Option<u64>,
+    /// The number of seconds elapsed since the last successful submission, if any.
+    pub last_success_age_secs: Option<u64>,
+}
+
+/// A report of the health of the sidecar and its dependencies, exposed in the `/health` and
+/// `/ready` endpoints.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct HealthReport {
+    /// Whether the sidecar is ready to accept commitments.
+    pub ready: bool,
+    /// The UNIX timestamp in seconds of the last check, if any.
+    pub last_checked: Option<u64>,
+    /// The health of the execution client.
+    pub execution: ExecutionHealth,
+    /// The health of the beacon node.
+    pub consensus: ConsensusHealth,
+    /// The drift of the local slot clock.
+    pub slot_clock: SlotClockHealth,
+    /// The health of the constraint signer.
+    pub signer: SignerHealth,
+    /// The health of the commitment signer.
+    pub commitment_signer: CommitmentSignerHealth,
+    /// The last successful call to the constraints API.
+    pub constraints_api: ConstraintsApiHealth,
+}
+
+/// The last health report and the time of the last successful constraints submission.
+#[derive(Debug, Default)]
+struct HealthSet {
+    report: HealthReport,
+    last_constraints_success: Option<u64>,
+}
+
+/// The health of the sidecar, shared between the commitments API, the constraints streamer and
+/// the [`HealthMonitor`] that keeps it up to date.
+///
+/// The sidecar is never ready before the first check has completed.
+#[derive(Debug, Clone, Default)]
+pub struct SidecarHealth {
+    inner: Arc<RwLock<HealthSet>>,
+}
+
+impl SidecarHealth {
+    /// Replaces the last health report with the given one.
+    pub fn update(&self, report: HealthReport) {
+        self.inner.write().report = report;
+    }
+
+    /// Records a successful submission of constraints to the constraints API.
+    pub fn record_constraints_success(&self) {
+        self.inner.write().last_constraints_success = Some(unix_now());
+    }
+
+    /// Returns whether the sidecar is ready to accept commitments.
+    pub fn is_ready(&self) -> bool {
+        self.inner.read().report.ready
+    }
+
+    /// Returns the last health report, with the up-to-date constraints API status.
+    pub fn report(&self) -> HealthReport {
+        let set = self.inner.read();
+        let mut report = set.report.clone();
+        report.constraints_api = ConstraintsApiHealth {
+            last_success: set.last_constraints_success,
+            last_success_age_secs: set
+                .last_constraints_success
+                .map(|last| unix_now().saturating_sub(last)),
+        };
+        report
+    }
+}
+
+/// Periodically checks the sync status and head freshness of the execution client and the
+/// beacon node, the drift of the local slot clock and the reachability of the constraint and
+/// commitment signers, updating the shared [`SidecarHealth`].
+#[derive(Debug)]
+pub struct HealthMonitor {
+    execution: ExecutionClient,
+    beacon: BeaconClient,
+    signer: SignerBLS,
+    commitment_signer: Box<dyn SignerECDSA>,
+    health: SidecarHealth,
+    genesis_time: u64,
+    slot_time: u64,
+}
+
+impl HealthMonitor {
+    /// Creates a new monitor, updating the given health once per slot.
+    pub fn new(
+        execution: ExecutionClient,
+        beacon: BeaconClient,
+        signer: SignerBLS,
+        commitment_signer: impl SignerECDSA + 'static,
+        health: SidecarHealth,
+        genesis_time: u64,
+        slot_time: u64,
+    ) -> Self {
+        let commitment_signer = Box::new(commitment_signer);
+        Self { execution, beacon, signer, commitment_signer, health, genesis_time, slot_time }
+    }
+
+    /// Runs all the checks and updates the shared health with the resulting report.
+    pub async fn check(&self) -> HealthReport {
+        let now = unix_now();
+        let (execution, consensus, signer, commitment_signer) = tokio::join!(
+            self.check_execution(now),
+            self.check_consensus(),
+            self.check_signer(),
+            self.check_commitment_signer()
+        );
+        let slot_clock = self.check_slot_clock(now, consensus.head_slot);
+
+        let ready =
+            execution.ok && consensus.ok && slot_clock.ok && signer.ok && commitment_signer.ok;
+        let report = HealthReport {
+            ready,
+            last_checked: Some(now),
+            execution,
+            consensus,
+            slot_clock,
+            signer,
+            commitment_signer,
+            constraints_api: ConstraintsApiHealth::default(),
+        };
+
+        if ready != self.health.is_ready() {
+            if ready {
+                info!("Sidecar is ready");
+            } else {
+                warn!(?report, "Sidecar is not ready");
+            }
+        }
+
+        self.health.update(report.clone());
+        report
+    }
+
+    async fn check_execution(&self, now: u64) -> ExecutionHealth {
+        let (syncing, head) =
+            tokio::join!(self.execution.syncing(), self.execution.get_block(None, false));
+
+        match (syncing, head) {
+            (Ok(syncing), Ok(head)) => {
+                let syncing = !matches!(syncing, SyncStatus::None);
+                let head_age = now.saturating_sub(head.header.timestamp);
+                ExecutionHealth {
+                    ok: !syncing && head_age <= MAX_HEAD_AGE_SLOTS * self.slot_time,
+                    syncing,
+                    head_block: Some(head.header.number),
+                    head_age_secs: Some(head_age),
+                    error: None,
+                }
+            }
+            (Err(err), _) | (_, Err(err)) => {
+                ExecutionHealth { error: Some(err.to_string()), ..Default::default() }
+            }
+        }
+    }
+
+    async fn check_consensus(&self) -> ConsensusHealth {
+        match self.beacon.get_sync_status().await {
+            Ok(status) => ConsensusHealth {
+                ok: !status.is_syncing,
+                syncing: status.is_syncing,
+                head_slot: Some(status.head_slot),
+                sync_distance: Some(status.sync_distance as u64),
+                error: None,
+            },
+            Err(err) => ConsensusHealth { error: Some(err.to_string()), ..Default::default() },
+        }
+    }
+
+    fn check_slot_clock(&self, now: u64, head_slot: Option<u64>) -> SlotClockHealth {
+        let local_slot = now.saturating_sub(self.genesis_time) / self.slot_time;
+        let drift_slots = head_slot.map(|head| local_slot as i64 - head as i64);
+
+        SlotClockHealth {
+            ok: drift_slots.is_some_and(|drift| drift.unsigned_abs() <= MAX_SLOT_DRIFT),
+            local_slot,
+            drift_slots,
+        }
+    }
+
+    async fn check_signer(&self) -> SignerHealth {
+        let available_keys = self.signer.available_pubkeys().len();
+        let result = self.signer.check_reachable().await;
+
+        SignerHealth {
+            ok: result.is_ok() && available_keys > 0,
+            kind: self.signer.kind(),
+            available_keys,
+            error: result.err().map(|err| err.to_string()),
+        }
+    }
+
+    async fn check_commitment_signer(&self) -> CommitmentSignerHealth {
+        let result = self.commitment_signer.check_reachable().await;
+
+        CommitmentSignerHealth {
+            ok: result.is_ok(),
+            address: self.commitment_signer.public_key(),
+            error: result.err().map(|err| err.to_string()),
+        }
+    }
+
+    /// Spawns a background task that checks the health once per slot.
+    pub fn spawn(self) {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(self.slot_time));
+            loop {
+                interval.tick().await;
+                self.check().await;
+            }
+        });
+    }
+}
+
+/// Returns the current UNIX timestamp in seconds.
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_health_report() {
+        let health = SidecarHealth::default();
+
+        // The sidecar is never ready before the first check
+        let report = health.report();
+        assert!(!report.ready);
+        assert!(report.last_checked.is_none());
+        assert!(report.constraints_api.last_success.is_none());
+
+        health.update(HealthReport { ready: true, last_checked: Some(1), ..Default::default() });
+        assert!(health.is_ready());
+
+        health.record_constraints_success();
+        let report = health.report();
+        assert!(report.ready);
+        assert!(report.constraints_api.last_success.is_some());
+        assert!(report.constraints_api.last_success_age_secs.is_some_and(|age| age <= 1));
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["ready"], true);
+        assert!(json["execution"].get("error").is_none());
+    }
+}
@@ -0,0 +1,171 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+use tracing::warn;
+
+/// The minimum number of entries in a journal before it is compacted.
+const MIN_COMPACTION_ENTRIES: usize = 1024;
+
+/// An append-only journal of JSON entries, one per line, backing a persisted database.
+///
+/// Recording an entry only appends and syncs that entry, so that it stays cheap on the hot path.
+/// Entries that are not live anymore, e.g. because they were pruned, are dropped by compacting
+/// the journal, which atomically rewrites it with the live entries only.
+///
+/// Databases must roll back an entry if recording it fails: without a durable record, they
+/// can't guarantee their safety properties across restarts.
+#[derive(Debug)]
+pub(crate) struct Journal {
+    /// The path of the journal file.
+    path: PathBuf,
+    /// The journal file, opened in append mode.
+    file: File,
+    /// The number of entries in the journal file, including the ones that are not live.
+    entries: usize,
+    /// Whether the last append failed, possibly leaving a partially written entry behind.
+    torn: bool,
+}
+
+impl Journal {
+    /// Read the entries of the journal at the given path. A missing file has no entries.
+    ///
+    /// A trailing entry without a newline that can't be parsed was torn by a crash while it was
+    /// being appended, before it was synced: as it was never durable, it is skipped.
+    pub(crate) fn read<T, E>(path: &Path) -> Result<Vec<T>, E>
+    where
+        T: DeserializeOwned,
+        E: From<io::Error> + From<serde_json::Error>,
+    {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err.into()),
+        };
+
+        let mut entries = Vec::new();
+        for line in contents.split_inclusive('\n') {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            match serde_json::from_str(line) {
+                Ok(entry) => entries.push(entry),
+                Err(err) if !line.ends_with('\n') => {
+                    warn!(?err, ?path, "Skipping torn entry at the end of the journal")
+                }
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        Ok(entries)
+    }
+
+    /// Atomically create the journal at the given path with the given entries, replacing any
+    /// existing journal, by writing to a temporary file first and renaming it over the path.
+    pub(crate) fn create<T: Serialize>(
+        path: impl Into<PathBuf>,
+        entries: impl IntoIterator<Item = T>,
+    ) -> io::Result<Self> {
+        let path = path.into();
+        let tmp_path = path.with_extension("tmp");
+
+        let mut count = 0;
+        let mut writer = io::BufWriter::new(File::create(&tmp_path)?);
+        for entry in entries {
+            serde_json::to_writer(&mut writer, &entry)?;
+            writer.write_all(b"\n")?;
+            count += 1;
+        }
+        writer.into_inner().map_err(io::IntoInnerError::into_error)?.sync_all()?;
+
+        fs::rename(tmp_path, &path)?;
+
+        let file = OpenOptions::new().append(true).open(&path)?;
+        Ok(Self { path, file, entries: count, torn: false })
+    }
+
+    /// Durably record a new entry, which must already be part of the live entries.
+    ///
+    /// The entry is appended to the journal, unless it's time to compact it, in which case the
+    /// journal is rewritten with the live entries only.
+    pub(crate) fn record<T, I>(
+        &mut self,
+        entry: &T,
+        live_count: usize,
+        live: impl FnOnce() -> I,
+    ) -> io::Result<()>
+    where
+        T: Serialize,
+        I: IntoIterator<Item = T>,
+    {
+        if self.should_compact(live_count) {
+            return self.compact(live());
+        }
+
+        let mut line = serde_json::to_vec(entry)?;
+        line.push(b'\n');
+
+        let appended = self.file.write_all(&line).and_then(|_| self.file.sync_data());
+        match appended {
+            Ok(()) => self.entries += 1,
+            Err(_) => self.torn = true,
+        }
+
+        appended
+    }
+
+    /// Atomically rewrite the journal with the given live entries only.
+    pub(crate) fn compact<T: Serialize>(
+        &mut self,
+        live: impl IntoIterator<Item = T>,
+    ) -> io::Result<()> {
+        *self = Self::create(self.path.clone(), live)?;
+        Ok(())
+    }
+
+    /// Returns true if the journal should be compacted before appending a new entry: either
+    /// because most of its entries are not live anymore, or because a previous append failed.
+    fn should_compact(&self, live_count: usize) -> bool {
+        self.torn || (self.entries >= MIN_COMPACTION_ENTRIES && self.entries >= 2 * live_count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_file_path;
+
+    #[test]
+    fn test_appends_and_compacts_entries() {
+        let path = temp_file_path("bolt_journal");
+
+        let mut journal = Journal::create(&path, [1u64, 2]).unwrap();
+        journal.record(&3u64, 3, || [1, 2, 3]).unwrap();
+        assert_eq!(Journal::read::<u64, io::Error>(&path).unwrap(), vec![1, 2, 3]);
+
+        // Once most entries are stale, recording an entry compacts the journal
+        journal.entries = MIN_COMPACTION_ENTRIES;
+        journal.record(&4u64, 2, || [3, 4]).unwrap();
+        assert_eq!(Journal::read::<u64, io::Error>(&path).unwrap(), vec![3, 4]);
+        assert_eq!(journal.entries, 2);
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_skips_torn_trailing_entry() {
+        let path = temp_file_path("bolt_journal");
+        fs::write(&path, "1\n2\n{\"tor").unwrap();
+        assert_eq!(Journal::read::<u64, io::Error>(&path).unwrap(), vec![1, 2]);
+
+        // A corrupted entry in the middle of the journal is an error
+        fs::write(&path, "1\n{\"tor\n2\n").unwrap();
+        assert!(Journal::read::<u64, io::Error>(&path).is_err());
+
+        fs::remove_file(path).unwrap();
+    }
+}
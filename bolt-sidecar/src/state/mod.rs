@@ -9,7 +9,9 @@ use tokio::time::Sleep;
 
 /// Module to perform state validation.
 mod execution;
-pub use execution::{ExecutionState, ValidationError};
+pub use execution::{
+    AccountSnapshot, BlockTemplateSnapshot, ExecutionSnapshot, ExecutionState, ValidationError,
+};
 
 /// Module to calculate pricing.
 pub mod pricing;
@@ -31,6 +33,34 @@ pub use head_tracker::HeadTracker;
 pub mod account_state;
 pub use account_state::AccountStateCache;
 
+/// Module that defines the append-only journal backing the persisted databases.
+mod journal;
+
+/// Module that defines the persisted record of signed headers, used to prevent equivocation.
+pub mod signed_headers;
+pub use signed_headers::SignedHeadersDb;
+
+/// Module that defines the persisted record of signed commitments, used to prevent conflicting
+/// commitments.
+pub mod commitments_db;
+pub use commitments_db::CommitmentsDb;
+
+/// Module that defines the runtime store of delegations and revocations, and its file watcher.
+pub mod delegations;
+pub use delegations::{DelegationStore, DelegationsWatcher};
+
+/// Module that tracks the on-chain statuses of our validators, pausing inactive ones.
+pub mod validator_status;
+pub use validator_status::{ValidatorStatusMonitor, ValidatorStatuses};
+
+/// Module that audits whether the commitments made for past slots were fulfilled.
+pub mod fulfillment;
+pub use fulfillment::FulfillmentAudits;
+
+/// Module that monitors the health of the sidecar and its dependencies.
+pub mod health;
+pub use health::{HealthMonitor, SidecarHealth};
+
 /// The deadline for a which a commitment is considered valid.
 #[derive(Debug)]
 pub struct CommitmentDeadline {
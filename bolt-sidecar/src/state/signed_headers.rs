@@ -0,0 +1,204 @@
+use std::{collections::BTreeMap, io, path::PathBuf};
+
+use ethereum_consensus::primitives::Hash32;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::primitives::Slot;
+
+use super::journal::Journal;
+
+/// The number of slots for which signed headers are retained on disk (~1 day on mainnet).
+pub const SIGNED_HEADERS_RETENTION_SLOTS: u64 = 8192;
+
+/// The origin of the payload behind a signed header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HeaderSource {
+    /// The header was built locally by the fallback builder.
+    Local,
+    /// The header was returned by the relay through the constraints API.
+    Remote,
+}
+
+/// A record of a header that has been signed by the proposer for a given slot.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedHeaderRecord {
+    /// The block hash of the signed execution payload header.
+    pub block_hash: Hash32,
+    /// The parent hash of the signed execution payload header.
+    pub parent_hash: Hash32,
+    /// Where the payload behind the header was sourced from.
+    pub source: HeaderSource,
+}
+
+/// Errors that can occur when interacting with the signed headers database.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum EquivocationError {
+    #[error("Conflicting header for slot {slot}: already signed {signed}, got {have}")]
+    ConflictingHeader { slot: Slot, signed: Hash32, have: Hash32 },
+    #[error("I/O error on signed headers database: {0}")]
+    Io(#[from] io::Error),
+    #[error("Failed to (de)serialize signed headers database: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// A persisted, per-slot record of the headers signed by our proposers, similar in spirit
+/// to a slashing protection database.
+///
+/// Once a header is recorded for a slot, any other header (and payload) for the same slot
+/// is refused, even across restarts of the sidecar.
+#[derive(Debug)]
+pub struct SignedHeadersDb {
+    /// The journal backing the database, with one `(slot, record)` entry per line.
+    /// If `None`, records are kept in memory.
+    journal: Option<Journal>,
+    /// The signed header records, indexed by slot.
+    records: BTreeMap<Slot, SignedHeaderRecord>,
+}
+
+impl SignedHeadersDb {
+    /// Open the database at the given path, loading any existing records from it.
+    /// If the file does not exist, it is created.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, EquivocationError> {
+        let path = path.into();
+
+        let entries = Journal::read::<(Slot, SignedHeaderRecord), EquivocationError>(&path)?;
+        let mut db = Self { journal: None, records: entries.into_iter().collect() };
+        if let Some(latest_slot) = db.records.keys().next_back().copied() {
+            db.prune(latest_slot);
+        }
+        db.journal = Some(Journal::create(&path, db.entries())?);
+
+        debug!(path = ?path, records = db.records.len(), "Opened signed headers database");
+        Ok(db)
+    }
+
+    /// Create a new database that is not persisted to disk.
+    pub fn in_memory() -> Self {
+        Self { journal: None, records: BTreeMap::new() }
+    }
+
+    /// Get the signed header record for the given slot, if any.
+    pub fn get(&self, slot: Slot) -> Option<&SignedHeaderRecord> {
+        self.records.get(&slot)
+    }
+
+    /// Check that serving a header with the given block hash for the given slot
+    /// would not result in an equivocation.
+    pub fn check(&self, slot: Slot, block_hash: &Hash32) -> Result<(), EquivocationError> {
+        match self.records.get(&slot) {
+            Some(record) if &record.block_hash != block_hash => {
+                Err(EquivocationError::ConflictingHeader {
+                    slot,
+                    signed: record.block_hash.clone(),
+                    have: block_hash.clone(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Record a signed header for the given slot and persist it to disk.
+    ///
+    /// Returns an error if a different header was already signed for the slot.
+    /// Recording the same header twice is a no-op.
+    pub fn insert(
+        &mut self,
+        slot: Slot,
+        record: SignedHeaderRecord,
+    ) -> Result<(), EquivocationError> {
+        self.check(slot, &record.block_hash)?;
+
+        if self.records.contains_key(&slot) {
+            return Ok(());
+        }
+
+        self.records.insert(slot, record.clone());
+        self.prune(slot);
+
+        if let Some(journal) = &mut self.journal {
+            let live = || self.records.iter().map(|(slot, record)| (*slot, record.clone()));
+            if let Err(err) = journal.record(&(slot, record), self.records.len(), live) {
+                warn!(?err, slot, "Failed to persist signed header record");
+                self.records.remove(&slot);
+                return Err(err.into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the journal entries of the records.
+    fn entries(&self) -> impl Iterator<Item = (Slot, SignedHeaderRecord)> + '_ {
+        self.records.iter().map(|(slot, record)| (*slot, record.clone()))
+    }
+
+    /// Remove all records older than the retention window relative to the given slot.
+    fn prune(&mut self, latest_slot: Slot) {
+        let min_slot = latest_slot.saturating_sub(SIGNED_HEADERS_RETENTION_SLOTS);
+        self.records = self.records.split_off(&min_slot);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::test_util::temp_file_path;
+
+    fn record(hash_byte: u8, source: HeaderSource) -> SignedHeaderRecord {
+        SignedHeaderRecord {
+            block_hash: Hash32::try_from([hash_byte; 32].as_ref()).unwrap(),
+            parent_hash: Hash32::default(),
+            source,
+        }
+    }
+
+    #[test]
+    fn test_refuses_conflicting_header() {
+        let mut db = SignedHeadersDb::in_memory();
+
+        db.insert(10, record(1, HeaderSource::Remote)).unwrap();
+
+        // Same header is fine
+        db.insert(10, record(1, HeaderSource::Local)).unwrap();
+        assert_eq!(db.get(10).unwrap().source, HeaderSource::Remote);
+
+        // Different header for the same slot must be refused
+        let err = db.insert(10, record(2, HeaderSource::Local)).unwrap_err();
+        assert!(matches!(err, EquivocationError::ConflictingHeader { slot: 10, .. }));
+
+        // Other slots are unaffected
+        db.insert(11, record(2, HeaderSource::Local)).unwrap();
+    }
+
+    #[test]
+    fn test_persists_across_restarts() {
+        let path = temp_file_path("bolt_signed_headers");
+
+        {
+            let mut db = SignedHeadersDb::open(&path).unwrap();
+            db.insert(42, record(1, HeaderSource::Local)).unwrap();
+        }
+
+        let db = SignedHeadersDb::open(&path).unwrap();
+        assert_eq!(db.get(42), Some(&record(1, HeaderSource::Local)));
+        assert!(db.check(42, &record(2, HeaderSource::Remote).block_hash).is_err());
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_prunes_old_records() {
+        let mut db = SignedHeadersDb::in_memory();
+
+        db.insert(1, record(1, HeaderSource::Remote)).unwrap();
+        db.insert(SIGNED_HEADERS_RETENTION_SLOTS + 2, record(2, HeaderSource::Remote)).unwrap();
+
+        assert!(db.get(1).is_none());
+        assert!(db.get(SIGNED_HEADERS_RETENTION_SLOTS + 2).is_some());
+    }
+}
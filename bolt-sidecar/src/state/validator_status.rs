@@ -0,0 +1,312 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use alloy::primitives::Address;
+use ethereum_consensus::crypto::PublicKey as BlsPublicKey;
+use parking_lot::RwLock;
+use serde::Serialize;
+use tracing::{error, info, warn};
+
+use crate::{
+    chain_io::{manager::BoltManagerContract::ProposerStatus, BoltManager, BoltValidators},
+    telemetry::ApiMetrics,
+};
+
+use super::DelegationStore;
+
+/// The on-chain state of a validator, as seen by the sidecar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValidatorState {
+    /// The validator is active in Bolt and authorizes our operator.
+    Active,
+    /// The validator is registered, but its operator isn't active (e.g. under-collateralized or
+    /// paused in the restaking protocol).
+    Inactive,
+    /// The validator authorizes another operator than our commitment signer.
+    OperatorMismatch,
+    /// The validator, or its operator, is not registered in Bolt.
+    NotRegistered,
+}
+
+impl ValidatorState {
+    /// Derive the state of a validator from its on-chain proposer status.
+    fn from_status(status: Option<&ProposerStatus>, operator: Address) -> Self {
+        match status {
+            None => Self::NotRegistered,
+            Some(status) if status.operator != operator => Self::OperatorMismatch,
+            Some(status) if !status.active => Self::Inactive,
+            Some(_) => Self::Active,
+        }
+    }
+}
+
+/// The on-chain status of a validator.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ValidatorStatus {
+    /// The validator public key.
+    pub pubkey: BlsPublicKey,
+    /// The on-chain state of the validator.
+    pub state: ValidatorState,
+    /// The maximum committed gas limit of the validator in the BoltValidators registry, if known.
+    pub max_committed_gas_limit: Option<u64>,
+}
+
+/// A report of the on-chain statuses of our validators, exposed in the metadata endpoint.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ValidatorStatusReport {
+    /// The UNIX timestamp in seconds of the last successful on-chain check, if any.
+    pub last_checked: Option<u64>,
+    /// The statuses of the validators.
+    pub validators: Vec<ValidatorStatus>,
+}
+
+/// The last known on-chain statuses of our validators and the time they were fetched.
+#[derive(Debug, Default)]
+struct StatusSet {
+    statuses: HashMap<BlsPublicKey, ValidatorState>,
+    max_committed_gas_limits: HashMap<BlsPublicKey, u64>,
+    last_checked: Option<u64>,
+}
+
+/// The last known on-chain statuses of our validators, shared between the driver, the
+/// commitments API and the [`ValidatorStatusMonitor`] that keeps them up to date.
+///
+/// Validators with an unknown status are never paused, so that an empty store (e.g. when
+/// on-chain checks are disabled) doesn't affect the sidecar. The monitor checks both our own
+/// and our delegated validators, so that every validator we sign for has a known status.
+///
+/// Once paused, a validator stays paused until it's seen active again, even if it's not part
+/// of the following checks anymore.
+#[derive(Debug, Clone, Default)]
+pub struct ValidatorStatuses {
+    inner: Arc<RwLock<StatusSet>>,
+}
+
+impl ValidatorStatuses {
+    /// Updates the statuses with the given ones, returning the validators whose state changed.
+    ///
+    /// Validators that are not part of the update are forgotten if they were active, while the
+    /// ones that were not active are kept paused until they are seen active again.
+    pub fn update(
+        &self,
+        mut statuses: HashMap<BlsPublicKey, ValidatorState>,
+    ) -> Vec<(BlsPublicKey, ValidatorState)> {
+        let mut set = self.inner.write();
+
+        let changed = statuses
+            .iter()
+            .filter(|(pubkey, state)| set.statuses.get(*pubkey) != Some(*state))
+            .map(|(pubkey, state)| (pubkey.clone(), *state))
+            .collect();
+
+        for (pubkey, state) in set.statuses.drain() {
+            if state != ValidatorState::Active {
+                statuses.entry(pubkey).or_insert(state);
+            }
+        }
+
+        let active = statuses.values().filter(|s| **s == ValidatorState::Active).count();
+        ApiMetrics::set_validator_statuses(active, statuses.len() - active);
+
+        set.statuses = statuses;
+        set.last_checked = SystemTime::now().duration_since(UNIX_EPOCH).ok().map(|d| d.as_secs());
+
+        changed
+    }
+
+    /// Replaces the maximum committed gas limits of the validators with the given ones.
+    pub fn update_max_committed_gas_limits(&self, limits: HashMap<BlsPublicKey, u64>) {
+        self.inner.write().max_committed_gas_limits = limits;
+    }
+
+    /// Returns the maximum committed gas limit of the given validator in the BoltValidators
+    /// registry, if known.
+    pub fn max_committed_gas_limit(&self, pubkey: &BlsPublicKey) -> Option<u64> {
+        self.inner.read().max_committed_gas_limits.get(pubkey).copied()
+    }
+
+    /// Returns true if commitments for the given validator must not be accepted, because it is
+    /// known not to be active in Bolt for our operator anymore.
+    pub fn is_paused(&self, pubkey: &BlsPublicKey) -> bool {
+        self.inner.read().statuses.get(pubkey).is_some_and(|s| *s != ValidatorState::Active)
+    }
+
+    /// Returns a report of the last known statuses.
+    pub fn report(&self) -> ValidatorStatusReport {
+        let set = self.inner.read();
+        ValidatorStatusReport {
+            last_checked: set.last_checked,
+            validators: set
+                .statuses
+                .iter()
+                .map(|(pubkey, state)| ValidatorStatus {
+                    pubkey: pubkey.clone(),
+                    state: *state,
+                    max_committed_gas_limit: set.max_committed_gas_limits.get(pubkey).copied(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Periodically re-checks the on-chain statuses of our validators in the BoltManager contract,
+/// pausing the acceptance of commitments for the validators that became inactive, and resuming
+/// it for the ones that became active again.
+///
+/// This protects the operator from signing commitments it could be slashed for if it gets
+/// deregistered, paused, under-collateralized or loses its authorization while running.
+///
+/// If a BoltValidators registry is set, the maximum committed gas limits of the validators are
+/// refreshed as well.
+#[derive(Debug)]
+pub struct ValidatorStatusMonitor {
+    manager: BoltManager,
+    validators: Option<BoltValidators>,
+    /// The address of our operator, i.e. the commitment signer.
+    operator: Address,
+    statuses: ValidatorStatuses,
+    /// The validator public keys to check.
+    validator_pubkeys: Vec<BlsPublicKey>,
+    /// If set, the validators with active delegations are checked as well, so that validators
+    /// delegated at runtime are treated as ours.
+    delegations: Option<DelegationStore>,
+    /// The interval between two checks.
+    interval: Duration,
+}
+
+impl ValidatorStatusMonitor {
+    /// Creates a new monitor for the given validators, updating the given statuses.
+    pub fn new(
+        manager: BoltManager,
+        operator: Address,
+        statuses: ValidatorStatuses,
+        validator_pubkeys: Vec<BlsPublicKey>,
+        interval: Duration,
+    ) -> Self {
+        Self {
+            manager,
+            validators: None,
+            operator,
+            statuses,
+            validator_pubkeys,
+            delegations: None,
+            interval,
+        }
+    }
+
+    /// Refreshes the maximum committed gas limits of the validators from the given registry.
+    pub fn with_validators_registry(mut self, validators: BoltValidators) -> Self {
+        self.validators = Some(validators);
+        self
+    }
+
+    /// Checks the validators with active delegations in the given store, in addition to the
+    /// fixed set of validators.
+    pub fn with_delegations(mut self, delegations: DelegationStore) -> Self {
+        self.delegations = Some(delegations);
+        self
+    }
+
+    /// Fetches the on-chain statuses of the validators and updates the shared statuses.
+    /// If the query fails, the last known statuses are kept.
+    ///
+    /// The maximum committed gas limits are refreshed after the statuses, and failing to fetch
+    /// them only keeps the last known limits, without affecting the statuses.
+    pub async fn check(&self) -> eyre::Result<()> {
+        let mut validator_pubkeys = self.validator_pubkeys.iter().cloned().collect::<HashSet<_>>();
+        if let Some(delegations) = &self.delegations {
+            validator_pubkeys.extend(delegations.validator_pubkeys());
+        }
+        let validator_pubkeys = Vec::from_iter(validator_pubkeys);
+
+        let statuses =
+            self.manager.get_proposer_statuses(validator_pubkeys.clone(), self.operator).await?;
+        let states = statuses
+            .into_iter()
+            .map(|(pubkey, status)| {
+                let state = ValidatorState::from_status(status.as_ref(), self.operator);
+                (pubkey, state)
+            })
+            .collect();
+
+        for (pubkey, state) in self.statuses.update(states) {
+            if state == ValidatorState::Active {
+                info!(?pubkey, "Validator is active in Bolt, accepting commitments");
+            } else {
+                warn!(?pubkey, ?state, "Validator is not active in Bolt, pausing commitments");
+            }
+        }
+
+        if let Some(validators) = &self.validators {
+            match validators.get_max_committed_gas_limits(validator_pubkeys).await {
+                Ok(limits) => self.statuses.update_max_committed_gas_limits(limits),
+                Err(err) => warn!(?err, "Failed to fetch the maximum committed gas limits"),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Spawns a background task that periodically checks the on-chain statuses.
+    pub fn spawn(self) {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.interval);
+            loop {
+                interval.tick().await;
+                if let Err(err) = self.check().await {
+                    error!(?err, "Failed to check on-chain validator statuses");
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signer::local::LocalSigner;
+
+    #[test]
+    fn test_pause_inactive_validators() {
+        let statuses = ValidatorStatuses::default();
+        let validator = LocalSigner::random().pubkey();
+
+        // Unknown validators are never paused
+        assert!(!statuses.is_paused(&validator));
+
+        let changed = statuses.update(HashMap::from([(validator.clone(), ValidatorState::Active)]));
+        assert_eq!(changed.len(), 1);
+        assert!(!statuses.is_paused(&validator));
+
+        let operator = Address::repeat_byte(1);
+        let status = ProposerStatus { active: true, operator, ..Default::default() };
+        let state = ValidatorState::from_status(Some(&status), Address::repeat_byte(2));
+        assert_eq!(state, ValidatorState::OperatorMismatch);
+
+        let changed = statuses.update(HashMap::from([(validator.clone(), state)]));
+        assert_eq!(changed, vec![(validator.clone(), ValidatorState::OperatorMismatch)]);
+        assert!(statuses.is_paused(&validator));
+
+        // Unchanged statuses are not reported again
+        assert!(statuses.update(HashMap::from([(validator.clone(), state)])).is_empty());
+        assert!(statuses.report().last_checked.is_some());
+
+        // Paused validators stay paused until seen active again, while active ones are forgotten
+        let other = LocalSigner::random().pubkey();
+        let changed = statuses.update(HashMap::from([(other, ValidatorState::Active)]));
+        assert_eq!(changed.len(), 1);
+        assert!(statuses.is_paused(&validator));
+        statuses.update(HashMap::from([(validator.clone(), ValidatorState::Active)]));
+        assert!(!statuses.is_paused(&validator));
+        statuses.update(HashMap::new());
+        assert!(statuses.report().validators.is_empty());
+
+        assert_eq!(statuses.max_committed_gas_limit(&validator), None);
+        statuses.update_max_committed_gas_limits(HashMap::from([(validator.clone(), 1_000_000)]));
+        assert_eq!(statuses.max_committed_gas_limit(&validator), Some(1_000_000));
+    }
+}
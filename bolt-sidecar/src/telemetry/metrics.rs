@@ -16,6 +16,8 @@ const REMOTE_BLOCKS_PROPOSED: &str = "bolt_sidecar_remote_blocks_proposed";
 const INCLUSION_COMMITMENTS_RECEIVED: &str = "bolt_sidecar_inclusion_commitments_received";
 /// Counter for the number of inclusion commitments accepted.
 const INCLUSION_COMMITMENTS_ACCEPTED: &str = "bolt_sidecar_inclusion_commitments_accepted";
+/// Counter for the number of inclusion requests that would have been committed to in shadow mode.
+const SHADOW_COMMITMENTS: &str = "bolt_sidecar_shadow_commitments";
 /// Counter for the number of transactions preconfirmed
 const TRANSACTIONS_PRECONFIRMED: &str = "bolt_sidecar_transactions_preconfirmed";
 /// Counter for the number of validation errors; to spot most the most common ones
@@ -24,16 +26,51 @@ const VALIDATION_ERRORS: &str = "bolt_sidecar_validation_errors";
 /// We call it "gross" because in the case of PBS, it doesn't mean the proposer will
 /// get all of this as revenue.
 const GROSS_TIP_REVENUE: &str = "bolt_sidecar_gross_tip_revenue";
+/// Counter for the number of failed requests to constraints API relays.
+const RELAY_REQUEST_FAILURES: &str = "bolt_sidecar_relay_request_failures";
+/// Counter for the number of bids received from relays with invalid inclusion proofs.
+const RELAY_INVALID_BIDS: &str = "bolt_sidecar_relay_invalid_bids";
+/// Counter for the outcomes of the committed transactions once their slot is over: fulfilled,
+/// breached or slot missed.
+const COMMITTED_TRANSACTIONS_FULFILLMENT: &str = "bolt_sidecar_committed_transactions_fulfillment";
+/// Counter for the outcomes of the submissions of constraints to the constraints API, by mode
+/// (streamed as soon as signed or reconciled at the commitment deadline).
+const CONSTRAINTS_SUBMISSIONS: &str = "bolt_sidecar_constraints_submissions";
+/// Counter for the number of commitment requests rejected because of rate limits, by reason
+/// (global, ip, signer, pending, queue full).
+const RATE_LIMITED_REQUESTS: &str = "bolt_sidecar_rate_limited_requests";
+/// Counter for the number of times a locally built block was requested on `get_header`,
+/// by reason (timeout, no bids, invalid proofs, relay error).
+const GET_HEADER_FALLBACKS: &str = "bolt_sidecar_get_header_fallbacks";
 
 //  Gauges ------------------------------------------------------------------
 /// Gauge for the latest slot number
 const LATEST_HEAD: &str = "bolt_sidecar_latest_head";
 /// Number of account states saved in cache.
 const ACCOUNT_STATES: &str = "bolt_sidecar_account_states";
+/// Number of validators active in Bolt for our operator, as of the last on-chain check.
+const ACTIVE_VALIDATORS: &str = "bolt_sidecar_active_validators";
+/// Number of validators for which commitments are paused, as of the last on-chain check.
+const PAUSED_VALIDATORS: &str = "bolt_sidecar_paused_validators";
+/// Gas committed for the last slot with commitments, as of its commitment deadline.
+const SLOT_COMMITTED_GAS: &str = "bolt_sidecar_slot_committed_gas";
+/// Blobs committed for the last slot with commitments, as of its commitment deadline.
+const SLOT_COMMITTED_BLOBS: &str = "bolt_sidecar_slot_committed_blobs";
+/// Transactions committed for the last slot with commitments, as of its commitment deadline.
+const SLOT_COMMITTED_TRANSACTIONS: &str = "bolt_sidecar_slot_committed_transactions";
 
 //  Histograms --------------------------------------------------------------
 /// Histogram for the total duration of HTTP requests in seconds.
 const HTTP_REQUESTS_DURATION_SECONDS: &str = "bolt_sidecar_http_requests_duration_seconds";
+/// Histogram for the duration of requests to constraints API relays in seconds.
+const RELAY_REQUEST_DURATION_SECONDS: &str = "bolt_sidecar_relay_request_duration_seconds";
+/// Histogram for the duration of the validation stages of commitment requests in seconds.
+const VALIDATION_DURATION_SECONDS: &str = "bolt_sidecar_validation_duration_seconds";
+/// Histogram for the duration of the signing of constraints and commitments in seconds.
+const SIGNING_DURATION_SECONDS: &str = "bolt_sidecar_signing_duration_seconds";
+/// Histogram for the duration of the submissions of constraints in seconds.
+const CONSTRAINTS_SUBMISSION_DURATION_SECONDS: &str =
+    "bolt_sidecar_constraints_submission_duration_seconds";
 
 /// Metrics for the commitments API.
 #[derive(Debug, Clone, Copy)]
@@ -48,19 +85,53 @@ impl ApiMetrics {
         describe_counter!(REMOTE_BLOCKS_PROPOSED, "Remote blocks proposed");
         describe_counter!(INCLUSION_COMMITMENTS_ACCEPTED, "Inclusion commitments");
         describe_counter!(INCLUSION_COMMITMENTS_ACCEPTED, "Inclusion commitments accepted");
+        describe_counter!(SHADOW_COMMITMENTS, "Inclusion commitments accepted in shadow mode");
         describe_counter!(TRANSACTIONS_PRECONFIRMED, "Transactions preconfirmed");
         describe_counter!(VALIDATION_ERRORS, "Validation errors");
         describe_counter!(GROSS_TIP_REVENUE, "Gross tip revenue");
+        describe_counter!(RELAY_REQUEST_FAILURES, "Failed requests to relays");
+        describe_counter!(RELAY_INVALID_BIDS, "Bids from relays with invalid inclusion proofs");
+        describe_counter!(
+            COMMITTED_TRANSACTIONS_FULFILLMENT,
+            "Outcomes of the committed transactions after their slot"
+        );
+        describe_counter!(CONSTRAINTS_SUBMISSIONS, "Outcomes of constraints submissions");
+        describe_counter!(RATE_LIMITED_REQUESTS, "Commitment requests rejected by rate limits");
+        describe_counter!(GET_HEADER_FALLBACKS, "Fallbacks to locally built blocks on get_header");
 
         // Gauges
         describe_gauge!(LATEST_HEAD, "Latest slot number");
         describe_gauge!(ACCOUNT_STATES, "Number of account states saved in cache");
+        describe_gauge!(ACTIVE_VALIDATORS, "Number of validators active in Bolt");
+        describe_gauge!(PAUSED_VALIDATORS, "Number of validators with paused commitments");
+        describe_gauge!(SLOT_COMMITTED_GAS, "Gas committed for the last slot with commitments");
+        describe_gauge!(SLOT_COMMITTED_BLOBS, "Blobs committed for the last slot with commitments");
+        describe_gauge!(
+            SLOT_COMMITTED_TRANSACTIONS,
+            "Transactions committed for the last slot with commitments"
+        );
 
         // Histograms
         describe_histogram!(
             HTTP_REQUESTS_DURATION_SECONDS,
             "Total duration of HTTP requests in seconds"
         );
+        describe_histogram!(
+            RELAY_REQUEST_DURATION_SECONDS,
+            "Duration of requests to relays in seconds"
+        );
+        describe_histogram!(
+            VALIDATION_DURATION_SECONDS,
+            "Duration of the validation stages of commitment requests in seconds"
+        );
+        describe_histogram!(
+            SIGNING_DURATION_SECONDS,
+            "Duration of the signing of constraints and commitments in seconds"
+        );
+        describe_histogram!(
+            CONSTRAINTS_SUBMISSION_DURATION_SECONDS,
+            "Duration of constraints submissions in seconds"
+        );
     }
 
     // Counters ----------------------------------------------------------------
@@ -89,6 +160,10 @@ impl ApiMetrics {
         counter!(INCLUSION_COMMITMENTS_ACCEPTED).increment(1);
     }
 
+    pub fn increment_shadow_commitments() {
+        counter!(SHADOW_COMMITMENTS).increment(1);
+    }
+
     pub fn increment_gross_tip_revenue(mut tip: u128) {
         // If the tip is too large, we need to split it into multiple u64 parts
         if tip > u64::MAX as u128 {
@@ -116,6 +191,28 @@ impl ApiMetrics {
         counter!(VALIDATION_ERRORS, &[("type", err_type)]).increment(1);
     }
 
+    pub fn increment_relay_request_failures(relay: String, method: &'static str) {
+        counter!(RELAY_REQUEST_FAILURES, &[("relay", relay), ("method", method.to_string())])
+            .increment(1);
+    }
+
+    pub fn increment_relay_invalid_bids(relay: String) {
+        counter!(RELAY_INVALID_BIDS, &[("relay", relay)]).increment(1);
+    }
+
+    pub fn increment_committed_transactions_fulfillment(outcome: &'static str) {
+        counter!(COMMITTED_TRANSACTIONS_FULFILLMENT, &[("outcome", outcome.to_string())])
+            .increment(1);
+    }
+
+    pub fn increment_get_header_fallbacks(reason: &'static str) {
+        counter!(GET_HEADER_FALLBACKS, &[("reason", reason.to_string())]).increment(1);
+    }
+
+    pub fn increment_rate_limited_requests(reason: &'static str) {
+        counter!(RATE_LIMITED_REQUESTS, &[("reason", reason.to_string())]).increment(1);
+    }
+
     /// Gauges ----------------------------------------------------------------
     pub fn set_latest_head(slot: u32) {
         gauge!(LATEST_HEAD).set(slot);
@@ -125,6 +222,17 @@ impl ApiMetrics {
         gauge!(ACCOUNT_STATES).set(count as f64);
     }
 
+    pub fn set_validator_statuses(active: usize, paused: usize) {
+        gauge!(ACTIVE_VALIDATORS).set(active as f64);
+        gauge!(PAUSED_VALIDATORS).set(paused as f64);
+    }
+
+    pub fn set_slot_commitments(committed_gas: u64, blobs: usize, transactions: usize) {
+        gauge!(SLOT_COMMITTED_GAS).set(committed_gas as f64);
+        gauge!(SLOT_COMMITTED_BLOBS).set(blobs as f64);
+        gauge!(SLOT_COMMITTED_TRANSACTIONS).set(transactions as f64);
+    }
+
     /// Mixed ----------------------------------------------------------------
     /// Observes the duration of an HTTP request by storing it in a histogram,
     /// and incrementing the total number of HTTP requests received.
@@ -133,4 +241,31 @@ impl ApiMetrics {
         counter!(HTTP_REQUESTS_TOTAL, &labels).increment(1);
         histogram!(HTTP_REQUESTS_DURATION_SECONDS, &labels,).record(duration.as_secs_f64());
     }
+
+    /// Observes the duration of a request to a relay.
+    pub fn observe_relay_request(duration: Duration, relay: String, method: &'static str) {
+        let labels = [("relay", relay), ("method", method.to_string())];
+        histogram!(RELAY_REQUEST_DURATION_SECONDS, &labels).record(duration.as_secs_f64());
+    }
+
+    /// Observes the duration of a validation stage of a commitment request, e.g. `consensus`
+    /// or `execution`.
+    pub fn observe_validation(duration: Duration, stage: &'static str) {
+        histogram!(VALIDATION_DURATION_SECONDS, &[("stage", stage.to_string())])
+            .record(duration.as_secs_f64());
+    }
+
+    /// Observes the duration of a signing operation, e.g. `constraints` or `commitment`.
+    pub fn observe_signing(duration: Duration, kind: &'static str) {
+        histogram!(SIGNING_DURATION_SECONDS, &[("kind", kind.to_string())])
+            .record(duration.as_secs_f64());
+    }
+
+    /// Observes the duration of a submission of constraints, and counts its outcome.
+    pub fn observe_constraints_submission(duration: Duration, mode: &'static str, success: bool) {
+        let outcome = if success { "success" } else { "failure" };
+        let labels = [("mode", mode.to_string()), ("outcome", outcome.to_string())];
+        counter!(CONSTRAINTS_SUBMISSIONS, &labels).increment(1);
+        histogram!(CONSTRAINTS_SUBMISSION_DURATION_SECONDS, &labels).record(duration.as_secs_f64());
+    }
 }
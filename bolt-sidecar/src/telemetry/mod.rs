@@ -2,6 +2,8 @@ use std::net::SocketAddr;
 
 use eyre::{bail, Result};
 use metrics_exporter_prometheus::PrometheusBuilder;
+use opentelemetry::trace::TracerProvider as _;
+use reqwest::Url;
 use tracing::info;
 use tracing_subscriber::{
     fmt::Layer as FmtLayer, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer,
@@ -11,10 +13,16 @@ use tracing_subscriber::{
 mod metrics;
 pub use metrics::ApiMetrics;
 
-/// Initialize the tracing stack and Prometheus metrics recorder.
+mod otlp;
+pub use otlp::{
+    set_parent_from_headers, shutdown_tracer_provider, trace_context_headers, trace_id,
+};
+
+/// Initialize the tracing stack and Prometheus metrics recorder. If an OTLP endpoint is
+/// provided, the sidecar spans are also exported to it.
 ///
-/// **This function should be called at the beginning of the program.**
-pub fn init_telemetry_stack(metrics_port: Option<u16>) -> Result<()> {
+/// **This function should be called at the beginning of the program, within a Tokio runtime.**
+pub fn init_telemetry_stack(metrics_port: Option<u16>, otlp_endpoint: Option<&Url>) -> Result<()> {
     let std_layer = FmtLayer::default().with_writer(std::io::stdout).with_filter(
         EnvFilter::builder()
             .with_default_directive("bolt_sidecar=info".parse()?)
@@ -23,7 +31,22 @@ pub fn init_telemetry_stack(metrics_port: Option<u16>) -> Result<()> {
             .add_directive("alloy_transport_http=error".parse()?),
     );
 
-    Registry::default().with(std_layer).try_init()?;
+    let otlp_layer = match otlp_endpoint {
+        Some(endpoint) => {
+            let tracer = otlp::init_tracer_provider(endpoint)?.tracer(otlp::SERVICE_NAME);
+            let layer = tracing_opentelemetry::layer()
+                .with_tracer(tracer)
+                .with_filter(EnvFilter::new("bolt_sidecar=info"));
+            Some(layer)
+        }
+        None => None,
+    };
+
+    Registry::default().with(std_layer).with(otlp_layer).try_init()?;
+    if let Some(endpoint) = otlp_endpoint {
+        info!(%endpoint, "Exporting traces over OTLP");
+    }
+
     if let Some(metrics_port) = metrics_port {
         let prometheus_addr = SocketAddr::from(([0, 0, 0, 0], metrics_port));
         let builder = PrometheusBuilder::new().with_http_listener(prometheus_addr);
@@ -0,0 +1,123 @@
+use opentelemetry::{
+    global,
+    propagation::{Extractor, Injector, TextMapPropagator},
+    trace::{TraceContextExt, TraceId},
+    KeyValue,
+};
+use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+use opentelemetry_sdk::{
+    propagation::TraceContextPropagator, runtime, trace::TracerProvider, Resource,
+};
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue},
+    Url,
+};
+use tracing::Span;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+/// The service name under which the sidecar spans are exported.
+pub(super) const SERVICE_NAME: &str = "bolt-sidecar";
+
+/// Builds the tracer provider exporting spans to the given OTLP gRPC endpoint, and installs the
+/// W3C trace context propagator used to propagate traces to downstream services.
+pub(super) fn init_tracer_provider(endpoint: &Url) -> eyre::Result<TracerProvider> {
+    let exporter = SpanExporter::builder().with_tonic().with_endpoint(endpoint.as_str()).build()?;
+
+    let provider = TracerProvider::builder()
+        .with_batch_exporter(exporter, runtime::Tokio)
+        .with_resource(Resource::new([KeyValue::new("service.name", SERVICE_NAME)]))
+        .build();
+
+    global::set_text_map_propagator(TraceContextPropagator::new());
+    global::set_tracer_provider(provider.clone());
+
+    Ok(provider)
+}
+
+/// Flushes the spans that haven't been exported yet and shuts down the tracer provider.
+/// This is a no-op if trace export is disabled.
+pub fn shutdown_tracer_provider() {
+    global::shutdown_tracer_provider();
+}
+
+/// Returns the W3C trace context headers (`traceparent` and `tracestate`) of the current span,
+/// to be attached to outgoing requests. Empty if trace export is disabled.
+pub fn trace_context_headers() -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    let context = Span::current().context();
+    global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&context, &mut HeaderInjector(&mut headers))
+    });
+
+    headers
+}
+
+/// Sets the parent of the given span from the W3C trace context headers of an incoming request,
+/// if any. This should be called before any child span is created.
+pub fn set_parent_from_headers(span: &Span, headers: &HeaderMap) {
+    let context =
+        global::get_text_map_propagator(|propagator| propagator.extract(&HeaderExtractor(headers)));
+    span.set_parent(context);
+}
+
+/// Returns the trace ID of the given span, or `None` if trace export is disabled.
+pub fn trace_id(span: &Span) -> Option<TraceId> {
+    let trace_id = span.context().span().span_context().trace_id();
+    (trace_id != TraceId::INVALID).then_some(trace_id)
+}
+
+/// Injects the trace context into HTTP headers.
+struct HeaderInjector<'a>(&'a mut HeaderMap);
+
+impl Injector for HeaderInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        if let (Ok(name), Ok(value)) =
+            (HeaderName::from_bytes(key.as_bytes()), HeaderValue::from_str(&value))
+        {
+            self.0.insert(name, value);
+        }
+    }
+}
+
+/// Extracts the trace context from HTTP headers.
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(HeaderName::as_str).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use opentelemetry::trace::{SpanContext, SpanId, TraceFlags, TraceState};
+
+    use super::*;
+
+    #[test]
+    fn test_trace_context_headers_roundtrip() {
+        let propagator = TraceContextPropagator::new();
+        let span_context = SpanContext::new(
+            TraceId::from_hex("4bf92f3577b34da6a3ce929d0e0e4736").unwrap(),
+            SpanId::from_hex("00f067aa0ba902b7").unwrap(),
+            TraceFlags::SAMPLED,
+            true,
+            TraceState::default(),
+        );
+        let context = opentelemetry::Context::new().with_remote_span_context(span_context.clone());
+
+        let mut headers = HeaderMap::new();
+        propagator.inject_context(&context, &mut HeaderInjector(&mut headers));
+        assert_eq!(
+            headers.get("traceparent").unwrap(),
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01"
+        );
+
+        let extracted = propagator.extract(&HeaderExtractor(&headers));
+        assert_eq!(extracted.span().span_context().trace_id(), span_context.trace_id());
+    }
+}
@@ -1,4 +1,4 @@
-use std::env;
+use std::{env, path::PathBuf};
 
 use alloy::{
     eips::eip2718::Encodable2718,
@@ -188,7 +188,12 @@ pub(crate) async fn create_signed_inclusion_request(
     Ok(request)
 }
 
-fn random_constraints(count: usize) -> Vec<FullTransaction> {
+/// Returns a unique path in the temporary directory for a test file with the given prefix.
+pub(crate) fn temp_file_path(prefix: &str) -> PathBuf {
+    env::temp_dir().join(format!("{prefix}_{}.json", rand::random::<u64>()))
+}
+
+pub(crate) fn random_constraints(count: usize) -> Vec<FullTransaction> {
     // Random inclusion request
     let json_req = r#"{
         "slot": 10,